@@ -128,7 +128,10 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				let newval = stack.peek(1);
 				let val = U256::from(&*ext.storage_at(&address)?);
 
-				let gas = if val.is_zero() && !newval.is_zero() {
+				let gas = if schedule.eip1283 {
+					let orig = U256::from(&*ext.initial_storage_at(&address)?);
+					calculate_eip1283_sstore_gas(schedule, &orig, &val, &newval)
+				} else if val.is_zero() && !newval.is_zero() {
 					schedule.sstore_set_gas
 				} else {
 					// Refund for below case is added when actually executing sstore
@@ -146,6 +149,9 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 			instructions::EXTCODESIZE => {
 				Request::Gas(Gas::from(schedule.extcodesize_gas))
 			},
+			instructions::EXTCODEHASH => {
+				Request::Gas(Gas::from(schedule.extcodehash_gas))
+			},
 			instructions::SUICIDE => {
 				let mut gas = Gas::from(schedule.suicide_gas);
 
@@ -226,12 +232,22 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 
 				Request::GasMemProvide(gas, mem, Some(requested))
 			},
-			instructions::CREATE | instructions::CREATE2 => {
+			instructions::CREATE => {
 				let gas = Gas::from(schedule.create_gas);
 				let mem = mem_needed(stack.peek(1), stack.peek(2))?;
 
 				Request::GasMemProvide(gas, mem, None)
 			},
+			instructions::CREATE2 => {
+				// init code is hashed to derive the address, charged like SHA3
+				let w = overflowing!(add_gas_usize(Gas::from_u256(*stack.peek(2))?, 31));
+				let words = w >> 5;
+				let word_gas = overflowing!(Gas::from(schedule.sha3_word_gas).overflow_mul(words));
+				let gas = overflowing!(Gas::from(schedule.create_gas).overflow_add(word_gas));
+				let mem = mem_needed(stack.peek(1), stack.peek(2))?;
+
+				Request::GasMemProvide(gas, mem, None)
+			},
 			instructions::EXP => {
 				let expon = stack.peek(1);
 				let bytes = ((expon.bits() + 7) / 8) as usize;
@@ -339,6 +355,64 @@ fn add_gas_usize<Gas: evm::CostType>(value: Gas, num: usize) -> (Gas, bool) {
 	value.overflow_add(Gas::from(num))
 }
 
+/// Gas cost of `SSTORE` under EIP-1283 net gas metering, given the value of the slot
+/// at the start of the transaction, its current value and the value being written.
+fn calculate_eip1283_sstore_gas(schedule: &Schedule, original: &U256, current: &U256, new: &U256) -> usize {
+	if current == new {
+		// 1. If current value equals new value (this is a no-op), 200 gas is deducted.
+		schedule.sstore_dirty_gas
+	} else if original == current {
+		// 2.1. If original value equals current value (this storage slot has not been changed by the current execution context)
+		if original.is_zero() {
+			// 2.1.1. If original value is 0, 20000 gas is deducted.
+			schedule.sstore_set_gas
+		} else {
+			// 2.1.2. Otherwise, 5000 gas is deducted.
+			schedule.sstore_reset_gas
+		}
+	} else {
+		// 2.2. If original value does not equal current value (this storage slot is dirty), 200 gas is deducted.
+		schedule.sstore_dirty_gas
+	}
+}
+
+/// Adjust the `SSTORE` refund counter according to EIP-1283 net gas metering.
+pub fn handle_eip1283_sstore_clears_refund(ext: &mut vm::Ext, original: &U256, current: &U256, new: &U256) {
+	let sstore_clears_schedule = ext.schedule().sstore_refund_gas;
+
+	if current == new {
+		// 1. If current value equals new value (this is a no-op), no refund is applied.
+	} else if original == current {
+		// 2.1.2.b. If new value is 0, add 15000 gas to refund counter.
+		if !original.is_zero() && new.is_zero() {
+			ext.add_sstore_refund(sstore_clears_schedule);
+		}
+	} else {
+		if !original.is_zero() {
+			if current.is_zero() {
+				// 2.2.1.1. If current value is 0 (also means that new value is not 0), remove 15000 gas from refund counter.
+				ext.sub_sstore_refund(sstore_clears_schedule);
+			} else if new.is_zero() {
+				// 2.2.1.2. If new value is 0 (also means that current value is not 0), add 15000 gas to refund counter.
+				ext.add_sstore_refund(sstore_clears_schedule);
+			}
+		}
+
+		if original == new {
+			// 2.2.2. If original value equals new value (this storage slot is reset)
+			if original.is_zero() {
+				// 2.2.2.1. If original value is 0, add 19800 gas to refund counter.
+				let refund = ext.schedule().sstore_set_gas - ext.schedule().sstore_dirty_gas;
+				ext.add_sstore_refund(refund);
+			} else {
+				// 2.2.2.2. Otherwise, add 4800 gas to refund counter.
+				let refund = ext.schedule().sstore_reset_gas - ext.schedule().sstore_dirty_gas;
+				ext.add_sstore_refund(refund);
+			}
+		}
+	}
+}
+
 #[test]
 fn test_mem_gas_cost() {
 	// given
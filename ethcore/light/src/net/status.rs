@@ -199,8 +199,8 @@ impl Capabilities {
 	/// Update the capabilities from an announcement.
 	pub fn update_from(&mut self, announcement: &Announcement) {
 		self.serve_headers = self.serve_headers || announcement.serve_headers;
-		self.serve_state_since = self.serve_state_since.or(announcement.serve_state_since);
-		self.serve_chain_since = self.serve_chain_since.or(announcement.serve_chain_since);
+		self.serve_state_since = announcement.serve_state_since.or(self.serve_state_since);
+		self.serve_chain_since = announcement.serve_chain_since.or(self.serve_chain_since);
 		self.tx_relay = self.tx_relay || announcement.tx_relay;
 	}
 }
@@ -574,4 +574,33 @@ mod tests {
 		assert_eq!(read_capabilities, capabilities);
 		assert!(read_flow.is_none());
 	}
+
+	#[test]
+	fn announcement_moves_serving_range() {
+		let mut capabilities = Capabilities {
+			serve_headers: true,
+			serve_chain_since: Some(5),
+			serve_state_since: Some(8),
+			tx_relay: true,
+		};
+
+		let mut announcement = Announcement {
+			head_hash: H256::random(),
+			head_num: 100,
+			head_td: 12345.into(),
+			reorg_depth: 0,
+			serve_headers: false,
+			serve_state_since: None,
+			serve_chain_since: Some(50),
+			tx_relay: false,
+		};
+
+		capabilities.update_from(&announcement);
+		assert_eq!(capabilities.serve_chain_since, Some(50));
+		assert_eq!(capabilities.serve_state_since, Some(8));
+
+		announcement.serve_chain_since = None;
+		capabilities.update_from(&announcement);
+		assert_eq!(capabilities.serve_chain_since, Some(50));
+	}
 }
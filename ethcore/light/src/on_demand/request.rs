@@ -56,6 +56,10 @@ pub enum Request {
 	Body(Body),
 	/// A request for an account.
 	Account(Account),
+	/// A request for an account together with its proof.
+	AccountProof(AccountProof),
+	/// A request for a storage value together with its proof.
+	Storage(Storage),
 	/// A request for a contract's code.
 	Code(Code),
 	/// A request for proof of execution.
@@ -141,6 +145,8 @@ impl_single!(TransactionIndex, TransactionIndex, net_request::TransactionIndexRe
 impl_single!(Receipts, BlockReceipts, Vec<Receipt>);
 impl_single!(Body, Body, encoded::Block);
 impl_single!(Account, Account, Option<BasicAccount>);
+impl_single!(AccountProof, AccountProof, (Vec<Bytes>, Option<BasicAccount>));
+impl_single!(Storage, Storage, (Vec<Bytes>, H256));
 impl_single!(Code, Code, Bytes);
 impl_single!(Execution, TransactionProof, super::ExecutionResult);
 impl_single!(Signal, Signal, Vec<u8>);
@@ -251,6 +257,8 @@ pub enum CheckedRequest {
 	Receipts(BlockReceipts, net_request::IncompleteReceiptsRequest),
 	Body(Body, net_request::IncompleteBodyRequest),
 	Account(Account, net_request::IncompleteAccountRequest),
+	AccountProof(AccountProof, net_request::IncompleteAccountRequest),
+	Storage(Storage, net_request::IncompleteStorageRequest),
 	Code(Code, net_request::IncompleteCodeRequest),
 	Execution(TransactionProof, net_request::IncompleteExecutionRequest),
 	Signal(Signal, net_request::IncompleteSignalRequest)
@@ -299,6 +307,21 @@ impl From<Request> for CheckedRequest {
 				};
 				CheckedRequest::Account(req, net_req)
 			}
+			Request::AccountProof(req) => {
+				let net_req = net_request::IncompleteAccountRequest {
+					block_hash: req.header.field(),
+					address_hash: ::hash::keccak(&req.address).into(),
+				};
+				CheckedRequest::AccountProof(req, net_req)
+			}
+			Request::Storage(req) => {
+				let net_req = net_request::IncompleteStorageRequest {
+					block_hash: req.header.field(),
+					address_hash: ::hash::keccak(&req.address).into(),
+					key_hash: ::hash::keccak(&req.key).into(),
+				};
+				CheckedRequest::Storage(req, net_req)
+			}
 			Request::Code(req) => {
 				let net_req = net_request::IncompleteCodeRequest {
 					block_hash: req.header.field(),
@@ -340,6 +363,8 @@ impl CheckedRequest {
 			CheckedRequest::Receipts(_, req) => NetRequest::Receipts(req),
 			CheckedRequest::Body(_, req) => NetRequest::Body(req),
 			CheckedRequest::Account(_, req) => NetRequest::Account(req),
+			CheckedRequest::AccountProof(_, req) => NetRequest::Account(req),
+			CheckedRequest::Storage(_, req) => NetRequest::Storage(req),
 			CheckedRequest::Code(_, req) => NetRequest::Code(req),
 			CheckedRequest::Execution(_, req) => NetRequest::Execution(req),
 			CheckedRequest::Signal(_, req) => NetRequest::Signal(req),
@@ -355,6 +380,8 @@ impl CheckedRequest {
 			CheckedRequest::Receipts(ref x, _) => x.0.needs_header(),
 			CheckedRequest::Body(ref x, _) => x.0.needs_header(),
 			CheckedRequest::Account(ref x, _) => x.header.needs_header(),
+			CheckedRequest::AccountProof(ref x, _) => x.header.needs_header(),
+			CheckedRequest::Storage(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Code(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Execution(ref x, _) => x.header.needs_header(),
 			_ => None,
@@ -369,6 +396,8 @@ impl CheckedRequest {
 			CheckedRequest::Receipts(ref mut x, _) => x.0 = HeaderRef::Stored(header),
 			CheckedRequest::Body(ref mut x, _) => x.0 = HeaderRef::Stored(header),
 			CheckedRequest::Account(ref mut x, _) => x.header = HeaderRef::Stored(header),
+			CheckedRequest::AccountProof(ref mut x, _) => x.header = HeaderRef::Stored(header),
+			CheckedRequest::Storage(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Code(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Execution(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			_ => {},
@@ -469,6 +498,8 @@ macro_rules! match_me {
 			CheckedRequest::Receipts($check, $req) => $e,
 			CheckedRequest::Body($check, $req) => $e,
 			CheckedRequest::Account($check, $req) => $e,
+			CheckedRequest::AccountProof($check, $req) => $e,
+			CheckedRequest::Storage($check, $req) => $e,
 			CheckedRequest::Code($check, $req) => $e,
 			CheckedRequest::Execution($check, $req) => $e,
 			CheckedRequest::Signal($check, $req) => $e,
@@ -498,6 +529,8 @@ impl IncompleteRequest for CheckedRequest {
 			CheckedRequest::Receipts(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Body(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Account(_, ref req) => req.check_outputs(f),
+			CheckedRequest::AccountProof(_, ref req) => req.check_outputs(f),
+			CheckedRequest::Storage(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Code(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Execution(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Signal(_, ref req) => req.check_outputs(f),
@@ -520,6 +553,8 @@ impl IncompleteRequest for CheckedRequest {
 			CheckedRequest::Receipts(_, req) => req.complete().map(CompleteRequest::Receipts),
 			CheckedRequest::Body(_, req) => req.complete().map(CompleteRequest::Body),
 			CheckedRequest::Account(_, req) => req.complete().map(CompleteRequest::Account),
+			CheckedRequest::AccountProof(_, req) => req.complete().map(CompleteRequest::Account),
+			CheckedRequest::Storage(_, req) => req.complete().map(CompleteRequest::Storage),
 			CheckedRequest::Code(_, req) => req.complete().map(CompleteRequest::Code),
 			CheckedRequest::Execution(_, req) => req.complete().map(CompleteRequest::Execution),
 			CheckedRequest::Signal(_, req) => req.complete().map(CompleteRequest::Signal),
@@ -571,6 +606,12 @@ impl net_request::CheckedRequest for CheckedRequest {
 			CheckedRequest::Account(ref prover, _) =>
 				expect!((&NetResponse::Account(ref res), _) =>
 					prover.check_response(cache, &res.proof).map(Response::Account)),
+			CheckedRequest::AccountProof(ref prover, _) =>
+				expect!((&NetResponse::Account(ref res), _) =>
+					prover.check_response(cache, &res.proof).map(Response::AccountProof)),
+			CheckedRequest::Storage(ref prover, _) =>
+				expect!((&NetResponse::Storage(ref res), _) =>
+					prover.check_response(cache, &res.proof).map(Response::Storage)),
 			CheckedRequest::Code(ref prover, _) =>
 				expect!((&NetResponse::Code(ref res), &CompleteRequest::Code(ref req)) =>
 					prover.check_response(cache, &req.code_hash, &res.code).map(Response::Code)),
@@ -601,6 +642,12 @@ pub enum Response {
 	/// Response to an Account request.
 	// TODO: `unwrap_or(engine_defaults)`
 	Account(Option<BasicAccount>),
+	/// Response to an account proof request.
+	/// Returns the proof nodes and the account.
+	AccountProof((Vec<Bytes>, Option<BasicAccount>)),
+	/// Response to a storage request.
+	/// Returns the proof nodes and the value.
+	Storage((Vec<Bytes>, H256)),
 	/// Response to a request for code.
 	Code(Vec<u8>),
 	/// Response to a request for proved execution.
@@ -613,11 +660,11 @@ impl net_request::ResponseLike for Response {
 	fn fill_outputs<F>(&self, mut f: F) where F: FnMut(usize, Output) {
 		match *self {
 			Response::HeaderProof((ref hash, _)) => f(0, Output::Hash(*hash)),
-			Response::Account(None) => {
+			Response::Account(None) | Response::AccountProof((_, None)) => {
 				f(0, Output::Hash(KECCAK_EMPTY)); // code hash
 				f(1, Output::Hash(KECCAK_NULL_RLP)); // storage root.
 			}
-			Response::Account(Some(ref acc)) => {
+			Response::Account(Some(ref acc)) | Response::AccountProof((_, Some(ref acc))) => {
 				f(0, Output::Hash(acc.code_hash));
 				f(1, Output::Hash(acc.storage_root));
 			}
@@ -832,23 +879,73 @@ pub struct Account {
 impl Account {
 	/// Check a response with an account against the stored header.
 	pub fn check_response(&self, _: &Mutex<::cache::Cache>, proof: &[Bytes]) -> Result<Option<BasicAccount>, Error> {
-		let header = self.header.as_ref()?;
-		let state_root = header.state_root();
+		check_account_proof(&self.header, &self.address, proof)
+	}
+}
 
+/// Request for an account structure, keeping the proof which verified it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountProof {
+	/// Header for verification.
+	pub header: HeaderRef,
+	/// Address requested.
+	pub address: Address,
+}
+
+impl AccountProof {
+	/// Check a response with an account against the stored header.
+	pub fn check_response(&self, _: &Mutex<::cache::Cache>, proof: &[Bytes]) -> Result<(Vec<Bytes>, Option<BasicAccount>), Error> {
+		check_account_proof(&self.header, &self.address, proof).map(|acc| (proof.to_vec(), acc))
+	}
+}
+
+// check an account proof against the state root of the given header.
+fn check_account_proof(header: &HeaderRef, address: &Address, proof: &[Bytes]) -> Result<Option<BasicAccount>, Error> {
+	let header = header.as_ref()?;
+	let state_root = header.state_root();
+
+	let mut db = MemoryDB::new();
+	for node in proof { db.insert(&node[..]); }
+
+	match TrieDB::new(&db, &state_root).and_then(|t| t.get(&keccak(address)))? {
+		Some(val) => {
+			let rlp = UntrustedRlp::new(&val);
+			Ok(Some(BasicAccount {
+				nonce: rlp.val_at(0)?,
+				balance: rlp.val_at(1)?,
+				storage_root: rlp.val_at(2)?,
+				code_hash: rlp.val_at(3)?,
+			}))
+		},
+		None => Ok(None),
+	}
+}
+
+/// Request for a storage value of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+	/// Header for verification.
+	pub header: HeaderRef,
+	/// Address of the account.
+	pub address: Address,
+	/// Storage root of the account, from a proved account at the same block.
+	pub storage_root: H256,
+	/// Storage key requested.
+	pub key: H256,
+}
+
+impl Storage {
+	/// Check a response with a storage value against the account's storage root.
+	pub fn check_response(&self, _: &Mutex<::cache::Cache>, proof: &[Bytes]) -> Result<(Vec<Bytes>, H256), Error> {
 		let mut db = MemoryDB::new();
 		for node in proof { db.insert(&node[..]); }
 
-		match TrieDB::new(&db, &state_root).and_then(|t| t.get(&keccak(&self.address)))? {
+		match TrieDB::new(&db, &self.storage_root).and_then(|t| t.get(&keccak(&self.key)))? {
 			Some(val) => {
-				let rlp = UntrustedRlp::new(&val);
-				Ok(Some(BasicAccount {
-					nonce: rlp.val_at(0)?,
-					balance: rlp.val_at(1)?,
-					storage_root: rlp.val_at(2)?,
-					code_hash: rlp.val_at(3)?,
-				}))
-			},
-			None => Ok(None),
+				let value: U256 = UntrustedRlp::new(&val).as_val()?;
+				Ok((proof.to_vec(), value.into()))
+			}
+			None => Ok((proof.to_vec(), H256::zero())),
 		}
 	}
 }
@@ -496,3 +496,98 @@ fn fill_from_cache() {
 
 	assert!(recv.wait().is_ok());
 }
+
+#[test]
+fn account_and_storage_proofs() {
+	use bigint::prelude::U256;
+	use rlp::{self, RlpStream};
+	use trie::{Trie, TrieMut, SecTrieDB, SecTrieDBMut};
+	use trie::recorder::Recorder;
+	use util::{Address, MemoryDB};
+
+	let harness = Harness::create();
+
+	let peer_id = 10101;
+	let req_id = ReqId(14426);
+
+	harness.inject_peer(peer_id, Peer {
+		status: dummy_status(),
+		capabilities: dummy_capabilities(),
+	});
+
+	let address = Address::from(0x1234);
+	let key = H256::from(1);
+	let value = H256::from(0xabcd);
+	let code_hash = H256::from(0x5678);
+
+	let mut db = MemoryDB::new();
+	let mut storage_root = H256::default();
+	{
+		let mut trie = SecTrieDBMut::new(&mut db, &mut storage_root);
+		trie.insert(&*key, &rlp::encode(&U256::from(&*value))).unwrap();
+		trie.insert(&*H256::from(2), &rlp::encode(&U256::from(0xef))).unwrap();
+	}
+
+	let mut state_root = H256::default();
+	{
+		let mut stream = RlpStream::new_list(4);
+		stream.append(&5u64).append(&100u64).append(&storage_root).append(&code_hash);
+
+		let mut trie = SecTrieDBMut::new(&mut db, &mut state_root);
+		trie.insert(&*address, &stream.out()).unwrap();
+		trie.insert(&*Address::from(0x4321), &stream.out()).unwrap();
+	}
+
+	let prove = |root: &H256, key: &[u8]| {
+		let trie = SecTrieDB::new(&db, root).unwrap();
+		let mut recorder = Recorder::new();
+		trie.get_with(key, &mut recorder).unwrap().unwrap();
+		recorder.drain().into_iter().map(|r| r.data).collect::<Vec<_>>()
+	};
+	let account_proof = prove(&state_root, &*address);
+	let storage_proof = prove(&storage_root, &*key);
+
+	let mut header = Header::default();
+	header.set_number(1000);
+	header.set_state_root(state_root);
+	let encoded = encoded::Header::new(header.rlp(Seal::With));
+
+	let recv = harness.service.request(
+		&Context::NoOp,
+		(
+			request::AccountProof { header: encoded.clone().into(), address: address },
+			request::Storage { header: encoded.into(), address: address, storage_root: storage_root, key: key },
+		)
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_id, req_id));
+	assert_eq!(harness.service.pending.read().len(), 0);
+
+	harness.service.on_responses(
+		&Context::WithPeer(peer_id),
+		req_id,
+		&[
+			Response::Account(basic_request::AccountResponse {
+				proof: account_proof.clone(),
+				nonce: 5.into(),
+				balance: 100.into(),
+				code_hash: code_hash,
+				storage_root: storage_root,
+			}),
+			Response::Storage(basic_request::StorageResponse {
+				proof: storage_proof.clone(),
+				value: value,
+			}),
+		]
+	);
+
+	let ((proof, account), (proof2, proved_value)) = recv.wait().unwrap();
+	let account = account.unwrap();
+	assert_eq!(proof, account_proof);
+	assert_eq!(account.nonce, 5.into());
+	assert_eq!(account.balance, 100.into());
+	assert_eq!(account.storage_root, storage_root);
+	assert_eq!(account.code_hash, code_hash);
+	assert_eq!(proof2, storage_proof);
+	assert_eq!(proved_value, value);
+}
@@ -166,7 +166,7 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 	}
 
 	fn block_body(&self, req: request::CompleteBodyRequest) -> Option<request::BodyResponse> {
-		BlockChainClient::block_body(self, BlockId::Hash(req.hash))
+		BlockChainClient::block_body(self, BlockId::Hash(req.hash)).ok()
 			.map(|body| ::request::BodyResponse { body: body })
 	}
 
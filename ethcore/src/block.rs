@@ -20,7 +20,7 @@ use std::cmp;
 use std::sync::Arc;
 use std::collections::HashSet;
 use hash::{keccak, KECCAK_NULL_RLP, KECCAK_EMPTY_LIST_RLP};
-use triehash::ordered_trie_root;
+use triehash::{ordered_trie_root, ordered_trie_root_parallel};
 
 use rlp::{UntrustedRlp, RlpStream, Encodable, Decodable, DecoderError};
 use bigint::prelude::U256;
@@ -38,7 +38,8 @@ use header::Header;
 use receipt::{Receipt, TransactionOutcome};
 use state::State;
 use state_db::StateDB;
-use trace::FlatTrace;
+use state_diff::StateDiff;
+use trace::{FlatTrace, VMTrace, TracingLevel, ExecutiveTracer, ExecutiveVMTracer};
 use transaction::{UnverifiedTransaction, SignedTransaction};
 use verification::PreverifiedBlock;
 use views::BlockView;
@@ -97,6 +98,8 @@ pub struct ExecutedBlock {
 	transactions_set: HashSet<H256>,
 	state: State<StateDB>,
 	traces: Option<Vec<Vec<FlatTrace>>>,
+	state_diffs: Option<Vec<StateDiff>>,
+	vm_traces: Option<Vec<VMTrace>>,
 	last_hashes: Arc<LastHashes>,
 }
 
@@ -154,6 +157,8 @@ impl ExecutedBlock {
 			transactions_set: Default::default(),
 			state: state,
 			traces: if tracing {Some(Vec::new())} else {None},
+			state_diffs: None,
+			vm_traces: None,
 			last_hashes: last_hashes,
 		}
 	}
@@ -195,6 +200,15 @@ impl ExecutedBlock {
 			gas_limit: self.header.gas_limit().clone(),
 		}
 	}
+
+	// compute the receipts root, in parallel if enabled for the state tries.
+	fn receipts_root(&self) -> H256 {
+		let receipts = self.receipts.iter().map(|r| r.rlp_bytes().into_vec());
+		match self.state.trie_factory().is_parallel() {
+			true => ordered_trie_root_parallel(receipts),
+			false => ordered_trie_root(receipts),
+		}
+	}
 }
 
 /// Trait for a object that is a `ExecutedBlock`.
@@ -226,6 +240,12 @@ pub trait IsBlock {
 	/// Get all information concerning transaction tracing in this block.
 	fn traces(&self) -> &Option<Vec<Vec<FlatTrace>>> { &self.block().traces }
 
+	/// Get the state diffs of the transactions in this block, if they are collected.
+	fn state_diffs(&self) -> &Option<Vec<StateDiff>> { &self.block().state_diffs }
+
+	/// Get the VM traces of the transactions in this block, if they are collected.
+	fn vm_traces(&self) -> &Option<Vec<VMTrace>> { &self.block().vm_traces }
+
 	/// Get all uncles in this block.
 	fn uncles(&self) -> &[Header] { &self.block().uncles }
 
@@ -394,6 +414,14 @@ impl<'x> OpenBlock<'x> {
 		self.block.env_info()
 	}
 
+	/// Collect the state diffs and VM traces of pushed transactions as required by `level`.
+	/// Has no effect unless tracing is enabled.
+	pub fn set_tracing_level(&mut self, level: TracingLevel) {
+		let tracing = self.block.traces.is_some();
+		self.block.state_diffs = if tracing && level.state_diffs() {Some(Vec::new())} else {None};
+		self.block.vm_traces = if tracing && level.vm_traces() {Some(Vec::new())} else {None};
+	}
+
 	/// Push a transaction into the block.
 	///
 	/// If valid, it will be executed, and archived together with the receipt.
@@ -403,13 +431,34 @@ impl<'x> OpenBlock<'x> {
 		}
 
 		let env_info = self.env_info();
+		// the state diff is built from the original accounts kept by the checkpoint.
+		let original_root = match self.block.state_diffs.is_some() {
+			true => {
+				self.block.state.checkpoint();
+				Some(self.block.state.root().clone())
+			},
+			false => None,
+		};
 //		info!("env_info says gas_used={}", env_info.gas_used);
-		match self.block.state.apply(&env_info, self.engine.machine(), &t, self.block.traces.is_some()) {
+		let result = match self.block.vm_traces.is_some() {
+			true => self.block.state.apply_with_tracing(&env_info, self.engine.machine(), &t, ExecutiveTracer::default(), ExecutiveVMTracer::toplevel()),
+			false => self.block.state.apply(&env_info, self.engine.machine(), &t, self.block.traces.is_some()),
+		};
+		if result.is_err() && original_root.is_some() {
+			self.block.state.revert_to_checkpoint();
+		}
+		match result {
 			Ok(outcome) => {
+				if let Some(root) = original_root {
+					let diff = self.block.state.diff_from_checkpoint(root)?;
+					self.block.state_diffs.as_mut().map(|diffs| diffs.push(diff));
+				}
 				self.block.transactions_set.insert(h.unwrap_or_else(||t.hash()));
 				self.block.transactions.push(t.into());
 				let t = outcome.trace;
 				self.block.traces.as_mut().map(|traces| traces.push(t));
+				let vm_trace = outcome.vm_trace.unwrap_or_default();
+				self.block.vm_traces.as_mut().map(|vm_traces| vm_traces.push(vm_trace));
 				self.block.receipts.push(outcome.receipt);
 				Ok(self.block.receipts.last().expect("receipt just pushed; qed"))
 			}
@@ -450,7 +499,8 @@ impl<'x> OpenBlock<'x> {
 		let uncle_bytes = s.block.uncles.iter().fold(RlpStream::new_list(s.block.uncles.len()), |mut s, u| {s.append_raw(&u.rlp(Seal::With), 1); s} ).out();
 		s.block.header.set_uncles_hash(keccak(&uncle_bytes));
 		s.block.header.set_state_root(s.block.state.root().clone());
-		s.block.header.set_receipts_root(ordered_trie_root(s.block.receipts.iter().map(|r| r.rlp_bytes().into_vec())));
+		let receipts_root = s.block.receipts_root();
+		s.block.header.set_receipts_root(receipts_root);
 		s.block.header.set_log_bloom(s.block.receipts.iter().fold(LogBloom::zero(), |mut b, r| {b = &b | &r.log_bloom; b})); //TODO: use |= operator
 		s.block.header.set_gas_used(s.block.receipts.last().map_or(U256::zero(), |r| r.gas_used));
 
@@ -480,7 +530,8 @@ impl<'x> OpenBlock<'x> {
 			s.block.header.set_uncles_hash(keccak(&uncle_bytes));
 		}
 		if s.block.header.receipts_root().is_zero() || s.block.header.receipts_root() == &KECCAK_NULL_RLP {
-			s.block.header.set_receipts_root(ordered_trie_root(s.block.receipts.iter().map(|r| r.rlp_bytes().into_vec())));
+			let receipts_root = s.block.receipts_root();
+			s.block.header.set_receipts_root(receipts_root);
 		}
 
 		s.block.header.set_state_root(s.block.state.root().clone());
@@ -550,6 +601,18 @@ impl LockedBlock {
 		Ok(SealedBlock { block: s.block, uncle_bytes: s.uncle_bytes })
 	}
 
+	/// Provide an internally generated seal in order to turn this into a `SealedBlock`,
+	/// letting the engine finalize the header with `Engine::on_seal_block`.
+	pub fn seal_internally(self, engine: &EthEngine, seal: Vec<Bytes>) -> Result<SealedBlock, Error> {
+		let mut s = self;
+		if seal.len() != engine.seal_fields() {
+			return Err(BlockError::InvalidSealArity(Mismatch{expected: engine.seal_fields(), found: seal.len()}).into());
+		}
+		s.block.header.set_seal(seal);
+		engine.on_seal_block(&mut s.block)?;
+		Ok(SealedBlock { block: s.block, uncle_bytes: s.uncle_bytes })
+	}
+
 	/// Provide a valid seal in order to turn this into a `SealedBlock`.
 	/// This does check the validity of `seal` with the engine.
 	/// Returns the `ClosedBlock` back again if the seal is no good.
@@ -574,7 +637,8 @@ impl LockedBlock {
 		for receipt in &mut block.block.receipts {
 			receipt.outcome = TransactionOutcome::Unknown;
 		}
-		block.block.header.set_receipts_root(ordered_trie_root(block.block.receipts.iter().map(|r| r.rlp_bytes().into_vec())));
+		let receipts_root = block.block.receipts_root();
+		block.block.header.set_receipts_root(receipts_root);
 		block
 	}
 }
@@ -608,14 +672,15 @@ impl IsBlock for SealedBlock {
 	fn block(&self) -> &ExecutedBlock { &self.block }
 }
 
-/// Enact the block given by block header, transactions and uncles
+/// Enact the block given by block header, transactions and uncles.
+/// `tracing` is the tracing level, `None` if tracing is disabled.
 #[cfg_attr(feature="dev", allow(too_many_arguments))]
 pub fn enact(
 	header: &Header,
 	transactions: &[SignedTransaction],
 	uncles: &[Header],
 	engine: &EthEngine,
-	tracing: bool,
+	tracing: Option<TracingLevel>,
 	db: StateDB,
 	parent: &Header,
 	last_hashes: Arc<LastHashes>,
@@ -633,7 +698,7 @@ pub fn enact(
 	let mut b = OpenBlock::new(
 		engine,
 		factories,
-		tracing,
+		tracing.is_some(),
 		db,
 		parent,
 		last_hashes,
@@ -643,6 +708,9 @@ pub fn enact(
 		is_epoch_begin,
 	)?;
 
+	if let Some(level) = tracing {
+		b.set_tracing_level(level);
+	}
 	b.populate_from(header);
 	b.push_transactions(transactions)?;
 
@@ -687,7 +755,7 @@ fn push_transactions(block: &mut OpenBlock, transactions: &[SignedTransaction])
 pub fn enact_verified(
 	block: &PreverifiedBlock,
 	engine: &EthEngine,
-	tracing: bool,
+	tracing: Option<TracingLevel>,
 	db: StateDB,
 	parent: &Header,
 	last_hashes: Arc<LastHashes>,
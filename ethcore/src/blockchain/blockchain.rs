@@ -18,7 +18,7 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::mem;
+use std::{cmp, mem};
 use itertools::Itertools;
 use bloomchain as bc;
 use heapsize::HeapSizeOf;
@@ -40,7 +40,7 @@ use types::blockchain_info::BlockChainInfo;
 use types::tree_route::TreeRoute;
 use blockchain::update::ExtrasUpdate;
 use blockchain::{CacheSize, ImportRoute, Config};
-use db::{self, Writable, Readable, CacheUpdatePolicy};
+use db::{self, Writable, Readable, CacheUpdatePolicy, Key};
 use cache_manager::CacheManager;
 use encoded;
 use engines::epoch::{Transition as EpochTransition, PendingTransition as PendingEpochTransition};
@@ -195,6 +195,9 @@ pub struct BlockChain {
 	// Stores the last block of the last sequence of blocks. `None` if there are no gaps.
 	// This is calculated on start and does not get updated.
 	first_block: Option<H256>,
+	// Stores the number of the first block whose body, receipts and transaction addresses
+	// are kept. `None` if the history was never pruned.
+	first_body: RwLock<Option<BlockNumber>>,
 
 	// block cache
 	block_headers: RwLock<HashMap<H256, Bytes>>,
@@ -215,6 +218,7 @@ pub struct BlockChain {
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
 	pending_transaction_addresses: RwLock<HashMap<H256, Option<TransactionAddress>>>,
+	pending_rewind: RwLock<bool>,
 }
 
 impl BlockProvider for BlockChain {
@@ -497,6 +501,7 @@ impl BlockChain {
 				elements_per_index: LOG_BLOOMS_ELEMENTS_PER_INDEX,
 			},
 			first_block: None,
+			first_body: RwLock::new(None),
 			best_block: RwLock::new(BestBlock::default()),
 			best_ancient_block: RwLock::new(None),
 			block_headers: RwLock::new(HashMap::new()),
@@ -512,6 +517,7 @@ impl BlockChain {
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
+			pending_rewind: RwLock::new(false),
 		};
 
 		// load best block
@@ -611,6 +617,9 @@ impl BlockChain {
 					number: number,
 				});
 			}
+
+			*bc.first_body.write() = bc.db.get(db::COL_EXTRA, b"first_body").unwrap()
+				.map(|n| UntrustedRlp::new(&n).as_val().expect("First body number is always valid RLP; qed"));
 		}
 
 		bc
@@ -1112,6 +1121,15 @@ impl BlockChain {
 			*best_block = block;
 		}
 
+		// a rewind leaves the caches of the removed blocks behind.
+		if mem::replace(&mut *self.pending_rewind.write(), false) {
+			write_block_details.clear();
+			write_hashes.clear();
+			write_txs.clear();
+			self.block_receipts.write().clear();
+			self.blocks_blooms.write().clear();
+		}
+
 		let pending_txs = mem::replace(&mut *pending_write_txs, HashMap::new());
 		let (retracted_txs, enacted_txs) = pending_txs.into_iter().partition::<HashMap<_, _>, _>(|&(_, ref value)| value.is_none());
 
@@ -1384,6 +1402,116 @@ impl BlockChain {
 		encoded::Header::new(raw)
 	}
 
+	/// Get the number of the first block whose body, receipts and transaction addresses are kept.
+	/// `None` if the history was never pruned.
+	pub fn first_body_number(&self) -> Option<BlockNumber> {
+		*self.first_body.read()
+	}
+
+	/// Remove the bodies, receipts and transaction addresses of canonical blocks preceding `until`,
+	/// at most `max` blocks at once. Headers and block details are kept, so is the genesis.
+	/// Returns the number of blocks pruned.
+	pub fn prune_history(&self, batch: &mut DBTransaction, until: BlockNumber, max: u64) -> u64 {
+		let mut first_body = self.first_body.write();
+		let from = first_body.unwrap_or(1);
+		let until = cmp::min(until, from + max);
+		if until <= from {
+			return 0;
+		}
+
+		let pruned: Vec<_> = (from..until)
+			.filter_map(|number| self.block_hash(number))
+			.map(|hash| {
+				let transactions = self.block_body(&hash).map_or_else(Vec::new, |body| body.transaction_hashes());
+				(hash, transactions)
+			})
+			.collect();
+
+		type ReceiptsKey = Key<BlockReceipts, Target=::bigint::hash::H264>;
+		type AddressKey = Key<TransactionAddress, Target=::bigint::hash::H264>;
+		for &(ref hash, ref transactions) in &pruned {
+			batch.delete(db::COL_BODIES, hash);
+			batch.delete(db::COL_EXTRA, &(ReceiptsKey::key(hash)));
+			for transaction in transactions {
+				batch.delete(db::COL_EXTRA, &(AddressKey::key(transaction)));
+			}
+		}
+		batch.put(db::COL_EXTRA, b"first_body", &::rlp::encode(&until));
+
+		{
+			let mut block_bodies = self.block_bodies.write();
+			let mut transaction_addresses = self.transaction_addresses.write();
+			let mut block_receipts = self.block_receipts.write();
+			for &(ref hash, ref transactions) in &pruned {
+				block_bodies.remove(hash);
+				block_receipts.remove(hash);
+				for transaction in transactions {
+					transaction_addresses.remove(transaction);
+				}
+			}
+		}
+
+		*first_body = Some(until);
+		until - from
+	}
+
+	/// Make the canonical block `target` the best block, removing the canonical blocks after it
+	/// from the chain. Their number to hash mappings, details, receipts, transaction addresses
+	/// and log blooms are removed, so they can be imported again; headers and bodies are kept.
+	/// Returns the hashes of the removed blocks, or `None` if `target` is not canonical.
+	///
+	/// The new best block is only published by `commit`, which should be called once
+	/// `batch` has been written.
+	pub fn rewind_to(&self, batch: &mut DBTransaction, target: &H256) -> Option<Vec<H256>> {
+		let mut details = otry!(self.block_details(target));
+		if self.block_hash(details.number).as_ref() != Some(target) {
+			return None;
+		}
+		let block = otry!(self.block(target));
+		let best_number = self.best_block_number();
+
+		type DetailsKey = Key<BlockDetails, Target=::bigint::hash::H264>;
+		type ReceiptsKey = Key<BlockReceipts, Target=::bigint::hash::H264>;
+		type AddressKey = Key<TransactionAddress, Target=::bigint::hash::H264>;
+		type HashKey = Key<H256, Target=BlockNumberKey>;
+		let mut removed = Vec::new();
+		for number in (details.number + 1)..(best_number + 1) {
+			if let Some(hash) = self.block_hash(number) {
+				let transactions = self.block_body(&hash).map_or_else(Vec::new, |body| body.transaction_hashes());
+				for transaction in transactions {
+					batch.delete(db::COL_EXTRA, &(AddressKey::key(&transaction)));
+				}
+				batch.delete(db::COL_EXTRA, &(DetailsKey::key(&hash)));
+				batch.delete(db::COL_EXTRA, &(ReceiptsKey::key(&hash)));
+				removed.push(hash);
+			}
+			batch.delete(db::COL_EXTRA, &(HashKey::key(&number)));
+		}
+
+		if best_number > details.number {
+			let range = (details.number + 1) as bc::Number .. best_number as bc::Number;
+			let chain = bc::group::BloomGroupChain::new(self.blooms_config, self);
+			for (k, v) in chain.replace(&range, vec![]) {
+				batch.write(db::COL_EXTRA, &LogGroupPosition::from(k), &BloomGroup::from(v));
+			}
+		}
+
+		details.children.retain(|child| !removed.contains(child));
+		batch.write(db::COL_EXTRA, target, &details);
+		batch.put(db::COL_EXTRA, b"best", target);
+
+		let block = block.into_inner();
+		*self.pending_best_block.write() = Some(BestBlock {
+			number: details.number,
+			total_difficulty: details.total_difficulty,
+			hash: *target,
+			timestamp: BlockView::new(&block).header().timestamp(),
+			block: block,
+		});
+		*self.pending_rewind.write() = true;
+		Some(removed)
+	}
+
 	/// Get current cache size.
 	pub fn cache_size(&self) -> CacheSize {
 		CacheSize {
@@ -1944,6 +2072,56 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_prune_history() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: "601080600c6000396000f3006000355415600957005b60203560003555".from_hex().unwrap(),
+		}.sign(&secret(), None);
+
+		let b1 = canon_chain.with_transaction(t1.clone()).generate(&mut finalizer).unwrap();
+		let b1_hash = BlockView::new(&b1).header_view().hash();
+		let db = new_db();
+
+		{
+			let bc = new_chain(&genesis, db.clone());
+			let mut batch = db.transaction();
+			bc.insert_block(&mut batch, &b1, vec![Receipt::new(TransactionOutcome::Unknown, 0.into(), vec![])]);
+			bc.commit();
+			for _ in 0..3 {
+				let block = canon_chain.generate(&mut finalizer).unwrap();
+				bc.insert_block(&mut batch, &block, vec![]);
+				bc.commit();
+			}
+			db.write(batch).unwrap();
+			assert!(bc.block_receipts(&b1_hash).is_some());
+
+			let mut batch = db.transaction();
+			assert_eq!(bc.prune_history(&mut batch, 3, 1), 1);
+			assert_eq!(bc.prune_history(&mut batch, 3, 10), 1);
+			assert_eq!(bc.prune_history(&mut batch, 3, 10), 0);
+			db.write(batch).unwrap();
+			assert_eq!(bc.first_body_number(), Some(3));
+		}
+
+		let bc = new_chain(&genesis, db.clone());
+		assert_eq!(bc.first_body_number(), Some(3));
+		assert!(bc.block_header(&b1_hash).is_some());
+		assert!(bc.block_body(&b1_hash).is_none());
+		assert!(bc.block_receipts(&b1_hash).is_none());
+		assert!(bc.transaction_address(&t1.hash()).is_none());
+		assert!(bc.block_body(&bc.block_hash(3).unwrap()).is_some());
+		assert!(bc.block_body(&bc.genesis_hash()).is_some());
+	}
+
 	#[test]
 	fn can_contain_arbitrary_block_sequence() {
 		let bc = generate_dummy_blockchain(50);
@@ -2262,6 +2440,51 @@ mod tests {
 		assert_eq!(bc.rewind(), None);
 	}
 
+	#[test]
+	fn rewinds_to_canonical_block() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let first = canon_chain.generate(&mut finalizer).unwrap();
+		let second = canon_chain.generate(&mut finalizer).unwrap();
+		let third = canon_chain.generate(&mut finalizer).unwrap();
+		let first_hash = BlockView::new(&first).header_view().hash();
+		let second_hash = BlockView::new(&second).header_view().hash();
+		let third_hash = BlockView::new(&third).header_view().hash();
+
+		let db = new_db();
+		let bc = new_chain(&genesis, db.clone());
+		let mut batch = db.transaction();
+		for block in &[&first, &second, &third] {
+			bc.insert_block(&mut batch, block, vec![]);
+			bc.commit();
+		}
+		db.write(batch).unwrap();
+
+		let mut batch = db.transaction();
+		assert_eq!(bc.rewind_to(&mut batch, &H256::default()), None);
+		assert_eq!(bc.rewind_to(&mut batch, &first_hash), Some(vec![second_hash, third_hash]));
+		assert_eq!(bc.best_block_hash(), third_hash);
+		db.write(batch).unwrap();
+		bc.commit();
+
+		assert_eq!(bc.best_block_hash(), first_hash);
+		assert!(!bc.is_known(&second_hash));
+		assert_eq!(bc.block_hash(2), None);
+		assert_eq!(bc.block_hash(3), None);
+		assert!(bc.block_details(&first_hash).unwrap().children.is_empty());
+
+		// the removed blocks can be imported again.
+		let mut batch = db.transaction();
+		bc.insert_block(&mut batch, &second, vec![]);
+		bc.commit();
+		db.write(batch).unwrap();
+		assert_eq!(bc.best_block_hash(), second_hash);
+
+		let bc = new_chain(&genesis, db);
+		assert_eq!(bc.best_block_number(), 2);
+	}
+
 	#[test]
 	fn epoch_transitions_iter() {
 		use ::engines::EpochTransition;
@@ -44,12 +44,13 @@ use client::Error as ClientError;
 use client::{
 	BlockId, TransactionId, UncleId, TraceId, ClientConfig, BlockChainClient,
 	MiningBlockChainClient, TraceFilter, CallAnalytics, BlockImportError, Mode,
-	ChainNotify, PruningInfo, ProvingBlockChainClient,
+	ChainNotify, PruningInfo, ProvingBlockChainClient, StateGcConfig, StateGcStatus,
 };
+use client::state_gc::StateGc;
 use encoded;
 use engines::{EthEngine, EpochTransition};
-use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult, Error as EthcoreError};
-use vm::{EnvInfo, LastHashes};
+use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult, HistoryError, Error as EthcoreError};
+use vm::{self, EnvInfo, LastHashes};
 use evm::{Factory as EvmFactory, Schedule};
 use executive::{Executive, Executed, TransactOptions, contract_address};
 use factory::Factories;
@@ -71,9 +72,11 @@ use state::{self, State};
 use trace;
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use trace::FlatTransactionTraces;
+use trace::{StructLogger, StructLoggerOptions, StructLog, VMTrace};
 use transaction::{LocalizedTransaction, UnverifiedTransaction, SignedTransaction, Transaction, PendingTransaction, Action};
 use types::filter::Filter;
 use types::mode::Mode as IpcMode;
+use types::state_diff::StateDiff;
 use verification;
 use verification::{PreverifiedBlock, Verifier};
 use verification::queue::BlockQueue;
@@ -88,6 +91,7 @@ pub use verification::queue::QueueInfo as BlockQueueInfo;
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+const MAX_HISTORY_PRUNED_PER_IMPORT: u64 = 1024;
 
 /// Report on the status of a client.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -166,11 +170,13 @@ pub struct Client {
 	last_hashes: RwLock<VecDeque<H256>>,
 	factories: Factories,
 	history: u64,
+	history_bodies: Option<u64>,
 	rng: Mutex<OsRng>,
 	ancient_verifier: Mutex<Option<AncientVerifier>>,
 	on_user_defaults_change: Mutex<Option<Box<FnMut(Option<Mode>) + 'static + Send>>>,
 	registrar: Mutex<Option<Registry>>,
 	exit_handler: Mutex<Option<Box<Fn(bool, Option<String>) + 'static + Send>>>,
+	state_gc: Mutex<Option<StateGc>>,
 }
 
 impl Client {
@@ -188,7 +194,8 @@ impl Client {
 			false => TrieSpec::Secure,
 		};
 
-		let trie_factory = TrieFactory::new(trie_spec);
+		let mut trie_factory = TrieFactory::new(trie_spec);
+		trie_factory.set_parallel(config.parallel_trie_root);
 		let factories = Factories {
 			vm: EvmFactory::new(config.vm_type.clone(), config.jump_table_size),
 			trie: trie_factory,
@@ -220,8 +227,20 @@ impl Client {
 			config.history
 		};
 
+		let history_bodies = config.history_bodies.map(|n| if n < history {
+			info!(target: "client", "Ignoring body pruning history parameter of {}\
+				, falling back to the state pruning history of {}",
+				n, history);
+			history
+		} else {
+			n
+		});
+
 		if !chain.block_header(&chain.best_block_hash()).map_or(true, |h| state_db.journal_db().contains(h.state_root())) {
 			warn!("State root not found for block #{} ({})", chain.best_block_number(), chain.best_block_hash().hex());
+		} else if config.flat_state {
+			let best = chain.best_block_header();
+			state_db.enable_flat_state(&factories.accountdb, best.number(), best.hash(), best.state_root())?;
 		}
 
 		let engine = spec.engine.clone();
@@ -253,10 +272,12 @@ impl Client {
 			last_hashes: RwLock::new(VecDeque::new()),
 			factories: factories,
 			history: history,
+			history_bodies: history_bodies,
 			rng: Mutex::new(OsRng::new().map_err(UtilError::from)?),
 			ancient_verifier: Mutex::new(None),
 			on_user_defaults_change: Mutex::new(None),
 			registrar: Mutex::new(None),
+			state_gc: Mutex::new(None),
 			exit_handler: Mutex::new(None),
 		});
 
@@ -455,9 +476,13 @@ impl Client {
 		let db = self.state_db.lock().boxed_clone_canon(header.parent_hash());
 
 		let is_epoch_begin = chain.epoch_transition(parent.number(), *header.parent_hash()).is_some();
+		let tracing = match self.tracedb.read().tracing_enabled() {
+			true => Some(self.config.tracing.level),
+			false => None,
+		};
 		let enact_result = enact_verified(block,
 			engine,
-			self.tracedb.read().tracing_enabled(),
+			tracing,
 			db,
 			&parent,
 			last_hashes,
@@ -665,6 +690,8 @@ impl Client {
 		let traces: Vec<FlatTransactionTraces> = traces.into_iter()
 			.map(Into::into)
 			.collect();
+		let state_diffs = block.state_diffs().clone();
+		let vm_traces = block.vm_traces().clone();
 
 		assert_eq!(header.hash(), BlockView::new(block_data).header_view().hash());
 
@@ -693,6 +720,8 @@ impl Client {
 
 		self.tracedb.read().import(&mut batch, TraceImportRequest {
 			traces: traces.into(),
+			state_diffs: state_diffs,
+			vm_traces: vm_traces,
 			block_hash: hash.clone(),
 			block_number: number,
 			enacted: route.enacted.clone(),
@@ -700,6 +729,7 @@ impl Client {
 		});
 
 		let is_canon = route.enacted.last().map_or(false, |h| h == hash);
+		state.journal_flat_state(&mut batch, is_canon);
 		state.sync_cache(&route.enacted, &route.retracted, is_canon);
 		// Final commit to the DB
 		self.db.read().write_buffered(batch);
@@ -712,6 +742,7 @@ impl Client {
 		if let Err(e) = self.prune_ancient(state, &chain) {
 			warn!("Failed to prune ancient state data: {}", e);
 		}
+		self.prune_history(&chain);
 
 		route
 	}
@@ -884,6 +915,22 @@ impl Client {
 		Ok(())
 	}
 
+	// remove bodies, receipts and transaction addresses of blocks older than the history kept.
+	fn prune_history(&self, chain: &BlockChain) {
+		let keep = match self.history_bodies {
+			Some(keep) => keep,
+			None => return,
+		};
+
+		let until = (chain.best_block_number() + 1).saturating_sub(keep);
+		let mut batch = DBTransaction::new();
+		let pruned = chain.prune_history(&mut batch, until, MAX_HISTORY_PRUNED_PER_IMPORT);
+		if pruned > 0 {
+			trace!(target: "client", "Pruned bodies and receipts of {} ancient blocks", pruned);
+			self.db.read().write_buffered(batch);
+		}
+	}
+
 	fn update_last_hashes(&self, parent: &H256, hash: &H256) {
 		let mut hashes = self.last_hashes.write();
 		if hashes.front().map_or(false, |h| h == parent) {
@@ -1144,19 +1191,28 @@ impl Client {
 			options: TransactOptions<T, V>,
 		) -> Result<Executed, CallError> where
 			T: trace::Tracer,
-			V: trace::VMTracer,
+			V: trace::VMTracer<Output = trace::VMTrace>,
 		{
 			let options = options
 				.dont_check_nonce()
 				.save_output_from_contract();
-			let original_state = if state_diff { Some(state.clone()) } else { None };
-
-			let mut ret = Executive::new(state, env_info, machine).transact_virtual(transaction, options)?;
+			if state_diff {
+				state.checkpoint();
+			}
+			let original_root = state.root().clone();
 
-			if let Some(original) = original_state {
-				ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
+			let result = Executive::new(state, env_info, machine).transact_virtual(transaction, options);
+			match (result, state_diff) {
+				(Ok(mut ret), true) => {
+					ret.state_diff = Some(state.diff_from_checkpoint(original_root).map_err(ExecutionError::from)?);
+					Ok(ret)
+				},
+				(Err(e), true) => {
+					state.revert_to_checkpoint();
+					Err(e.into())
+				},
+				(result, false) => Ok(result?),
 			}
-			Ok(ret)
 		}
 
 		let state_diff = analytics.state_diffing;
@@ -1170,6 +1226,73 @@ impl Client {
 		}
 	}
 
+	fn do_traced_call<T, V>(&self, env_info: &EnvInfo, state: &mut State<StateDB>, t: &SignedTransaction, tracer: T, vm_tracer: V) -> Result<Executed<V::Output>, CallError> where
+		T: trace::Tracer,
+		V: trace::VMTracer,
+	{
+		let options = TransactOptions::new(tracer, vm_tracer)
+			.dont_check_nonce()
+			.save_output_from_contract();
+
+		Ok(Executive::new(state, env_info, self.engine.machine()).transact_virtual(t, options)?)
+	}
+
+	/// Replays all transactions of a block in a single pass, optionally diffing the state
+	/// changed by each of them.
+	fn do_replay_block<T, V, F>(&self, block: BlockId, state_diff: bool, mut tracers: F) -> Result<Vec<Executed<V::Output>>, CallError> where
+		T: trace::Tracer,
+		V: trace::VMTracer,
+		F: FnMut(&SignedTransaction) -> (T, V),
+	{
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(block).map_err(|_| CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or(CallError::StatePruned)?;
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		let mut results = Vec::new();
+		for t in body.transactions() {
+			let t = SignedTransaction::new(t).expect(PROOF);
+			let (tracer, vm_tracer) = tracers(&t);
+			let original_root = state.root().clone();
+			if state_diff {
+				state.checkpoint();
+			}
+			let mut ret = self.do_traced_call(&env_info, &mut state, &t, tracer, vm_tracer)?;
+			if state_diff {
+				ret.state_diff = Some(state.diff_from_checkpoint(original_root).map_err(ExecutionError::from)?);
+			}
+			env_info.gas_used = ret.cumulative_gas_used;
+			results.push(ret);
+		}
+
+		Ok(results)
+	}
+
+	/// Returns the environment, the state right before the given transaction and the transaction itself.
+	fn prepare_replay(&self, id: TransactionId) -> Result<(EnvInfo, State<StateDB>, SignedTransaction), CallError> {
+		let address = self.transaction_address(id).ok_or(CallError::TransactionNotFound)?;
+		let mut env_info = self.env_info(BlockId::Hash(address.block_hash)).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(BlockId::Hash(address.block_hash)).map_err(|_| CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(BlockId::Hash(address.block_hash)).ok_or(CallError::StatePruned)?;
+		let mut txs = body.transactions();
+
+		if address.index >= txs.len() {
+			return Err(CallError::TransactionNotFound);
+		}
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		let rest = txs.split_off(address.index);
+		for t in txs {
+			let t = SignedTransaction::new(t).expect(PROOF);
+			let x = Executive::new(&mut state, &env_info, self.engine.machine()).transact(&t, TransactOptions::with_no_tracing())?;
+			env_info.gas_used = env_info.gas_used + x.gas_used;
+		}
+		let first = rest.into_iter().next().expect("We split off < `address.index`; Length is checked earlier; qed");
+		let t = SignedTransaction::new(first).expect(PROOF);
+
+		Ok((env_info, state, t))
+	}
+
 	fn block_number_ref(&self, id: &BlockId) -> Option<BlockNumber> {
 		match *id {
 			BlockId::Number(number) => Some(number),
@@ -1186,11 +1309,15 @@ impl snapshot::DatabaseRestore for Client {
 	fn restore_db(&self, new_db: &str) -> Result<(), EthcoreError> {
 		trace!(target: "snapshot", "Replacing client database with {:?}", new_db);
 
+		// the garbage collection works on the database being replaced.
+		self.stop_state_gc();
+
 		let _import_lock = self.import_lock.lock();
 		let mut state_db = self.state_db.lock();
 		let mut chain = self.chain.write();
 		let mut tracedb = self.tracedb.write();
 		self.miner.clear();
+		state_db.disable_flat_state();
 		let db = self.db.write();
 		db.restore(new_db)?;
 
@@ -1198,6 +1325,10 @@ impl snapshot::DatabaseRestore for Client {
 		*state_db = StateDB::new(journaldb::new(db.clone(), self.pruning, ::db::COL_STATE), cache_size);
 		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
 		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
+		if self.config.flat_state {
+			let best = chain.best_block_header();
+			state_db.enable_flat_state(&self.factories.accountdb, best.number(), best.hash(), best.state_root())?;
+		}
 		Ok(())
 	}
 }
@@ -1242,16 +1373,19 @@ impl BlockChainClient for Client {
 		// that's just a copy of the state.
 		let original_state = self.state_at(block).ok_or(CallError::StatePruned)?;
 		let sender = t.sender();
-		let options = || TransactOptions::with_tracing();
+		let options = || TransactOptions::with_tracing().save_output_from_contract();
 
-		let cond = |gas| {
+		let execute = |gas| {
 			let mut tx = t.as_unsigned().clone();
 			tx.gas = gas;
 			let tx = tx.fake_sign(sender);
 
 			let mut state = original_state.clone();
-			Ok(Executive::new(&mut state, &env_info, self.engine.machine())
+			Executive::new(&mut state, &env_info, self.engine.machine())
 				.transact_virtual(&tx, options())
+		};
+		let cond = |gas| {
+			Ok(execute(gas)
 				.map(|r| r.exception.is_none())
 				.unwrap_or(false))
 		};
@@ -1262,6 +1396,11 @@ impl BlockChainClient for Client {
 			upper = UPPER_CEILING.into();
 			if !cond(upper)? {
 				trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
+				if let Ok(executed) = execute(upper) {
+					if let Some(vm::Error::Reverted) = executed.exception {
+						return Err(CallError::Reverted(executed.output));
+					}
+				}
 				let err = ExecutionError::Internal(format!("Requires higher than upper limit of {}", upper));
 				return Err(err.into())
 			}
@@ -1297,27 +1436,57 @@ impl BlockChainClient for Client {
 	}
 
 	fn replay(&self, id: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let address = self.transaction_address(id).ok_or(CallError::TransactionNotFound)?;
-		let mut env_info = self.env_info(BlockId::Hash(address.block_hash)).ok_or(CallError::StatePruned)?;
-		let body = self.block_body(BlockId::Hash(address.block_hash)).ok_or(CallError::StatePruned)?;
-		let mut state = self.state_at_beginning(BlockId::Hash(address.block_hash)).ok_or(CallError::StatePruned)?;
-		let mut txs = body.transactions();
-
-		if address.index >= txs.len() {
-			return Err(CallError::TransactionNotFound);
-		}
+		let (env_info, mut state, t) = self.prepare_replay(id)?;
+		self.do_virtual_call(&env_info, &mut state, &t, analytics)
+	}
 
-		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
-		let rest = txs.split_off(address.index);
-		for t in txs {
-			let t = SignedTransaction::new(t).expect(PROOF);
-			let x = Executive::new(&mut state, &env_info, self.engine.machine()).transact(&t, TransactOptions::with_no_tracing())?;
-			env_info.gas_used = env_info.gas_used + x.gas_used;
-		}
-		let first = rest.into_iter().next().expect("We split off < `address.index`; Length is checked earlier; qed");
-		let t = SignedTransaction::new(first).expect(PROOF);
+	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Vec<(H256, Executed)>, CallError> {
+		let state_diff = analytics.state_diffing;
+		let mut hashes = Vec::new();
+		let results = match (analytics.transaction_tracing, analytics.vm_tracing) {
+			(true, true) => self.do_replay_block(block, state_diff, |t| {
+				hashes.push(t.hash());
+				(trace::ExecutiveTracer::default(), trace::ExecutiveVMTracer::toplevel())
+			}),
+			(true, false) => self.do_replay_block(block, state_diff, |t| {
+				hashes.push(t.hash());
+				(trace::ExecutiveTracer::default(), trace::NoopVMTracer)
+			}),
+			(false, true) => self.do_replay_block(block, state_diff, |t| {
+				hashes.push(t.hash());
+				(trace::NoopTracer, trace::ExecutiveVMTracer::toplevel())
+			}),
+			(false, false) => self.do_replay_block(block, state_diff, |t| {
+				hashes.push(t.hash());
+				(trace::NoopTracer, trace::NoopVMTracer)
+			}),
+		}?;
+
+		Ok(hashes.into_iter().zip(results).collect())
+	}
+
+	fn replay_struct_logs(&self, id: TransactionId, options: StructLoggerOptions) -> Result<Executed<Vec<StructLog>>, CallError> {
+		self.replay_with_tracers(id, trace::NoopTracer, StructLogger::new(options))
+	}
+
+	fn replay_block_struct_logs(&self, block: BlockId, options: StructLoggerOptions) -> Result<Vec<Executed<Vec<StructLog>>>, CallError> {
+		self.replay_block_with_tracers(block, |_| (trace::NoopTracer, StructLogger::new(options)))
+	}
+
+	fn replay_with_tracers<T, V>(&self, id: TransactionId, tracer: T, vm_tracer: V) -> Result<Executed<V::Output>, CallError> where
+		T: trace::Tracer,
+		V: trace::VMTracer,
+	{
+		let (env_info, mut state, t) = self.prepare_replay(id)?;
+		self.do_traced_call(&env_info, &mut state, &t, tracer, vm_tracer)
+	}
 
-		self.do_virtual_call(&env_info, &mut state, &t, analytics)
+	fn replay_block_with_tracers<T, V, F>(&self, block: BlockId, mut tracers: F) -> Result<Vec<Executed<V::Output>>, CallError> where
+		T: trace::Tracer,
+		V: trace::VMTracer,
+		F: FnMut(&SignedTransaction) -> (T, V),
+	{
+		self.do_replay_block(block, false, tracers)
 	}
 
 	fn mode(&self) -> IpcMode {
@@ -1391,18 +1560,25 @@ impl BlockChainClient for Client {
 		self.block_number_ref(&id)
 	}
 
-	fn block_body(&self, id: BlockId) -> Option<encoded::Body> {
+	fn block_body(&self, id: BlockId) -> Result<encoded::Body, HistoryError> {
 		let chain = self.chain.read();
 
 		if let BlockId::Pending = id {
 			if let Some(block) = self.miner.pending_block(chain.best_block_number()) {
-				return Some(encoded::Body::new(BlockChain::block_to_body(&block.rlp_bytes(Seal::Without))));
+				return Ok(encoded::Body::new(BlockChain::block_to_body(&block.rlp_bytes(Seal::Without))));
 			}
 			// fall back to latest
 			return self.block_body(BlockId::Latest);
 		}
 
-		Self::block_hash(&chain, &self.miner, id).and_then(|hash| chain.block_body(&hash))
+		let hash = Self::block_hash(&chain, &self.miner, id).ok_or(HistoryError::UnknownBlock)?;
+		chain.block_body(&hash).ok_or_else(|| {
+			let number = chain.block_number(&hash);
+			match (number, chain.first_body_number()) {
+				(Some(number), Some(first)) if number < first => HistoryError::Pruned,
+				_ => HistoryError::UnknownBlock,
+			}
+		})
 	}
 
 	fn block(&self, id: BlockId) -> Option<encoded::Block> {
@@ -1570,7 +1746,7 @@ impl BlockChainClient for Client {
 
 	fn uncle(&self, id: UncleId) -> Option<encoded::Header> {
 		let index = id.position;
-		self.block_body(id.block).and_then(|body| body.view().uncle_rlp_at(index))
+		self.block_body(id.block).ok().and_then(|body| body.view().uncle_rlp_at(index))
 			.map(encoded::Header::new)
 	}
 
@@ -1738,6 +1914,32 @@ impl BlockChainClient for Client {
 			.and_then(|number| self.tracedb.read().block_traces(number))
 	}
 
+	fn transaction_state_diff(&self, transaction: TransactionId) -> Option<StateDiff> {
+		self.transaction_address(transaction)
+			.and_then(|tx_address| {
+				self.block_number(BlockId::Hash(tx_address.block_hash))
+					.and_then(|number| self.tracedb.read().transaction_state_diff(number, tx_address.index))
+			})
+	}
+
+	fn block_state_diffs(&self, block: BlockId) -> Option<Vec<StateDiff>> {
+		self.block_number(block)
+			.and_then(|number| self.tracedb.read().block_state_diffs(number))
+	}
+
+	fn transaction_vm_trace(&self, transaction: TransactionId) -> Option<VMTrace> {
+		self.transaction_address(transaction)
+			.and_then(|tx_address| {
+				self.block_number(BlockId::Hash(tx_address.block_hash))
+					.and_then(|number| self.tracedb.read().transaction_vm_trace(number, tx_address.index))
+			})
+	}
+
+	fn block_vm_traces(&self, block: BlockId) -> Option<Vec<VMTrace>> {
+		self.block_number(block)
+			.and_then(|number| self.tracedb.read().block_vm_traces(number))
+	}
+
 	fn last_hashes(&self) -> LastHashes {
 		(*self.build_last_hashes(self.chain.read().best_block_hash())).clone()
 	}
@@ -1790,12 +1992,79 @@ impl BlockChainClient for Client {
 	}
 
 	fn pruning_info(&self) -> PruningInfo {
+		let chain = self.chain.read();
 		PruningInfo {
-			earliest_chain: self.chain.read().first_block_number().unwrap_or(1),
+			earliest_chain: ::std::cmp::max(chain.first_block_number().unwrap_or(1), chain.first_body_number().unwrap_or(1)),
 			earliest_state: self.state_db.lock().journal_db().earliest_era().unwrap_or(0),
 		}
 	}
 
+	fn start_state_gc(&self, config: StateGcConfig) -> Result<(), String> {
+		let mut state_gc = self.state_gc.lock();
+		if state_gc.as_ref().map_or(false, |gc| gc.status().is_running()) {
+			return Err("State garbage collection is already running".into());
+		}
+
+		let chain = self.chain.read().clone();
+		let db = self.state_db.lock().journal_db().boxed_clone();
+		*state_gc = Some(StateGc::start(chain, db, config)?);
+		Ok(())
+	}
+
+	fn stop_state_gc(&self) {
+		if let Some(ref mut gc) = *self.state_gc.lock() {
+			gc.stop();
+		}
+	}
+
+	fn state_gc_status(&self) -> Option<StateGcStatus> {
+		self.state_gc.lock().as_ref().map(StateGc::status)
+	}
+
+	fn reset_to(&self, id: BlockId) -> Result<H256, String> {
+		// the garbage collection works on the state being reverted.
+		self.stop_state_gc();
+
+		let (hash, removed) = {
+			let _import_lock = self.import_lock.lock();
+			let mut state_db = self.state_db.lock();
+			let chain = self.chain.read();
+
+			let header = match Self::block_hash(&chain, &self.miner, id).and_then(|hash| chain.block_header(&hash)) {
+				Some(header) => header,
+				None => return Err(format!("Unknown block: {:?}", id)),
+			};
+			let (number, hash) = (header.number(), header.hash());
+			if chain.block_hash(number) != Some(hash) {
+				return Err(format!("Block #{} ({}) is not canonical", number, hash));
+			}
+			if state_db.journal_db().earliest_era().map_or(false, |era| number < era) || !state_db.journal_db().contains(header.state_root()) {
+				return Err(format!("State of block #{} ({}) has been pruned", number, hash));
+			}
+
+			let mut batch = DBTransaction::new();
+			let removed = chain.rewind_to(&mut batch, &hash).expect("block is canonical; qed");
+			state_db.rewind(&mut batch, number).map_err(|e| format!("{}", e))?;
+			state_db.disable_flat_state();
+			self.db.read().write(batch).map_err(|e| format!("{}", e))?;
+			chain.commit();
+			if self.config.flat_state {
+				if let Err(e) = state_db.enable_flat_state(&self.factories.accountdb, number, hash, *header.state_root()) {
+					warn!(target: "client", "Disabling flat state after failing to enable it: {}", e);
+				}
+			}
+
+			info!(target: "client", "Best block reset to #{} ({}), {} blocks removed", number, hash, removed.len());
+			(hash, removed)
+		};
+
+		self.miner.chain_new_blocks(self, &[], &[], &[], &removed);
+		self.notify(|notify| {
+			notify.new_blocks(vec![], vec![], vec![], removed.clone(), vec![], vec![], 0);
+		});
+		Ok(hash)
+	}
+
 	fn call_contract(&self, block_id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String> {
 		let transaction = self.contract_call_tx(block_id, address, data);
 
@@ -2035,6 +2304,7 @@ impl ProvingBlockChainClient for Client {
 
 impl Drop for Client {
 	fn drop(&mut self) {
+		self.state_db.lock().disable_flat_state();
 		self.engine.stop();
 	}
 }
@@ -2205,4 +2475,65 @@ mod tests {
 			outcome: TransactionOutcome::StateRoot(state_root),
 		});
 	}
+
+	#[test]
+	fn state_gc_skips_states_pruned_while_marking() {
+		use std::sync::Arc;
+		use std::sync::atomic::AtomicBool;
+		use std::time::Duration;
+		use parking_lot::Mutex;
+		use util::{Address, HashDB};
+		use io::IoChannel;
+		use client::{BlockChainClient, MiningBlockChainClient, ClientConfig, BlockId};
+		use client::{StateGcConfig, StateGcStage, StateGcStatus};
+		use client::state_gc::{collect_from, retained_roots};
+		use miner::Miner;
+		use spec::Spec;
+		use state::CleanupMode;
+		use super::Client;
+
+		let spec = Spec::new_null();
+		let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+		let mut config = ClientConfig::default();
+		config.history = 8;
+		let client = Client::new(config, &spec, db, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+
+		let address = Address::random();
+		let import_block = || {
+			let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+			b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+			b.block_mut().fields_mut().state.commit().unwrap();
+			let b = b.close_and_lock().seal(&*spec.engine, vec![]).unwrap();
+			client.import_sealed_block(b).unwrap();
+		};
+		for _ in 0..20 {
+			import_block();
+		}
+
+		let chain = client.chain.read().clone();
+		let journal_db = client.state_db.lock().journal_db().boxed_clone();
+		let roots = retained_roots(&chain, &*journal_db);
+		let base = roots.iter().min_by_key(|state| state.number).unwrap().clone();
+
+		// canonicalise an era once the states to keep are known, as if a block was imported while marking.
+		import_block();
+		assert!(journal_db.get(&base.root).is_none());
+
+		let status = Mutex::new(StateGcStatus {
+			stage: StateGcStage::Marking,
+			marked: 0,
+			examined: 0,
+			removed: 0,
+			progress: 0,
+		});
+		let config = StateGcConfig { batch_size: 2, throttle: Duration::from_millis(0) };
+		assert!(journal_db.begin_gc());
+		let result = collect_from(&chain, &*journal_db, roots, &config, &status, &AtomicBool::new(false));
+		journal_db.end_gc();
+
+		assert_eq!(result.unwrap(), true);
+		assert_eq!(client.state().balance(&address).unwrap(), 105.into());
+		let earliest = client.pruning_info().earliest_state;
+		assert_eq!(client.state_at(BlockId::Number(earliest)).unwrap().balance(&address).unwrap(), (earliest * 5).into());
+	}
 }
@@ -16,12 +16,15 @@
 
 use std::str::FromStr;
 use std::path::Path;
+use std::sync::Arc;
 use std::fmt::{Display, Formatter, Error as FmtError};
 
 use mode::Mode as IpcMode;
 use verification::{VerifierType, QueueConfig};
 use journaldb;
-use kvdb_rocksdb::CompactionProfile;
+use kvdb::{self, KeyValueDB};
+use kvdb_rocksdb::{self, CompactionProfile, DatabaseConfig};
+use kvdb_paritydb;
 
 pub use std::time::Duration;
 pub use blockchain::Config as BlockChainConfig;
@@ -69,6 +72,93 @@ impl FromStr for DatabaseCompactionProfile {
 	}
 }
 
+/// Key-value store backing the client database.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DatabaseBackend {
+	/// RocksDB
+	RocksDB,
+	/// Append-only store with an in-memory index
+	ParityDB,
+}
+
+impl Default for DatabaseBackend {
+	fn default() -> Self {
+		DatabaseBackend::RocksDB
+	}
+}
+
+impl DatabaseBackend {
+	/// Detect the backend of an existing database. Returns `None` if there is no database at given path.
+	pub fn detect(path: &Path) -> Option<DatabaseBackend> {
+		if path.join(kvdb_paritydb::METADATA_FILE).exists() {
+			Some(DatabaseBackend::ParityDB)
+		} else if path.join("CURRENT").exists() {
+			Some(DatabaseBackend::RocksDB)
+		} else {
+			None
+		}
+	}
+
+	/// Open the database at given path, creating it if it does not exist.
+	/// Settings which have no equivalent in the chosen backend are ignored.
+	pub fn open(&self, config: &DatabaseConfig, path: &str) -> Result<Arc<KeyValueDB>, kvdb::Error> {
+		match Self::detect(Path::new(path)) {
+			Some(existing) if existing != *self => {
+				return Err(format!("Database at {} was created with the {} backend", path, existing).into());
+			},
+			_ => {},
+		}
+
+		match *self {
+			DatabaseBackend::RocksDB => Ok(Arc::new(kvdb_rocksdb::Database::open(config, path)?)),
+			DatabaseBackend::ParityDB => {
+				let paritydb_config = kvdb_paritydb::DatabaseConfig::with_columns(config.columns);
+				Ok(Arc::new(kvdb_paritydb::Database::open(&paritydb_config, path)?))
+			},
+		}
+	}
+
+	/// Open the existing database at given path for reading. ParityDB databases are never written
+	/// to. RocksDB has no such mode, so parity only refrains from writing to them.
+	pub fn open_read_only(&self, config: &DatabaseConfig, path: &str) -> Result<Arc<KeyValueDB>, kvdb::Error> {
+		match Self::detect(Path::new(path)) {
+			Some(existing) if existing == *self => {},
+			Some(existing) => return Err(format!("Database at {} was created with the {} backend", path, existing).into()),
+			None => return Err(format!("No {} database found at {}", self, path).into()),
+		}
+
+		match *self {
+			DatabaseBackend::RocksDB => Ok(Arc::new(kvdb_rocksdb::Database::open(config, path)?)),
+			DatabaseBackend::ParityDB => {
+				let mut paritydb_config = kvdb_paritydb::DatabaseConfig::with_columns(config.columns);
+				paritydb_config.read_only = true;
+				Ok(Arc::new(kvdb_paritydb::Database::open(&paritydb_config, path)?))
+			},
+		}
+	}
+}
+
+impl FromStr for DatabaseBackend {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"rocksdb" => Ok(DatabaseBackend::RocksDB),
+			"paritydb" => Ok(DatabaseBackend::ParityDB),
+			_ => Err("Invalid database backend given. Expected rocksdb/paritydb.".into()),
+		}
+	}
+}
+
+impl Display for DatabaseBackend {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		match *self {
+			DatabaseBackend::RocksDB => write!(f, "rocksdb"),
+			DatabaseBackend::ParityDB => write!(f, "paritydb"),
+		}
+	}
+}
+
 /// Operating mode for the client.
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Mode {
@@ -137,6 +227,10 @@ pub struct ClientConfig {
 	pub vm_type: VMType,
 	/// Fat DB enabled?
 	pub fat_db: bool,
+	/// Compute state, storage and receipts trie roots on a thread pool?
+	pub parallel_trie_root: bool,
+	/// Keep a flat index of the recent state for faster account and storage reads?
+	pub flat_state: bool,
 	/// The JournalDB ("pruning") algorithm to use.
 	pub pruning: journaldb::Algorithm,
 	/// The name of the client instance.
@@ -147,6 +241,8 @@ pub struct ClientConfig {
 	pub db_compaction: DatabaseCompactionProfile,
 	/// Should db have WAL enabled?
 	pub db_wal: bool,
+	/// Database backend
+	pub db_backend: DatabaseBackend,
 	/// Operating mode
 	pub mode: Mode,
 	/// The chain spec name
@@ -161,13 +257,16 @@ pub struct ClientConfig {
 	pub history: u64,
 	/// Ideal memory usage for state pruning history.
 	pub history_mem: usize,
+	/// Number of recent blocks whose bodies, receipts and transaction addresses are kept.
+	/// `None` keeps them all.
+	pub history_bodies: Option<u64>,
 	/// Check seal valididity on block import
 	pub check_seal: bool,
 }
 
 #[cfg(test)]
 mod test {
-	use super::{DatabaseCompactionProfile, Mode};
+	use super::{DatabaseCompactionProfile, DatabaseBackend, Mode};
 
 	#[test]
 	fn test_default_compaction_profile() {
@@ -181,6 +280,14 @@ mod test {
 		assert_eq!(DatabaseCompactionProfile::HDD, "hdd".parse().unwrap());
 	}
 
+	#[test]
+	fn test_parsing_database_backend() {
+		assert_eq!(DatabaseBackend::default(), DatabaseBackend::RocksDB);
+		assert_eq!(DatabaseBackend::RocksDB, "rocksdb".parse().unwrap());
+		assert_eq!(DatabaseBackend::ParityDB, "paritydb".parse().unwrap());
+		assert!("leveldb".parse::<DatabaseBackend>().is_err());
+	}
+
 	#[test]
 	fn test_mode_default() {
 		assert_eq!(Mode::default(), Mode::Active);
@@ -0,0 +1,405 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Online garbage collection of the state database.
+
+use std::collections::HashSet;
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use bigint::hash::H256;
+use bloom_journal::Bloom;
+use journaldb::JournalDB;
+use kvdb::{KeyValueDB, DBTransaction};
+use parking_lot::Mutex;
+use trie::TrieError;
+use util::HashDB;
+
+use blockchain::{BlockChain, BlockProvider};
+use header::BlockNumber;
+use db;
+use error::Error;
+use state::walk_state;
+
+/// Prefix of the keys under which reachable nodes are recorded in the state column.
+/// Nodes are keyed by 32-byte hashes, so the sweep never mistakes a mark for a node.
+const MARK_PREFIX: &'static [u8] = b"state-gc-mark";
+
+/// Size in bytes of the filter kept in memory in front of the recorded marks.
+const MARK_FILTER_SIZE: usize = 64 * 1024 * 1024;
+
+/// Number of reachable nodes the filter is sized for. More only make lookups slower.
+const MARK_FILTER_ITEMS: usize = 50_000_000;
+
+/// State garbage collection configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateGcConfig {
+	/// Number of keys removed from the database at once.
+	pub batch_size: usize,
+	/// Pause between two batches.
+	pub throttle: Duration,
+}
+
+impl Default for StateGcConfig {
+	fn default() -> Self {
+		StateGcConfig {
+			batch_size: 1000,
+			throttle: Duration::from_millis(100),
+		}
+	}
+}
+
+/// Stage of a state garbage collection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateGcStage {
+	/// Looking for the nodes reachable from the retained states.
+	Marking,
+	/// Removing unreachable nodes from the database.
+	Sweeping,
+	/// Finished.
+	Done,
+	/// Stopped before it finished.
+	Aborted,
+	/// Stopped by an error.
+	Failed(String),
+}
+
+/// Progress of a state garbage collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateGcStatus {
+	/// Current stage.
+	pub stage: StateGcStage,
+	/// Number of reachable keys found.
+	pub marked: usize,
+	/// Number of database keys examined while sweeping.
+	pub examined: usize,
+	/// Number of keys removed from the database.
+	pub removed: usize,
+	/// Estimated percentage of the current stage done.
+	pub progress: u8,
+}
+
+impl StateGcStatus {
+	/// Whether the garbage collection is still running.
+	pub fn is_running(&self) -> bool {
+		match self.stage {
+			StateGcStage::Marking | StateGcStage::Sweeping => true,
+			_ => false,
+		}
+	}
+}
+
+/// Handle to a garbage collection running in the background.
+pub struct StateGc {
+	status: Arc<Mutex<StateGcStatus>>,
+	abort: Arc<AtomicBool>,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl StateGc {
+	/// Start collecting the garbage of `db`, keeping every state of the retained blocks of `chain`.
+	///
+	/// Nodes reachable from these states are recorded in the database until the collection finishes.
+	/// Fails if the pruning algorithm doesn't support it or a collection is already running.
+	pub fn start(chain: Arc<BlockChain>, db: Box<JournalDB>, config: StateGcConfig) -> Result<Self, String> {
+		if !db.begin_gc() {
+			return Err("State garbage collection is not supported by the pruning method or already running".into());
+		}
+
+		let status = Arc::new(Mutex::new(StateGcStatus {
+			stage: StateGcStage::Marking,
+			marked: 0,
+			examined: 0,
+			removed: 0,
+			progress: 0,
+		}));
+		let abort = Arc::new(AtomicBool::new(false));
+
+		let thread = {
+			let status = status.clone();
+			let abort = abort.clone();
+			thread::Builder::new().name("state-gc".into()).spawn(move || {
+				let result = collect(&*chain, &*db, &config, &*status, &*abort);
+				db.end_gc();
+
+				let mut status = status.lock();
+				status.stage = match result {
+					Ok(true) => StateGcStage::Done,
+					Ok(false) => StateGcStage::Aborted,
+					Err(e) => {
+						warn!(target: "client", "State garbage collection failed: {}", e);
+						StateGcStage::Failed(format!("{}", e))
+					},
+				};
+				info!(target: "client", "State garbage collection finished: {:?}, {} keys removed", status.stage, status.removed);
+			}).map_err(|e| format!("Unable to spawn the garbage collection thread: {}", e))?
+		};
+
+		Ok(StateGc {
+			status: status,
+			abort: abort,
+			thread: Some(thread),
+		})
+	}
+
+	/// Current progress.
+	pub fn status(&self) -> StateGcStatus {
+		self.status.lock().clone()
+	}
+
+	/// Abort the garbage collection and wait until it has stopped.
+	pub fn stop(&mut self) {
+		self.abort.store(true, AtomicOrdering::SeqCst);
+		if let Some(thread) = self.thread.take() {
+			if thread.join().is_err() {
+				warn!(target: "client", "State garbage collection thread panicked");
+			}
+		}
+	}
+}
+
+impl Drop for StateGc {
+	fn drop(&mut self) {
+		self.stop();
+	}
+}
+
+// reachable nodes, recorded in the state column with a bloom filter in front of them,
+// so that neither depends on the size of the state to fit in memory.
+struct Marks {
+	db: Arc<KeyValueDB>,
+	filter: Bloom,
+	pending: HashSet<H256>,
+	batch_size: usize,
+	count: usize,
+}
+
+impl Marks {
+	fn new(db: Arc<KeyValueDB>, batch_size: usize) -> Result<Self, Error> {
+		let marks = Marks {
+			db: db,
+			filter: Bloom::new(MARK_FILTER_SIZE, MARK_FILTER_ITEMS),
+			pending: HashSet::new(),
+			batch_size: batch_size,
+			count: 0,
+		};
+		// left over by a collection which didn't finish.
+		marks.clear()?;
+		Ok(marks)
+	}
+
+	fn db_key(key: &H256) -> Vec<u8> {
+		let mut db_key = MARK_PREFIX.to_vec();
+		db_key.extend_from_slice(key);
+		db_key
+	}
+
+	fn contains(&self, key: &H256) -> Result<bool, Error> {
+		if !self.filter.check(key) {
+			return Ok(false);
+		}
+		if self.pending.contains(key) {
+			return Ok(true);
+		}
+		Ok(self.db.get(db::COL_STATE, &Self::db_key(key))?.is_some())
+	}
+
+	fn insert(&mut self, key: H256) -> Result<(), Error> {
+		self.filter.set(&key);
+		self.pending.insert(key);
+		self.count += 1;
+		if self.pending.len() >= self.batch_size {
+			self.flush()?;
+		}
+		Ok(())
+	}
+
+	fn flush(&mut self) -> Result<(), Error> {
+		let mut batch = DBTransaction::new();
+		for key in self.pending.drain() {
+			batch.put(db::COL_STATE, &Self::db_key(&key), &[]);
+		}
+		self.db.write(batch)?;
+		Ok(())
+	}
+
+	// remove all the marks from the database.
+	fn clear(&self) -> Result<(), Error> {
+		loop {
+			let mut batch = DBTransaction::new();
+			for (key, _) in self.db.iter_from_prefix(db::COL_STATE, MARK_PREFIX).take_while(|&(ref key, _)| key.starts_with(MARK_PREFIX)).take(self.batch_size) {
+				batch.delete(db::COL_STATE, &key);
+			}
+			if batch.ops.is_empty() {
+				return Ok(());
+			}
+			self.db.write(batch)?;
+		}
+	}
+}
+
+// mark and sweep. returns `false` if aborted.
+fn collect(chain: &BlockChain, db: &JournalDB, config: &StateGcConfig, status: &Mutex<StateGcStatus>, abort: &AtomicBool) -> Result<bool, Error> {
+	let roots = retained_roots(chain, db);
+	collect_from(chain, db, roots, config, status, abort)
+}
+
+/// Mark and sweep, keeping the states of the given blocks which are still retained by `db`.
+pub fn collect_from(chain: &BlockChain, db: &JournalDB, roots: Vec<RetainedState>, config: &StateGcConfig, status: &Mutex<StateGcStatus>, abort: &AtomicBool) -> Result<bool, Error> {
+	let batch_size = ::std::cmp::max(config.batch_size, 1);
+	let mut marks = Marks::new(db.backing().clone(), batch_size)?;
+	let result = mark_and_sweep(chain, db, roots, config, &mut marks, status, abort);
+	marks.clear()?;
+	result
+}
+
+fn mark_and_sweep(chain: &BlockChain, db: &JournalDB, roots: Vec<RetainedState>, config: &StateGcConfig, marks: &mut Marks, status: &Mutex<StateGcStatus>, abort: &AtomicBool) -> Result<bool, Error> {
+	info!(target: "client", "State garbage collection: marking nodes of {} states", roots.len());
+	for (i, state) in roots.iter().enumerate() {
+		let walked = walk_state(&state.root, &mut |key| {
+			if abort.load(AtomicOrdering::SeqCst) || marks.contains(&key)? {
+				return Ok(None);
+			}
+			match db.get(&key) {
+				Some(value) => {
+					marks.insert(key)?;
+					Ok(Some(value))
+				},
+				None => Err(TrieError::IncompleteDatabase(key).into()),
+			}
+		});
+
+		match walked {
+			Ok(()) => {},
+			// the oldest states are pruned as new blocks are imported.
+			Err(Error::Trie(TrieError::IncompleteDatabase(_))) if !is_retained(chain, db, state) => {
+				debug!(target: "client", "State garbage collection: state of block #{} pruned while marking", state.number);
+			},
+			// sweeping now could remove what's left of a retained state.
+			Err(Error::Trie(TrieError::IncompleteDatabase(key))) => {
+				warn!(target: "client", "State garbage collection: node {} of the state of block #{} is missing", key, state.number);
+				return Err(TrieError::IncompleteDatabase(key).into());
+			},
+			Err(e) => return Err(e),
+		}
+
+		if abort.load(AtomicOrdering::SeqCst) {
+			return Ok(false);
+		}
+
+		let mut status = status.lock();
+		status.marked = marks.count;
+		status.progress = ((i + 1) * 100 / roots.len()) as u8;
+	}
+	marks.flush()?;
+
+	{
+		let mut status = status.lock();
+		status.stage = StateGcStage::Sweeping;
+		status.progress = 0;
+	}
+
+	info!(target: "client", "State garbage collection: sweeping, {} nodes reachable", marks.count);
+	let backing = db.backing().clone();
+	let mut garbage = Vec::with_capacity(marks.batch_size);
+	let mut examined = 0;
+	for (key, _) in backing.iter(db::COL_STATE) {
+		examined += 1;
+		// everything else is journal metadata or marks.
+		if key.len() != 32 {
+			continue;
+		}
+
+		let key = H256::from_slice(&key);
+		if marks.contains(&key)? {
+			continue;
+		}
+
+		garbage.push(key);
+		if garbage.len() == marks.batch_size {
+			if abort.load(AtomicOrdering::SeqCst) {
+				return Ok(false);
+			}
+
+			let removed = db.collect_garbage(mem::replace(&mut garbage, Vec::with_capacity(marks.batch_size)))?;
+			{
+				let mut status = status.lock();
+				status.examined = examined;
+				status.removed += removed;
+				// keys are hashes, so the first byte tells how far the sweep went.
+				status.progress = (key[0] as usize * 100 / 256) as u8;
+			}
+			thread::sleep(config.throttle);
+		}
+	}
+
+	let removed = db.collect_garbage(garbage)?;
+	let mut status = status.lock();
+	status.examined = examined;
+	status.removed += removed;
+	status.progress = 100;
+	Ok(true)
+}
+
+/// State kept by the journal when the garbage collection started.
+#[derive(Debug, Clone)]
+pub struct RetainedState {
+	/// Block number.
+	pub number: BlockNumber,
+	/// Block hash.
+	pub hash: H256,
+	/// State root of the block.
+	pub root: H256,
+}
+
+// whether the journal still keeps the given state: the canonical one right before the earliest
+// journalled era, and everything after it.
+fn is_retained(chain: &BlockChain, db: &JournalDB, state: &RetainedState) -> bool {
+	match db.earliest_era() {
+		Some(earliest) if state.number + 1 < earliest => false,
+		Some(earliest) if state.number + 1 == earliest => chain.block_hash(state.number).map_or(false, |hash| hash == state.hash),
+		_ => true,
+	}
+}
+
+/// States of the canonical block before the earliest journalled era and all its descendants.
+pub fn retained_roots(chain: &BlockChain, db: &JournalDB) -> Vec<RetainedState> {
+	let best = chain.best_block_number();
+	let base = match (db.earliest_era(), db.latest_era()) {
+		(Some(earliest), Some(_)) => earliest.saturating_sub(1),
+		_ => best,
+	};
+
+	let mut roots = Vec::new();
+	let mut pending: Vec<H256> = chain.block_hash(base).into_iter().collect();
+	while let Some(hash) = pending.pop() {
+		if let Some(header) = chain.block_header(&hash) {
+			roots.push(RetainedState {
+				number: header.number(),
+				hash: hash.clone(),
+				root: header.state_root().clone(),
+			});
+		}
+		if let Some(details) = chain.block_details(&hash) {
+			pending.extend(details.children);
+		}
+	}
+
+	roots
+}
@@ -38,7 +38,7 @@ use blockchain::TreeRoute;
 use client::{
 	BlockChainClient, MiningBlockChainClient, BlockChainInfo, BlockStatus, BlockId,
 	TransactionId, UncleId, TraceId, TraceFilter, LastHashes, CallAnalytics, BlockImportError,
-	ProvingBlockChainClient,
+	ProvingBlockChainClient, StateGcConfig, StateGcStage, StateGcStatus,
 };
 use db::{NUM_COLUMNS, COL_STATE};
 use header::{Header as BlockHeader, BlockNumber};
@@ -46,7 +46,7 @@ use filter::Filter;
 use log_entry::LocalizedLogEntry;
 use receipt::{Receipt, LocalizedReceipt, TransactionOutcome};
 use blockchain::extras::BlockReceipts;
-use error::{ImportResult, Error as EthcoreError};
+use error::{ImportResult, HistoryError, Error as EthcoreError};
 use evm::{Factory as EvmFactory, VMType};
 use vm::Schedule;
 use miner::{Miner, MinerService, TransactionImportResult};
@@ -54,12 +54,13 @@ use spec::Spec;
 use types::basic_account::BasicAccount;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::state_diff::StateDiff;
 
 use verification::queue::QueueInfo;
 use block::{OpenBlock, SealedBlock, ClosedBlock};
 use executive::Executed;
 use error::CallError;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, StructLog, StructLoggerOptions, Tracer, VMTracer, VMTrace};
 use state_db::StateDB;
 use encoded;
 
@@ -87,6 +88,8 @@ pub struct TestBlockChainClient {
 	pub code: RwLock<HashMap<Address, Bytes>>,
 	/// Execution result.
 	pub execution_result: RwLock<Option<Result<Executed, CallError>>>,
+	/// Struct logs replay result.
+	pub struct_logs_result: RwLock<Option<Result<Executed<Vec<StructLog>>, CallError>>>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionId, LocalizedReceipt>>,
 	/// Logs
@@ -107,8 +110,16 @@ pub struct TestBlockChainClient {
 	pub first_block: RwLock<Option<(H256, u64)>>,
 	/// Traces to return
 	pub traces: RwLock<Option<Vec<LocalizedTrace>>>,
+	/// State diffs stored in the traces database, one per transaction
+	pub state_diffs: RwLock<Option<Vec<StateDiff>>>,
+	/// VM traces stored in the traces database, one per transaction
+	pub vm_traces: RwLock<Option<Vec<VMTrace>>>,
 	/// Pruning history size to report.
 	pub history: RwLock<Option<u64>>,
+	/// State garbage collection progress.
+	pub state_gc: RwLock<Option<StateGcStatus>>,
+	/// Number of the first block whose body wasn't pruned.
+	pub first_body: RwLock<Option<BlockNumber>>,
 }
 
 /// Used for generating test client blocks.
@@ -164,6 +175,7 @@ impl TestBlockChainClient {
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
+			struct_logs_result: RwLock::new(None),
 			receipts: RwLock::new(HashMap::new()),
 			logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
@@ -174,7 +186,11 @@ impl TestBlockChainClient {
 			ancient_block: RwLock::new(None),
 			first_block: RwLock::new(None),
 			traces: RwLock::new(None),
+			state_diffs: RwLock::new(None),
+			vm_traces: RwLock::new(None),
 			history: RwLock::new(None),
+			state_gc: RwLock::new(None),
+			first_body: RwLock::new(None),
 		};
 
 		// insert genesis hash.
@@ -195,6 +211,11 @@ impl TestBlockChainClient {
 		*self.execution_result.write() = Some(result);
 	}
 
+	/// Set the struct logs replay result.
+	pub fn set_struct_logs_result(&self, result: Result<Executed<Vec<StructLog>>, CallError>) {
+		*self.struct_logs_result.write() = Some(result);
+	}
+
 	/// Set the balance of account `address` to `balance`.
 	pub fn set_balance(&self, address: Address, balance: U256) {
 		self.balances.write().insert(address, balance);
@@ -364,6 +385,18 @@ pub fn get_temp_state_db() -> GuardedTempResult<StateDB> {
 	}
 }
 
+/// Strip the parts of preset struct logs which `options` disable, as the real struct logger would.
+fn apply_struct_logger_options(mut executed: Executed<Vec<StructLog>>, options: StructLoggerOptions) -> Executed<Vec<StructLog>> {
+	if let Some(ref mut logs) = executed.vm_trace {
+		for log in logs.iter_mut() {
+			if options.disable_stack { log.stack = None; }
+			if options.disable_memory { log.memory = None; }
+			if options.disable_storage { log.storage = None; }
+		}
+	}
+	executed
+}
+
 impl MiningBlockChainClient for TestBlockChainClient {
 	fn latest_schedule(&self) -> Schedule {
 		Schedule::new_post_eip150(24576, true, true, true)
@@ -429,6 +462,54 @@ impl BlockChainClient for TestBlockChainClient {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Vec<(H256, Executed)>, CallError> {
+		let body = self.block_body(block).map_err(|_| CallError::StatePruned)?;
+		body.transactions().into_iter().enumerate().map(|(index, t)| {
+			let result = self.replay(TransactionId::Location(block, index), analytics)?;
+			Ok((t.hash(), result))
+		}).collect()
+	}
+
+	fn replay_struct_logs(&self, _id: TransactionId, options: StructLoggerOptions) -> Result<Executed<Vec<StructLog>>, CallError> {
+		self.struct_logs_result.read().clone().unwrap().map(|e| apply_struct_logger_options(e, options))
+	}
+
+	fn replay_block_struct_logs(&self, _block: BlockId, options: StructLoggerOptions) -> Result<Vec<Executed<Vec<StructLog>>>, CallError> {
+		self.struct_logs_result.read().clone().unwrap().map(|e| vec![apply_struct_logger_options(e, options)])
+	}
+
+	fn replay_with_tracers<T, V>(&self, _id: TransactionId, tracer: T, vm_tracer: V) -> Result<Executed<V::Output>, CallError> where
+		T: Tracer,
+		V: VMTracer,
+	{
+		self.execution_result.read().clone().unwrap().map(|e| Executed {
+			exception: e.exception,
+			gas: e.gas,
+			gas_used: e.gas_used,
+			refunded: e.refunded,
+			cumulative_gas_used: e.cumulative_gas_used,
+			logs: e.logs,
+			contracts_created: e.contracts_created,
+			output: e.output,
+			trace: tracer.drain(),
+			vm_trace: vm_tracer.drain(),
+			state_diff: e.state_diff,
+		})
+	}
+
+	fn replay_block_with_tracers<T, V, F>(&self, block: BlockId, mut tracers: F) -> Result<Vec<Executed<V::Output>>, CallError> where
+		T: Tracer,
+		V: VMTracer,
+		F: FnMut(&SignedTransaction) -> (T, V),
+	{
+		let body = self.block_body(block).map_err(|_| CallError::StatePruned)?;
+		body.transactions().into_iter().enumerate().map(|(index, t)| {
+			let t = SignedTransaction::new(t).map_err(|_| CallError::TransactionNotFound)?;
+			let (tracer, vm_tracer) = tracers(&t);
+			self.replay_with_tracers(TransactionId::Location(block, index), tracer, vm_tracer)
+		}).collect()
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -535,20 +616,35 @@ impl BlockChainClient for TestBlockChainClient {
 			.map(encoded::Header::new)
 	}
 
-	fn block_number(&self, _id: BlockId) -> Option<BlockNumber> {
-		unimplemented!()
+	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
+		match id {
+			BlockId::Number(number) => Some(number),
+			BlockId::Earliest => Some(0),
+			BlockId::Latest | BlockId::Pending => Some(self.chain_info().best_block_number),
+			BlockId::Hash(hash) => self.numbers.read().iter().find(|&(_, h)| *h == hash).map(|(n, _)| *n as BlockNumber),
+		}
 	}
 
-	fn block_body(&self, id: BlockId) -> Option<encoded::Body> {
+	fn block_body(&self, id: BlockId) -> Result<encoded::Body, HistoryError> {
+		if let (BlockId::Number(number), Some(first)) = (id, *self.first_body.read()) {
+			if number < first {
+				return Err(HistoryError::Pruned);
+			}
+		}
+
 		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).map(|r| {
 			let mut stream = RlpStream::new_list(2);
 			stream.append_raw(Rlp::new(r).at(1).as_raw(), 1);
 			stream.append_raw(Rlp::new(r).at(2).as_raw(), 1);
 			encoded::Body::new(stream.out())
-		}))
+		})).ok_or(HistoryError::UnknownBlock)
 	}
 
 	fn block(&self, id: BlockId) -> Option<encoded::Block> {
+		if let Err(HistoryError::Pruned) = self.block_body(id) {
+			return None;
+		}
+
 		self.block_hash(id)
 			.and_then(|hash| self.blocks.read().get(&hash).cloned())
 			.map(encoded::Block::new)
@@ -729,6 +825,22 @@ impl BlockChainClient for TestBlockChainClient {
 		self.traces.read().clone()
 	}
 
+	fn transaction_state_diff(&self, _transaction: TransactionId) -> Option<StateDiff> {
+		self.state_diffs.read().clone().and_then(|vec| vec.into_iter().next())
+	}
+
+	fn block_state_diffs(&self, _block: BlockId) -> Option<Vec<StateDiff>> {
+		self.state_diffs.read().clone()
+	}
+
+	fn transaction_vm_trace(&self, _transaction: TransactionId) -> Option<VMTrace> {
+		self.vm_traces.read().clone().and_then(|vec| vec.into_iter().next())
+	}
+
+	fn block_vm_traces(&self, _block: BlockId) -> Option<Vec<VMTrace>> {
+		self.vm_traces.read().clone()
+	}
+
 	fn queue_transactions(&self, transactions: Vec<Bytes>, _peer_id: usize) {
 		// import right here
 		let txs = transactions.into_iter().filter_map(|bytes| UntrustedRlp::new(&bytes).as_val().ok()).collect();
@@ -759,11 +871,60 @@ impl BlockChainClient for TestBlockChainClient {
 	fn pruning_info(&self) -> PruningInfo {
 		let best_num = self.chain_info().best_block_number;
 		PruningInfo {
-			earliest_chain: 1,
+			earliest_chain: self.first_body.read().unwrap_or(1),
 			earliest_state: self.history.read().as_ref().map(|x| best_num - x).unwrap_or(0),
 		}
 	}
 
+	fn start_state_gc(&self, _config: StateGcConfig) -> Result<(), String> {
+		let mut state_gc = self.state_gc.write();
+		if state_gc.as_ref().map_or(false, |s| s.is_running()) {
+			return Err("State garbage collection is already running".into());
+		}
+		*state_gc = Some(StateGcStatus {
+			stage: StateGcStage::Marking,
+			marked: 0,
+			examined: 0,
+			removed: 0,
+			progress: 0,
+		});
+		Ok(())
+	}
+
+	fn stop_state_gc(&self) {
+		if let Some(ref mut status) = *self.state_gc.write() {
+			if status.is_running() {
+				status.stage = StateGcStage::Aborted;
+			}
+		}
+	}
+
+	fn state_gc_status(&self) -> Option<StateGcStatus> {
+		self.state_gc.read().clone()
+	}
+
+	fn reset_to(&self, id: BlockId) -> Result<H256, String> {
+		let hash = self.block_hash(id).ok_or_else(|| format!("Unknown block: {:?}", id))?;
+		let mut numbers = self.numbers.write();
+		let number = match numbers.iter().find(|&(_, h)| *h == hash) {
+			Some((number, _)) => *number,
+			None => return Err(format!("Block {} is not canonical", hash)),
+		};
+		let best = numbers.len() - 1;
+		if self.history.read().map_or(false, |history| number as u64 + history < best as u64) {
+			return Err(format!("State of block #{} has been pruned", number));
+		}
+
+		let mut blocks = self.blocks.write();
+		for n in (number + 1)..(best + 1) {
+			if let Some(hash) = numbers.remove(&n) {
+				blocks.remove(&hash);
+			}
+		}
+		*self.last_hash.write() = hash;
+		Ok(hash)
+	}
+
 	fn call_contract(&self, _id: BlockId, _address: Address, _data: Bytes) -> Result<Bytes, String> { Ok(vec![]) }
 
 	fn transact_contract(&self, address: Address, data: Bytes) -> Result<TransactionImportResult, EthcoreError> {
@@ -19,9 +19,10 @@ use itertools::Itertools;
 
 use block::{OpenBlock, SealedBlock, ClosedBlock};
 use blockchain::TreeRoute;
+use client::{StateGcConfig, StateGcStatus};
 use encoded;
 use vm::LastHashes;
-use error::{ImportResult, CallError, Error as EthcoreError};
+use error::{ImportResult, CallError, HistoryError, Error as EthcoreError};
 use error::{TransactionImportResult, BlockImportError};
 use evm::{Factory as EvmFactory, Schedule};
 use executive::Executed;
@@ -29,7 +30,7 @@ use filter::Filter;
 use header::{BlockNumber};
 use log_entry::LocalizedLogEntry;
 use receipt::LocalizedReceipt;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, StructLog, StructLoggerOptions, Tracer, VMTracer, VMTrace};
 use transaction::{LocalizedTransaction, PendingTransaction, SignedTransaction};
 use verification::queue::QueueInfo as BlockQueueInfo;
 
@@ -47,6 +48,7 @@ use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::state_diff::StateDiff;
 
 /// Blockchain database client. Owns and manages a blockchain and a block queue.
 pub trait BlockChainClient : Sync + Send {
@@ -59,7 +61,8 @@ pub trait BlockChainClient : Sync + Send {
 
 	/// Get raw block body data by block id.
 	/// Block body is an RLP list of two items: uncles and transactions.
-	fn block_body(&self, id: BlockId) -> Option<encoded::Body>;
+	/// Fails with `HistoryError::Pruned` if the body was removed by history pruning.
+	fn block_body(&self, id: BlockId) -> Result<encoded::Body, HistoryError>;
 
 	/// Get raw block data by block header hash.
 	fn block(&self, id: BlockId) -> Option<encoded::Block>;
@@ -196,6 +199,30 @@ pub trait BlockChainClient : Sync + Send {
 	/// Replays a given transaction for inspection.
 	fn replay(&self, t: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
+	/// Replays all transactions of a given block for inspection.
+	/// Returns the hash of each transaction along with its result.
+	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Vec<(H256, Executed)>, CallError>;
+
+	/// Replays a given transaction, collecting geth-style struct logs.
+	fn replay_struct_logs(&self, t: TransactionId, options: StructLoggerOptions) -> Result<Executed<Vec<StructLog>>, CallError>;
+
+	/// Replays all transactions of a given block, collecting geth-style struct logs.
+	fn replay_block_struct_logs(&self, block: BlockId, options: StructLoggerOptions) -> Result<Vec<Executed<Vec<StructLog>>>, CallError>;
+
+	/// Replays a given transaction with the given tracers.
+	fn replay_with_tracers<T, V>(&self, t: TransactionId, tracer: T, vm_tracer: V) -> Result<Executed<V::Output>, CallError> where
+		Self: Sized,
+		T: Tracer,
+		V: VMTracer;
+
+	/// Replays all transactions of a given block in a single pass. Each transaction is traced
+	/// with the tracers returned by `tracers` for it.
+	fn replay_block_with_tracers<T, V, F>(&self, block: BlockId, tracers: F) -> Result<Vec<Executed<V::Output>>, CallError> where
+		Self: Sized,
+		T: Tracer,
+		V: VMTracer,
+		F: FnMut(&SignedTransaction) -> (T, V);
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -208,6 +235,18 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns traces created by transaction from block.
 	fn block_traces(&self, trace: BlockId) -> Option<Vec<LocalizedTrace>>;
 
+	/// Returns the state diff of a transaction, if stored in the traces database.
+	fn transaction_state_diff(&self, transaction: TransactionId) -> Option<StateDiff>;
+
+	/// Returns the state diffs of all transactions in a block, if stored in the traces database.
+	fn block_state_diffs(&self, block: BlockId) -> Option<Vec<StateDiff>>;
+
+	/// Returns the VM trace of a transaction, if stored in the traces database.
+	fn transaction_vm_trace(&self, transaction: TransactionId) -> Option<VMTrace>;
+
+	/// Returns the VM traces of all transactions in a block, if stored in the traces database.
+	fn block_vm_traces(&self, block: BlockId) -> Option<Vec<VMTrace>>;
+
 	/// Get last hashes starting from best block.
 	fn last_hashes(&self) -> LastHashes;
 
@@ -269,6 +308,21 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns information about pruning/data availability.
 	fn pruning_info(&self) -> PruningInfo;
 
+	/// Start removing the state which isn't reachable from the retained blocks from the database
+	/// in the background.
+	fn start_state_gc(&self, config: StateGcConfig) -> Result<(), String>;
+
+	/// Abort the state garbage collection, if running.
+	fn stop_state_gc(&self);
+
+	/// Progress of the last state garbage collection started.
+	fn state_gc_status(&self) -> Option<StateGcStatus>;
+
+	/// Make the given canonical block the best block, removing the later blocks from the chain
+	/// and reverting their state. Fails if the state of the block was pruned.
+	/// Returns the hash of the new best block.
+	fn reset_to(&self, id: BlockId) -> Result<H256, String>;
+
 	/// Like `call`, but with various defaults. Designed to be used for calling contracts.
 	fn call_contract(&self, id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String>;
 
@@ -41,8 +41,14 @@ pub const COL_ACCOUNT_BLOOM: Option<u32> = Some(5);
 pub const COL_NODE_INFO: Option<u32> = Some(6);
 /// Column for the light client chain.
 pub const COL_LIGHT_CHAIN: Option<u32> = Some(7);
+/// Column for the flat state index.
+pub const COL_FLAT_STATE: Option<u32> = Some(8);
+/// Column for the state diffs of traced blocks.
+pub const COL_STATE_DIFF: Option<u32> = Some(9);
+/// Column for the VM traces of traced blocks.
+pub const COL_VM_TRACE: Option<u32> = Some(10);
 /// Number of columns in DB
-pub const NUM_COLUMNS: Option<u32> = Some(8);
+pub const NUM_COLUMNS: Option<u32> = Some(11);
 
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
@@ -0,0 +1,353 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Integrity check of the blockchain and of the state of the best block.
+
+use std::fmt;
+use std::sync::Arc;
+
+use bigint::prelude::U256;
+use bigint::hash::H256;
+use hash::keccak;
+use journaldb::{self, Algorithm, JournalDB};
+use kvdb::{DBTransaction, KeyValueDB};
+use rlp::UntrustedRlp;
+use trie::TrieError;
+use triehash::ordered_trie_root;
+use util::HashDB;
+
+use blockchain::{BlockChain, BlockProvider};
+use blockchain::extras::{BlockDetails, ExtrasIndex};
+use client::BlockId;
+use db::{self, Readable};
+use encoded;
+use error::Error;
+use header::BlockNumber;
+use snapshot::Error as SnapshotError;
+use state::walk_state;
+
+/// Maximum number of states walked while looking for the latest complete one.
+/// Each walk visits the whole state, so only the few latest blocks are tried.
+const MAX_STATE_WALKS: usize = 4;
+
+/// Number of blocks checked between two collections of the chain caches.
+const GARBAGE_COLLECTION_INTERVAL: u64 = 1000;
+
+/// Inconsistency found in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inconsistency {
+	/// The details, header or body of the best block are missing.
+	BestBlock(H256),
+	/// No canonical block is known at this number.
+	MissingBlock(BlockNumber),
+	/// The header of the block is missing or stored under another hash or number.
+	Header(H256),
+	/// The block is not a child of the previous canonical block.
+	Parent(H256),
+	/// The details of the block are missing or don't match its header.
+	Details(H256),
+	/// The body of the block is missing or doesn't match its header.
+	Body(H256),
+	/// The receipts of the block are missing or don't match its header.
+	Receipts(H256),
+	/// The address of a transaction is missing or points to another block.
+	TransactionAddress {
+		/// Hash of the block.
+		block: H256,
+		/// Hash of the transaction.
+		transaction: H256,
+	},
+	/// The log bloom of the block is missing from the bloom groups.
+	Blooms(H256),
+	/// A node of the state of the block is missing.
+	State {
+		/// Hash of the block.
+		block: H256,
+		/// Key of the missing node.
+		missing: H256,
+	},
+}
+
+impl fmt::Display for Inconsistency {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Inconsistency::BestBlock(ref hash) => write!(f, "Best block {} is incomplete", hash),
+			Inconsistency::MissingBlock(number) => write!(f, "No canonical block at #{}", number),
+			Inconsistency::Header(ref hash) => write!(f, "Header of block {} is missing or invalid", hash),
+			Inconsistency::Parent(ref hash) => write!(f, "Block {} is not a child of the previous canonical block", hash),
+			Inconsistency::Details(ref hash) => write!(f, "Details of block {} are missing or invalid", hash),
+			Inconsistency::Body(ref hash) => write!(f, "Body of block {} is missing or does not match its header", hash),
+			Inconsistency::Receipts(ref hash) => write!(f, "Receipts of block {} are missing or do not match its header", hash),
+			Inconsistency::TransactionAddress { ref block, ref transaction } =>
+				write!(f, "Address of transaction {} of block {} is missing or invalid", transaction, block),
+			Inconsistency::Blooms(ref hash) => write!(f, "Log bloom of block {} is missing", hash),
+			Inconsistency::State { ref block, ref missing } => write!(f, "State of block {} is incomplete: node {} is missing", block, missing),
+		}
+	}
+}
+
+/// Result of a database check.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CheckReport {
+	/// Number of blocks checked.
+	pub blocks: u64,
+	/// First inconsistency found, `None` if the database is consistent.
+	pub inconsistency: Option<Inconsistency>,
+	/// Latest block up to which the chain is consistent and whose state is complete.
+	/// Blocks preceding the first checked one are assumed to be consistent.
+	pub last_consistent: Option<(BlockNumber, H256)>,
+	/// Whether the best block, which couldn't be read, was moved to the latest complete block.
+	pub repaired: bool,
+}
+
+/// Check that the canonical chain is consistent from the given block up to the best block,
+/// and that the state of the best block is complete.
+///
+/// Headers have to chain, bodies and receipts have to match the roots of their headers,
+/// and block details, transaction addresses and bloom groups have to match the blocks.
+/// Bodies and receipts removed by history pruning are not checked.
+///
+/// With `repair`, a best block which can't be read is moved to the latest canonical block
+/// which can, so that a client can be opened. Rewinding to `last_consistent` is left to
+/// `BlockChainClient::reset_to`, which also reverts the state journal.
+pub fn check_database(db: Arc<KeyValueDB>, genesis: &[u8], algorithm: Algorithm, from: BlockId, repair: bool) -> Result<CheckReport, Error> {
+	let mut report = CheckReport::default();
+	if let Some(best) = incomplete_best_block(&*db)? {
+		report.inconsistency = Some(Inconsistency::BestBlock(best));
+		if !repair || !reset_best_block(&*db)? {
+			return Ok(report);
+		}
+		report.repaired = true;
+	}
+
+	let chain = BlockChain::new(Default::default(), genesis, db.clone());
+	let best_number = chain.best_block_number();
+	let start = match from {
+		BlockId::Hash(hash) => chain.block_number(&hash).ok_or(SnapshotError::BlockNotFound(hash))?,
+		BlockId::Number(number) => number,
+		BlockId::Earliest => 0,
+		BlockId::Latest | BlockId::Pending => best_number,
+	};
+
+	// blocks missing between the ancient blocks and the first block after a snapshot restoration.
+	let gap = match chain.first_block_number() {
+		Some(first) => chain.best_ancient_number().map_or(1, |n| n + 1)..first,
+		None => 0..0,
+	};
+
+	let mut parent = match start {
+		0 => None,
+		_ => chain.block_hash(start - 1).and_then(|hash| chain.block_details(&hash).map(|details| (hash, details))),
+	};
+	let mut last_consistent = parent.as_ref().map(|&(ref hash, ref details)| (details.number, *hash));
+
+	for number in start..(best_number + 1) {
+		if number >= gap.start && number < gap.end {
+			parent = None;
+			continue;
+		}
+
+		// the chain caches every block read, like it does while importing.
+		if number % GARBAGE_COLLECTION_INTERVAL == 0 {
+			chain.collect_garbage();
+		}
+
+		let result = check_block(&chain, number, parent.as_ref());
+		match result {
+			Ok((hash, details)) => {
+				report.blocks += 1;
+				last_consistent = Some((number, hash));
+				parent = Some((hash, details));
+			},
+			Err(inconsistency) => {
+				report.inconsistency = Some(inconsistency);
+				break;
+			},
+		}
+	}
+
+	// look for the latest consistent block with a complete state.
+	let state_db = journaldb::new(db.clone(), algorithm, db::COL_STATE);
+	let earliest_era = state_db.earliest_era().unwrap_or(0);
+	let mut walks = 0;
+	while let Some((number, hash)) = last_consistent {
+		if number < earliest_era || walks == MAX_STATE_WALKS {
+			last_consistent = None;
+			break;
+		}
+
+		// the block preceding the first checked one may have no header.
+		let state_root = match chain.block_header_data(&hash) {
+			Some(header) => header.state_root(),
+			None => {
+				last_consistent = None;
+				break;
+			},
+		};
+		walks += 1;
+		match missing_state(&*state_db, &state_root)? {
+			None => break,
+			Some(missing) => {
+				if report.inconsistency.is_none() {
+					report.inconsistency = Some(Inconsistency::State { block: hash, missing: missing });
+				}
+				last_consistent = match number {
+					0 => None,
+					_ => chain.block_hash(number - 1).map(|hash| (number - 1, hash)),
+				};
+			},
+		}
+	}
+	report.last_consistent = last_consistent;
+	Ok(report)
+}
+
+// check a canonical block against its parent, returning its hash and details.
+fn check_block(chain: &BlockChain, number: BlockNumber, parent: Option<&(H256, BlockDetails)>) -> Result<(H256, BlockDetails), Inconsistency> {
+	let hash = chain.block_hash(number).ok_or(Inconsistency::MissingBlock(number))?;
+	let header = chain.block_header_data(&hash).ok_or(Inconsistency::Header(hash))?;
+	if header.hash() != hash || header.number() != number {
+		return Err(Inconsistency::Header(hash));
+	}
+	if let Some(&(ref parent_hash, _)) = parent {
+		if header.parent_hash() != *parent_hash {
+			return Err(Inconsistency::Parent(hash));
+		}
+	}
+
+	let total_difficulty = match parent {
+		Some(&(_, ref details)) => Some(details.total_difficulty + header.difficulty()),
+		None if number == 0 => Some(header.difficulty()),
+		None => None,
+	};
+	let details = match chain.block_details(&hash) {
+		Some(details) => details,
+		None => return Err(Inconsistency::Details(hash)),
+	};
+	if details.number != number || details.parent != header.parent_hash() ||
+		total_difficulty.map_or(false, |td: U256| td != details.total_difficulty) {
+		return Err(Inconsistency::Details(hash));
+	}
+
+	// bodies, receipts and transaction addresses of old blocks may be pruned.
+	if number >= chain.first_body_number().unwrap_or(0) {
+		let transactions = check_body(chain, &hash, &header).ok_or(Inconsistency::Body(hash))?;
+
+		if number > 0 {
+			let receipts = chain.block_receipts(&hash).ok_or(Inconsistency::Receipts(hash))?;
+			if ordered_trie_root(receipts.receipts.iter().map(|r| ::rlp::encode(r).into_vec())) != header.receipts_root() {
+				return Err(Inconsistency::Receipts(hash));
+			}
+		}
+
+		for (index, transaction) in transactions.into_iter().enumerate() {
+			match chain.transaction_address(&transaction) {
+				Some(ref address) if address.block_hash == hash && address.index == index => {},
+				_ => return Err(Inconsistency::TransactionAddress { block: hash, transaction: transaction }),
+			}
+		}
+	}
+
+	let bloom = header.log_bloom();
+	if !bloom.is_zero() && !chain.blocks_with_bloom(&bloom, number, number).contains(&number) {
+		return Err(Inconsistency::Blooms(hash));
+	}
+
+	Ok((hash, details))
+}
+
+// check the body against the header, returning the transaction hashes.
+fn check_body(chain: &BlockChain, hash: &H256, header: &encoded::Header) -> Option<Vec<H256>> {
+	let body = match chain.block_body(hash) {
+		Some(body) => body.into_inner(),
+		None => return None,
+	};
+	let body = UntrustedRlp::new(&body);
+	let (transactions, uncles) = match (body.at(0), body.at(1)) {
+		(Ok(transactions), Ok(uncles)) => (transactions, uncles),
+		_ => return None,
+	};
+
+	if ordered_trie_root(transactions.iter().map(|r| r.as_raw().to_vec())) != header.transactions_root() ||
+		keccak(uncles.as_raw()) != header.uncles_hash() {
+		return None;
+	}
+
+	Some(transactions.iter().map(|r| keccak(r.as_raw())).collect())
+}
+
+// walk the state under `root`, returning the key of the first missing node.
+fn missing_state(state_db: &JournalDB, root: &H256) -> Result<Option<H256>, Error> {
+	let mut missing = None;
+	let result = walk_state(root, &mut |key| match state_db.get(&key) {
+		Some(value) => Ok(Some(value)),
+		None => {
+			missing = Some(key);
+			Err(TrieError::IncompleteDatabase(key).into())
+		},
+	});
+
+	match missing {
+		Some(key) => Ok(Some(key)),
+		None => result.map(|_| None),
+	}
+}
+
+// whether the block has details, a header and a body.
+fn is_complete(db: &KeyValueDB, hash: &H256) -> Result<bool, Error> {
+	let details: Option<BlockDetails> = db.read(db::COL_EXTRA, hash);
+	Ok(details.is_some() && db.get(db::COL_HEADERS, hash)?.is_some() && db.get(db::COL_BODIES, hash)?.is_some())
+}
+
+// the best block, if it can't be loaded.
+fn incomplete_best_block(db: &KeyValueDB) -> Result<Option<H256>, Error> {
+	match db.get(db::COL_EXTRA, b"best")? {
+		Some(best) => {
+			let best = H256::from_slice(&best);
+			Ok(match is_complete(db, &best)? {
+				true => None,
+				false => Some(best),
+			})
+		},
+		None => Ok(None),
+	}
+}
+
+// make the latest complete canonical block the best block, removing the later canonical hashes.
+// returns false if there is no such block.
+fn reset_best_block(db: &KeyValueDB) -> Result<bool, Error> {
+	let mut canonical: Vec<(BlockNumber, H256, Box<[u8]>)> = db.iter(db::COL_EXTRA)
+		.filter(|&(ref key, _)| key.len() == 5 && key[0] == ExtrasIndex::BlockHash as u8)
+		.filter_map(|(key, value)| {
+			let number = key[1..].iter().fold(0, |n, b| (n << 8) + *b as BlockNumber);
+			UntrustedRlp::new(&value).as_val::<H256>().ok().map(|hash| (number, hash, key))
+		})
+		.collect();
+	canonical.sort_by(|a, b| b.0.cmp(&a.0));
+
+	let mut batch = DBTransaction::new();
+	for (number, hash, key) in canonical {
+		if is_complete(db, &hash)? {
+			info!("Moving best block to #{} ({})", number, hash);
+			batch.put(db::COL_EXTRA, b"best", &hash);
+			db.write(batch)?;
+			return Ok(true);
+		}
+		batch.delete(db::COL_EXTRA, &key);
+	}
+
+	Ok(false)
+}
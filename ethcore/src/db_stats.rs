@@ -0,0 +1,242 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Statistics of the database columns and of the state.
+
+use std::sync::Arc;
+
+use bigint::hash::H256;
+use hash::KECCAK_EMPTY;
+use journaldb::{self, Algorithm, JournalDB};
+use kvdb::KeyValueDB;
+use rlp::UntrustedRlp;
+use trie::TrieError;
+use util::{HashDB, DBValue};
+
+use blockchain::{BlockChain, BlockProvider};
+use client::BlockId;
+use db;
+use error::Error;
+use header::BlockNumber;
+use state::{walk_state_with, StateVisitor};
+use types::basic_account::BasicAccount;
+
+/// Number of buckets of the value size histograms. Bucket `i` counts the values
+/// of at most `2^i` bytes, except the last one which counts all larger values.
+pub const SIZE_BUCKETS: usize = 17;
+
+const COLUMN_NAMES: &'static [(Option<u32>, &'static str)] = &[
+	(None, "default"),
+	(db::COL_STATE, "state"),
+	(db::COL_HEADERS, "headers"),
+	(db::COL_BODIES, "bodies"),
+	(db::COL_EXTRA, "extras"),
+	(db::COL_TRACE, "traces"),
+	(db::COL_ACCOUNT_BLOOM, "account bloom"),
+	(db::COL_NODE_INFO, "node info"),
+	(db::COL_LIGHT_CHAIN, "light chain"),
+	(db::COL_FLAT_STATE, "flat state"),
+	(db::COL_STATE_DIFF, "state diffs"),
+	(db::COL_VM_TRACE, "vm traces"),
+];
+
+/// Statistics of a database column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+	/// The column, `None` for the default one.
+	pub column: Option<u32>,
+	/// Number of keys.
+	pub keys: u64,
+	/// Total size of the keys in bytes.
+	pub key_bytes: u64,
+	/// Total size of the values in bytes.
+	pub value_bytes: u64,
+	/// Histogram of the value sizes; see `SIZE_BUCKETS`.
+	pub value_sizes: [u64; SIZE_BUCKETS],
+}
+
+impl ColumnStats {
+	/// Human readable name of the column.
+	pub fn name(&self) -> &'static str {
+		COLUMN_NAMES.iter()
+			.find(|&&(column, _)| column == self.column)
+			.map_or("unknown", |&(_, name)| name)
+	}
+}
+
+/// Statistics of the state trie of a block and of the storage tries under it.
+/// Nodes shared by several tries are counted for each of them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StateStats {
+	/// Number of the block.
+	pub number: BlockNumber,
+	/// Hash of the block.
+	pub hash: H256,
+	/// Number of accounts.
+	pub accounts: u64,
+	/// Number of accounts with code.
+	pub contracts: u64,
+	/// Number of non-zero storage slots.
+	pub storage_slots: u64,
+	/// Number of trie nodes and code entries read from the database.
+	pub entries: u64,
+	/// Total size of these entries in bytes.
+	pub entry_bytes: u64,
+	/// Number of accounts by the number of nodes above them in the state trie.
+	pub account_depths: Vec<u64>,
+	/// Number of storage slots by the number of nodes above them in their storage trie.
+	pub storage_depths: Vec<u64>,
+}
+
+/// Gather the statistics of all database columns.
+pub fn column_stats(db: &KeyValueDB) -> Vec<ColumnStats> {
+	::std::iter::once(None)
+		.chain((0..db::NUM_COLUMNS.unwrap_or(0)).map(Some))
+		.map(|column| {
+			let mut stats = ColumnStats {
+				column: column,
+				keys: 0,
+				key_bytes: 0,
+				value_bytes: 0,
+				value_sizes: [0; SIZE_BUCKETS],
+			};
+			for (key, value) in db.iter(column) {
+				stats.keys += 1;
+				stats.key_bytes += key.len() as u64;
+				stats.value_bytes += value.len() as u64;
+				stats.value_sizes[size_bucket(value.len())] += 1;
+			}
+			stats
+		})
+		.collect()
+}
+
+/// Walk the state of the given block and gather its statistics.
+/// Returns `None` if the block is not known.
+pub fn state_stats(db: Arc<KeyValueDB>, genesis: &[u8], algorithm: Algorithm, at: BlockId) -> Result<Option<StateStats>, Error> {
+	let chain = BlockChain::new(Default::default(), genesis, db.clone());
+	let hash = match at {
+		BlockId::Hash(hash) => Some(hash),
+		BlockId::Number(number) => chain.block_hash(number),
+		BlockId::Earliest => chain.block_hash(0),
+		BlockId::Latest | BlockId::Pending => Some(chain.best_block_hash()),
+	};
+	let header = match hash.and_then(|hash| chain.block_header(&hash)) {
+		Some(header) => header,
+		None => return Ok(None),
+	};
+
+	let state_db = journaldb::new(db, algorithm, db::COL_STATE);
+	if !state_db.contains(header.state_root()) {
+		return Err(TrieError::InvalidStateRoot(*header.state_root()).into());
+	}
+
+	let mut visitor = StatsVisitor {
+		db: &*state_db,
+		stats: StateStats {
+			number: header.number(),
+			hash: header.hash(),
+			..Default::default()
+		},
+	};
+	walk_state_with(header.state_root(), &mut visitor)?;
+	Ok(Some(visitor.stats))
+}
+
+// the histogram bucket of a value size.
+fn size_bucket(size: usize) -> usize {
+	let bits = (0usize.leading_zeros() - size.saturating_sub(1).leading_zeros()) as usize;
+	::std::cmp::min(bits, SIZE_BUCKETS - 1)
+}
+
+struct StatsVisitor<'a> {
+	db: &'a JournalDB,
+	stats: StateStats,
+}
+
+impl<'a> StateVisitor for StatsVisitor<'a> {
+	fn visit(&mut self, key: H256) -> Result<Option<DBValue>, Error> {
+		let value = self.db.get(&key).ok_or_else(|| TrieError::IncompleteDatabase(key))?;
+		self.stats.entries += 1;
+		self.stats.entry_bytes += value.len() as u64;
+		Ok(Some(value))
+	}
+
+	fn leaf(&mut self, address_hash: Option<&H256>, depth: usize, value: &[u8]) {
+		let depths = match address_hash {
+			None => {
+				self.stats.accounts += 1;
+				match UntrustedRlp::new(value).as_val::<BasicAccount>() {
+					Ok(ref account) if account.code_hash != KECCAK_EMPTY => self.stats.contracts += 1,
+					Ok(_) => {},
+					Err(e) => warn!("Invalid account in the state trie: {}", e),
+				}
+				&mut self.stats.account_depths
+			},
+			Some(_) => {
+				self.stats.storage_slots += 1;
+				&mut self.stats.storage_depths
+			},
+		};
+
+		if depths.len() <= depth {
+			depths.resize(depth + 1, 0);
+		}
+		depths[depth] += 1;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{size_bucket, column_stats, SIZE_BUCKETS};
+	use kvdb::{DBTransaction, KeyValueDB};
+	use db;
+
+	#[test]
+	fn buckets_value_sizes() {
+		assert_eq!(size_bucket(0), 0);
+		assert_eq!(size_bucket(1), 0);
+		assert_eq!(size_bucket(2), 1);
+		assert_eq!(size_bucket(3), 2);
+		assert_eq!(size_bucket(4), 2);
+		assert_eq!(size_bucket(5), 3);
+		assert_eq!(size_bucket(1024), 10);
+		assert_eq!(size_bucket(1 << 20), SIZE_BUCKETS - 1);
+	}
+
+	#[test]
+	fn gathers_column_stats() {
+		let kvdb = ::kvdb_memorydb::create(db::NUM_COLUMNS.unwrap_or(0));
+		let mut batch = DBTransaction::new();
+		batch.put(db::COL_HEADERS, b"a", &[0u8; 100]);
+		batch.put(db::COL_HEADERS, b"bb", &[0u8; 4]);
+		batch.put(db::COL_BODIES, b"c", &[]);
+		kvdb.write(batch).unwrap();
+
+		let stats = column_stats(&kvdb);
+		assert_eq!(stats.len(), db::NUM_COLUMNS.unwrap() as usize + 1);
+
+		let headers = stats.iter().find(|s| s.column == db::COL_HEADERS).unwrap();
+		assert_eq!(headers.name(), "headers");
+		assert_eq!((headers.keys, headers.key_bytes, headers.value_bytes), (2, 3, 104));
+		assert_eq!(headers.value_sizes[2], 1);
+		assert_eq!(headers.value_sizes[7], 1);
+
+		let bodies = stats.iter().find(|s| s.column == db::COL_BODIES).unwrap();
+		assert_eq!((bodies.keys, bodies.value_sizes[0]), (1, 1));
+		assert_eq!(stats.iter().find(|s| s.column == db::COL_STATE).unwrap().keys, 0);
+	}
+}
@@ -0,0 +1,196 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique signer set, recent signers and vote tally.
+
+use std::collections::{BTreeMap, BTreeSet};
+use bigint::hash::H64;
+use bigint::prelude::U256;
+use engines::EngineError;
+use error::{BlockError, Error};
+use header::{BlockNumber, Header};
+use rlp::UntrustedRlp;
+use unexpected::Mismatch;
+use util::Address;
+use super::{extract_signers, recover_signer, DIFF_INTURN, DIFF_NOTURN, NONCE_AUTH_VOTE, NONCE_DROP_VOTE};
+
+/// Kind of a vote cast through the header nonce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteType {
+	/// Vote to add the beneficiary to the signer set.
+	Add,
+	/// Vote to remove the beneficiary from the signer set.
+	Remove,
+}
+
+impl VoteType {
+	/// Decode the vote from a header nonce.
+	pub fn from_nonce(nonce: H64) -> Result<Self, Error> {
+		if nonce == NONCE_AUTH_VOTE {
+			Ok(VoteType::Add)
+		} else if nonce == NONCE_DROP_VOTE {
+			Ok(VoteType::Remove)
+		} else {
+			Err(EngineError::CliqueInvalidNonce(nonce).into())
+		}
+	}
+
+	/// Header nonce casting this vote.
+	pub fn as_nonce(&self) -> H64 {
+		match *self {
+			VoteType::Add => NONCE_AUTH_VOTE,
+			VoteType::Remove => NONCE_DROP_VOTE,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Vote {
+	signer: Address,
+	beneficiary: Address,
+	kind: VoteType,
+}
+
+/// Clique consensus state after a given block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliqueBlockState {
+	/// Authorized signers, in ascending order.
+	signers: BTreeSet<Address>,
+	/// Signers of the most recent blocks, by block number.
+	recent_signers: BTreeMap<BlockNumber, Address>,
+	/// Pending votes since the last checkpoint.
+	votes: Vec<Vote>,
+}
+
+impl CliqueBlockState {
+	/// Create the state from a checkpoint header, taking the signer list from its extra data.
+	pub fn from_checkpoint(header: &Header) -> Result<Self, Error> {
+		Ok(CliqueBlockState {
+			signers: extract_signers(header)?.into_iter().collect(),
+			recent_signers: BTreeMap::new(),
+			votes: Vec::new(),
+		})
+	}
+
+	/// Create the state from a checkpoint header, recovering the signers of the blocks
+	/// leading up to it, which checkpoints don't record, through `parent_of`.
+	pub fn from_checkpoint_with_recents<F>(header: &Header, parent_of: F) -> Result<Self, Error>
+		where F: Fn(&Header) -> Result<Header, Error>
+	{
+		let mut state = Self::from_checkpoint(header)?;
+		if header.number() == 0 {
+			return Ok(state);
+		}
+
+		let limit = state.signers.len() as u64 / 2 + 1;
+		let mut ancestor = header.clone();
+		loop {
+			state.recent_signers.insert(ancestor.number(), recover_signer(&ancestor)?);
+			if ancestor.number() == 1 || header.number() - ancestor.number() + 1 >= limit {
+				break;
+			}
+			ancestor = parent_of(&ancestor)?;
+		}
+
+		Ok(state)
+	}
+
+	/// Authorized signers, in ascending order.
+	pub fn signers(&self) -> &BTreeSet<Address> {
+		&self.signers
+	}
+
+	/// Whether `signer` is the in-turn signer of block `number`.
+	pub fn is_inturn(&self, number: BlockNumber, signer: &Address) -> bool {
+		match self.signers.len() {
+			0 => false,
+			n => self.signers.iter().nth((number % n as u64) as usize) == Some(signer),
+		}
+	}
+
+	/// Whether `signer` signed one of the blocks preventing it from signing block `number`.
+	pub fn is_recent(&self, number: BlockNumber, signer: &Address) -> bool {
+		let limit = self.signers.len() as u64 / 2 + 1;
+		self.recent_signers.iter().any(|(n, s)| s == signer && n + limit > number)
+	}
+
+	/// Whether the vote would change the signer set if it passed.
+	pub fn is_valid_vote(&self, beneficiary: &Address, kind: VoteType) -> bool {
+		let is_signer = self.signers.contains(beneficiary);
+		match kind {
+			VoteType::Add => !is_signer,
+			VoteType::Remove => is_signer,
+		}
+	}
+
+	/// Apply the next block to the state, returning its signer.
+	pub fn apply(&mut self, header: &Header, is_checkpoint: bool) -> Result<Address, Error> {
+		let number = header.number();
+		let signer = recover_signer(header)?;
+
+		if !self.signers.contains(&signer) {
+			return Err(EngineError::NotAuthorized(signer).into());
+		}
+
+		if self.is_recent(number, &signer) {
+			return Err(EngineError::CliqueTooRecentlySigned(signer).into());
+		}
+
+		let expected_difficulty = U256::from(if self.is_inturn(number, &signer) { DIFF_INTURN } else { DIFF_NOTURN });
+		if *header.difficulty() != expected_difficulty {
+			return Err(BlockError::InvalidDifficulty(Mismatch { expected: expected_difficulty, found: *header.difficulty() }).into());
+		}
+
+		if is_checkpoint {
+			self.votes.clear();
+		}
+
+		self.recent_signers.insert(number, signer);
+
+		let beneficiary = *header.author();
+		if !is_checkpoint && !beneficiary.is_zero() {
+			let nonce: H64 = UntrustedRlp::new(&header.seal()[1]).as_val()?;
+			let kind = VoteType::from_nonce(nonce)?;
+
+			// only the latest vote of a signer on a beneficiary counts.
+			self.votes.retain(|v| !(v.signer == signer && v.beneficiary == beneficiary));
+			if self.is_valid_vote(&beneficiary, kind) {
+				self.votes.push(Vote { signer: signer, beneficiary: beneficiary, kind: kind });
+			}
+
+			let tally = self.votes.iter().filter(|v| v.beneficiary == beneficiary && v.kind == kind).count();
+			if tally > self.signers.len() / 2 {
+				match kind {
+					VoteType::Add => {
+						self.signers.insert(beneficiary);
+					},
+					VoteType::Remove => {
+						self.signers.remove(&beneficiary);
+						self.votes.retain(|v| v.signer != beneficiary);
+					},
+				}
+				self.votes.retain(|v| v.beneficiary != beneficiary);
+			}
+		}
+
+		let limit = self.signers.len() as u64 / 2 + 1;
+		if number >= limit {
+			self.recent_signers = self.recent_signers.split_off(&(number - limit + 1));
+		}
+
+		Ok(signer)
+	}
+}
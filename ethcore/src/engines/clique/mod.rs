@@ -0,0 +1,711 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique proof-of-authority engine, as specified in EIP-225.
+//!
+//! The signer of a block seals it by appending its signature to the header extra data.
+//! Signers vote to add or remove other signers by setting the header author to the
+//! beneficiary and the nonce to the vote type. Every `epoch` blocks a checkpoint lists
+//! the current signers in the extra data and pending votes are discarded.
+
+mod block_state;
+
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Weak, Arc};
+use std::time::{UNIX_EPOCH, Duration};
+
+use account_provider::AccountProvider;
+use block::*;
+use client::{BlockId, EngineClient};
+use engines::{Engine, Seal, EngineError};
+use error::{Error, BlockError};
+use ethjson;
+use header::{Header, BlockNumber};
+use machine::EthereumMachine;
+use super::signer::EngineSigner;
+
+use ethkey::{public_to_address, recover, Signature};
+use hash::KECCAK_EMPTY_LIST_RLP;
+use io::{IoContext, IoHandler, TimerToken, IoService};
+use lru_cache::LruCache;
+use rand::{self, Rng};
+use rlp::{self, UntrustedRlp};
+use bigint::prelude::U256;
+use bigint::hash::{H64, H256, H520};
+use semantic_version::SemanticVersion;
+use parking_lot::{Mutex, RwLock};
+use unexpected::{Mismatch, OutOfBounds};
+use util::*;
+
+pub use self::block_state::{CliqueBlockState, VoteType};
+
+/// Number of extra data prefix bytes reserved for the signer vanity.
+pub const VANITY_LENGTH: usize = 32;
+/// Number of extra data suffix bytes reserved for the signer seal.
+pub const SIGNATURE_LENGTH: usize = 65;
+/// Difficulty of a block signed in turn.
+pub const DIFF_INTURN: u64 = 2;
+/// Difficulty of a block signed out of turn.
+pub const DIFF_NOTURN: u64 = 1;
+/// Nonce casting a vote to add a signer.
+pub const NONCE_AUTH_VOTE: H64 = H64([0xff; 8]);
+/// Nonce casting a vote to remove a signer.
+pub const NONCE_DROP_VOTE: H64 = H64([0; 8]);
+
+const DEFAULT_PERIOD: u64 = 15;
+const DEFAULT_EPOCH: u64 = 30000;
+/// Additional delay before an out-of-turn signer seals a block.
+const SIGNING_DELAY_NOTURN_MS: u64 = 500;
+/// Number of block states kept in memory.
+const STATE_CACHE_NUM: usize = 128;
+
+/// `Clique` params.
+#[derive(Debug, PartialEq)]
+pub struct CliqueParams {
+	/// Minimum time between blocks in seconds.
+	pub period: u64,
+	/// Number of blocks between checkpoints.
+	pub epoch: u64,
+}
+
+impl From<ethjson::spec::CliqueParams> for CliqueParams {
+	fn from(p: ethjson::spec::CliqueParams) -> Self {
+		CliqueParams {
+			period: p.period.map_or(DEFAULT_PERIOD, Into::into),
+			epoch: p.epoch.map_or(DEFAULT_EPOCH, Into::into),
+		}
+	}
+}
+
+/// Hash signed by the block signer: the full header without the signature in the extra data.
+fn sig_hash(header: &Header) -> Result<H256, Error> {
+	let extra_data = header.extra_data();
+	if extra_data.len() < VANITY_LENGTH + SIGNATURE_LENGTH {
+		return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds {
+			min: Some(VANITY_LENGTH + SIGNATURE_LENGTH),
+			max: None,
+			found: extra_data.len(),
+		}).into());
+	}
+
+	let mut unsigned = header.clone();
+	unsigned.set_extra_data(extra_data[..extra_data.len() - SIGNATURE_LENGTH].to_vec());
+	Ok(unsigned.hash())
+}
+
+/// Recover the signer of a block from the signature in its extra data.
+pub fn recover_signer(header: &Header) -> Result<Address, Error> {
+	let hash = sig_hash(header)?;
+	let extra_data = header.extra_data();
+	let signature = H520::from_slice(&extra_data[extra_data.len() - SIGNATURE_LENGTH..]);
+	Ok(public_to_address(&recover(&signature.into(), &hash)?))
+}
+
+/// Extract the signer list from the extra data of a checkpoint block.
+pub fn extract_signers(header: &Header) -> Result<Vec<Address>, Error> {
+	let extra_data = header.extra_data();
+	if extra_data.len() < VANITY_LENGTH + SIGNATURE_LENGTH {
+		return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds {
+			min: Some(VANITY_LENGTH + SIGNATURE_LENGTH),
+			max: None,
+			found: extra_data.len(),
+		}).into());
+	}
+
+	let signers = &extra_data[VANITY_LENGTH..extra_data.len() - SIGNATURE_LENGTH];
+	if signers.is_empty() || signers.len() % 20 != 0 {
+		return Err(EngineError::CliqueCheckpointInvalidSigners(signers.len()).into());
+	}
+
+	Ok(signers.chunks(20).map(Address::from_slice).collect())
+}
+
+fn unix_now() -> Duration {
+	UNIX_EPOCH.elapsed().expect("Valid time has to be set in your system.")
+}
+
+/// Engine using `Clique` proof-of-authority consensus.
+pub struct Clique {
+	machine: EthereumMachine,
+	period: u64,
+	epoch: u64,
+	client: RwLock<Option<Weak<EngineClient>>>,
+	signer: RwLock<EngineSigner>,
+	block_states: Mutex<LruCache<H256, CliqueBlockState>>,
+	proposals: RwLock<HashMap<Address, VoteType>>,
+	step_service: IoService<()>,
+}
+
+impl Clique {
+	/// Create a new instance of Clique engine.
+	pub fn new(our_params: CliqueParams, machine: EthereumMachine) -> Result<Arc<Self>, Error> {
+		let engine = Arc::new(
+			Clique {
+				machine: machine,
+				period: our_params.period,
+				epoch: if our_params.epoch == 0 { DEFAULT_EPOCH } else { our_params.epoch },
+				client: RwLock::new(None),
+				signer: Default::default(),
+				block_states: Mutex::new(LruCache::new(STATE_CACHE_NUM)),
+				proposals: RwLock::new(HashMap::new()),
+				step_service: IoService::<()>::start()?,
+			});
+
+		// With a zero period blocks are only sealed on demand.
+		if engine.period > 0 {
+			let handler = StepHandler { engine: Arc::downgrade(&engine) };
+			engine.step_service.register_handler(Arc::new(handler))?;
+		}
+		Ok(engine)
+	}
+
+	/// Propose a vote to be cast in blocks sealed by this node.
+	pub fn propose(&self, beneficiary: Address, kind: VoteType) {
+		self.proposals.write().insert(beneficiary, kind);
+	}
+
+	/// Withdraw a previously proposed vote.
+	pub fn discard(&self, beneficiary: &Address) {
+		self.proposals.write().remove(beneficiary);
+	}
+
+	fn is_checkpoint(&self, number: BlockNumber) -> bool {
+		number % self.epoch == 0
+	}
+
+	/// Clique state after the given block.
+	///
+	/// Unknown states are backfilled from the closest known state or checkpoint.
+	fn state(&self, header: &Header) -> Result<CliqueBlockState, Error> {
+		if let Some(state) = self.block_states.lock().get_mut(&header.hash()) {
+			return Ok(state.clone());
+		}
+
+		if header.number() == 0 {
+			let state = CliqueBlockState::from_checkpoint(header)?;
+			self.block_states.lock().insert(header.hash(), state.clone());
+			return Ok(state);
+		}
+
+		let client = self.client.read().as_ref().and_then(|weak| weak.upgrade()).ok_or(EngineError::RequiresClient)?;
+		let chain = client.as_full_client().ok_or(EngineError::RequiresClient)?;
+
+		let mut pending = vec![header.clone()];
+		let mut state = loop {
+			let parent_hash = *pending.last().expect("pending is never empty; qed").parent_hash();
+			if let Some(state) = self.block_states.lock().get_mut(&parent_hash) {
+				break state.clone();
+			}
+
+			let parent = chain.block_header(BlockId::Hash(parent_hash))
+				.ok_or(EngineError::CliqueMissingCheckpoint(parent_hash))?
+				.decode();
+
+			if self.is_checkpoint(parent.number()) {
+				break CliqueBlockState::from_checkpoint_with_recents(&parent, |header| {
+					let parent_hash = *header.parent_hash();
+					chain.block_header(BlockId::Hash(parent_hash))
+						.map(|h| h.decode())
+						.ok_or(EngineError::CliqueMissingCheckpoint(parent_hash).into())
+				})?;
+			}
+			pending.push(parent);
+		};
+
+		for header in pending.iter().rev() {
+			state.apply(header, self.is_checkpoint(header.number()))?;
+			self.block_states.lock().insert(header.hash(), state.clone());
+		}
+
+		Ok(state)
+	}
+
+	/// Clique state after the block with given hash.
+	fn state_by_hash(&self, hash: H256) -> Result<CliqueBlockState, Error> {
+		if let Some(state) = self.block_states.lock().get_mut(&hash) {
+			return Ok(state.clone());
+		}
+
+		let client = self.client.read().as_ref().and_then(|weak| weak.upgrade()).ok_or(EngineError::RequiresClient)?;
+		let header = client.as_full_client().ok_or(EngineError::RequiresClient)?
+			.block_header(BlockId::Hash(hash))
+			.ok_or(BlockError::UnknownParent(hash))?
+			.decode();
+		self.state(&header)
+	}
+}
+
+struct StepHandler {
+	engine: Weak<Clique>,
+}
+
+const ENGINE_STEP_TOKEN: TimerToken = 23;
+const ENGINE_STEP_INTERVAL_MS: u64 = 500;
+
+impl IoHandler<()> for StepHandler {
+	fn initialize(&self, io: &IoContext<()>) {
+		io.register_timer(ENGINE_STEP_TOKEN, ENGINE_STEP_INTERVAL_MS)
+			.unwrap_or_else(|e| warn!(target: "engine", "Failed to start Clique step timer: {}.", e))
+	}
+
+	fn timeout(&self, _io: &IoContext<()>, timer: TimerToken) {
+		if timer == ENGINE_STEP_TOKEN {
+			if let Some(engine) = self.engine.upgrade() {
+				engine.step();
+			}
+		}
+	}
+}
+
+impl Engine<EthereumMachine> for Clique {
+	fn name(&self) -> &str { "Clique" }
+
+	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
+
+	fn machine(&self) -> &EthereumMachine { &self.machine }
+
+	/// Two fields - mix hash and nonce.
+	fn seal_fields(&self) -> usize { 2 }
+
+	fn maximum_uncle_count(&self) -> usize { 0 }
+
+	fn seals_internally(&self) -> Option<bool> {
+		Some(self.signer.read().is_some())
+	}
+
+	fn step(&self) {
+		if !self.signer.read().is_some() {
+			return;
+		}
+
+		if let Some(ref weak) = *self.client.read() {
+			if let Some(c) = weak.upgrade() {
+				c.update_sealing();
+			}
+		}
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header) {
+		let timestamp = cmp::max(header.timestamp(), parent.timestamp() + self.period);
+		header.set_timestamp(timestamp);
+
+		let signer = self.signer.read().address();
+		let inturn = signer.map_or(false, |signer| match self.state(parent) {
+			Ok(state) => state.is_inturn(header.number(), &signer),
+			Err(_) => false,
+		});
+		header.set_difficulty(U256::from(if inturn { DIFF_INTURN } else { DIFF_NOTURN }));
+	}
+
+	fn on_new_block(&self, block: &mut ExecutedBlock, _epoch_begin: bool) -> Result<(), Error> {
+		// only blocks sealed by this node need their author and extra data prepared.
+		if !self.signer.read().is_some() {
+			return Ok(());
+		}
+
+		let header = block.fields_mut().header;
+		let state = match self.state_by_hash(*header.parent_hash()) {
+			Ok(state) => state,
+			Err(e) => {
+				debug!(target: "engine", "on_new_block: unable to compute parent state: {}", e);
+				return Ok(());
+			},
+		};
+		let is_checkpoint = self.is_checkpoint(header.number());
+
+		let mut beneficiary = Address::default();
+		if !is_checkpoint {
+			let votes: Vec<_> = self.proposals.read().iter()
+				.filter(|&(address, kind)| state.is_valid_vote(address, *kind))
+				.map(|(address, _)| *address)
+				.collect();
+			if !votes.is_empty() {
+				beneficiary = votes[rand::thread_rng().gen_range(0, votes.len())];
+			}
+		}
+		header.set_author(beneficiary);
+
+		let mut extra_data = header.extra_data().clone();
+		extra_data.resize(VANITY_LENGTH, 0);
+		if is_checkpoint {
+			for signer in state.signers() {
+				extra_data.extend_from_slice(&signer[..]);
+			}
+		}
+		extra_data.extend_from_slice(&[0u8; SIGNATURE_LENGTH]);
+		header.set_extra_data(extra_data);
+
+		Ok(())
+	}
+
+	/// Attempt to seal the block internally.
+	fn generate_seal(&self, block: &ExecutedBlock) -> Seal {
+		let header = block.header();
+		let signer = match self.signer.read().address() {
+			Some(signer) => signer,
+			None => return Seal::None,
+		};
+
+		let state = match self.state_by_hash(*header.parent_hash()) {
+			Ok(state) => state,
+			Err(e) => {
+				warn!(target: "engine", "generate_seal: unable to compute parent state: {}", e);
+				return Seal::None;
+			},
+		};
+
+		if !state.signers().contains(&signer) {
+			trace!(target: "engine", "generate_seal: {} is not an authorized signer", signer);
+			return Seal::None;
+		}
+
+		if state.is_recent(header.number(), &signer) {
+			trace!(target: "engine", "generate_seal: {} signed a block too recently", signer);
+			return Seal::None;
+		}
+
+		let mut ready_at = Duration::from_secs(header.timestamp());
+		if !state.is_inturn(header.number(), &signer) {
+			ready_at = ready_at + Duration::from_millis(SIGNING_DELAY_NOTURN_MS);
+		}
+		if unix_now() < ready_at {
+			trace!(target: "engine", "generate_seal: block #{} is not ready to be sealed yet", header.number());
+			return Seal::None;
+		}
+
+		let vote = if header.author().is_zero() {
+			NONCE_DROP_VOTE
+		} else if state.signers().contains(header.author()) {
+			VoteType::Remove.as_nonce()
+		} else {
+			VoteType::Add.as_nonce()
+		};
+
+		Seal::Regular(vec![
+			rlp::encode(&H256::default()).into_vec(),
+			rlp::encode(&vote).into_vec(),
+		])
+	}
+
+	fn on_seal_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
+		let header = block.fields_mut().header;
+		let signature = self.sign(sig_hash(header)?)?;
+
+		let mut extra_data = header.extra_data().clone();
+		let len = extra_data.len();
+		extra_data[len - SIGNATURE_LENGTH..].copy_from_slice(&H520::from(signature)[..]);
+		header.set_extra_data(extra_data);
+
+		Ok(())
+	}
+
+	fn verify_local_seal(&self, _header: &Header) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+		if header.number() == 0 {
+			return Ok(());
+		}
+
+		if header.seal().len() != self.seal_fields() {
+			return Err(BlockError::InvalidSealArity(
+				Mismatch { expected: self.seal_fields(), found: header.seal().len() }
+			).into());
+		}
+
+		let is_checkpoint = self.is_checkpoint(header.number());
+		let extra_data_len = header.extra_data().len();
+		if is_checkpoint {
+			extract_signers(header)?;
+		} else if extra_data_len != VANITY_LENGTH + SIGNATURE_LENGTH {
+			return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds {
+				min: Some(VANITY_LENGTH + SIGNATURE_LENGTH),
+				max: Some(VANITY_LENGTH + SIGNATURE_LENGTH),
+				found: extra_data_len,
+			}).into());
+		}
+
+		let mix_hash: H256 = UntrustedRlp::new(&header.seal()[0]).as_val()?;
+		if !mix_hash.is_zero() {
+			return Err(BlockError::MismatchedH256SealElement(Mismatch { expected: H256::default(), found: mix_hash }).into());
+		}
+
+		let nonce: H64 = UntrustedRlp::new(&header.seal()[1]).as_val()?;
+		if is_checkpoint {
+			if !header.author().is_zero() {
+				return Err(EngineError::CliqueCheckpointVote(*header.author()).into());
+			}
+			if nonce != NONCE_DROP_VOTE {
+				return Err(EngineError::CliqueInvalidNonce(nonce).into());
+			}
+		} else {
+			VoteType::from_nonce(nonce)?;
+		}
+
+		if *header.uncles_hash() != KECCAK_EMPTY_LIST_RLP {
+			return Err(BlockError::InvalidUnclesHash(Mismatch { expected: KECCAK_EMPTY_LIST_RLP, found: *header.uncles_hash() }).into());
+		}
+
+		let difficulty = *header.difficulty();
+		if difficulty != U256::from(DIFF_INTURN) && difficulty != U256::from(DIFF_NOTURN) {
+			return Err(BlockError::DifficultyOutOfBounds(OutOfBounds {
+				min: Some(U256::from(DIFF_NOTURN)),
+				max: Some(U256::from(DIFF_INTURN)),
+				found: difficulty,
+			}).into());
+		}
+
+		Ok(())
+	}
+
+	fn verify_block_family(&self, header: &Header, parent: &Header) -> Result<(), Error> {
+		if header.number() == 0 {
+			return Ok(());
+		}
+
+		let min_timestamp = parent.timestamp().saturating_add(self.period);
+		if header.timestamp() < min_timestamp {
+			return Err(BlockError::InvalidTimestamp(OutOfBounds { min: Some(min_timestamp), max: None, found: header.timestamp() }).into());
+		}
+
+		let mut state = self.state(parent)?;
+		let is_checkpoint = self.is_checkpoint(header.number());
+		if is_checkpoint {
+			let signers = extract_signers(header)?;
+			if signers.iter().ne(state.signers().iter()) {
+				return Err(EngineError::CliqueCheckpointMismatch.into());
+			}
+		}
+
+		state.apply(header, is_checkpoint)?;
+		self.block_states.lock().insert(header.hash(), state);
+
+		Ok(())
+	}
+
+	fn register_client(&self, client: Weak<EngineClient>) {
+		*self.client.write() = Some(client);
+	}
+
+	fn propose_signer(&self, address: Address, authorize: bool) -> Result<(), EngineError> {
+		self.propose(address, if authorize { VoteType::Add } else { VoteType::Remove });
+		Ok(())
+	}
+
+	fn discard_signer(&self, address: &Address) -> Result<(), EngineError> {
+		self.discard(address);
+		Ok(())
+	}
+
+	fn signer_proposals(&self) -> Result<BTreeMap<Address, bool>, EngineError> {
+		Ok(self.proposals.read().iter().map(|(address, kind)| (*address, *kind == VoteType::Add)).collect())
+	}
+
+	fn set_signer(&self, ap: Arc<AccountProvider>, address: Address, password: String) {
+		self.signer.write().set(ap, address, password);
+	}
+
+	fn sign(&self, hash: H256) -> Result<Signature, Error> {
+		self.signer.read().sign(hash).map_err(Into::into)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use hash::keccak;
+	use bigint::hash::{H64, H256, H520};
+	use bigint::prelude::U256;
+	use ethkey::{self, Generator, Random, Secret};
+	use rlp;
+	use block::*;
+	use tests::helpers::*;
+	use account_provider::AccountProvider;
+	use header::Header;
+	use spec::Spec;
+	use engines::Seal;
+	use util::Address;
+	use super::{CliqueBlockState, recover_signer, sig_hash, VANITY_LENGTH, SIGNATURE_LENGTH};
+	use super::{DIFF_INTURN, DIFF_NOTURN, NONCE_AUTH_VOTE, NONCE_DROP_VOTE};
+
+	fn checkpoint(signers: &[Address]) -> Header {
+		let mut extra_data = vec![0u8; VANITY_LENGTH];
+		for signer in signers {
+			extra_data.extend_from_slice(&signer[..]);
+		}
+		extra_data.extend_from_slice(&[0u8; SIGNATURE_LENGTH]);
+
+		let mut header = Header::default();
+		header.set_extra_data(extra_data);
+		header
+	}
+
+	fn signed_header(number: u64, difficulty: u64, beneficiary: Address, nonce: H64, secret: &Secret) -> Header {
+		let mut header = Header::default();
+		header.set_number(number);
+		header.set_difficulty(difficulty.into());
+		header.set_author(beneficiary);
+		header.set_seal(vec![rlp::encode(&H256::default()).into_vec(), rlp::encode(&nonce).into_vec()]);
+		header.set_extra_data(vec![0u8; VANITY_LENGTH + SIGNATURE_LENGTH]);
+		sign_header(header, secret)
+	}
+
+	fn sign_header(mut header: Header, secret: &Secret) -> Header {
+		let signature = ethkey::sign(secret, &sig_hash(&header).unwrap()).unwrap();
+		let mut extra_data = header.extra_data().clone();
+		let len = extra_data.len();
+		extra_data[len - SIGNATURE_LENGTH..].copy_from_slice(&H520::from(signature)[..]);
+		header.set_extra_data(extra_data);
+		header
+	}
+
+	fn difficulty(state: &CliqueBlockState, number: u64, signer: &Address) -> u64 {
+		if state.is_inturn(number, signer) { DIFF_INTURN } else { DIFF_NOTURN }
+	}
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = Spec::new_test_clique().engine;
+		assert!(!engine.name().is_empty());
+		assert!(engine.version().major >= 1);
+	}
+
+	#[test]
+	fn can_seal_and_verify_block() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account(keccak("").into(), "").unwrap();
+
+		let spec = Spec::new_test_clique();
+		let engine = &*spec.engine;
+		engine.set_signer(Arc::new(tap), addr, "".into());
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, addr, (3141562.into(), 31415620.into()), vec![], false).unwrap();
+		let b = b.close_and_lock();
+
+		let seal = match engine.generate_seal(b.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("the only signer should always be able to seal"),
+		};
+		let sealed = b.seal_internally(engine, seal).unwrap();
+		let header = sealed.header();
+
+		assert_eq!(recover_signer(header).unwrap(), addr);
+		assert_eq!(*header.difficulty(), U256::from(DIFF_INTURN));
+		assert!(engine.verify_block_basic(header).is_ok());
+		assert!(engine.verify_block_family(header, &genesis_header).is_ok());
+	}
+
+	#[test]
+	fn adds_signer_on_majority_vote() {
+		let (a, b, c) = (Random.generate().unwrap(), Random.generate().unwrap(), Random.generate().unwrap());
+		let mut state = CliqueBlockState::from_checkpoint(&checkpoint(&[a.address(), b.address()])).unwrap();
+
+		let header = signed_header(1, difficulty(&state, 1, &a.address()), c.address(), NONCE_AUTH_VOTE, a.secret());
+		assert_eq!(state.apply(&header, false).unwrap(), a.address());
+		assert!(!state.signers().contains(&c.address()));
+
+		let header = signed_header(2, difficulty(&state, 2, &b.address()), c.address(), NONCE_AUTH_VOTE, b.secret());
+		assert_eq!(state.apply(&header, false).unwrap(), b.address());
+		assert!(state.signers().contains(&c.address()));
+		assert_eq!(state.signers().len(), 3);
+	}
+
+	#[test]
+	fn rejects_recent_signer_and_wrong_difficulty() {
+		let (a, b) = (Random.generate().unwrap(), Random.generate().unwrap());
+		let mut state = CliqueBlockState::from_checkpoint(&checkpoint(&[a.address(), b.address()])).unwrap();
+
+		let header = signed_header(1, difficulty(&state, 1, &a.address()), Address::default(), NONCE_DROP_VOTE, a.secret());
+		assert!(state.apply(&header, false).is_ok());
+
+		let header = signed_header(2, difficulty(&state, 2, &a.address()), Address::default(), NONCE_DROP_VOTE, a.secret());
+		assert!(state.apply(&header, false).is_err());
+
+		let wrong = DIFF_INTURN + DIFF_NOTURN - difficulty(&state, 2, &b.address());
+		let header = signed_header(2, wrong, Address::default(), NONCE_DROP_VOTE, b.secret());
+		assert!(state.apply(&header, false).is_err());
+
+		let header = signed_header(2, difficulty(&state, 2, &b.address()), Address::default(), NONCE_DROP_VOTE, b.secret());
+		assert!(state.apply(&header, false).is_ok());
+	}
+
+	#[test]
+	fn casts_proposed_votes() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account(keccak("").into(), "").unwrap();
+		let beneficiary = Address::from(0x1234);
+
+		let spec = Spec::new_test_clique();
+		let engine = &*spec.engine;
+		engine.set_signer(Arc::new(tap), addr, "".into());
+		engine.propose_signer(beneficiary, true).unwrap();
+		assert_eq!(engine.signer_proposals().unwrap().get(&beneficiary), Some(&true));
+
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, addr, (3141562.into(), 31415620.into()), vec![], false).unwrap();
+		let b = b.close_and_lock();
+
+		let seal = match engine.generate_seal(b.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("the only signer should always be able to seal"),
+		};
+		let sealed = b.seal_internally(engine, seal).unwrap();
+		let header = sealed.header();
+
+		assert_eq!(*header.author(), beneficiary);
+		assert_eq!(header.seal()[1], rlp::encode(&NONCE_AUTH_VOTE).into_vec());
+		assert!(engine.verify_block_family(header, &genesis_header).is_ok());
+
+		engine.discard_signer(&beneficiary).unwrap();
+		assert!(engine.signer_proposals().unwrap().is_empty());
+	}
+
+	#[test]
+	fn recovers_recent_signers_at_checkpoint() {
+		let (a, b) = (Random.generate().unwrap(), Random.generate().unwrap());
+		let signers = [a.address(), b.address()];
+		let state = CliqueBlockState::from_checkpoint(&checkpoint(&signers)).unwrap();
+
+		let first = signed_header(1, difficulty(&state, 1, &a.address()), Address::default(), NONCE_DROP_VOTE, a.secret());
+
+		let mut second = checkpoint(&signers);
+		second.set_number(2);
+		second.set_parent_hash(first.hash());
+		second.set_difficulty(difficulty(&state, 2, &b.address()).into());
+		second.set_seal(vec![rlp::encode(&H256::default()).into_vec(), rlp::encode(&NONCE_DROP_VOTE).into_vec()]);
+		let second = sign_header(second, b.secret());
+
+		let mut state = CliqueBlockState::from_checkpoint_with_recents(&second, |header| {
+			assert_eq!(*header.parent_hash(), first.hash());
+			Ok(first.clone())
+		}).unwrap();
+
+		assert!(state.is_recent(3, &b.address()));
+		assert!(!state.is_recent(3, &a.address()));
+
+		let header = signed_header(3, difficulty(&state, 3, &b.address()), Address::default(), NONCE_DROP_VOTE, b.secret());
+		assert!(state.apply(&header, false).is_err());
+
+		let header = signed_header(3, difficulty(&state, 3, &a.address()), Address::default(), NONCE_DROP_VOTE, a.secret());
+		assert!(state.apply(&header, false).is_ok());
+	}
+}
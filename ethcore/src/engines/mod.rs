@@ -18,6 +18,7 @@
 
 mod authority_round;
 mod basic_authority;
+mod clique;
 mod instant_seal;
 mod null_engine;
 mod signer;
@@ -30,6 +31,7 @@ pub mod epoch;
 
 pub use self::authority_round::AuthorityRound;
 pub use self::basic_authority::BasicAuthority;
+pub use self::clique::Clique;
 pub use self::epoch::{EpochVerifier, Transition as EpochTransition};
 pub use self::instant_seal::InstantSeal;
 pub use self::null_engine::NullEngine;
@@ -53,7 +55,7 @@ use transaction::{UnverifiedTransaction, SignedTransaction};
 use ethkey::Signature;
 use parity_machine::{Machine, LocalizedMachine as Localized};
 use bigint::prelude::U256;
-use bigint::hash::H256;
+use bigint::hash::{H64, H256};
 use semantic_version::SemanticVersion;
 use util::*;
 use unexpected::{Mismatch, OutOfBounds};
@@ -84,6 +86,20 @@ pub enum EngineError {
 	MalformedMessage(String),
 	/// Requires client ref, but none registered.
 	RequiresClient,
+	/// Block needed to rebuild the Clique state is missing.
+	CliqueMissingCheckpoint(H256),
+	/// Checkpoint extra data does not contain a valid signer list.
+	CliqueCheckpointInvalidSigners(usize),
+	/// Checkpoint signer list differs from the current signer set.
+	CliqueCheckpointMismatch,
+	/// Checkpoint casts a vote.
+	CliqueCheckpointVote(Address),
+	/// Header nonce is not a valid vote.
+	CliqueInvalidNonce(H64),
+	/// Signer signed one of the most recent blocks.
+	CliqueTooRecentlySigned(Address),
+	/// Engine does not support voting on signers.
+	VotingUnsupported,
 }
 
 impl fmt::Display for EngineError {
@@ -99,6 +115,13 @@ impl fmt::Display for EngineError {
 			FailedSystemCall(ref msg) => format!("Failed to make system call: {}", msg),
 			MalformedMessage(ref msg) => format!("Received malformed consensus message: {}", msg),
 			RequiresClient => format!("Call requires client but none registered"),
+			CliqueMissingCheckpoint(ref hash) => format!("Missing block {} needed to rebuild the signer set", hash),
+			CliqueCheckpointInvalidSigners(len) => format!("Checkpoint signer list has an invalid length of {} bytes", len),
+			CliqueCheckpointMismatch => "Checkpoint signer list does not match the current signer set".into(),
+			CliqueCheckpointVote(ref address) => format!("Checkpoint casts a vote for {}", address),
+			CliqueInvalidNonce(ref nonce) => format!("Nonce {} is not a valid vote", nonce),
+			CliqueTooRecentlySigned(ref address) => format!("Signer {} signed a block too recently", address),
+			VotingUnsupported => "This Engine does not support voting on signers.".into(),
 		};
 
 		f.write_fmt(format_args!("Engine error ({})", msg))
@@ -227,6 +250,12 @@ pub trait Engine<M: Machine>: Sync + Send {
 	/// light clients do not generate seals.
 	fn generate_seal(&self, _block: &M::LiveBlock) -> Seal { Seal::None }
 
+	/// Finalize a locally sealed block after the seal from `generate_seal` has been set,
+	/// e.g. by signing the header.
+	fn on_seal_block(&self, _block: &mut M::LiveBlock) -> Result<(), M::Error> {
+		Ok(())
+	}
+
 	/// Verify a locally-generated seal of a header.
 	///
 	/// If this engine seals internally,
@@ -304,6 +333,15 @@ pub trait Engine<M: Machine>: Sync + Send {
 	/// Takes a header of a fully verified block.
 	fn is_proposal(&self, _verified_header: &M::Header) -> bool { false }
 
+	/// Propose a vote adding (`authorize`) or removing a signer, cast in blocks sealed by this node.
+	fn propose_signer(&self, _address: Address, _authorize: bool) -> Result<(), EngineError> { Err(EngineError::VotingUnsupported) }
+
+	/// Withdraw a signer vote proposed by this node.
+	fn discard_signer(&self, _address: &Address) -> Result<(), EngineError> { Err(EngineError::VotingUnsupported) }
+
+	/// Signer votes proposed by this node, with whether each authorizes the signer.
+	fn signer_proposals(&self) -> Result<BTreeMap<Address, bool>, EngineError> { Err(EngineError::VotingUnsupported) }
+
 	/// Register an account which signs consensus messages.
 	fn set_signer(&self, _account_provider: Arc<AccountProvider>, _address: Address, _password: String) {}
 
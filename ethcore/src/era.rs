@@ -0,0 +1,639 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Era1-style chain history archives.
+//!
+//! An archive holds up to `MAX_BLOCKS` consecutive blocks along with their receipts and
+//! total difficulty, so that history can be inserted into a database without executing it.
+//! It is written as a sequence of e2store records, each made of a 2-byte type, a 4-byte
+//! data length and 2 reserved bytes (all little-endian) followed by the data:
+//!
+//! `Version | (Header | Body | Receipts | TotalDifficulty)* | Accumulator | BlockIndex`
+//!
+//! Headers, bodies and receipts are RLP compressed with the snappy framing format. The
+//! accumulator is the SSZ root of the list of `(block hash, total difficulty)`
+//! records. The block index holds the number of the first block, the offset of every
+//! block's header record relative to the index record and the number of blocks.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use bigint::prelude::U256;
+use bigint::hash::H256;
+use byteorder::{ByteOrder, LittleEndian};
+use bytes::Bytes;
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use hash::keccak;
+use rlp::{RlpStream, UntrustedRlp, DecoderError};
+use snappy::{self, InvalidInput};
+use triehash::ordered_trie_root;
+
+use header::{Header, BlockNumber};
+
+/// Maximum number of blocks in a single archive.
+pub const MAX_BLOCKS: u64 = 8192;
+
+const VERSION: u16 = 0x3265;
+const COMPRESSED_HEADER: u16 = 0x03;
+const COMPRESSED_BODY: u16 = 0x04;
+const COMPRESSED_RECEIPTS: u16 = 0x05;
+const TOTAL_DIFFICULTY: u16 = 0x06;
+const ACCUMULATOR: u16 = 0x07;
+const BLOCK_INDEX: u16 = 0x3266;
+
+const RECORD_HEADER_LEN: u64 = 8;
+
+const SNAPPY_STREAM_IDENTIFIER: &'static [u8] = b"\xff\x06\x00\x00sNaPpY";
+const SNAPPY_COMPRESSED_CHUNK: u8 = 0x00;
+const SNAPPY_UNCOMPRESSED_CHUNK: u8 = 0x01;
+const SNAPPY_MAX_CHUNK_LEN: usize = 65536;
+
+/// Archive errors.
+#[derive(Debug)]
+pub enum Error {
+	/// Io error.
+	Io(io::Error),
+	/// Snappy error.
+	Snappy(InvalidInput),
+	/// Decoder error.
+	Decoder(DecoderError),
+	/// Unexpected record: expected, found.
+	UnexpectedRecord(u16, u16),
+	/// Archive holds no blocks or more than `MAX_BLOCKS`.
+	BadBlockCount(u64),
+	/// Block doesn't follow the previous one.
+	BrokenChain(BlockNumber),
+	/// Body or receipts don't match the block header.
+	BadBlockData(BlockNumber),
+	/// Total difficulty doesn't match the block difficulty.
+	BadTotalDifficulty(BlockNumber),
+	/// Accumulator doesn't match the blocks. Holds the root computed from the blocks.
+	BadAccumulator(H256),
+	/// Block index doesn't match the records.
+	BadIndex,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Io(ref err) => err.fmt(f),
+			Error::Snappy(ref err) => err.fmt(f),
+			Error::BadFrame => write!(f, "Malformed snappy frame"),
+			Error::Decoder(ref err) => err.fmt(f),
+			Error::UnexpectedRecord(expected, found) => write!(f, "Unexpected record: expected {:#06x}, found {:#06x}", expected, found),
+			Error::BadBlockCount(count) => write!(f, "Archive holds {} blocks, must be between 1 and {}", count, MAX_BLOCKS),
+			Error::BrokenChain(number) => write!(f, "Block #{} doesn't follow the previous block", number),
+			Error::BadBlockData(number) => write!(f, "Body or receipts of block #{} don't match its header", number),
+			Error::BadTotalDifficulty(number) => write!(f, "Bad total difficulty of block #{}", number),
+			Error::BadAccumulator(ref root) => write!(f, "Accumulator doesn't match the blocks, expected {}", root),
+			Error::BadIndex => write!(f, "Block index doesn't match the records"),
+		}
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self {
+		Error::Io(err)
+	}
+}
+
+impl From<InvalidInput> for Error {
+	fn from(err: InvalidInput) -> Self {
+		Error::Snappy(err)
+	}
+}
+
+impl From<DecoderError> for Error {
+	fn from(err: DecoderError) -> Self {
+		Error::Decoder(err)
+	}
+}
+
+/// Whether `prefix` is the beginning of an archive.
+pub fn is_archive(prefix: &[u8]) -> bool {
+	let mut version = [0u8; RECORD_HEADER_LEN as usize];
+	LittleEndian::write_u16(&mut version[..2], VERSION);
+	prefix.len() >= version.len() && prefix[..version.len()] == version[..]
+}
+
+/// A block read from an archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveBlock {
+	/// RLP-encoded block.
+	pub block: Bytes,
+	/// RLP-encoded list of the block receipts.
+	pub receipts: Bytes,
+	/// Total difficulty of the chain up to and including the block.
+	pub total_difficulty: U256,
+}
+
+/// Writes consecutive blocks into an archive.
+pub struct ArchiveWriter<W> {
+	out: W,
+	start: BlockNumber,
+	position: u64,
+	offsets: Vec<u64>,
+	records: Vec<H256>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+	/// Create a new archive whose first block is `start`.
+	pub fn new(out: W, start: BlockNumber) -> Result<Self, Error> {
+		let mut writer = ArchiveWriter {
+			out: out,
+			start: start,
+			position: 0,
+			offsets: Vec::new(),
+			records: Vec::new(),
+		};
+		writer.write_record(VERSION, &[])?;
+		Ok(writer)
+	}
+
+	/// Number of blocks written so far.
+	pub fn len(&self) -> u64 {
+		self.offsets.len() as u64
+	}
+
+	/// Whether the archive can't hold more blocks.
+	pub fn is_full(&self) -> bool {
+		self.len() == MAX_BLOCKS
+	}
+
+	/// Append the next block with its RLP-encoded receipts and total difficulty.
+	pub fn push(&mut self, block: &[u8], receipts: &[u8], total_difficulty: U256) -> Result<(), Error> {
+		if self.is_full() {
+			return Err(Error::BadBlockCount(MAX_BLOCKS + 1));
+		}
+
+		let rlp = UntrustedRlp::new(block);
+		let header = rlp.at(0)?;
+		let number: BlockNumber = header.val_at(8)?;
+		if number != self.start + self.len() {
+			return Err(Error::BrokenChain(number));
+		}
+
+		let mut body = RlpStream::new_list(2);
+		body.append_raw(rlp.at(1)?.as_raw(), 1);
+		body.append_raw(rlp.at(2)?.as_raw(), 1);
+
+		let mut difficulty = [0u8; 32];
+		total_difficulty.to_little_endian(&mut difficulty);
+
+		self.offsets.push(self.position);
+		self.records.push(header_record_root(&keccak(header.as_raw()), &total_difficulty));
+		self.write_record(COMPRESSED_HEADER, &compress_framed(header.as_raw()))?;
+		self.write_record(COMPRESSED_BODY, &compress_framed(&body.out()))?;
+		self.write_record(COMPRESSED_RECEIPTS, &compress_framed(receipts))?;
+		self.write_record(TOTAL_DIFFICULTY, &difficulty)?;
+		Ok(())
+	}
+
+	/// Write the accumulator and the block index. Returns the accumulator root.
+	pub fn finish(mut self) -> Result<H256, Error> {
+		let count = self.offsets.len();
+		if count == 0 {
+			return Err(Error::BadBlockCount(0));
+		}
+
+		let root = accumulator_root(&self.records);
+		self.write_record(ACCUMULATOR, &root)?;
+
+		let index_position = self.position as i64;
+		let mut index = vec![0u8; 16 + 8 * count];
+		LittleEndian::write_u64(&mut index[..8], self.start);
+		for (i, offset) in self.offsets.iter().enumerate() {
+			LittleEndian::write_i64(&mut index[8 + 8 * i..], *offset as i64 - index_position);
+		}
+		LittleEndian::write_u64(&mut index[8 + 8 * count..], count as u64);
+		self.write_record(BLOCK_INDEX, &index)?;
+
+		self.out.flush()?;
+		Ok(root)
+	}
+
+	fn write_record(&mut self, kind: u16, data: &[u8]) -> Result<(), Error> {
+		let mut header = [0u8; RECORD_HEADER_LEN as usize];
+		LittleEndian::write_u16(&mut header[..2], kind);
+		LittleEndian::write_u32(&mut header[2..6], data.len() as u32);
+		self.out.write_all(&header)?;
+		self.out.write_all(data)?;
+		self.position += RECORD_HEADER_LEN + data.len() as u64;
+		Ok(())
+	}
+}
+
+/// Reads the blocks of an archive in order.
+///
+/// Every block is checked against the previous one, and its body and receipts against
+/// its header. The accumulator and the block index are checked once all blocks are read.
+pub struct ArchiveReader<R> {
+	input: R,
+	position: u64,
+	offsets: Vec<u64>,
+	records: Vec<H256>,
+	start: BlockNumber,
+	parent: Option<(H256, U256)>,
+	accumulator: Option<H256>,
+}
+
+impl<R: Read> ArchiveReader<R> {
+	/// Create a new reader, reading the version record.
+	pub fn new(input: R) -> Result<Self, Error> {
+		let mut reader = ArchiveReader {
+			input: input,
+			position: 0,
+			offsets: Vec::new(),
+			records: Vec::new(),
+			start: 0,
+			parent: None,
+			accumulator: None,
+		};
+		reader.expect_record(VERSION)?;
+		Ok(reader)
+	}
+
+	/// Accumulator root of the archive, known once all blocks were read.
+	pub fn accumulator(&self) -> Option<H256> {
+		self.accumulator
+	}
+
+	/// Read the next block. Returns `None` after the last one.
+	pub fn next_block(&mut self) -> Result<Option<ArchiveBlock>, Error> {
+		if self.accumulator.is_some() {
+			return Ok(None);
+		}
+
+		let position = self.position;
+		let (kind, data) = self.read_record()?;
+		match kind {
+			COMPRESSED_HEADER => {},
+			ACCUMULATOR => {
+				self.finish(&data)?;
+				return Ok(None);
+			},
+			_ => return Err(Error::UnexpectedRecord(COMPRESSED_HEADER, kind)),
+		}
+
+		let header_bytes = decompress_framed(&data)?;
+		let body = decompress_framed(&self.expect_record(COMPRESSED_BODY)?)?;
+		let receipts = decompress_framed(&self.expect_record(COMPRESSED_RECEIPTS)?)?;
+		let difficulty = self.expect_record(TOTAL_DIFFICULTY)?;
+
+		let header: Header = UntrustedRlp::new(&header_bytes).as_val()?;
+		let number = header.number();
+		if self.offsets.is_empty() {
+			self.start = number;
+		}
+		if self.offsets.len() as u64 == MAX_BLOCKS {
+			return Err(Error::BadBlockCount(MAX_BLOCKS + 1));
+		}
+		if number != self.start + self.offsets.len() as u64 {
+			return Err(Error::BrokenChain(number));
+		}
+
+		if difficulty.len() != 32 {
+			return Err(Error::BadTotalDifficulty(number));
+		}
+		let total_difficulty = U256::from_little_endian(&difficulty);
+		match self.parent {
+			Some((ref parent_hash, ref parent_difficulty)) => {
+				if header.parent_hash() != parent_hash {
+					return Err(Error::BrokenChain(number));
+				}
+				if *parent_difficulty + *header.difficulty() != total_difficulty {
+					return Err(Error::BadTotalDifficulty(number));
+				}
+			},
+			None => if total_difficulty < *header.difficulty() {
+				return Err(Error::BadTotalDifficulty(number));
+			},
+		}
+
+		let body = UntrustedRlp::new(&body);
+		let transactions = body.at(0)?;
+		let uncles = body.at(1)?;
+		let receipts_rlp = UntrustedRlp::new(&receipts);
+		if ordered_trie_root(transactions.iter().map(|tx| tx.as_raw().to_vec())) != *header.transactions_root()
+			|| keccak(uncles.as_raw()) != *header.uncles_hash()
+			|| ordered_trie_root(receipts_rlp.iter().map(|r| r.as_raw().to_vec())) != *header.receipts_root() {
+			return Err(Error::BadBlockData(number));
+		}
+
+		let mut block = RlpStream::new_list(3);
+		block.append_raw(&header_bytes, 1);
+		block.append_raw(transactions.as_raw(), 1);
+		block.append_raw(uncles.as_raw(), 1);
+
+		let hash = header.hash();
+		self.offsets.push(position);
+		self.records.push(header_record_root(&hash, &total_difficulty));
+		self.parent = Some((hash, total_difficulty));
+
+		Ok(Some(ArchiveBlock {
+			block: block.out(),
+			receipts: receipts,
+			total_difficulty: total_difficulty,
+		}))
+	}
+
+	// check the accumulator and the block index.
+	fn finish(&mut self, accumulator: &[u8]) -> Result<(), Error> {
+		let count = self.offsets.len();
+		if count == 0 {
+			return Err(Error::BadBlockCount(0));
+		}
+
+		let root = accumulator_root(&self.records);
+		if accumulator != &*root {
+			return Err(Error::BadAccumulator(root));
+		}
+
+		let index_position = self.position as i64;
+		let index = self.expect_record(BLOCK_INDEX)?;
+		if index.len() != 16 + 8 * count
+			|| LittleEndian::read_u64(&index[..8]) != self.start
+			|| LittleEndian::read_u64(&index[8 + 8 * count..]) != count as u64 {
+			return Err(Error::BadIndex);
+		}
+		for (i, offset) in self.offsets.iter().enumerate() {
+			if LittleEndian::read_i64(&index[8 + 8 * i..]) != *offset as i64 - index_position {
+				return Err(Error::BadIndex);
+			}
+		}
+
+		self.accumulator = Some(root);
+		Ok(())
+	}
+
+	fn read_record(&mut self) -> Result<(u16, Bytes), Error> {
+		let mut header = [0u8; RECORD_HEADER_LEN as usize];
+		self.input.read_exact(&mut header)?;
+		let kind = LittleEndian::read_u16(&header[..2]);
+		let len = LittleEndian::read_u32(&header[2..6]) as usize;
+
+		let mut data = vec![0u8; len];
+		self.input.read_exact(&mut data)?;
+		self.position += RECORD_HEADER_LEN + len as u64;
+		Ok((kind, data))
+	}
+
+	fn expect_record(&mut self, kind: u16) -> Result<Bytes, Error> {
+		let (found, data) = self.read_record()?;
+		match found == kind {
+			true => Ok(data),
+			false => Err(Error::UnexpectedRecord(kind, found)),
+		}
+	}
+}
+
+// CRC-32C of `data`, masked as required by the snappy framing format.
+fn masked_crc32c(data: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for byte in data {
+		crc ^= *byte as u32;
+		for _ in 0..8 {
+			crc = match crc & 1 {
+				1 => (crc >> 1) ^ 0x82f6_3b78,
+				_ => crc >> 1,
+			};
+		}
+	}
+	let crc = !crc;
+	((crc >> 15) | (crc << 17)).wrapping_add(0xa282_ead8)
+}
+
+// compress `input` as a snappy framed stream. Chunks which don't shrink by at least
+// an eighth are stored uncompressed.
+fn compress_framed(input: &[u8]) -> Bytes {
+	let mut out = SNAPPY_STREAM_IDENTIFIER.to_vec();
+	for chunk in input.chunks(SNAPPY_MAX_CHUNK_LEN) {
+		let compressed = snappy::compress(chunk);
+		let (kind, data) = match compressed.len() < chunk.len() - chunk.len() / 8 {
+			true => (SNAPPY_COMPRESSED_CHUNK, &compressed[..]),
+			false => (SNAPPY_UNCOMPRESSED_CHUNK, chunk),
+		};
+
+		let mut header = [0u8; 8];
+		LittleEndian::write_u32(&mut header[..4], (data.len() as u32 + 4) << 8 | kind as u32);
+		LittleEndian::write_u32(&mut header[4..], masked_crc32c(chunk));
+		out.extend_from_slice(&header);
+		out.extend_from_slice(data);
+	}
+	out
+}
+
+// decompress a snappy framed stream, checking the checksum of every chunk.
+fn decompress_framed(mut input: &[u8]) -> Result<Bytes, Error> {
+	if !input.starts_with(SNAPPY_STREAM_IDENTIFIER) {
+		return Err(Error::BadFrame);
+	}
+
+	let mut out = Vec::new();
+	while input.len() >= 4 {
+		let kind = input[0];
+		let len = LittleEndian::read_u32(&input[..4]) as usize >> 8;
+		if input.len() < 4 + len {
+			return Err(Error::BadFrame);
+		}
+		let chunk = &input[4..4 + len];
+		input = &input[4 + len..];
+
+		let data = match kind {
+			SNAPPY_COMPRESSED_CHUNK | SNAPPY_UNCOMPRESSED_CHUNK if len < 4 => return Err(Error::BadFrame),
+			SNAPPY_COMPRESSED_CHUNK => snappy::decompress(&chunk[4..])?,
+			SNAPPY_UNCOMPRESSED_CHUNK => chunk[4..].to_vec(),
+			0xff => match chunk == &SNAPPY_STREAM_IDENTIFIER[4..] {
+				true => continue,
+				false => return Err(Error::BadFrame),
+			},
+			// skippable chunks.
+			0x80...0xfe => continue,
+			_ => return Err(Error::BadFrame),
+		};
+		if data.len() > SNAPPY_MAX_CHUNK_LEN || LittleEndian::read_u32(&chunk[..4]) != masked_crc32c(&data) {
+			return Err(Error::BadFrame);
+		}
+		out.extend_from_slice(&data);
+	}
+
+	match input.is_empty() {
+		true => Ok(out),
+		false => Err(Error::BadFrame),
+	}
+}
+
+fn sha256_pair(left: &[u8], right: &[u8]) -> H256 {
+	let mut sha = Sha256::new();
+	sha.input(left);
+	sha.input(right);
+
+	let mut out = [0u8; 32];
+	sha.result(&mut out);
+	H256::from(out)
+}
+
+// SSZ root of a `(block hash, total difficulty)` record.
+fn header_record_root(hash: &H256, total_difficulty: &U256) -> H256 {
+	let mut difficulty = [0u8; 32];
+	total_difficulty.to_little_endian(&mut difficulty);
+	sha256_pair(hash, &difficulty)
+}
+
+// SSZ root of a list of at most `MAX_BLOCKS` records: the merkle root of the records
+// padded with zero leaves, mixed in with the length of the list.
+fn accumulator_root(records: &[H256]) -> H256 {
+	let mut layer = records.to_vec();
+	let mut zero = H256::zero();
+	let mut width = MAX_BLOCKS;
+	while width > 1 {
+		if layer.len() % 2 == 1 {
+			layer.push(zero);
+		}
+		layer = layer.chunks(2).map(|pair| sha256_pair(&pair[0], &pair[1])).collect();
+		zero = sha256_pair(&zero, &zero);
+		width /= 2;
+	}
+
+	let mut length = [0u8; 32];
+	LittleEndian::write_u64(&mut length[..8], records.len() as u64);
+	sha256_pair(&layer.pop().unwrap_or(zero), &length)
+}
+
+#[cfg(test)]
+mod tests {
+	use bigint::prelude::U256;
+	use bigint::hash::H256;
+	use header::{Header, Seal};
+	use rlp::RlpStream;
+	use super::{ArchiveWriter, ArchiveReader, ArchiveBlock, Error, compress_framed, decompress_framed, header_record_root, accumulator_root};
+
+	fn blocks(count: u64) -> Vec<ArchiveBlock> {
+		let mut parent_hash = H256::zero();
+		let mut total_difficulty = U256::zero();
+		(0..count).map(|number| {
+			let mut header = Header::new();
+			header.set_number(number);
+			header.set_parent_hash(parent_hash);
+			header.set_difficulty(U256::from(131_072 + number));
+			parent_hash = header.hash();
+			total_difficulty = total_difficulty + *header.difficulty();
+
+			let mut block = RlpStream::new_list(3);
+			block.append_raw(&header.rlp(Seal::With), 1);
+			block.begin_list(0);
+			block.begin_list(0);
+
+			ArchiveBlock {
+				block: block.out(),
+				receipts: vec![0xc0],
+				total_difficulty: total_difficulty,
+			}
+		}).collect()
+	}
+
+	fn write(blocks: &[ArchiveBlock]) -> (Vec<u8>, H256) {
+		let mut out = Vec::new();
+		let root = {
+			let mut writer = ArchiveWriter::new(&mut out, 0).unwrap();
+			for b in blocks {
+				writer.push(&b.block, &b.receipts, b.total_difficulty).unwrap();
+			}
+			writer.finish().unwrap()
+		};
+		(out, root)
+	}
+
+	fn read(data: &[u8]) -> Result<(Vec<ArchiveBlock>, H256), Error> {
+		let mut reader = ArchiveReader::new(data)?;
+		let mut blocks = Vec::new();
+		while let Some(block) = reader.next_block()? {
+			blocks.push(block);
+		}
+		Ok((blocks, reader.accumulator().unwrap()))
+	}
+
+	#[test]
+	fn archive_roundtrip() {
+		let blocks = blocks(10);
+		let (out, root) = write(&blocks);
+
+		let (read_blocks, read_root) = read(&out).unwrap();
+		assert_eq!(read_blocks, blocks);
+		assert_eq!(read_root, root);
+	}
+
+	#[test]
+	fn rejects_bad_archives() {
+		let blocks = blocks(4);
+
+		let mut bad_difficulty = blocks.clone();
+		bad_difficulty[2].total_difficulty = bad_difficulty[2].total_difficulty + 1.into();
+		match read(&write(&bad_difficulty).0) {
+			Err(Error::BadTotalDifficulty(2)) => {},
+			x => panic!("unexpected result: {:?}", x),
+		}
+
+		let mut bad_receipts = blocks.clone();
+		bad_receipts[1].receipts = vec![0xc1, 0x80];
+		match read(&write(&bad_receipts).0) {
+			Err(Error::BadBlockData(1)) => {},
+			x => panic!("unexpected result: {:?}", x),
+		}
+
+		let (mut out, _) = write(&blocks);
+		let len = out.len();
+		// corrupt the number of blocks stored at the end of the index.
+		out[len - 8] = 5;
+		match read(&out) {
+			Err(Error::BadIndex) => {},
+			x => panic!("unexpected result: {:?}", x),
+		}
+
+		let mut writer = ArchiveWriter::new(Vec::new(), 0).unwrap();
+		match writer.push(&blocks[1].block, &blocks[1].receipts, blocks[1].total_difficulty) {
+			Err(Error::BrokenChain(1)) => {},
+			x => panic!("unexpected result: {:?}", x),
+		}
+	}
+
+	#[test]
+	fn snappy_framing() {
+		// stream identifier, uncompressed chunk of 13 bytes and the masked CRC-32C of "123456789".
+		let framed = b"\xff\x06\x00\x00sNaPpY\x01\x0d\x00\x00\xe5\xb0\x8a\xc7123456789";
+		assert_eq!(compress_framed(b"123456789"), framed.to_vec());
+		assert_eq!(decompress_framed(framed).unwrap(), b"123456789".to_vec());
+
+		let data = vec![42u8; 200_000];
+		assert_eq!(decompress_framed(&compress_framed(&data)).unwrap(), data);
+
+		let mut corrupted = framed.to_vec();
+		corrupted[20] = b'0';
+		match decompress_framed(&corrupted) {
+			Err(Error::BadFrame) => {},
+			x => panic!("unexpected result: {:?}", x),
+		}
+	}
+
+	#[test]
+	fn mainnet_genesis_accumulator() {
+		let genesis_hash: H256 = "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3".into();
+		let record = header_record_root(&genesis_hash, &U256::from(17_179_869_184u64));
+		assert_eq!(record, "23d6398abe4eba641e97a075b30780c12ebe18b24e83a9a9c7bdd94a910cf749".into());
+		assert_eq!(accumulator_root(&[record]), "c26dcbaf5b6a0d60410dec217bc97d7ff112e1fa48b5809ec2b9ff33b39f07f2".into());
+	}
+}
@@ -43,9 +43,14 @@ pub enum TransactionError {
 	AlreadyImported,
 	/// Transaction is not valid anymore (state already has higher nonce)
 	Old,
-	/// Transaction has too low fee
-	/// (there is already a transaction with the same sender-nonce but higher gas price)
-	TooCheapToReplace,
+	/// Transaction's gas price is too low to replace the transaction with the same sender-nonce
+	/// that is already in the queue.
+	ReplacementUnderpriced {
+		/// Minimal gas price required for the replacement
+		minimal: U256,
+		/// Transaction gas price
+		got: U256,
+	},
 	/// Transaction was not imported to the queue because limit has been reached.
 	LimitReached,
 	/// Transaction's gas price is below threshold.
@@ -96,7 +101,8 @@ impl fmt::Display for TransactionError {
 		let msg = match *self {
 			AlreadyImported => "Already imported".into(),
 			Old => "No longer valid".into(),
-			TooCheapToReplace => "Gas price too low to replace".into(),
+			ReplacementUnderpriced { minimal, got } =>
+				format!("Replacement underpriced. Min={}, Given={}", minimal, got),
 			LimitReached => "Transaction limit reached".into(),
 			InsufficientGasPrice { minimal, got } =>
 				format!("Insufficient gas price. Min={}, Given={}", minimal, got),
@@ -248,6 +254,27 @@ impl fmt::Display for ImportError {
 		f.write_fmt(format_args!("Block import error ({})", msg))
 	}
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Error looking up the history of the chain.
+pub enum HistoryError {
+	/// The block is not known.
+	UnknownBlock,
+	/// The block's body and receipts were removed by history pruning.
+	Pruned,
+}
+
+impl fmt::Display for HistoryError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let msg = match *self {
+			HistoryError::UnknownBlock => "unknown block",
+			HistoryError::Pruned => "block body pruned",
+		};
+
+		f.write_fmt(format_args!("History error ({})", msg))
+	}
+}
+
 /// Error dedicated to import block function
 #[derive(Debug)]
 pub enum BlockImportError {
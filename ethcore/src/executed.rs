@@ -29,7 +29,7 @@ use std::fmt;
 
 /// Transaction execution receipt.
 #[derive(Debug, PartialEq, Clone)]
-pub struct Executed {
+pub struct Executed<V = VMTrace> {
 	/// True if the outer call/create resulted in an exceptional exit.
 	pub exception: Option<vm::Error>,
 
@@ -65,7 +65,7 @@ pub struct Executed {
 	/// The trace of this transaction.
 	pub trace: Vec<FlatTrace>,
 	/// The VM trace of this transaction.
-	pub vm_trace: Option<VMTrace>,
+	pub vm_trace: Option<V>,
 	/// The state diff, if we traced it.
 	pub state_diff: Option<StateDiff>,
 }
@@ -162,6 +162,8 @@ pub enum CallError {
 	StateCorrupt,
 	/// Error executing.
 	Execution(ExecutionError),
+	/// Execution was reverted with the REVERT instruction, with the given output.
+	Reverted(Bytes),
 }
 
 impl From<ExecutionError> for CallError {
@@ -180,6 +182,7 @@ impl fmt::Display for CallError {
 			Exceptional => "An exception happened in the execution".into(),
 			StateCorrupt => "Stored state found to be corrupted.".into(),
 			Execution(ref e) => format!("{}", e),
+			Reverted(_) => "Execution reverted".into(),
 		};
 
 		f.write_fmt(format_args!("Transaction execution error ({}).", msg))
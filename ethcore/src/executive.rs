@@ -30,7 +30,7 @@ use evm::{CallType, Factory, Finalize, FinalizationResult};
 use vm::{self, Ext, CreateContractAddress, ReturnData, CleanDustMode, ActionParams, ActionValue};
 use wasm;
 use externalities::*;
-use trace::{self, FlatTrace, VMTrace, Tracer, VMTracer};
+use trace::{self, FlatTrace, Tracer, VMTracer};
 use transaction::{Action, SignedTransaction};
 use crossbeam;
 pub use executed::{Executed, ExecutionResult};
@@ -66,6 +66,15 @@ pub fn contract_address(address_scheme: CreateContractAddress, sender: &Address,
 			&mut buffer[20..].copy_from_slice(&code_hash[..]);
 			(From::from(keccak(&buffer[..])), Some(code_hash))
 		},
+		CreateContractAddress::FromSenderSaltAndCodeHash(salt) => {
+			let code_hash = keccak(code);
+			let mut buffer = [0u8; 1 + 20 + 32 + 32];
+			buffer[0] = 0xff;
+			&mut buffer[1..(1+20)].copy_from_slice(&sender[..]);
+			&mut buffer[(1+20)..(1+20+32)].copy_from_slice(&salt[..]);
+			&mut buffer[(1+20+32)..].copy_from_slice(&code_hash[..]);
+			(From::from(keccak(&buffer[..])), Some(code_hash))
+		},
 	}
 }
 
@@ -214,7 +223,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 	/// This function should be used to execute transaction.
 	pub fn transact<T, V>(&'a mut self, t: &SignedTransaction, options: TransactOptions<T, V>)
-		-> Result<Executed, ExecutionError> where T: Tracer, V: VMTracer,
+		-> Result<Executed<V::Output>, ExecutionError> where T: Tracer, V: VMTracer,
 	{
 		self.transact_with_tracer(t, options.check_nonce, options.output_from_init_contract, options.tracer, options.vm_tracer)
 	}
@@ -223,7 +232,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 	/// This will ensure the caller has enough balance to execute the desired transaction.
 	/// Used for extra-block executions for things like consensus contracts and RPCs
 	pub fn transact_virtual<T, V>(&'a mut self, t: &SignedTransaction, options: TransactOptions<T, V>)
-		-> Result<Executed, ExecutionError> where T: Tracer, V: VMTracer,
+		-> Result<Executed<V::Output>, ExecutionError> where T: Tracer, V: VMTracer,
 	{
 		let sender = t.sender();
 		let balance = self.state.balance(&sender)?;
@@ -244,7 +253,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		output_from_create: bool,
 		mut tracer: T,
 		mut vm_tracer: V
-	) -> Result<Executed, ExecutionError> where T: Tracer, V: VMTracer {
+	) -> Result<Executed<V::Output>, ExecutionError> where T: Tracer, V: VMTracer {
 		let sender = t.sender();
 		let nonce = self.state.nonce(&sender)?;
 
@@ -484,7 +493,13 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 				let traces = subtracer.drain();
 				match res {
-					Ok(ref res) => tracer.trace_call(
+					Ok(ref res) if res.apply_state => tracer.trace_call(
+						trace_info,
+						gas - res.gas_left,
+						trace_output,
+						traces
+					),
+					Ok(ref res) => tracer.trace_reverted_call(
 						trace_info,
 						gas - res.gas_left,
 						trace_output,
@@ -572,13 +587,19 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		vm_tracer.done_subtrace(subvmtracer);
 
 		match res {
-			Ok(ref res) => tracer.trace_create(
+			Ok(ref res) if res.apply_state => tracer.trace_create(
 				trace_info,
 				gas - res.gas_left,
 				trace_output.map(|data| output.as_ref().map(|out| out.to_vec()).unwrap_or(data)),
 				created,
 				subtracer.drain()
 			),
+			Ok(ref res) => tracer.trace_reverted_create(
+				trace_info,
+				gas - res.gas_left,
+				trace_output.map(|data| output.as_ref().map(|out| out.to_vec()).unwrap_or(data)),
+				subtracer.drain()
+			),
 			Err(ref e) => tracer.trace_failed_create(trace_info, subtracer.drain(), e.into())
 		};
 
@@ -587,19 +608,23 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 	}
 
 	/// Finalizes the transaction (does refunds and suicides).
-	fn finalize(
+	fn finalize<V>(
 		&mut self,
 		t: &SignedTransaction,
 		mut substate: Substate,
 		result: vm::Result<FinalizationResult>,
 		output: Bytes,
 		trace: Vec<FlatTrace>,
-		vm_trace: Option<VMTrace>
-	) -> ExecutionResult {
+		vm_trace: Option<V>
+	) -> Result<Executed<V>, ExecutionError> {
 		let schedule = self.machine.schedule(self.info.number);
 
 		// refunds from SSTORE nonzero -> zero
-		let sstore_refunds = U256::from(schedule.sstore_refund_gas) * substate.sstore_clears_count;
+		let sstore_refunds = match substate.sstore_clears_refund {
+			refund if refund <= 0 => U256::zero(),
+			refund if refund >= u64::max_value() as i128 => U256::from(u64::max_value()),
+			refund => U256::from(refund as u64),
+		};
 		// refunds from contract suicides
 		let suicide_refunds = U256::from(schedule.suicide_refund_gas) * U256::from(substate.suicides.len());
 		let refunds_bound = sstore_refunds + suicide_refunds;
@@ -726,6 +751,22 @@ mod tests {
 		assert_eq!(expected_address, contract_address(CreateContractAddress::FromSenderAndNonce, &address, &U256::from(88), &[]).0);
 	}
 
+	#[test]
+	fn test_contract_address_create2() {
+		// EIP-1014 test cases
+		let address = Address::zero();
+		let expected_address = Address::from_str("4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38").unwrap();
+		assert_eq!(expected_address, contract_address(CreateContractAddress::FromSenderSaltAndCodeHash(H256::zero()), &address, &U256::zero(), &[0x00]).0);
+
+		let address = Address::from_str("deadbeef00000000000000000000000000000000").unwrap();
+		let expected_address = Address::from_str("b928f69bb1d91cd65274e3c79d8986362984fda3").unwrap();
+		assert_eq!(expected_address, contract_address(CreateContractAddress::FromSenderSaltAndCodeHash(H256::zero()), &address, &U256::zero(), &[0x00]).0);
+
+		let address = Address::zero();
+		let expected_address = Address::from_str("e33c0c7f7df4809055c3eba6c09cfe4baf1bd9e0").unwrap();
+		assert_eq!(expected_address, contract_address(CreateContractAddress::FromSenderSaltAndCodeHash(H256::zero()), &address, &U256::zero(), &[]).0);
+	}
+
 	// TODO: replace params with transactions!
 	evm_test!{test_sender_balance: test_sender_balance_jit, test_sender_balance_int}
 	fn test_sender_balance(factory: Factory) {
@@ -1422,4 +1463,180 @@ mod tests {
 		assert_eq!(output[..], returns[..]);
 		assert_eq!(state.storage_at(&contract_address, &H256::from(&U256::zero())).unwrap(), H256::from(&U256::from(0)));
 	}
+
+	evm_test!{test_revert_trace: test_revert_trace_jit, test_revert_trace_int}
+	fn test_revert_trace(factory: Factory) {
+		let contract_address = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let sender = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		// EIP-140 test case
+		let code = "6c726576657274656420646174616000557f726576657274206d657373616765000000000000000000000000000000000000600052600e6000fd".from_hex().unwrap();
+		let returns = "726576657274206d657373616765".from_hex().unwrap();
+
+		let mut params = ActionParams::default();
+		params.address = contract_address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(20025);
+		params.code = Some(Arc::new(code));
+		params.value = ActionValue::Transfer(U256::zero());
+		let mut state = get_temp_state_with_factory(factory);
+		state.add_balance(&sender, &U256::from_str("152d02c7e14af68000000").unwrap(), CleanupMode::NoEmpty).unwrap();
+		let info = EnvInfo::default();
+		let machine = ::ethereum::new_byzantium_test_machine();
+		let mut substate = Substate::new();
+		let mut tracer = ExecutiveTracer::default();
+		let mut vm_tracer = ExecutiveVMTracer::toplevel();
+
+		let mut output = [0u8; 14];
+		{
+			let mut ex = Executive::new(&mut state, &info, &machine);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut output), &mut tracer, &mut vm_tracer).unwrap();
+		}
+
+		let traces = tracer.drain();
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0].result, trace::Res::RevertedCall(trace::CallResult {
+			gas_used: U256::from(20024),
+			output: returns,
+		}));
+	}
+
+	fn test_sstore_eip1283(factory: Factory, code: &str, original: u64, expected_gas_used: u64, expected_refund: i128) {
+		let address = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let sender = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+
+		let mut state = get_temp_state_with_factory(factory);
+		state.add_balance(&sender, &U256::from(1_000_000u64), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&address, H256::new(), H256::from(&U256::from(original))).unwrap();
+		state.commit().unwrap();
+
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code.from_hex().unwrap()));
+		params.value = ActionValue::Transfer(U256::zero());
+		let info = EnvInfo::default();
+		let machine = ::ethereum::new_constantinople_test_machine();
+		let mut substate = Substate::new();
+
+		let FinalizationResult { gas_left, .. } = {
+			let mut ex = Executive::new(&mut state, &info, &machine);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap()
+		};
+
+		assert_eq!(U256::from(100_000) - gas_left, U256::from(expected_gas_used), "gas used for {}", code);
+		assert_eq!(substate.sstore_clears_refund, expected_refund, "refund for {}", code);
+	}
+
+	evm_test!{ignorejit => test_create2: test_create2_jit, test_create2_int}
+	fn test_create2(factory: Factory) {
+		// 60 00 - push 0 (salt)
+		// 60 01 - push 1 (size of the init code 0x00)
+		// 60 00 - push 0 (offset)
+		// 60 00 - push 0 (endowment)
+		// f5 - create2
+		// 60 00 - push 0
+		// 55 - sstore
+		let code = "6000600160006000f5600055".from_hex().unwrap();
+
+		// EIP-1014 example 1
+		let address = Address::from_str("deadbeef00000000000000000000000000000000").unwrap();
+		let expected_address = Address::from_str("b928f69bb1d91cd65274e3c79d8986362984fda3").unwrap();
+		let sender = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code));
+		params.value = ActionValue::Transfer(U256::zero());
+		let mut state = get_temp_state_with_factory(factory);
+		let info = EnvInfo::default();
+		let machine = ::ethereum::new_constantinople_test_machine();
+		let mut substate = Substate::new();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &machine);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+		}
+
+		assert_eq!(substate.contracts_created, vec![expected_address.clone()]);
+		assert_eq!(state.storage_at(&address, &H256::new()).unwrap(), H256::from(expected_address));
+	}
+
+	evm_test!{ignorejit => test_sstore_eip1283_in_subcall: test_sstore_eip1283_in_subcall_jit, test_sstore_eip1283_in_subcall_int}
+	fn test_sstore_eip1283_in_subcall(factory: Factory) {
+		// 60 00 - push 0
+		// 60 00 - push 0
+		// 60 00 - push 0
+		// 60 00 - push 0
+		// 60 00 - push 0
+		// 73 945304eb96065b2a98b57a48a06ae28d285a71b5 - push callee address
+		// 5a - get gas
+		// f1 - message call
+		let code_a = "6000600060006000600073945304eb96065b2a98b57a48a06ae28d285a71b55af1".from_hex().unwrap();
+		// 60 00 - push 0
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 01 - push 1
+		// 60 00 - push 0
+		// 55 - sstore
+		let code_b = "60006000556001600055".from_hex().unwrap();
+
+		let address_a = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		let address_b = Address::from_str("945304eb96065b2a98b57a48a06ae28d285a71b5").unwrap();
+		let sender = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+
+		let mut state = get_temp_state_with_factory(factory);
+		state.init_code(&address_a, code_a.clone()).unwrap();
+		state.init_code(&address_b, code_b).unwrap();
+		state.set_storage(&address_b, H256::new(), H256::from(&U256::from(1))).unwrap();
+		state.commit().unwrap();
+
+		let mut params = ActionParams::default();
+		params.address = address_a.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code_a));
+		params.value = ActionValue::Transfer(U256::zero());
+		let info = EnvInfo::default();
+		let machine = ::ethereum::new_constantinople_test_machine();
+		let mut substate = Substate::new();
+
+		let FinalizationResult { gas_left, .. } = {
+			let mut ex = Executive::new(&mut state, &info, &machine);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap()
+		};
+
+		// the callee's second SSTORE resets the slot it cleared first: 5000 + 200 gas
+		// and a refund of 4800, on top of the caller's 5 pushes, PUSH20, GAS and CALL.
+		assert_eq!(U256::from(100_000) - gas_left, U256::from(15 + 3 + 2 + 700 + 5212));
+		assert_eq!(substate.sstore_clears_refund, 4800);
+	}
+
+	// EIP-1283 test cases
+	evm_test!{ignorejit => test_sstore_eip1283_net_metering: test_sstore_eip1283_net_metering_jit, test_sstore_eip1283_net_metering_int}
+	fn test_sstore_eip1283_net_metering(factory: Factory) {
+		test_sstore_eip1283(factory.clone(), "60006000556000600055", 0, 1612, 0);
+		test_sstore_eip1283(factory.clone(), "60006000556001600055", 0, 20212, 0);
+		test_sstore_eip1283(factory.clone(), "60016000556000600055", 0, 20212, 19800);
+		test_sstore_eip1283(factory.clone(), "60016000556002600055", 0, 20212, 0);
+		test_sstore_eip1283(factory.clone(), "60016000556001600055", 0, 20212, 0);
+		test_sstore_eip1283(factory.clone(), "60006000556000600055", 1, 5212, 15000);
+		test_sstore_eip1283(factory.clone(), "60006000556001600055", 1, 5212, 4800);
+		test_sstore_eip1283(factory.clone(), "60006000556002600055", 1, 5212, 0);
+		test_sstore_eip1283(factory.clone(), "60026000556000600055", 1, 5212, 15000);
+		test_sstore_eip1283(factory.clone(), "60026000556003600055", 1, 5212, 0);
+		test_sstore_eip1283(factory.clone(), "60026000556001600055", 1, 5212, 4800);
+		test_sstore_eip1283(factory.clone(), "60026000556002600055", 1, 5212, 0);
+		test_sstore_eip1283(factory.clone(), "60016000556000600055", 1, 5212, 15000);
+		test_sstore_eip1283(factory.clone(), "60016000556002600055", 1, 5212, 0);
+		test_sstore_eip1283(factory.clone(), "60016000556001600055", 1, 412, 0);
+		test_sstore_eip1283(factory.clone(), "600160005560006000556001600055", 0, 40218, 19800);
+		test_sstore_eip1283(factory, "600060005560016000556000600055", 1, 10218, 19800);
+	}
 }
@@ -0,0 +1,651 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Flat state: a key-value index of the accounts and storage of a recent canonical state.
+//!
+//! Accounts are stored under their hashed address and storage values under the hashed
+//! address followed by the hashed key, so reading them doesn't involve trie lookups.
+//! The database holds the state of a single "base" block. Changes of the following blocks
+//! are kept as diff layers, journalled along with the blocks they belong to and merged
+//! into the base once they are deep enough in the canonical chain not to be reverted.
+//! The base is generated from the state trie in the background; until it's done, nothing
+//! is read from the flat state.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bigint::hash::H256;
+use bigint::prelude::U256;
+use bytes::Bytes;
+use hash::KECCAK_NULL_RLP;
+use kvdb::{DBTransaction, DBValue, KeyValueDB};
+use parking_lot::RwLock;
+use rlp::{self, DecoderError, RlpStream, UntrustedRlp};
+use trie::{Trie, TrieDB, TrieError};
+use util::HashDB;
+
+use account_db::Factory as AccountFactory;
+use db::COL_FLAT_STATE;
+use error::Error;
+use header::BlockNumber;
+
+/// Number of canonical blocks kept as diff layers on top of the base.
+pub const FLAT_STATE_LAYERS: usize = 64;
+
+const BASE_KEY: &'static [u8] = b"base";
+
+const LAYER_PREFIX: &'static [u8] = b"layer";
+
+const BATCH_SIZE: usize = 10_000;
+
+/// State changes committed by a block, keyed by hashed address and hashed storage key.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FlatChanges {
+	/// New account RLPs. `None` for removed accounts.
+	pub accounts: HashMap<H256, Option<Bytes>>,
+	/// Accounts whose storage was wiped before the changes in `storage` were made.
+	pub cleared: HashSet<H256>,
+	/// New storage values. Zero values are removed.
+	pub storage: HashMap<(H256, H256), H256>,
+}
+
+impl FlatChanges {
+	/// Apply later changes on top of these ones.
+	pub fn merge(&mut self, other: FlatChanges) {
+		for address_hash in other.cleared {
+			self.storage.retain(|&(ref a, _), _| *a != address_hash);
+			self.cleared.insert(address_hash);
+		}
+		self.accounts.extend(other.accounts);
+		self.storage.extend(other.storage);
+	}
+}
+
+// the block whose state is stored in the database.
+#[derive(Debug, Clone, PartialEq)]
+struct Base {
+	number: BlockNumber,
+	hash: H256,
+	state_root: H256,
+}
+
+// changes of a block on top of its parent.
+struct Layer {
+	number: BlockNumber,
+	parent: H256,
+	state_root: H256,
+	changes: FlatChanges,
+}
+
+// a generation of the base running in the background.
+struct Generation {
+	target: Base,
+	abort: Arc<AtomicBool>,
+}
+
+/// Flat state database along with the diff layers of recent blocks.
+pub struct FlatState {
+	db: Arc<KeyValueDB>,
+	base: Option<Base>,
+	layers: HashMap<H256, Layer>,
+	best: Option<H256>,
+	// storage keys written by the latest merges into the base, by account.
+	// The database may still buffer them, out of reach of prefix iteration.
+	recent_storage: VecDeque<HashMap<H256, Vec<H256>>>,
+	generation: Option<Generation>,
+}
+
+impl FlatState {
+	/// Open the flat state stored in the database along with its journalled layers.
+	/// It's only usable once it's known to hold the best block; see `is_at` and `set_best`.
+	pub fn open(db: Arc<KeyValueDB>) -> Result<Self, Error> {
+		let base = match db.get(COL_FLAT_STATE, BASE_KEY)? {
+			Some(raw) => {
+				let rlp = UntrustedRlp::new(&raw);
+				Some(Base {
+					number: rlp.val_at(0)?,
+					hash: rlp.val_at(1)?,
+					state_root: rlp.val_at(2)?,
+				})
+			},
+			None => None,
+		};
+
+		let mut layers = HashMap::new();
+		if base.is_some() {
+			for (key, value) in db.iter_from_prefix(COL_FLAT_STATE, LAYER_PREFIX).take_while(|&(ref k, _)| k.starts_with(LAYER_PREFIX)) {
+				let hash = H256::from_slice(&key[LAYER_PREFIX.len()..]);
+				layers.insert(hash, decode_layer(&value)?);
+			}
+		}
+
+		Ok(FlatState {
+			db: db,
+			base: base,
+			layers: layers,
+			best: None,
+			recent_storage: VecDeque::new(),
+			generation: None,
+		})
+	}
+
+	/// Whether the flat state holds the state of the given block.
+	pub fn is_at(&self, hash: &H256) -> bool {
+		let base = match self.base {
+			Some(ref base) => base,
+			None => return false,
+		};
+
+		let mut hash = hash.clone();
+		while hash != base.hash {
+			match self.layers.get(&hash) {
+				Some(layer) => hash = layer.parent.clone(),
+				None => return false,
+			}
+		}
+		true
+	}
+
+	/// Make the given block the best one. Its state must be held; see `is_at`.
+	pub fn set_best(&mut self, hash: H256) {
+		self.best = Some(hash);
+	}
+
+	/// Start generating the base from the state of the given block, forgetting the current
+	/// state. Until `generate` is done, blocks added are only kept in memory and nothing is read
+	/// from the flat state. Returns the flag aborting the generation, which `discard` raises.
+	pub fn begin_generation(&mut self, number: BlockNumber, hash: H256, state_root: H256) -> Arc<AtomicBool> {
+		self.discard();
+		let abort = Arc::new(AtomicBool::new(false));
+		self.generation = Some(Generation {
+			target: Base { number: number, hash: hash, state_root: state_root },
+			abort: abort.clone(),
+		});
+		abort
+	}
+
+	/// Whether the base is being generated.
+	pub fn is_generating(&self) -> bool {
+		self.generation.is_some()
+	}
+
+	// generate the base from the best block instead, once the state of the target was pruned.
+	// Returns `false` if no block was added since the generation started.
+	fn restart_generation(&mut self) -> bool {
+		let target = match (self.best.as_ref(), self.generation.as_ref()) {
+			(Some(best), Some(generation)) => match self.layers.get(best) {
+				Some(layer) if layer.number > generation.target.number => Base { number: layer.number, hash: best.clone(), state_root: layer.state_root.clone() },
+				_ => return false,
+			},
+			_ => return false,
+		};
+
+		let number = target.number;
+		self.layers.retain(|_, layer| layer.number > number);
+		if let Some(ref mut generation) = self.generation {
+			generation.target = target;
+		}
+		true
+	}
+
+	// make the generated base usable, journalling the layers added meanwhile.
+	// Returns `false` if the generation was aborted.
+	fn finish_generation(&mut self, abort: &AtomicBool) -> Result<bool, Error> {
+		if abort.load(Ordering::SeqCst) {
+			return Ok(false);
+		}
+		let base = match self.generation.take() {
+			Some(generation) => generation.target,
+			None => return Ok(false),
+		};
+
+		let number = base.number;
+		self.layers.retain(|_, layer| layer.number > number);
+
+		let mut batch = DBTransaction::new();
+		batch.put(COL_FLAT_STATE, BASE_KEY, &encode_base(&base));
+		for (hash, layer) in &self.layers {
+			batch.put(COL_FLAT_STATE, &layer_key(hash), &encode_layer(layer));
+		}
+		self.db.write(batch)?;
+
+		if self.best.is_none() {
+			self.best = Some(base.hash.clone());
+		}
+		self.base = Some(base);
+		Ok(true)
+	}
+
+	/// Forget the in-memory flat state, aborting its generation. Whatever has been written to
+	/// the database is kept.
+	pub fn discard(&mut self) {
+		if let Some(generation) = self.generation.take() {
+			generation.abort.store(true, Ordering::SeqCst);
+		}
+		self.base = None;
+		self.best = None;
+		self.layers.clear();
+		self.recent_storage.clear();
+	}
+
+	/// Remove everything from the flat state.
+	pub fn clear(&mut self) -> Result<(), Error> {
+		self.discard();
+
+		let mut batch = DBTransaction::new();
+		batch.delete(COL_FLAT_STATE, BASE_KEY);
+		self.db.write(batch)?;
+
+		loop {
+			let mut batch = DBTransaction::new();
+			for (key, _) in self.db.iter(COL_FLAT_STATE).take(BATCH_SIZE) {
+				batch.delete(COL_FLAT_STATE, &key);
+			}
+			if batch.ops.is_empty() {
+				return Ok(());
+			}
+			self.db.write(batch)?;
+		}
+	}
+
+	/// Get an account RLP as of the given state root.
+	/// Returns `None` if that state is not known, `Some(None)` if the account doesn't exist.
+	pub fn account(&self, state_root: &H256, address_hash: &H256) -> Option<Option<Bytes>> {
+		let layers = match self.layers_of(state_root) {
+			Some(layers) => layers,
+			None => return None,
+		};
+
+		for layer in layers {
+			if let Some(account) = layer.changes.accounts.get(address_hash) {
+				return Some(account.clone());
+			}
+		}
+
+		self.db_get(address_hash).map(|account| account.map(|a| a.to_vec()))
+	}
+
+	/// Get a storage value as of the given state root, provided the storage root of the
+	/// account in that state is `storage_root`. Returns `None` if it can't be answered.
+	pub fn storage(&self, state_root: &H256, address_hash: &H256, storage_root: &H256, key_hash: &H256) -> Option<H256> {
+		match self.account(state_root, address_hash) {
+			Some(Some(ref account)) if UntrustedRlp::new(account).val_at::<H256>(2).ok().as_ref() == Some(storage_root) => {},
+			_ => return None,
+		}
+
+		let layers = match self.layers_of(state_root) {
+			Some(layers) => layers,
+			None => return None,
+		};
+
+		let key = (address_hash.clone(), key_hash.clone());
+		for layer in layers {
+			if let Some(value) = layer.changes.storage.get(&key) {
+				return Some(value.clone());
+			}
+			if layer.changes.cleared.contains(address_hash) {
+				return Some(H256::zero());
+			}
+		}
+
+		self.db_get(&storage_key(address_hash, key_hash))
+			.map(|value| value.map_or_else(H256::zero, |v| rlp::decode::<U256>(&v).into()))
+	}
+
+	/// Add the changes of a block, journalling them to `batch`, which must be the one the
+	/// block is committed in. `is_best` tells whether it's the new best block, in which case
+	/// the layers falling out of the window are merged into the base in the same batch.
+	/// While the base is being generated, the layer is only kept in memory.
+	pub fn add_layer(&mut self, batch: &mut DBTransaction, number: BlockNumber, hash: H256, parent: H256, state_root: H256, changes: FlatChanges, is_best: bool) -> Result<(), Error> {
+		if self.base.is_none() && self.generation.is_none() {
+			return Ok(());
+		}
+
+		let layer = Layer {
+			number: number,
+			parent: parent,
+			state_root: state_root,
+			changes: changes,
+		};
+		if self.generation.is_none() {
+			batch.put(COL_FLAT_STATE, &layer_key(&hash), &encode_layer(&layer));
+		}
+		self.layers.insert(hash.clone(), layer);
+
+		if is_best {
+			self.best = Some(hash);
+			self.flush(batch, FLAT_STATE_LAYERS)?;
+		}
+		Ok(())
+	}
+
+	// merge the layers of the canonical chain deeper than `keep` blocks into the base.
+	// The flat state is cleared if the best block doesn't descend from the base.
+	fn flush(&mut self, batch: &mut DBTransaction, keep: usize) -> Result<(), Error> {
+		let base = match self.base.clone() {
+			Some(base) => base,
+			None => return Ok(()),
+		};
+
+		// the canonical layers, most recent first.
+		let mut chain = Vec::new();
+		let mut hash = match self.best.clone() {
+			Some(hash) => hash,
+			None => return Ok(()),
+		};
+		while hash != base.hash {
+			let parent = self.layers.get(&hash).map(|layer| layer.parent.clone());
+			match parent {
+				Some(parent) => {
+					chain.push(hash);
+					hash = parent;
+				},
+				None => {
+					warn!(target: "flat_state", "Best block doesn't descend from flat state base #{}; clearing flat state", base.number);
+					return self.clear();
+				},
+			}
+		}
+
+		if chain.len() <= keep {
+			return Ok(());
+		}
+
+		// storage cleared in a later layer must not be written by an earlier one,
+		// so the changes are merged first.
+		let mut changes = FlatChanges::default();
+		let mut new_base = base;
+		for hash in chain.drain(keep..).rev() {
+			let layer = self.layers.remove(&hash).expect("chain consists of known layers; qed");
+			batch.delete(COL_FLAT_STATE, &layer_key(&hash));
+			changes.merge(layer.changes);
+			new_base = Base { number: layer.number, hash: hash, state_root: layer.state_root };
+		}
+
+		self.write_changes(batch, &changes);
+		batch.put(COL_FLAT_STATE, BASE_KEY, &encode_base(&new_base));
+
+		let mut written = HashMap::new();
+		for &(ref address_hash, ref key_hash) in changes.storage.keys() {
+			written.entry(address_hash.clone()).or_insert_with(Vec::new).push(key_hash.clone());
+		}
+		self.recent_storage.push_front(written);
+		self.recent_storage.truncate(FLAT_STATE_LAYERS);
+
+		// layers not descending from the new base can't be used anymore.
+		let number = new_base.number;
+		let stale: Vec<H256> = self.layers.iter()
+			.filter(|&(_, layer)| layer.number <= number)
+			.map(|(hash, _)| hash.clone())
+			.collect();
+		for hash in stale {
+			self.layers.remove(&hash);
+			batch.delete(COL_FLAT_STATE, &layer_key(&hash));
+		}
+		trace!(target: "flat_state", "Flat state base moved to #{} ({})", new_base.number, new_base.hash);
+		self.base = Some(new_base);
+		Ok(())
+	}
+
+	// the layers between the base and the state with the given root, most recent first.
+	// `None` if that state isn't held.
+	fn layers_of(&self, state_root: &H256) -> Option<Vec<&Layer>> {
+		let base = match self.base {
+			Some(ref base) => base,
+			None => return None,
+		};
+
+		let mut layers = Vec::new();
+		let mut next = self.layers.values().find(|l| l.state_root == *state_root);
+		if next.is_none() && base.state_root != *state_root {
+			return None;
+		}
+
+		while let Some(layer) = next {
+			layers.push(layer);
+			if layer.parent == base.hash {
+				return Some(layers);
+			}
+			next = self.layers.get(&layer.parent);
+		}
+
+		match layers.is_empty() {
+			true => Some(layers),
+			// the chain of layers is broken.
+			false => None,
+		}
+	}
+
+	fn db_get(&self, key: &[u8]) -> Option<Option<DBValue>> {
+		match self.db.get(COL_FLAT_STATE, key) {
+			Ok(value) => Some(value),
+			Err(e) => {
+				warn!(target: "flat_state", "Failed to read flat state: {}", e);
+				None
+			},
+		}
+	}
+
+	fn write_changes(&self, batch: &mut DBTransaction, changes: &FlatChanges) {
+		for address_hash in &changes.cleared {
+			let prefix = &address_hash[..];
+			for (key, _) in self.db.iter_from_prefix(COL_FLAT_STATE, prefix).take_while(|&(ref k, _)| k.starts_with(prefix)) {
+				if key.len() == 64 {
+					batch.delete(COL_FLAT_STATE, &key);
+				}
+			}
+			for key_hashes in self.recent_storage.iter().filter_map(|written| written.get(address_hash)) {
+				for key_hash in key_hashes {
+					batch.delete(COL_FLAT_STATE, &storage_key(address_hash, key_hash));
+				}
+			}
+		}
+
+		for (address_hash, account) in &changes.accounts {
+			match *account {
+				Some(ref account) => batch.put(COL_FLAT_STATE, address_hash, account),
+				None => batch.delete(COL_FLAT_STATE, address_hash),
+			}
+		}
+
+		for (&(ref address_hash, ref key_hash), value) in &changes.storage {
+			let key = storage_key(address_hash, key_hash);
+			match value.is_zero() {
+				true => batch.delete(COL_FLAT_STATE, &key),
+				false => batch.put(COL_FLAT_STATE, &key, &rlp::encode(&U256::from(&**value))),
+			}
+		}
+	}
+}
+
+/// Generate the base of a flat state from the state trie, once `begin_generation` was called.
+///
+/// Blocks with a pruned state are skipped: the generation restarts from the best block added
+/// meanwhile. Returns `false` if the generation was aborted.
+pub fn generate(flat_state: &RwLock<FlatState>, state_db: &HashDB, accountdb: &AccountFactory, abort: &AtomicBool) -> Result<bool, Error> {
+	let db = flat_state.read().db.clone();
+	// batches are written under the lock, so that none is written once the flat state is discarded.
+	let write = |batch: DBTransaction| -> Result<bool, Error> {
+		let _flat_state = flat_state.read();
+		match abort.load(Ordering::SeqCst) {
+			true => Ok(false),
+			false => db.write(batch).map(|_| true).map_err(Into::into),
+		}
+	};
+
+	loop {
+		let target = match flat_state.read().generation {
+			Some(ref generation) => generation.target.clone(),
+			None => return Ok(false),
+		};
+
+		info!(target: "flat_state", "Generating flat state of block #{} ({})", target.number, target.hash);
+		let accounts = match write_state(&*db, state_db, accountdb, &target.state_root, &write) {
+			Ok(Some(accounts)) => accounts,
+			Ok(None) => return Ok(false),
+			Err(Error::Trie(TrieError::InvalidStateRoot(key))) | Err(Error::Trie(TrieError::IncompleteDatabase(key))) => {
+				if abort.load(Ordering::SeqCst) {
+					return Ok(false);
+				}
+				if !flat_state.write().restart_generation() {
+					return Err(TrieError::IncompleteDatabase(key).into());
+				}
+				info!(target: "flat_state", "State of block #{} was pruned while generating the flat state", target.number);
+				continue;
+			},
+			Err(e) => return Err(e),
+		};
+
+		return match flat_state.write().finish_generation(abort)? {
+			true => {
+				info!(target: "flat_state", "Flat state generated with {} accounts", accounts);
+				Ok(true)
+			},
+			false => Ok(false),
+		};
+	}
+}
+
+// replace the content of the database with the accounts and storage of the given state.
+// Returns the number of accounts, or `None` if `write` refused a batch.
+fn write_state<F>(db: &KeyValueDB, state_db: &HashDB, accountdb: &AccountFactory, state_root: &H256, write: F) -> Result<Option<usize>, Error>
+	where F: Fn(DBTransaction) -> Result<bool, Error>
+{
+	// the base goes first, so that the database isn't taken for a complete state meanwhile.
+	let mut batch = DBTransaction::new();
+	batch.delete(COL_FLAT_STATE, BASE_KEY);
+	if !write(batch)? {
+		return Ok(None);
+	}
+
+	loop {
+		let mut batch = DBTransaction::new();
+		for (key, _) in db.iter(COL_FLAT_STATE).take(BATCH_SIZE) {
+			batch.delete(COL_FLAT_STATE, &key);
+		}
+		if batch.ops.is_empty() {
+			break;
+		}
+		if !write(batch)? {
+			return Ok(None);
+		}
+	}
+
+	let mut batch = DBTransaction::new();
+	let mut accounts = 0usize;
+	let trie = TrieDB::new(state_db, state_root)?;
+	for item in trie.iter()? {
+		let (address_hash, account) = item?;
+		batch.put(COL_FLAT_STATE, &address_hash[..], &account[..]);
+		accounts += 1;
+
+		let storage_root: H256 = UntrustedRlp::new(&account).val_at(2)?;
+		if storage_root != KECCAK_NULL_RLP {
+			let address_hash = H256::from_slice(&address_hash);
+			let account_db = accountdb.readonly(state_db, address_hash);
+			let storage = TrieDB::new(&*account_db, &storage_root)?;
+			for item in storage.iter()? {
+				let (key, value) = item?;
+				batch.put(COL_FLAT_STATE, &storage_key(&address_hash, &H256::from_slice(&key)), &value[..]);
+				if batch.ops.len() >= BATCH_SIZE && !write(mem::replace(&mut batch, DBTransaction::new()))? {
+					return Ok(None);
+				}
+			}
+		}
+
+		if batch.ops.len() >= BATCH_SIZE && !write(mem::replace(&mut batch, DBTransaction::new()))? {
+			return Ok(None);
+		}
+	}
+
+	match write(batch)? {
+		true => Ok(Some(accounts)),
+		false => Ok(None),
+	}
+}
+
+// key of a storage value in the database.
+fn storage_key(address_hash: &H256, key_hash: &H256) -> Vec<u8> {
+	let mut key = Vec::with_capacity(64);
+	key.extend_from_slice(address_hash);
+	key.extend_from_slice(key_hash);
+	key
+}
+
+// key of a journalled layer in the database.
+fn layer_key(hash: &H256) -> Vec<u8> {
+	let mut key = Vec::with_capacity(LAYER_PREFIX.len() + 32);
+	key.extend_from_slice(LAYER_PREFIX);
+	key.extend_from_slice(hash);
+	key
+}
+
+fn encode_base(base: &Base) -> Bytes {
+	let mut stream = RlpStream::new_list(3);
+	stream.append(&base.number).append(&base.hash).append(&base.state_root);
+	stream.out()
+}
+
+fn encode_layer(layer: &Layer) -> Bytes {
+	let changes = &layer.changes;
+	let mut stream = RlpStream::new_list(6);
+	stream.append(&layer.number).append(&layer.parent).append(&layer.state_root);
+
+	stream.begin_list(changes.accounts.len());
+	for (address_hash, account) in &changes.accounts {
+		match *account {
+			Some(ref account) => stream.begin_list(2).append(address_hash).append(account),
+			None => stream.begin_list(1).append(address_hash),
+		};
+	}
+
+	stream.begin_list(changes.cleared.len());
+	for address_hash in &changes.cleared {
+		stream.append(address_hash);
+	}
+
+	stream.begin_list(changes.storage.len());
+	for (&(ref address_hash, ref key_hash), value) in &changes.storage {
+		stream.begin_list(3).append(address_hash).append(key_hash).append(value);
+	}
+	stream.out()
+}
+
+fn decode_layer(raw: &[u8]) -> Result<Layer, DecoderError> {
+	let rlp = UntrustedRlp::new(raw);
+	let mut changes = FlatChanges::default();
+
+	for item in rlp.at(3)?.iter() {
+		let account = match item.item_count()? {
+			2 => Some(item.val_at(1)?),
+			_ => None,
+		};
+		changes.accounts.insert(item.val_at(0)?, account);
+	}
+	changes.cleared = rlp.list_at::<H256>(4)?.into_iter().collect();
+	for item in rlp.at(5)?.iter() {
+		changes.storage.insert((item.val_at(0)?, item.val_at(1)?), item.val_at(2)?);
+	}
+
+	Ok(Layer {
+		number: rlp.val_at(0)?,
+		parent: rlp.val_at(1)?,
+		state_root: rlp.val_at(2)?,
+		changes: changes,
+	})
+}
@@ -113,6 +113,7 @@ extern crate semantic_version;
 extern crate unexpected;
 extern crate kvdb;
 extern crate kvdb_rocksdb;
+extern crate kvdb_paritydb;
 extern crate kvdb_memorydb;
 extern crate util_error;
 extern crate snappy;
@@ -151,8 +152,11 @@ pub mod account_provider;
 pub mod block;
 pub mod client;
 pub mod db;
+pub mod db_check;
+pub mod db_stats;
 pub mod encoded;
 pub mod engines;
+pub mod era;
 pub mod error;
 pub mod ethereum;
 pub mod executed;
@@ -176,6 +180,7 @@ mod blooms;
 mod basic_types;
 mod pod_account;
 mod state_db;
+mod flat_state;
 mod account_db;
 mod builtin;
 mod executive;
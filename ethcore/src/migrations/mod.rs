@@ -29,6 +29,9 @@ pub use self::v9::Extract;
 mod v10;
 pub use self::v10::ToV10;
 
+mod pruning;
+pub use self::pruning::{prune_state, PruningReport};
+
 /// The migration from v10 to v11.
 /// Adds a column for node info.
 pub const TO_V11: ChangeColumns = ChangeColumns {
@@ -44,3 +47,19 @@ pub const TO_V12: ChangeColumns = ChangeColumns {
 	post_columns: Some(8),
 	version: 12,
 };
+
+/// The migration from v12 to v13.
+/// Adds a column for the flat state.
+pub const TO_V13: ChangeColumns = ChangeColumns {
+	pre_columns: Some(8),
+	post_columns: Some(9),
+	version: 13,
+};
+
+/// The migration from v13 to v14.
+/// Adds columns for state diffs and VM traces.
+pub const TO_V14: ChangeColumns = ChangeColumns {
+	pre_columns: Some(9),
+	post_columns: Some(11),
+	version: 14,
+};
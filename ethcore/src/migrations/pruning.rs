@@ -0,0 +1,156 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline conversion of an archive database into a pruned one.
+
+use std::collections::HashSet;
+use std::mem;
+use std::sync::Arc;
+
+use bigint::hash::H256;
+use journaldb::{self, Algorithm, JournalDB};
+use kvdb::{DBTransaction, KeyValueDB};
+use trie::TrieError;
+use util::{HashDB, DBValue};
+
+use blockchain::{BlockChain, BlockProvider};
+use db;
+use error::Error;
+use snapshot::Error as SnapshotError;
+use state::walk_state;
+
+/// Number of items written to the new database at once.
+const BATCH_SIZE: usize = 10_000;
+
+/// Summary of a state pruning.
+#[derive(Debug, PartialEq)]
+pub struct PruningReport {
+	/// Oldest block whose state was kept. Its state is written directly to the database.
+	pub base_era: u64,
+	/// Best block of the chain.
+	pub latest_era: u64,
+	/// Number of state nodes copied.
+	pub nodes: usize,
+}
+
+/// Copies an archive database into `dest`, keeping only the state of the last `keep` canonical blocks.
+///
+/// All other columns are copied verbatim. The state of the oldest kept block is written
+/// directly to the database, and the nodes introduced by each following block are journalled
+/// under that block for the `target` algorithm. Nodes replaced within the kept blocks are not
+/// journalled as removed, so they stay in the database once these blocks become ancient.
+pub fn prune_state(
+	source: Arc<KeyValueDB>,
+	dest: Arc<KeyValueDB>,
+	genesis: &[u8],
+	target: Algorithm,
+	keep: u64,
+) -> Result<PruningReport, Error> {
+	let chain = BlockChain::new(Default::default(), genesis, source.clone());
+	let latest_era = chain.best_block_number();
+	let base_era = latest_era.saturating_sub(keep);
+
+	copy_chain_data(&*source, &*dest)?;
+
+	let mut copy = StateCopy {
+		source: journaldb::new(source, Algorithm::Archive, db::COL_STATE),
+		dest: journaldb::new(dest.clone(), target, db::COL_STATE),
+		copied: HashSet::new(),
+		inject: true,
+		nodes: 0,
+	};
+
+	for era in base_era..(latest_era + 1) {
+		let hash = chain.block_hash(era).ok_or(SnapshotError::IncompleteChain)?;
+		let header = chain.block_header(&hash).ok_or(SnapshotError::BlockNotFound(hash))?;
+		info!("Copying state of block #{} ({})", era, hash);
+		walk_state(header.state_root(), &mut |key| copy.copy_node(key))?;
+
+		let mut batch = DBTransaction::new();
+		if copy.inject {
+			copy.dest.inject(&mut batch)?;
+			copy.copied.clear();
+			copy.inject = false;
+		}
+		copy.dest.journal_under(&mut batch, era, &hash)?;
+		dest.write(batch)?;
+	}
+
+	Ok(PruningReport {
+		base_era: base_era,
+		latest_era: latest_era,
+		nodes: copy.nodes,
+	})
+}
+
+// copy all columns but the state.
+fn copy_chain_data(source: &KeyValueDB, dest: &KeyValueDB) -> Result<(), Error> {
+	let columns = ::std::iter::once(None)
+		.chain((0..db::NUM_COLUMNS.unwrap_or(0)).map(Some))
+		.filter(|col| *col != db::COL_STATE);
+
+	for col in columns {
+		let mut batch = DBTransaction::new();
+		for (key, value) in source.iter(col) {
+			batch.put(col, &key, &value);
+			if batch.ops.len() == BATCH_SIZE {
+				dest.write(mem::replace(&mut batch, DBTransaction::new()))?;
+			}
+		}
+		dest.write(batch)?;
+	}
+
+	Ok(())
+}
+
+struct StateCopy {
+	source: Box<JournalDB>,
+	dest: Box<JournalDB>,
+	// keys copied but not written to the database yet.
+	copied: HashSet<H256>,
+	// whether copied nodes are written to the database directly rather than journalled.
+	inject: bool,
+	nodes: usize,
+}
+
+impl StateCopy {
+	// whether the node is already in the new database. The journal overlay is keyed
+	// by key prefixes, so it's not asked directly.
+	fn contains(&self, key: &H256) -> Result<bool, Error> {
+		Ok(self.copied.contains(key) || self.dest.backing().get(db::COL_STATE, key)?.is_some())
+	}
+
+	// copy the node stored under `key`. returns `None` if it had been copied before.
+	fn copy_node(&mut self, key: H256) -> Result<Option<DBValue>, Error> {
+		if self.contains(&key)? {
+			return Ok(None);
+		}
+
+		let value = self.source.get(&key).ok_or_else(|| TrieError::IncompleteDatabase(key.clone()))?;
+		self.dest.emplace(key.clone(), value.clone());
+		self.copied.insert(key);
+		self.nodes += 1;
+
+		if self.inject && self.copied.len() >= BATCH_SIZE {
+			let mut batch = DBTransaction::new();
+			self.dest.inject(&mut batch)?;
+			self.dest.backing().write(batch)?;
+			self.copied.clear();
+		}
+
+		Ok(Some(value))
+	}
+}
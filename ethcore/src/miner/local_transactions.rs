@@ -16,12 +16,34 @@
 
 //! Local Transactions List.
 
+use std::fmt;
 use linked_hash_map::LinkedHashMap;
 use transaction::{SignedTransaction, PendingTransaction};
 use error::TransactionError;
 use bigint::prelude::U256;
 use bigint::hash::H256;
 
+/// Reason why a local transaction was replaced by another one with the same sender and nonce.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReplaceReason {
+	/// Another transaction paid at least the minimal gas price bump.
+	Outbid,
+	/// The transaction didn't pay enough to replace the one already in the queue.
+	Underpriced {
+		/// Minimal gas price required for the replacement
+		minimal: U256,
+	},
+}
+
+impl fmt::Display for ReplaceReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ReplaceReason::Outbid => write!(f, "Outbid by a transaction with higher gas price"),
+			ReplaceReason::Underpriced { minimal } => write!(f, "Replacement underpriced. Min={}", minimal),
+		}
+	}
+}
+
 /// Status of local transaction.
 /// Can indicate that the transaction is currently part of the queue (`Pending/Future`)
 /// or gives a reason why the transaction was removed.
@@ -35,8 +57,8 @@ pub enum Status {
 	Mined(SignedTransaction),
 	/// Transaction is dropped because of limit
 	Dropped(SignedTransaction),
-	/// Replaced because of higher gas price of another transaction.
-	Replaced(SignedTransaction, U256, H256),
+	/// Replaced by another transaction with the same nonce (gas price and hash of the transaction that stays).
+	Replaced(SignedTransaction, U256, H256, ReplaceReason),
 	/// Transaction was never accepted to the queue.
 	Rejected(SignedTransaction, TransactionError),
 	/// Transaction is invalid.
@@ -90,9 +112,9 @@ impl LocalTransactionsList {
 		self.clear_old();
 	}
 
-	pub fn mark_replaced(&mut self, tx: SignedTransaction, gas_price: U256, hash: H256) {
-		debug!(target: "own_tx", "Transaction replaced (hash {:?}) by {:?} (new gas price: {:?})", tx.hash(), hash, gas_price);
-		self.transactions.insert(tx.hash(), Status::Replaced(tx, gas_price, hash));
+	pub fn mark_replaced(&mut self, tx: SignedTransaction, gas_price: U256, hash: H256, reason: ReplaceReason) {
+		debug!(target: "own_tx", "Transaction replaced (hash {:?}) by {:?} (new gas price: {:?}): {}", tx.hash(), hash, gas_price, reason);
+		self.transactions.insert(tx.hash(), Status::Replaced(tx, gas_price, hash, reason));
 		self.clear_old();
 	}
 
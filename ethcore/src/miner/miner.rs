@@ -35,7 +35,7 @@ use error::*;
 use transaction::{Action, UnverifiedTransaction, PendingTransaction, SignedTransaction, Condition as TransactionCondition};
 use receipt::{Receipt, RichReceipt};
 use spec::Spec;
-use engines::{EthEngine, Seal};
+use engines::{EthEngine, EngineError, Seal};
 use miner::{MinerService, MinerStatus, TransactionQueue, RemovalReason, TransactionQueueDetailsProvider, PrioritizationStrategy,
 	AccountDetails, TransactionOrigin};
 use miner::banning_queue::{BanningTransactionQueue, Threshold};
@@ -111,6 +111,9 @@ pub struct MinerOptions {
 	pub tx_queue_memory_limit: Option<usize>,
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
+	/// Minimal gas price bump (in percent) required to replace a transaction with the same sender and nonce.
+	/// `None` for the default of 12.5%.
+	pub tx_queue_min_gas_price_bump: Option<usize>,
 	/// Whether we should fallback to providing all the queue's transactions or just pending.
 	pub pending_set: PendingSet,
 	/// How many historical work packages can we store before running out?
@@ -138,6 +141,7 @@ impl Default for MinerOptions {
 			tx_queue_memory_limit: Some(2 * 1024 * 1024),
 			tx_queue_gas_limit: GasLimit::None,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_min_gas_price_bump: None,
 			pending_set: PendingSet::AlwaysQueue,
 			reseal_min_period: Duration::from_secs(2),
 			reseal_max_period: Duration::from_secs(120),
@@ -268,13 +272,14 @@ impl Miner {
 		};
 		let mem_limit = options.tx_queue_memory_limit.unwrap_or_else(usize::max_value);
 
-		let txq = TransactionQueue::with_limits(
+		let mut txq = TransactionQueue::with_limits(
 			options.tx_queue_strategy,
 			options.tx_queue_size,
 			mem_limit,
 			gas_limit,
 			options.tx_gas_limit
 		);
+		txq.set_min_gas_price_bump(options.tx_queue_min_gas_price_bump);
 		let txq = match options.tx_queue_banning {
 			Banning::Disabled => BanningTransactionQueue::new(txq, Threshold::NeverBan, Duration::from_secs(180)),
 			Banning::Enabled { ban_duration, min_offends, .. } => BanningTransactionQueue::new(
@@ -537,7 +542,7 @@ impl Miner {
 					}
 					block
 						.lock()
-						.seal(&*self.engine, seal)
+						.seal_internally(&*self.engine, seal)
 						.map(|sealed| { chain.broadcast_proposal_block(sealed); true })
 						.unwrap_or_else(|e| {
 							warn!("ERROR: seal failed when given internally generated seal: {}", e);
@@ -549,7 +554,7 @@ impl Miner {
 					*self.next_mandatory_reseal.write() = Instant::now() + self.options.reseal_max_period;
 					block
 						.lock()
-						.seal(&*self.engine, seal)
+						.seal_internally(&*self.engine, seal)
 						.map(|sealed| chain.import_sealed_block(sealed).is_ok())
 						.unwrap_or_else(|e| {
 							warn!("ERROR: seal failed when given internally generated seal: {}", e);
@@ -769,6 +774,18 @@ impl MinerService for Miner {
 		}
 	}
 
+	fn propose_signer(&self, address: Address, authorize: bool) -> Result<(), EngineError> {
+		self.engine.propose_signer(address, authorize)
+	}
+
+	fn discard_signer(&self, address: &Address) -> Result<(), EngineError> {
+		self.engine.discard_signer(address)
+	}
+
+	fn signer_proposals(&self) -> Result<BTreeMap<Address, bool>, EngineError> {
+		self.engine.signer_proposals()
+	}
+
 	fn set_extra_data(&self, extra_data: Bytes) {
 		*self.extra_data.write() = extra_data;
 	}
@@ -1296,6 +1313,7 @@ mod tests {
 				tx_queue_memory_limit: None,
 				tx_queue_gas_limit: GasLimit::None,
 				tx_queue_strategy: PrioritizationStrategy::GasFactorAndGasPrice,
+				tx_queue_min_gas_price_bump: None,
 				pending_set: PendingSet::AlwaysSealing,
 				work_queue_size: 5,
 				enable_resubmission: true,
@@ -55,7 +55,7 @@ pub use self::external::{ExternalMiner, ExternalMinerService};
 pub use self::miner::{Miner, MinerOptions, Banning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasLimit};
 pub use self::transaction_queue::{TransactionQueue, RemovalReason, TransactionDetailsProvider as TransactionQueueDetailsProvider,
 	PrioritizationStrategy, AccountDetails, TransactionOrigin};
-pub use self::local_transactions::{Status as LocalTransactionStatus};
+pub use self::local_transactions::{Status as LocalTransactionStatus, ReplaceReason};
 pub use client::TransactionImportResult;
 pub use self::work_notify::NotifyWork;
 pub use self::stratum::{Stratum, Error as StratumError, Options as StratumOptions};
@@ -69,6 +69,7 @@ use client::{MiningBlockChainClient};
 use block::ClosedBlock;
 use header::BlockNumber;
 use receipt::{RichReceipt, Receipt};
+use engines::EngineError;
 use error::{Error};
 use transaction::{UnverifiedTransaction, PendingTransaction};
 
@@ -87,6 +88,15 @@ pub trait MinerService : Send + Sync {
 	/// Set info necessary to sign consensus messages.
 	fn set_engine_signer(&self, address: Address, password: String) -> Result<(), ::account_provider::SignError>;
 
+	/// Propose a vote adding (`authorize`) or removing a signer of a voting engine.
+	fn propose_signer(&self, address: Address, authorize: bool) -> Result<(), EngineError>;
+
+	/// Withdraw a signer vote proposed by this node.
+	fn discard_signer(&self, address: &Address) -> Result<(), EngineError>;
+
+	/// Signer votes proposed by this node, with whether each authorizes the signer.
+	fn signer_proposals(&self) -> Result<BTreeMap<Address, bool>, EngineError>;
+
 	/// Get the extra_data that we will seal blocks with.
 	fn extra_data(&self) -> Bytes;
 
@@ -116,7 +116,7 @@ use transaction::*;
 use error::{Error, TransactionError};
 use client::TransactionImportResult;
 use header::BlockNumber;
-use miner::local_transactions::{LocalTransactionsList, Status as LocalTransactionStatus};
+use miner::local_transactions::{LocalTransactionsList, Status as LocalTransactionStatus, ReplaceReason};
 
 /// Transaction origin
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -511,8 +511,8 @@ pub struct AccountDetails {
 	pub balance: U256,
 }
 
-/// Transaction with the same (sender, nonce) can be replaced only if
-/// `new_gas_price > old_gas_price + old_gas_price >> SHIFT`
+/// Unless a bump is configured, transaction with the same (sender, nonce) can be replaced only if
+/// `new_gas_price >= old_gas_price + old_gas_price >> SHIFT`
 const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
 
 /// Future queue limits are lower from current queue limits:
@@ -571,6 +571,9 @@ pub struct TransactionQueue {
 	strategy: PrioritizationStrategy,
 	/// Gas Price threshold for transactions that can be imported to this queue (defaults to 0)
 	minimal_gas_price: U256,
+	/// Minimal gas price bump (in percent) required to replace a transaction with the same (sender, nonce).
+	/// `None` for the default of 12.5%.
+	min_gas_price_bump: Option<usize>,
 	/// The maximum amount of gas any individual transaction may use.
 	tx_gas_limit: U256,
 	/// Current gas limit (block gas limit). Transactions above the limit will not be accepted (default to !0)
@@ -634,6 +637,7 @@ impl TransactionQueue {
 		TransactionQueue {
 			strategy,
 			minimal_gas_price: U256::zero(),
+			min_gas_price_bump: None,
 			block_gas_limit: !U256::zero(),
 			tx_gas_limit,
 			max_time_in_queue: DEFAULT_QUEUING_PERIOD,
@@ -671,6 +675,18 @@ impl TransactionQueue {
 		self.minimal_gas_price = min_gas_price;
 	}
 
+	/// Get the minimal gas price bump (in percent) required to replace a transaction.
+	/// `None` if the default of 12.5% is used.
+	pub fn min_gas_price_bump(&self) -> Option<usize> {
+		self.min_gas_price_bump
+	}
+
+	/// Sets the minimal gas price bump (in percent) required to replace a transaction
+	/// with the same sender and nonce. `None` restores the default of 12.5%.
+	pub fn set_min_gas_price_bump(&mut self, bump: Option<usize>) {
+		self.min_gas_price_bump = bump;
+	}
+
 	/// Get one more than the lowest gas price in the queue iff the pool is
 	/// full, otherwise 0.
 	pub fn effective_minimum_gas_price(&self) -> U256 {
@@ -1110,7 +1126,7 @@ impl TransactionQueue {
 					self.local_transactions.mark_future(order.hash);
 				}
 				if let Some(old) = self.future.insert(*sender, k, order.clone()) {
-					Self::replace_orders(*sender, k, old, order, &mut self.future, &mut self.by_hash, &mut self.local_transactions);
+					let _ = Self::replace_orders(*sender, k, old, order, self.min_gas_price_bump, &mut self.future, &mut self.by_hash, &mut self.local_transactions);
 				}
 			} else {
 				trace!(target: "txqueue", "Removing old transaction: {:?} (nonce: {} < {})", order.hash, k, current_nonce);
@@ -1236,7 +1252,7 @@ impl TransactionQueue {
 					self.local_transactions.mark_pending(order.hash);
 				}
 				if let Some(old) = self.current.insert(address, current_nonce, order.clone()) {
-					Self::replace_orders(address, current_nonce, old, order, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
+					let _ = Self::replace_orders(address, current_nonce, old, order, self.min_gas_price_bump, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
 				}
 				update_last_nonce_to = Some(current_nonce);
 				current_nonce = current_nonce + U256::one();
@@ -1299,9 +1315,7 @@ impl TransactionQueue {
 		if nonce > next_nonce {
 			// We have a gap - put to future.
 			// Insert transaction (or replace old one with lower gas price)
-			check_too_cheap(
-				Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.future, &mut self.by_hash, &mut self.local_transactions)
-			)?;
+			Self::replace_transaction(tx, state_nonce, min_gas_price, self.min_gas_price_bump, &mut self.future, &mut self.by_hash, &mut self.local_transactions)?;
 			// Enforce limit in Future
 			let removed = self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
 			// Return an error if this transaction was not imported because of limit.
@@ -1317,9 +1331,7 @@ impl TransactionQueue {
 		self.move_matching_future_to_current(address, nonce + U256::one(), state_nonce);
 
 		// Replace transaction if any
-		check_too_cheap(
-			Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.current, &mut self.by_hash, &mut self.local_transactions)
-		)?;
+		Self::replace_transaction(tx, state_nonce, min_gas_price, self.min_gas_price_bump, &mut self.current, &mut self.by_hash, &mut self.local_transactions)?;
 		// Keep track of highest nonce stored in current
 		let new_max = self.last_nonces.get(&address).map_or(nonce, |n| cmp::max(nonce, *n));
 		self.last_nonces.insert(address, new_max);
@@ -1351,19 +1363,21 @@ impl TransactionQueue {
 
 	/// Replaces transaction in given set (could be `future` or `current`).
 	///
-	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher.
+	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher
+	/// by at least `min_gas_price_bump` percent (12.5% if `None`).
 	/// One of the transactions is dropped from set and also removed from queue entirely (from `by_hash`).
 	///
-	/// Returns `true` if transaction actually got to the queue (`false` if there was already a transaction with higher
-	/// gas_price)
+	/// Returns `ReplacementUnderpriced` error if the transaction didn't get to the queue because the gas price bump
+	/// over the transaction already in the queue was too small.
 	fn replace_transaction(
 		tx: VerifiedTransaction,
 		base_nonce: U256,
 		min_gas_price: (U256, PrioritizationStrategy),
+		min_gas_price_bump: Option<usize>,
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
-	) -> bool {
+	) -> Result<(), TransactionError> {
 		let order = TransactionOrder::for_transaction(&tx, base_nonce, min_gas_price.0, min_gas_price.1);
 		let hash = tx.hash();
 		let address = tx.sender();
@@ -1375,9 +1389,9 @@ impl TransactionQueue {
 		trace!(target: "txqueue", "Inserting: {:?}", order);
 
 		if let Some(old) = set.insert(address, nonce, order.clone()) {
-			Self::replace_orders(address, nonce, old, order, set, by_hash, local)
+			Self::replace_orders(address, nonce, old, order, min_gas_price_bump, set, by_hash, local)
 		} else {
-			true
+			Ok(())
 		}
 	}
 
@@ -1386,17 +1400,18 @@ impl TransactionQueue {
 		nonce: U256,
 		old: TransactionOrder,
 		order: TransactionOrder,
+		min_gas_price_bump: Option<usize>,
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
-	) -> bool {
+	) -> Result<(), TransactionError> {
 		// There was already transaction in queue. Let's check which one should stay
 		let old_hash = old.hash;
 		let new_hash = order.hash;
 
 		let old_gas_price = old.gas_price;
 		let new_gas_price = order.gas_price;
-		let min_required_gas_price = old_gas_price + (old_gas_price >> GAS_PRICE_BUMP_SHIFT);
+		let min_required_gas_price = min_replacement_gas_price(old_gas_price, min_gas_price_bump);
 
 		if min_required_gas_price > new_gas_price {
 			trace!(target: "txqueue", "Didn't insert transaction because gas price was too low: {:?} ({:?} stays in the queue)", order.hash, old.hash);
@@ -1405,27 +1420,34 @@ impl TransactionQueue {
 			// and remove new one
 			let order = by_hash.remove(&order.hash).expect("The hash has been just inserted and no other line is altering `by_hash`.");
 			if order.origin.is_local() {
-				local.mark_replaced(order.transaction, old_gas_price, old_hash);
+				local.mark_replaced(order.transaction, old_gas_price, old_hash, ReplaceReason::Underpriced { minimal: min_required_gas_price });
 			}
-			false
+			Err(TransactionError::ReplacementUnderpriced {
+				minimal: min_required_gas_price,
+				got: new_gas_price,
+			})
 		} else {
 			trace!(target: "txqueue", "Replaced transaction: {:?} with transaction with higher gas price: {:?}", old.hash, order.hash);
 			// Make sure we remove old transaction entirely
 			let old = by_hash.remove(&old.hash).expect("The hash is coming from `future` so it has to be in `by_hash`.");
 			if old.origin.is_local() {
-				local.mark_replaced(old.transaction, new_gas_price, new_hash);
+				local.mark_replaced(old.transaction, new_gas_price, new_hash, ReplaceReason::Outbid);
 			}
-			true
+			Ok(())
 		}
 	}
 }
 
-fn check_too_cheap(is_in: bool) -> Result<(), TransactionError> {
-	if is_in {
-		Ok(())
-	} else {
-		Err(TransactionError::TooCheapToReplace)
-	}
+/// Minimal gas price a transaction needs to replace one with `old_gas_price` and the same (sender, nonce).
+fn min_replacement_gas_price(old_gas_price: U256, min_gas_price_bump: Option<usize>) -> U256 {
+	let bump = match min_gas_price_bump {
+		None => old_gas_price >> GAS_PRICE_BUMP_SHIFT,
+		Some(percent) => match old_gas_price.overflowing_mul(U256::from(percent)) {
+			(_, true) => return U256::max_value(),
+			(bump, false) => bump / U256::from(100),
+		},
+	};
+	old_gas_price.saturating_add(bump)
 }
 
 fn check_if_removed(sender: &Address, nonce: &U256, dropped: Option<HashMap<Address, U256>>) -> Result<(), TransactionError> {
@@ -2570,13 +2592,113 @@ pub mod test {
 		let res = txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider());
 
 		// then
-		assert_eq!(unwrap_tx_err(res), TransactionError::TooCheapToReplace);
+		assert_eq!(unwrap_tx_err(res), TransactionError::ReplacementUnderpriced {
+			minimal: 22.into(),
+			got: 21.into(),
+		});
 		let stats = txq.status();
 		assert_eq!(stats.pending, 1);
 		assert_eq!(stats.future, 0);
 		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(20));
 	}
 
+	#[test]
+	fn should_require_default_bump_of_one_eighth() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), default_gas_val(), 80.into()).sign(keypair.secret(), None);
+		let tx2 = {
+			let mut tx2 = (**tx).clone();
+			tx2.gas_price = U256::from(89);
+			tx2.sign(keypair.secret(), None)
+		};
+
+		// when
+		txq.add(tx, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		let res = txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider());
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::ReplacementUnderpriced {
+			minimal: 90.into(),
+			got: 89.into(),
+		});
+	}
+
+	#[test]
+	fn should_respect_configured_minimal_bump() {
+		// given
+		let mut txq = TransactionQueue::default();
+		txq.set_min_gas_price_bump(Some(50));
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), default_gas_val(), 20.into()).sign(keypair.secret(), None);
+		let tx2 = {
+			let mut tx2 = (**tx).clone();
+			tx2.gas_price = U256::from(29);
+			tx2.sign(keypair.secret(), None)
+		};
+		let tx3 = {
+			let mut tx3 = (**tx).clone();
+			tx3.gas_price = U256::from(30);
+			tx3.sign(keypair.secret(), None)
+		};
+
+		// when
+		txq.add(tx, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		let res = txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider());
+		txq.add(tx3, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::ReplacementUnderpriced {
+			minimal: 30.into(),
+			got: 29.into(),
+		});
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(30));
+	}
+
+	#[test]
+	fn should_report_replace_reason_of_local_transactions() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), default_gas_val(), 20.into()).sign(keypair.secret(), None);
+		let tx2 = {
+			let mut tx2 = (**tx).clone();
+			tx2.gas_price = U256::from(21);
+			tx2.sign(keypair.secret(), None)
+		};
+		let tx3 = {
+			let mut tx3 = (**tx).clone();
+			tx3.gas_price = U256::from(40);
+			tx3.sign(keypair.secret(), None)
+		};
+		let (hash, hash2, hash3) = (tx.hash(), tx2.hash(), tx3.hash());
+
+		// when
+		txq.add(tx, TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2, TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap_err();
+		txq.add(tx3, TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		let local = txq.local_transactions();
+		match local[&hash2] {
+			LocalTransactionStatus::Replaced(_, gas_price, replaced_by, reason) => {
+				assert_eq!((gas_price, replaced_by), (20.into(), hash));
+				assert_eq!(reason, ReplaceReason::Underpriced { minimal: 22.into() });
+			},
+			ref status => panic!("Unexpected status: {:?}", status),
+		}
+		match local[&hash] {
+			LocalTransactionStatus::Replaced(_, gas_price, replaced_by, reason) => {
+				assert_eq!((gas_price, replaced_by), (40.into(), hash3));
+				assert_eq!(reason, ReplaceReason::Outbid);
+			},
+			ref status => panic!("Unexpected status: {:?}", status),
+		}
+		assert_eq!(local[&hash3], LocalTransactionStatus::Pending);
+	}
+
 	#[test]
 	fn should_replace_same_transaction_when_has_higher_fee() {
 		use ethcore_logger::init_log;
@@ -20,7 +20,7 @@ use std::sync::Arc;
 use std::path::Path;
 use bigint::hash::H256;
 use kvdb::KeyValueDB;
-use kvdb_rocksdb::{Database, DatabaseConfig};
+use kvdb_rocksdb::DatabaseConfig;
 use bytes::Bytes;
 use io::*;
 use spec::Spec;
@@ -58,7 +58,7 @@ pub struct ClientService {
 	io_service: Arc<IoService<ClientIoMessage>>,
 	client: Arc<Client>,
 	snapshot: Arc<SnapshotService>,
-	database: Arc<Database>,
+	database: Arc<KeyValueDB>,
 	_stop_guard: ::devtools::StopGuard,
 }
 
@@ -88,11 +88,11 @@ impl ClientService {
 		db_config.compaction = config.db_compaction.compaction_profile(client_path);
 		db_config.wal = config.db_wal;
 
-		let db = Arc::new(Database::open(
+		let db_backend = config.db_backend;
+		let db = db_backend.open(
 			&db_config,
 			&client_path.to_str().expect("DB path could not be converted to string.")
-		).map_err(::client::Error::Database)?);
-
+		).map_err(::client::Error::Database)?;
 
 		let pruning = config.pruning;
 		let client = Client::new(config, &spec, db.clone(), miner, io_service.channel())?;
@@ -101,6 +101,7 @@ impl ClientService {
 			engine: spec.engine.clone(),
 			genesis_block: spec.genesis_block(),
 			db_config: db_config.clone(),
+			db_backend: db_backend,
 			pruning: pruning,
 			channel: io_service.channel(),
 			snapshot_root: snapshot_path.into(),
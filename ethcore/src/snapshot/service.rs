@@ -27,7 +27,7 @@ use super::{ManifestData, StateRebuilder, Rebuilder, RestorationStatus, Snapshot
 use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
 
 use blockchain::BlockChain;
-use client::{BlockChainClient, Client};
+use client::{BlockChainClient, Client, DatabaseBackend};
 use engines::EthEngine;
 use error::Error;
 use ids::BlockId;
@@ -40,7 +40,8 @@ use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 use util_error::UtilError;
 use bytes::Bytes;
 use journaldb::Algorithm;
-use kvdb_rocksdb::{Database, DatabaseConfig};
+use kvdb::KeyValueDB;
+use kvdb_rocksdb::DatabaseConfig;
 use snappy;
 
 /// Helper for removing directories in case of error.
@@ -80,7 +81,7 @@ struct Restoration {
 	snappy_buffer: Bytes,
 	final_state_root: H256,
 	guard: Guard,
-	db: Arc<Database>,
+	db: Arc<KeyValueDB>,
 }
 
 struct RestorationParams<'a> {
@@ -88,6 +89,7 @@ struct RestorationParams<'a> {
 	pruning: Algorithm, // pruning algorithm for the database.
 	db_path: PathBuf, // database path
 	db_config: &'a DatabaseConfig, // configuration for the database.
+	db_backend: DatabaseBackend, // backend of the database.
 	writer: Option<LooseWriter>, // writer for recovered snapshot.
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
@@ -102,8 +104,8 @@ impl Restoration {
 		let state_chunks = manifest.state_hashes.iter().cloned().collect();
 		let block_chunks = manifest.block_hashes.iter().cloned().collect();
 
-		let raw_db = Arc::new(Database::open(params.db_config, &*params.db_path.to_string_lossy())
-			.map_err(UtilError::from)?);
+		let raw_db = params.db_backend.open(params.db_config, &*params.db_path.to_string_lossy())
+			.map_err(UtilError::from)?;
 
 		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
 		let components = params.engine.snapshot_components()
@@ -204,6 +206,8 @@ pub struct ServiceParams {
 	pub genesis_block: Bytes,
 	/// Database configuration options.
 	pub db_config: DatabaseConfig,
+	/// Database backend.
+	pub db_backend: DatabaseBackend,
 	/// State pruning algorithm.
 	pub pruning: Algorithm,
 	/// Async IO channel for sending messages.
@@ -221,6 +225,7 @@ pub struct Service {
 	restoration: Mutex<Option<Restoration>>,
 	snapshot_root: PathBuf,
 	db_config: DatabaseConfig,
+	db_backend: DatabaseBackend,
 	io_channel: Mutex<Channel>,
 	pruning: Algorithm,
 	status: Mutex<RestorationStatus>,
@@ -242,6 +247,7 @@ impl Service {
 			restoration: Mutex::new(None),
 			snapshot_root: params.snapshot_root,
 			db_config: params.db_config,
+			db_backend: params.db_backend,
 			io_channel: Mutex::new(params.channel),
 			pruning: params.pruning,
 			status: Mutex::new(RestorationStatus::Inactive),
@@ -430,6 +436,7 @@ impl Service {
 			pruning: self.pruning,
 			db_path: self.restoration_db(),
 			db_config: &self.db_config,
+			db_backend: self.db_backend,
 			writer: writer,
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_dir),
@@ -652,6 +659,7 @@ mod tests {
 			engine: spec.engine.clone(),
 			genesis_block: spec.genesis_block(),
 			db_config: Default::default(),
+			db_backend: Default::default(),
 			pruning: Algorithm::Archive,
 			channel: service.channel(),
 			snapshot_root: dir,
@@ -705,6 +713,7 @@ mod tests {
 			pruning: Algorithm::Archive,
 			db_path: dir.as_path().to_owned(),
 			db_config: &db_config,
+			db_backend: Default::default(),
 			writer: None,
 			genesis: &gb,
 			guard: Guard::benign(),
@@ -36,7 +36,7 @@ use super::genesis::Genesis;
 use super::seal::Generic as GenericSeal;
 
 use builtin::Builtin;
-use engines::{EthEngine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, DEFAULT_BLOCKHASH_CONTRACT};
+use engines::{EthEngine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, Clique, DEFAULT_BLOCKHASH_CONTRACT};
 use error::Error;
 use executive::Executive;
 use factory::Factories;
@@ -104,6 +104,14 @@ pub struct CommonParams {
 	pub eip211_transition: BlockNumber,
 	/// Number of first block where EIP-214 rules begin.
 	pub eip214_transition: BlockNumber,
+	/// Number of first block where EIP-145 (Constantinople: bitwise shifting instructions) rules begin.
+	pub eip145_transition: BlockNumber,
+	/// Number of first block where EIP-1052 (Constantinople: EXTCODEHASH) rules begin.
+	pub eip1052_transition: BlockNumber,
+	/// Number of first block where EIP-1283 (Constantinople: net gas metering for SSTORE) rules begin.
+	pub eip1283_transition: BlockNumber,
+	/// Number of first block where EIP-1014 (Constantinople: CREATE2 with salt) rules begin.
+	pub eip1014_transition: BlockNumber,
 	/// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
 	pub dust_protection_transition: BlockNumber,
 	/// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
@@ -134,10 +142,13 @@ impl CommonParams {
 
 	/// Apply common spec config parameters to the schedule.
 	pub fn update_schedule(&self, block_number: u64, schedule: &mut ::vm::Schedule) {
-		schedule.have_create2 = block_number >= self.eip86_transition;
+		schedule.have_create2 = block_number >= self.eip1014_transition;
 		schedule.have_revert = block_number >= self.eip140_transition;
 		schedule.have_static_call = block_number >= self.eip214_transition;
 		schedule.have_return_data = block_number >= self.eip211_transition;
+		schedule.have_bitwise_shifting = block_number >= self.eip145_transition;
+		schedule.have_extcodehash = block_number >= self.eip1052_transition;
+		schedule.eip1283 = block_number >= self.eip1283_transition;
 		if block_number >= self.eip210_transition {
 			schedule.blockhash_gas = 800;
 		}
@@ -155,7 +166,9 @@ impl CommonParams {
 			self.validate_receipts_transition != 0 && self.eip86_transition != 0 &&
 			self.eip140_transition != 0 && self.eip210_transition != 0 &&
 			self.eip211_transition != 0 && self.eip214_transition != 0 &&
-			self.validate_chain_id_transition != 0 && self.dust_protection_transition != 0
+			self.validate_chain_id_transition != 0 && self.dust_protection_transition != 0 &&
+			self.eip145_transition != 0 && self.eip1052_transition != 0 &&
+			self.eip1283_transition != 0 && self.eip1014_transition != 0
 	}
 }
 
@@ -211,6 +224,22 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value(),
 				Into::into,
 			),
+			eip145_transition: p.eip145_transition.map_or(
+				BlockNumber::max_value(),
+				Into::into,
+			),
+			eip1052_transition: p.eip1052_transition.map_or(
+				BlockNumber::max_value(),
+				Into::into,
+			),
+			eip1283_transition: p.eip1283_transition.map_or(
+				BlockNumber::max_value(),
+				Into::into,
+			),
+			eip1014_transition: p.eip1014_transition.map_or(
+				BlockNumber::max_value(),
+				Into::into,
+			),
 			eip658_transition: p.eip658_transition.map_or(
 				BlockNumber::max_value(),
 				Into::into,
@@ -444,6 +473,8 @@ impl Spec {
 				.expect("Failed to start AuthorityRound consensus engine."),
 			ethjson::spec::Engine::Tendermint(tendermint) => Tendermint::new(tendermint.params.into(), machine)
 				.expect("Failed to start the Tendermint consensus engine."),
+			ethjson::spec::Engine::Clique(clique) => Clique::new(clique.params.into(), machine)
+				.expect("Failed to start the Clique consensus engine."),
 		}
 	}
 
@@ -769,6 +800,13 @@ impl Spec {
 		load_bundled!("tendermint")
 	}
 
+	/// Create a new Spec with Clique consensus which does internal sealing (not requiring
+	/// work).
+	/// Account keccak("") is the only signer.
+	pub fn new_test_clique() -> Self {
+		load_bundled!("clique")
+	}
+
 	/// TestList.sol used in both specs: https://github.com/paritytech/contracts/pull/30/files
 	/// Accounts with secrets keccak("0") and keccak("1") are initially the validators.
 	/// Create a new Spec with BasicAuthority which uses a contract at address 5 to determine
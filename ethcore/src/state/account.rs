@@ -51,6 +51,10 @@ pub struct Account {
 	// Modified storage. Accumulates changes to storage made in `set_storage`
 	// Takes precedence over `storage_cache`.
 	storage_changes: HashMap<H256, H256>,
+	// Values of the slots modified by the current transaction as they were
+	// when it started. Filled on the first write to a slot and reset when
+	// the transaction's changes are committed.
+	original_storage: HashMap<H256, H256>,
 	// Code hash of the account.
 	code_hash: H256,
 	// Size of the accoun code.
@@ -61,6 +65,9 @@ pub struct Account {
 	code_filth: Filth,
 	// Cached address hash.
 	address_hash: Cell<Option<H256>>,
+	// Account was created since the last commit, replacing any previous one
+	// at its address along with its storage.
+	created: bool,
 }
 
 impl From<BasicAccount> for Account {
@@ -71,11 +78,13 @@ impl From<BasicAccount> for Account {
 			storage_root: basic.storage_root,
 			storage_cache: Self::empty_storage_cache(),
 			storage_changes: HashMap::new(),
+			original_storage: HashMap::new(),
 			code_hash: basic.code_hash,
 			code_size: None,
 			code_cache: Arc::new(vec![]),
 			code_filth: Filth::Clean,
 			address_hash: Cell::new(None),
+			created: false,
 		}
 	}
 }
@@ -90,11 +99,13 @@ impl Account {
 			storage_root: KECCAK_NULL_RLP,
 			storage_cache: Self::empty_storage_cache(),
 			storage_changes: storage,
+			original_storage: HashMap::new(),
 			code_hash: keccak(&code),
 			code_size: Some(code.len()),
 			code_cache: Arc::new(code),
 			code_filth: Filth::Dirty,
 			address_hash: Cell::new(None),
+			created: true,
 		}
 	}
 
@@ -110,11 +121,13 @@ impl Account {
 			storage_root: KECCAK_NULL_RLP,
 			storage_cache: Self::empty_storage_cache(),
 			storage_changes: pod.storage.into_iter().collect(),
+			original_storage: HashMap::new(),
 			code_hash: pod.code.as_ref().map_or(KECCAK_EMPTY, |c| keccak(c)),
 			code_filth: Filth::Dirty,
 			code_size: Some(pod.code.as_ref().map_or(0, |c| c.len())),
 			code_cache: Arc::new(pod.code.map_or_else(|| { warn!("POD account with unknown code is being created! Assuming no code."); vec![] }, |c| c)),
 			address_hash: Cell::new(None),
+			created: true,
 		}
 	}
 
@@ -126,11 +139,13 @@ impl Account {
 			storage_root: KECCAK_NULL_RLP,
 			storage_cache: Self::empty_storage_cache(),
 			storage_changes: HashMap::new(),
+			original_storage: HashMap::new(),
 			code_hash: KECCAK_EMPTY,
 			code_cache: Arc::new(vec![]),
 			code_size: Some(0),
 			code_filth: Filth::Clean,
 			address_hash: Cell::new(None),
+			created: true,
 		}
 	}
 
@@ -149,11 +164,13 @@ impl Account {
 			storage_root: KECCAK_NULL_RLP,
 			storage_cache: Self::empty_storage_cache(),
 			storage_changes: HashMap::new(),
+			original_storage: HashMap::new(),
 			code_hash: KECCAK_EMPTY,
 			code_cache: Arc::new(vec![]),
 			code_size: None,
 			code_filth: Filth::Clean,
 			address_hash: Cell::new(None),
+			created: true,
 		}
 	}
 
@@ -176,6 +193,23 @@ impl Account {
 		self.storage_changes.insert(key, value);
 	}
 
+	/// Remember `value` as the contents of the storage at `key` at the start of the
+	/// current transaction, unless the slot was already changed by it.
+	pub fn note_original_storage(&mut self, key: H256, value: H256) {
+		self.original_storage.entry(key).or_insert(value);
+	}
+
+	/// Get the contents of the storage at `key` at the start of the current transaction.
+	/// Returns `None` if the slot wasn't changed since, i.e. it still holds its original value.
+	pub fn original_storage_at(&self, key: &H256) -> Option<H256> {
+		self.original_storage.get(key).cloned()
+	}
+
+	/// Forget the original storage values once the transaction that changed them is over.
+	pub fn commit_original_storage(&mut self) {
+		self.original_storage.clear();
+	}
+
 	/// Get (and cache) the contents of the trie's storage at `key`.
 	/// Takes modifed storage into account.
 	pub fn storage_at(&self, db: &HashDB, key: &H256) -> trie::Result<H256> {
@@ -190,6 +224,11 @@ impl Account {
 		Ok(value)
 	}
 
+	/// Cache a storage value read from elsewhere than the storage trie.
+	pub fn cache_storage(&self, key: H256, value: H256) {
+		self.storage_cache.borrow_mut().insert(key, value);
+	}
+
 	/// Get cached storage value if any. Returns `None` if the
 	/// key is not in the cache.
 	pub fn cached_storage_at(&self, key: &H256) -> Option<H256> {
@@ -324,6 +363,13 @@ impl Account {
 		self.code_hash == KECCAK_EMPTY
 	}
 
+	/// Return the storage root as of the last commit, disregarding any storage changes since.
+	pub fn base_storage_root(&self) -> &H256 { &self.storage_root }
+
+	/// Check if the account was created since the last commit, replacing any previous
+	/// account at its address and wiping its storage.
+	pub fn was_created(&self) -> bool { self.created }
+
 	/// Check if account is basic (Has no code).
 	pub fn is_basic(&self) -> bool {
 		self.code_hash == KECCAK_EMPTY
@@ -365,6 +411,7 @@ impl Account {
 
 			self.storage_cache.borrow_mut().insert(k, v);
 		}
+		self.created = false;
 		Ok(())
 	}
 
@@ -403,11 +450,13 @@ impl Account {
 			storage_root: self.storage_root.clone(),
 			storage_cache: Self::empty_storage_cache(),
 			storage_changes: HashMap::new(),
+			original_storage: HashMap::new(),
 			code_hash: self.code_hash.clone(),
 			code_size: self.code_size.clone(),
 			code_cache: self.code_cache.clone(),
 			code_filth: self.code_filth,
 			address_hash: self.address_hash.clone(),
+			created: self.created,
 		}
 	}
 
@@ -415,6 +464,7 @@ impl Account {
 	pub fn clone_dirty(&self) -> Account {
 		let mut account = self.clone_basic();
 		account.storage_changes = self.storage_changes.clone();
+		account.original_storage = self.original_storage.clone();
 		account.code_cache = self.code_cache.clone();
 		account
 	}
@@ -438,11 +488,13 @@ impl Account {
 		self.code_cache = other.code_cache;
 		self.code_size = other.code_size;
 		self.address_hash = other.address_hash;
+		self.created = other.created;
 		let mut cache = self.storage_cache.borrow_mut();
 		for (k, v) in other.storage_cache.into_inner() {
 			cache.insert(k.clone() , v.clone()); //TODO: cloning should not be required here
 		}
 		self.storage_changes = other.storage_changes;
+		self.original_storage = other.original_storage;
 	}
 }
 
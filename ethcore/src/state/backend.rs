@@ -26,6 +26,8 @@ use std::sync::Arc;
 
 use state::Account;
 use bigint::hash::H256;
+use bytes::Bytes;
+use flat_state::FlatChanges;
 use parking_lot::Mutex;
 use util::{Address, MemoryDB};
 use hashdb::{AsHashDB, HashDB, DBValue};
@@ -66,6 +68,22 @@ pub trait Backend: Send {
 	/// Check whether an account is known to be empty. Returns true if known to be
 	/// empty, false otherwise.
 	fn is_known_null(&self, address: &Address) -> bool;
+
+	/// Get the RLP of an account from the flat state, given its address hash and
+	/// the root of the state. `Some(None)` if the account is known not to exist.
+	/// `None` if the flat state is disabled or doesn't hold that state.
+	fn get_flat_account(&self, _root: &H256, _address_hash: &H256) -> Option<Option<Bytes>> { None }
+
+	/// Get a storage value from the flat state, given the hashes of the address and key, the root of
+	/// the state and the storage root of the account in that state.
+	/// `None` if the flat state is disabled or doesn't hold that storage.
+	fn get_flat_storage(&self, _root: &H256, _address_hash: &H256, _storage_root: &H256, _key_hash: &H256) -> Option<H256> { None }
+
+	/// Whether committed state changes should be passed to `note_flat_changes`.
+	fn tracks_flat_changes(&self) -> bool { false }
+
+	/// Note state changes committed to the trie, along with the resulting state root.
+	fn note_flat_changes(&mut self, _changes: FlatChanges, _root: H256) {}
 }
 
 /// A raw backend used to check proofs of execution.
@@ -24,12 +24,13 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, BTreeMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
-use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY};
+use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY, keccak};
 
 use receipt::{Receipt, TransactionOutcome};
 use machine::EthereumMachine as Machine;
 use vm::EnvInfo;
 use error::Error;
+use flat_state::FlatChanges;
 use executive::{Executive, TransactOptions};
 use factory::Factories;
 use trace::{self, FlatTrace, VMTrace};
@@ -48,21 +49,23 @@ use util::*;
 use bytes::Bytes;
 
 use trie;
-use trie::{Trie, TrieError, TrieDB};
+use trie::{Trie, TrieError, TrieDB, TrieFactory};
 use trie::recorder::Recorder;
 
 
 mod account;
 mod substate;
+mod walk;
 
 pub mod backend;
 
 pub use self::account::Account;
 pub use self::backend::Backend;
 pub use self::substate::Substate;
+pub use self::walk::{walk_state, walk_state_with, StateVisitor};
 
 /// Used to return information about an `State::apply` operation.
-pub struct ApplyOutcome {
+pub struct ApplyOutcome<V = VMTrace> {
 	/// The receipt for the applied transaction.
 	pub receipt: Receipt,
 	/// The output of the applied transaction.
@@ -70,11 +73,11 @@ pub struct ApplyOutcome {
 	/// The trace for the applied transaction, empty if tracing was not produced.
 	pub trace: Vec<FlatTrace>,
 	/// The VM trace for the applied transaction, None if tracing was not produced.
-	pub vm_trace: Option<VMTrace>
+	pub vm_trace: Option<V>
 }
 
 /// Result type for the execution ("application") of a transaction.
-pub type ApplyResult = Result<ApplyOutcome, Error>;
+pub type ApplyResult<V = VMTrace> = Result<ApplyOutcome<V>, Error>;
 
 /// Return type of proof validity check.
 #[derive(Debug, Clone)]
@@ -334,9 +337,6 @@ pub enum CleanupMode<'a> {
 	TrackTouched(&'a mut HashSet<Address>),
 }
 
-const SEC_TRIE_DB_UNWRAP_STR: &'static str = "A state can only be created with valid root. Creating a SecTrieDB with a valid root will not fail. \
-			 Therefore creating a SecTrieDB with this state's root will not fail.";
-
 impl<B: Backend> State<B> {
 	/// Creates new state with empty state root
 	/// Used for tests.
@@ -380,6 +380,11 @@ impl<B: Backend> State<B> {
 		self.factories.vm.clone()
 	}
 
+	/// Get the factory of the tries of this state.
+	pub fn trie_factory(&self) -> &TrieFactory {
+		&self.factories.trie
+	}
+
 	/// Swap the current backend for another.
 	// TODO: [rob] find a less hacky way to avoid duplication of `Client::state_at`.
 	pub fn replace_backend<T: Backend>(self, backend: T) -> State<T> {
@@ -411,6 +416,15 @@ impl<B: Backend> State<B> {
 						prev.entry(k).or_insert(v);
 					}
 				}
+			} else {
+				// the outermost checkpoint spans the whole transaction and holds every
+				// account it changed. Its current storage is the next one's original.
+				let cache = self.cache.get_mut();
+				for address in checkpoint.keys() {
+					if let Some(&mut AccountEntry { account: Some(ref mut account), .. }) = cache.get_mut(address) {
+						account.commit_original_storage();
+					}
+				}
 			}
 		}
 	}
@@ -550,10 +564,7 @@ impl<B: Backend> State<B> {
 			// check the global cache and and cache storage key there if found,
 			let trie_res = self.db.get_cached(address, |acc| match acc {
 				None => Ok(H256::new()),
-				Some(a) => {
-					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address));
-					a.storage_at(account_db.as_hashdb(), key)
-				}
+				Some(a) => self.account_storage_at(address, a, key),
 			});
 
 			if let Some(res) = trie_res {
@@ -563,8 +574,7 @@ impl<B: Backend> State<B> {
 			// otherwise cache the account localy and cache storage key there.
 			if let Some(ref mut acc) = local_account {
 				if let Some(ref account) = acc.account {
-					let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(address));
-					return account.storage_at(account_db.as_hashdb(), key)
+					return self.account_storage_at(address, account, key)
 				} else {
 					return Ok(H256::new())
 				}
@@ -575,16 +585,25 @@ impl<B: Backend> State<B> {
 		if self.db.is_known_null(address) { return Ok(H256::zero()) }
 
 		// account is not found in the global cache, get from the DB and insert into local
-		let db = self.factories.trie.readonly(self.db.as_hashdb(), &self.root).expect(SEC_TRIE_DB_UNWRAP_STR);
-		let maybe_acc = db.get_with(address, Account::from_rlp)?;
-		let r = maybe_acc.as_ref().map_or(Ok(H256::new()), |a| {
-			let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.address_hash(address));
-			a.storage_at(account_db.as_hashdb(), key)
-		});
+		let maybe_acc = self.load_account(address)?;
+		let r = maybe_acc.as_ref().map_or(Ok(H256::new()), |a| self.account_storage_at(address, a, key));
 		self.insert_cache(address, AccountEntry::new_clean(maybe_acc));
 		r
 	}
 
+	/// Get the value of storage at `key` at the start of the currently executing
+	/// transaction. Same as `storage_at` for slots the transaction hasn't changed.
+	pub fn original_storage_at(&self, address: &Address, key: &H256) -> trie::Result<H256> {
+		let original = self.cache.borrow().get(address)
+			.and_then(|entry| entry.account.as_ref())
+			.and_then(|account| account.original_storage_at(key));
+
+		match original {
+			Some(value) => Ok(value),
+			None => self.storage_at(address, key),
+		}
+	}
+
 	/// Get accounts' code.
 	pub fn code(&self, a: &Address) -> trie::Result<Option<Arc<Bytes>>> {
 		self.ensure_cached(a, RequireCache::Code, true,
@@ -646,8 +665,14 @@ impl<B: Backend> State<B> {
 	/// Mutate storage of account `a` so that it is `value` for `key`.
 	pub fn set_storage(&mut self, a: &Address, key: H256, value: H256) -> trie::Result<()> {
 		trace!(target: "state", "set_storage({}:{} to {})", a, key.hex(), value.hex());
-		if self.storage_at(a, &key)? != value {
-			self.require(a, false)?.set_storage(key, value)
+		let current = self.storage_at(a, &key)?;
+		if current != value {
+			let in_transaction = !self.checkpoints.borrow().is_empty();
+			let mut account = self.require(a, false)?;
+			if in_transaction {
+				account.note_original_storage(key.clone(), current);
+			}
+			account.set_storage(key, value)
 		}
 
 		Ok(())
@@ -687,7 +712,7 @@ impl<B: Backend> State<B> {
 		t: &SignedTransaction,
 		tracer: T,
 		vm_tracer: V,
-	) -> ApplyResult where
+	) -> ApplyResult<V::Output> where
 		T: trace::Tracer,
 		V: trace::VMTracer,
 	{
@@ -728,7 +753,7 @@ impl<B: Backend> State<B> {
 	// `virt` signals that we are executing outside of a block set and restrictions like
 	// gas limits and gas costs should be lifted.
 	fn execute<T, V>(&mut self, env_info: &EnvInfo, machine: &Machine, t: &SignedTransaction, options: TransactOptions<T, V>, virt: bool)
-		-> Result<Executed, ExecutionError> where T: trace::Tracer, V: trace::VMTracer,
+		-> Result<Executed<V::Output>, ExecutionError> where T: trace::Tracer, V: trace::VMTracer,
 	{
 		let mut e = Executive::new(self, env_info, machine);
 
@@ -747,11 +772,23 @@ impl<B: Backend> State<B> {
 	#[cfg_attr(feature="dev", allow(match_ref_pats))]
 	#[cfg_attr(feature="dev", allow(needless_borrow))]
 	pub fn commit(&mut self) -> Result<(), Error> {
+		let track_flat = self.db.tracks_flat_changes();
+		let mut flat_changes = FlatChanges::default();
+
 		// first, commit the sub trees.
 		let mut accounts = self.cache.borrow_mut();
 		for (address, ref mut a) in accounts.iter_mut().filter(|&(_, ref a)| a.is_dirty()) {
 			if let Some(ref mut account) = a.account {
 				let addr_hash = account.address_hash(address);
+				if track_flat {
+					// new and recreated accounts start off with an empty storage.
+					if account.was_created() {
+						flat_changes.cleared.insert(addr_hash.clone());
+					}
+					for (key, value) in account.storage_changes() {
+						flat_changes.storage.insert((addr_hash.clone(), keccak(key)), value.clone());
+					}
+				}
 				{
 					let mut account_db = self.factories.accountdb.create(self.db.as_hashdb_mut(), addr_hash);
 					account.commit_storage(&self.factories.trie, account_db.as_hashdb_mut())?;
@@ -760,6 +797,13 @@ impl<B: Backend> State<B> {
 				if !account.is_empty() {
 					self.db.note_non_null_account(address);
 				}
+				if track_flat {
+					flat_changes.accounts.insert(addr_hash, Some(account.rlp()));
+				}
+			} else if track_flat {
+				let addr_hash = keccak(address);
+				flat_changes.cleared.insert(addr_hash.clone());
+				flat_changes.accounts.insert(addr_hash, None);
 			}
 		}
 
@@ -778,6 +822,10 @@ impl<B: Backend> State<B> {
 			}
 		}
 
+		if track_flat {
+			self.db.note_flat_changes(flat_changes, self.root.clone());
+		}
+
 		Ok(())
 	}
 
@@ -920,8 +968,7 @@ impl<B: Backend> State<B> {
 				if check_null && self.db.is_known_null(a) { return Ok(f(None)); }
 
 				// not found in the global cache, get from the DB and insert into local
-				let db = self.factories.trie.readonly(self.db.as_hashdb(), &self.root)?;
-				let mut maybe_acc = db.get_with(a, Account::from_rlp)?;
+				let mut maybe_acc = self.load_account(a)?;
 				if let Some(ref mut account) = maybe_acc.as_mut() {
 					let accountdb = self.factories.accountdb.readonly(self.db.as_hashdb(), account.address_hash(a));
 					Self::update_account_cache(require, account, &self.db, accountdb.as_hashdb());
@@ -933,6 +980,30 @@ impl<B: Backend> State<B> {
 		}
 	}
 
+	/// Load account `a` as of the last commit. Uses the flat state of the backend if it
+	/// holds the current state root, the trie otherwise.
+	fn load_account(&self, a: &Address) -> trie::Result<Option<Account>> {
+		if let Some(account) = self.db.get_flat_account(&self.root, &keccak(a)) {
+			return Ok(account.map(|rlp| Account::from_rlp(&rlp)));
+		}
+		let db = self.factories.trie.readonly(self.db.as_hashdb(), &self.root)?;
+		db.get_with(a, Account::from_rlp)
+	}
+
+	/// Get the storage of `account` at `key`, trying the flat state of the backend before the storage trie.
+	fn account_storage_at(&self, address: &Address, account: &Account, key: &H256) -> trie::Result<H256> {
+		if let Some(value) = account.cached_storage_at(key) {
+			return Ok(value);
+		}
+		let address_hash = account.address_hash(address);
+		if let Some(value) = self.db.get_flat_storage(&self.root, &address_hash, account.base_storage_root(), &keccak(key)) {
+			account.cache_storage(key.clone(), value.clone());
+			return Ok(value);
+		}
+		let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), address_hash);
+		account.storage_at(account_db.as_hashdb(), key)
+	}
+
 	/// Pull account `a` in our cache from the trie DB. `require_code` requires that the code be cached, too.
 	fn require<'a>(&'a self, a: &Address, require_code: bool) -> trie::Result<RefMut<'a, Account>> {
 		self.require_or_from(a, require_code, || Account::new_basic(0u8.into(), self.account_start_nonce), |_|{})
@@ -949,8 +1020,7 @@ impl<B: Backend> State<B> {
 				Some(acc) => self.insert_cache(a, AccountEntry::new_clean_cached(acc)),
 				None => {
 					let maybe_acc = if !self.db.is_known_null(a) {
-						let db = self.factories.trie.readonly(self.db.as_hashdb(), &self.root)?;
-						AccountEntry::new_clean(db.get_with(a, Account::from_rlp)?)
+						AccountEntry::new_clean(self.load_account(a)?)
 					} else {
 						AccountEntry::new_clean(None)
 					};
@@ -1035,6 +1105,41 @@ impl<B: Backend> fmt::Debug for State<B> {
 	}
 }
 
+impl State<StateDB> {
+	/// Returns a `StateDiff` describing the changes made since the last checkpoint, and discards it.
+	/// Accounts are diffed against their originals in the checkpoint, or against the trie under
+	/// `root`, the state root when the checkpoint was created, if they weren't cached.
+	pub fn diff_from_checkpoint(&mut self, root: H256) -> trie::Result<StateDiff> {
+		let (cache, post) = {
+			let checkpoints = self.checkpoints.borrow();
+			let accounts = self.cache.borrow();
+			let mut cache = HashMap::new();
+			let mut post = BTreeMap::new();
+			for (address, original) in checkpoints.last().into_iter().flat_map(|checkpoint| checkpoint.iter()) {
+				if let Some(ref original) = *original {
+					cache.insert(*address, original.clone_dirty());
+				}
+				if let Some(&AccountEntry { account: Some(ref account), .. }) = accounts.get(address) {
+					post.insert(*address, PodAccount::from_account(account));
+				}
+			}
+			(cache, PodState::from(post))
+		};
+		self.discard_checkpoint();
+
+		let mut state_pre = State {
+			db: self.db.boxed_clone(),
+			root: root,
+			cache: RefCell::new(cache),
+			checkpoints: RefCell::new(Vec::new()),
+			account_start_nonce: self.account_start_nonce.clone(),
+			factories: self.factories.clone(),
+		};
+		state_pre.query_pod(&post)?;
+		Ok(pod_state::diff_pod(&state_pre.to_pod(), &post))
+	}
+}
+
 // TODO: cloning for `State` shouldn't be possible in general; Remove this and use
 // checkpoints where possible.
 impl Clone for State<StateDB> {
@@ -2145,6 +2250,29 @@ mod tests {
 		new_state.diff_from(state).unwrap();
 	}
 
+	#[test]
+	fn should_diff_from_checkpoint() {
+		let mut state = get_temp_state();
+		let a: Address = 0xa.into();
+		let b: Address = 0xb.into();
+		state.init_code(&a, b"abcdefg".to_vec()).unwrap();
+		state.add_balance(&a, &256.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&a, 0xb.into(), 0xc.into()).unwrap();
+		state.commit().unwrap();
+
+		let original = state.clone();
+		let root = state.root().clone();
+		state.checkpoint();
+		state.set_storage(&a, 0xb.into(), 0xd.into()).unwrap();
+		state.add_balance(&b, &5.into(), CleanupMode::NoEmpty).unwrap();
+
+		let expected = state.clone().diff_from(original).unwrap();
+		let diff = state.diff_from_checkpoint(root).unwrap();
+		assert_eq!(diff, expected);
+		assert_eq!(diff.raw.len(), 2);
+		assert!(state.checkpoints.borrow().is_empty());
+	}
+
 	#[test]
 	fn should_kill_garbage() {
 		let a = 10.into();
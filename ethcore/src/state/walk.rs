@@ -0,0 +1,133 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Traversal of the state as laid out in the state database.
+
+use bigint::hash::H256;
+use hash::{KECCAK_EMPTY, KECCAK_NULL_RLP};
+use rlp::{Rlp, UntrustedRlp};
+use trie::node::Node;
+use util::DBValue;
+
+use account_db::combine_key;
+use error::Error;
+use types::basic_account::BasicAccount;
+
+/// Receives what's met while walking the state with `walk_state_with`.
+pub trait StateVisitor {
+	/// Given the database key of a trie node or code, with storage nodes and code keyed like
+	/// `AccountDB` does. Returns the value stored under the key to walk further down,
+	/// or `None` to skip everything below the key.
+	fn visit(&mut self, key: H256) -> Result<Option<DBValue>, Error>;
+
+	/// Given each leaf along with the number of nodes above it, inline ones included.
+	/// `address_hash` is the hash of the owning account's address for storage leaves,
+	/// `None` for accounts.
+	fn leaf(&mut self, _address_hash: Option<&H256>, _depth: usize, _value: &[u8]) {}
+}
+
+impl<F> StateVisitor for F where F: FnMut(H256) -> Result<Option<DBValue>, Error> {
+	fn visit(&mut self, key: H256) -> Result<Option<DBValue>, Error> {
+		self(key)
+	}
+}
+
+/// Walk the state trie under `root`, along with the storage trie and code of every account in it.
+///
+/// `visit` is given the database key of each trie node and code, with storage nodes and code
+/// keyed like `AccountDB` does. It returns the value stored under the key to walk further down,
+/// or `None` to skip everything below the key.
+pub fn walk_state<F>(root: &H256, visit: &mut F) -> Result<(), Error>
+	where F: FnMut(H256) -> Result<Option<DBValue>, Error>
+{
+	walk_trie(root, None, visit)
+}
+
+/// Walk the state trie under `root` like `walk_state` does, reporting leaves as well.
+pub fn walk_state_with<V: StateVisitor>(root: &H256, visitor: &mut V) -> Result<(), Error> {
+	walk_trie(root, None, visitor)
+}
+
+// walk a trie, with storage tries given the hash of their account's address.
+fn walk_trie<V: StateVisitor>(root: &H256, address_hash: Option<&H256>, visitor: &mut V) -> Result<(), Error> {
+	if *root == KECCAK_NULL_RLP {
+		return Ok(());
+	}
+
+	let db_key = |hash: &H256| address_hash.map_or_else(|| hash.clone(), |a| combine_key(a, hash));
+	// nodes paired with the nibbles leading to them and their depth.
+	let mut stack = match visitor.visit(db_key(root))? {
+		Some(node) => vec![(node, Vec::new(), 0)],
+		None => return Ok(()),
+	};
+
+	while let Some((node, path, depth)) = stack.pop() {
+		let mut children = Vec::new();
+		match Node::decoded(&node) {
+			Node::Leaf(partial, value) => {
+				visitor.leaf(address_hash, depth, value);
+				if address_hash.is_none() {
+					let mut key = path.clone();
+					key.extend((0..partial.len()).map(|i| partial.at(i)));
+					walk_account(&key, value, visitor)?;
+				}
+			},
+			Node::Extension(partial, child) => {
+				let mut key = path.clone();
+				key.extend((0..partial.len()).map(|i| partial.at(i)));
+				children.push((child, key));
+			},
+			Node::Branch(nodes, _) => for (i, child) in nodes.iter().enumerate() {
+				if !Rlp::new(child).is_empty() {
+					let mut key = path.clone();
+					key.push(i as u8);
+					children.push((*child, key));
+				}
+			},
+			Node::Empty => {},
+		}
+
+		for (child, key) in children {
+			let rlp = Rlp::new(child);
+			if rlp.is_data() && rlp.size() == 32 {
+				if let Some(node) = visitor.visit(db_key(&rlp.as_val()))? {
+					stack.push((node, key, depth + 1));
+				}
+			} else {
+				// inline node.
+				stack.push((DBValue::from_slice(child), key, depth + 1));
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn walk_account<V: StateVisitor>(nibbles: &[u8], value: &[u8], visitor: &mut V) -> Result<(), Error> {
+	if nibbles.len() != 64 {
+		warn!("Unexpected key length {} in the state trie", nibbles.len());
+		return Ok(());
+	}
+
+	let address_hash = H256::from_slice(&nibbles.chunks(2).map(|n| n[0] << 4 | n[1]).collect::<Vec<_>>());
+	let account: BasicAccount = UntrustedRlp::new(value).as_val()?;
+	walk_trie(&account.storage_root, Some(&address_hash), visitor)?;
+	if account.code_hash != KECCAK_EMPTY {
+		visitor.visit(combine_key(&address_hash, &account.code_hash))?;
+	}
+
+	Ok(())
+}
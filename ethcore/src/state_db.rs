@@ -16,6 +16,8 @@
 
 use std::collections::{VecDeque, HashSet};
 use std::sync::Arc;
+use std::thread;
+use bytes::Bytes;
 use lru_cache::LruCache;
 use memory_cache::MemoryLruCache;
 use journaldb::JournalDB;
@@ -23,9 +25,12 @@ use kvdb::{KeyValueDB, DBTransaction};
 use bigint::hash::H256;
 use hashdb::HashDB;
 use state::{self, Account};
+use account_db::Factory as AccountFactory;
+use error::Error;
+use flat_state::{self, FlatChanges, FlatState};
 use header::BlockNumber;
 use hash::keccak;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use util::Address;
 use util_error::UtilError;
 use bloom_journal::{Bloom, BloomJournal};
@@ -112,6 +117,10 @@ pub struct StateDB {
 	commit_hash: Option<H256>,
 	/// Number of the committing block or `None` if not committed yet.
 	commit_number: Option<BlockNumber>,
+	/// Shared flat state index or `None` if disabled.
+	flat_state: Option<Arc<RwLock<FlatState>>>,
+	/// Flat state changes of the committing block along with its state root.
+	local_flat: Option<(FlatChanges, H256)>,
 }
 
 impl StateDB {
@@ -139,6 +148,45 @@ impl StateDB {
 			parent_hash: None,
 			commit_hash: None,
 			commit_number: None,
+			flat_state: None,
+			local_flat: None,
+		}
+	}
+
+	/// Enable the flat state index. Unless it already holds the given block, it's generated
+	/// from the state of that block in the background, and reads go to the trie meanwhile.
+	pub fn enable_flat_state(&mut self, accountdb: &AccountFactory, number: BlockNumber, hash: H256, state_root: H256) -> Result<(), Error> {
+		let flat_state = Arc::new(RwLock::new(FlatState::open(self.db.backing().clone())?));
+		if flat_state.read().is_at(&hash) {
+			flat_state.write().set_best(hash);
+			self.flat_state = Some(flat_state);
+			return Ok(());
+		}
+
+		let abort = flat_state.write().begin_generation(number, hash, state_root);
+		let generating = flat_state.clone();
+		let db = self.db.boxed_clone();
+		let accountdb = accountdb.clone();
+		thread::Builder::new().name("flat-state".into()).spawn(move || {
+			if let Err(e) = flat_state::generate(&*generating, db.as_hashdb(), &accountdb, &*abort) {
+				warn!(target: "flat_state", "Disabling flat state after failing to generate it: {}", e);
+				generating.write().discard();
+			}
+		})?;
+
+		self.flat_state = Some(flat_state);
+		Ok(())
+	}
+
+	/// Whether the flat state index is being generated.
+	pub fn is_generating_flat_state(&self) -> bool {
+		self.flat_state.as_ref().map_or(false, |flat_state| flat_state.read().is_generating())
+	}
+
+	/// Disable the flat state index, dropping the changes not written to the database yet.
+	pub fn disable_flat_state(&mut self) {
+		if let Some(flat_state) = self.flat_state.take() {
+			flat_state.write().discard();
 		}
 	}
 
@@ -202,6 +250,16 @@ impl StateDB {
 		self.db.mark_canonical(batch, end_era, canon_id)
 	}
 
+	/// Revert the commits of all eras after `era` and make it the latest one.
+	/// The shared account cache is cleared as it may hold the state of reverted blocks.
+	pub fn rewind(&mut self, batch: &mut DBTransaction, era: u64) -> Result<u32, UtilError> {
+		let records = self.db.rewind(batch, era)?;
+		let mut cache = self.account_cache.lock();
+		cache.accounts.clear();
+		cache.modifications.clear();
+		Ok(records)
+	}
+
 	/// Propagate local cache into the global cache and synchonize
 	/// the global cache with the best block state.
 	/// This function updates the global cache by removing entries
@@ -210,6 +268,7 @@ impl StateDB {
 	/// blockchain route has ben calculated.
 	pub fn sync_cache(&mut self, enacted: &[H256], retracted: &[H256], is_best: bool) {
 		trace!("sync_cache id = (#{:?}, {:?}), parent={:?}, best={}", self.commit_number, self.commit_hash, self.parent_hash, is_best);
+
 		let mut cache = self.account_cache.lock();
 		let cache = &mut *cache;
 
@@ -298,6 +357,22 @@ impl StateDB {
 		}
 	}
 
+	/// Add the changes of the committed block to the flat state, writing them to the batch the
+	/// block is committed in. Should be called after `journal_under`, once it's known whether
+	/// the block is the new best one.
+	pub fn journal_flat_state(&mut self, batch: &mut DBTransaction, is_best: bool) {
+		let local_flat = self.local_flat.take();
+		if let (Some(flat_state), Some((changes, state_root))) = (self.flat_state.as_ref(), local_flat) {
+			if let (Some(number), Some(hash), Some(parent)) = (self.commit_number, self.commit_hash.as_ref(), self.parent_hash.as_ref()) {
+				let mut flat_state = flat_state.write();
+				if let Err(e) = flat_state.add_layer(batch, number, hash.clone(), parent.clone(), state_root, changes, is_best) {
+					warn!(target: "flat_state", "Disabling flat state after failing to update it: {}", e);
+					flat_state.discard();
+				}
+			}
+		}
+	}
+
 	pub fn as_hashdb(&self) -> &HashDB {
 		self.db.as_hashdb()
 	}
@@ -318,6 +393,8 @@ impl StateDB {
 			parent_hash: None,
 			commit_hash: None,
 			commit_number: None,
+			flat_state: self.flat_state.clone(),
+			local_flat: None,
 		}
 	}
 
@@ -333,6 +410,8 @@ impl StateDB {
 			parent_hash: Some(parent.clone()),
 			commit_hash: None,
 			commit_number: None,
+			flat_state: self.flat_state.clone(),
+			local_flat: None,
 		}
 	}
 
@@ -455,17 +534,49 @@ impl state::Backend for StateDB {
 		let is_null = !bloom.check(&*keccak(address));
 		is_null
 	}
+
+	fn get_flat_account(&self, root: &H256, address_hash: &H256) -> Option<Option<Bytes>> {
+		self.flat_state.as_ref().and_then(|flat_state| flat_state.read().account(root, address_hash))
+	}
+
+	fn get_flat_storage(&self, root: &H256, address_hash: &H256, storage_root: &H256, key_hash: &H256) -> Option<H256> {
+		self.flat_state.as_ref().and_then(|flat_state| flat_state.read().storage(root, address_hash, storage_root, key_hash))
+	}
+
+	fn tracks_flat_changes(&self) -> bool {
+		self.flat_state.is_some()
+	}
+
+	fn note_flat_changes(&mut self, changes: FlatChanges, root: H256) {
+		match self.local_flat {
+			Some((ref mut local, ref mut local_root)) => {
+				local.merge(changes);
+				*local_root = root;
+			},
+			None => self.local_flat = Some((changes, root)),
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
+	use std::sync::Arc;
+	use std::thread;
+	use std::time::Duration;
 	use bigint::prelude::U256;
 	use bigint::hash::H256;
+	use hash::keccak;
+	use journaldb;
 	use util::Address;
 	use kvdb::DBTransaction;
+	use trie::{Trie, SecTrieDB};
 	use tests::helpers::*;
-	use state::{Account, Backend};
+	use state::{Account, Backend, CleanupMode, State};
+	use account_db::Factory as AccountFactory;
+	use parking_lot::RwLock;
+	use flat_state::{self, FlatState, FLAT_STATE_LAYERS};
 	use ethcore_logger::init_log;
+	use super::StateDB;
 
 	#[test]
 	fn state_db_smoke() {
@@ -533,4 +644,189 @@ mod tests {
 		let s = state_db.boxed_clone_canon(&h3a);
 		assert!(s.get_cached_account(&address).is_none());
 	}
+
+	// commit the state as the new best block, returning its hash and state root.
+	fn commit_block(mut state: State<StateDB>, number: u64) -> (H256, H256) {
+		state.commit().unwrap();
+		let (root, mut db) = state.drop();
+		let hash = H256::random();
+		let mut batch = DBTransaction::new();
+		db.journal_under(&mut batch, number, &hash).unwrap();
+		db.journal_flat_state(&mut batch, true);
+		db.journal_db().backing().write(batch).unwrap();
+		db.sync_cache(&[], &[], true);
+		(hash, root)
+	}
+
+	fn wait_for_flat_state(state_db: &StateDB) {
+		while state_db.is_generating_flat_state() {
+			thread::sleep(Duration::from_millis(10));
+		}
+	}
+
+	fn assert_flat_state_matches_trie(state_db: &StateDB, root: &H256, addresses: &[Address], keys: &[H256]) {
+		let trie = SecTrieDB::new(state_db.as_hashdb(), root).unwrap();
+		for address in addresses {
+			let address_hash = keccak(address);
+			let expected = trie.get(address).unwrap().map(|rlp| rlp.to_vec());
+			assert_eq!(state_db.get_flat_account(root, &address_hash), Some(expected.clone()));
+
+			let account = match expected {
+				Some(rlp) => Account::from_rlp(&rlp),
+				None => continue,
+			};
+			let account_db = AccountFactory::default().readonly(state_db.as_hashdb(), address_hash.clone());
+			for key in keys {
+				let expected = account.storage_at(&*account_db, key).unwrap();
+				assert_eq!(state_db.get_flat_storage(root, &address_hash, account.base_storage_root(), &keccak(key)), Some(expected));
+			}
+		}
+	}
+
+	#[test]
+	fn flat_state_matches_trie() {
+		init_log();
+
+		let mut state_db = get_temp_state_db();
+		let a: Vec<Address> = (0..4).map(|_| Address::random()).collect();
+		let k: Vec<H256> = (0..4u64).map(H256::from).collect();
+
+		let mut state = State::new(state_db.boxed_clone_canon(&H256::zero()), 0.into(), Default::default());
+		state.add_balance(&a[0], &10.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&a[1], k[0], 1.into()).unwrap();
+		state.set_storage(&a[1], k[1], 2.into()).unwrap();
+		state.add_balance(&a[2], &5.into(), CleanupMode::NoEmpty).unwrap();
+		let (h0, r0) = commit_block(state, 0);
+
+		// generated from the trie.
+		state_db.enable_flat_state(&AccountFactory::default(), 0, h0, r0).unwrap();
+		wait_for_flat_state(&state_db);
+		assert_flat_state_matches_trie(&state_db, &r0, &a, &k);
+
+		let mut state = State::from_existing(state_db.boxed_clone_canon(&h0), r0, 0.into(), Default::default()).unwrap();
+		assert_eq!(state.storage_at(&a[1], &k[1]).unwrap(), 2.into());
+		state.set_storage(&a[1], k[1], 3.into()).unwrap();
+		state.set_storage(&a[1], k[2], 4.into()).unwrap();
+		state.add_balance(&a[0], &1.into(), CleanupMode::NoEmpty).unwrap();
+		state.add_balance(&a[3], &1.into(), CleanupMode::NoEmpty).unwrap();
+		let (h1, r1) = commit_block(state, 1);
+		assert_flat_state_matches_trie(&state_db, &r1, &a, &k);
+
+		let mut state = State::from_existing(state_db.boxed_clone_canon(&h1), r1, 0.into(), Default::default()).unwrap();
+		state.kill_account(&a[1]);
+		state.set_storage(&a[2], k[0], 7.into()).unwrap();
+		let (h2, r2) = commit_block(state, 2);
+		assert_flat_state_matches_trie(&state_db, &r2, &a, &k);
+
+		// recreated account doesn't see its old storage.
+		let mut state = State::from_existing(state_db.boxed_clone_canon(&h2), r2, 0.into(), Default::default()).unwrap();
+		state.set_storage(&a[1], k[3], 9.into()).unwrap();
+		let (h3, r3) = commit_block(state, 3);
+		assert_flat_state_matches_trie(&state_db, &r3, &a, &k);
+		assert_flat_state_matches_trie(&state_db, &r1, &a, &k);
+
+		let state = State::from_existing(state_db.boxed_clone(), r3, 0.into(), Default::default()).unwrap();
+		assert_eq!(state.storage_at(&a[1], &k[1]).unwrap(), H256::zero());
+		assert_eq!(state.storage_at(&a[1], &k[3]).unwrap(), 9.into());
+		assert_eq!(state.balance(&a[0]).unwrap(), 11.into());
+
+		// the layers are journalled along with their blocks.
+		let backing = state_db.journal_db().backing().clone();
+		assert!(FlatState::open(backing.clone()).unwrap().is_at(&h3));
+		drop(state);
+		drop(state_db);
+
+		let mut state_db = StateDB::new(journaldb::new(backing, journaldb::Algorithm::EarlyMerge, ::db::COL_STATE), 5 * 1024 * 1024);
+		state_db.enable_flat_state(&AccountFactory::default(), 3, h3, r3).unwrap();
+		assert_flat_state_matches_trie(&state_db, &r3, &a, &k);
+		assert_flat_state_matches_trie(&state_db, &r1, &a, &k);
+	}
+
+	#[test]
+	fn flat_state_merges_old_layers_into_base() {
+		init_log();
+
+		let mut state_db = get_temp_state_db();
+		let a: Vec<Address> = (0..3).map(|_| Address::random()).collect();
+		let k: Vec<H256> = (0..2u64).map(H256::from).collect();
+
+		let mut state = State::new(state_db.boxed_clone_canon(&H256::zero()), 0.into(), Default::default());
+		state.set_storage(&a[0], k[0], 1.into()).unwrap();
+		state.set_storage(&a[1], k[1], 2.into()).unwrap();
+		let (mut hash, mut root) = commit_block(state, 0);
+		state_db.enable_flat_state(&AccountFactory::default(), 0, hash, root).unwrap();
+		wait_for_flat_state(&state_db);
+
+		let mut state = State::from_existing(state_db.boxed_clone_canon(&hash), root, 0.into(), Default::default()).unwrap();
+		state.kill_account(&a[0]);
+		let (h, r) = commit_block(state, 1);
+		hash = h;
+		root = r;
+
+		// only touch an account without storage until the kill is deep enough to be merged.
+		for number in 2..(FLAT_STATE_LAYERS as u64 + 3) {
+			let mut state = State::from_existing(state_db.boxed_clone_canon(&hash), root, 0.into(), Default::default()).unwrap();
+			state.add_balance(&a[2], &1.into(), CleanupMode::NoEmpty).unwrap();
+			let (h, r) = commit_block(state, number);
+			hash = h;
+			root = r;
+		}
+		assert_flat_state_matches_trie(&state_db, &root, &a, &k);
+
+		let backing = state_db.journal_db().backing().clone();
+		let raw_key = |address: &Address, key: &H256| {
+			let mut raw = keccak(address).to_vec();
+			raw.extend_from_slice(&keccak(key));
+			raw
+		};
+		assert_eq!(backing.get(::db::COL_FLAT_STATE, &raw_key(&a[0], &k[0])).unwrap(), None);
+		assert!(backing.get(::db::COL_FLAT_STATE, &raw_key(&a[1], &k[1])).unwrap().is_some());
+	}
+
+	#[test]
+	fn flat_state_generation_keeps_blocks_added_meanwhile() {
+		init_log();
+
+		let mut state_db = get_temp_state_db();
+		let a: Vec<Address> = (0..2).map(|_| Address::random()).collect();
+		let k: Vec<H256> = (0..2u64).map(H256::from).collect();
+
+		let mut state = State::new(state_db.boxed_clone_canon(&H256::zero()), 0.into(), Default::default());
+		state.set_storage(&a[0], k[0], 1.into()).unwrap();
+		let (h0, r0) = commit_block(state, 0);
+
+		let flat_state = Arc::new(RwLock::new(FlatState::open(state_db.journal_db().backing().clone()).unwrap()));
+		let abort = flat_state.write().begin_generation(0, h0, r0);
+		state_db.flat_state = Some(flat_state.clone());
+
+		// reads go to the trie until the generation is done.
+		assert_eq!(state_db.get_flat_account(&r0, &keccak(&a[0])), None);
+		let mut state = State::from_existing(state_db.boxed_clone_canon(&h0), r0, 0.into(), Default::default()).unwrap();
+		state.set_storage(&a[0], k[1], 2.into()).unwrap();
+		state.add_balance(&a[1], &1.into(), CleanupMode::NoEmpty).unwrap();
+		let (h1, r1) = commit_block(state, 1);
+		assert_eq!(state_db.get_flat_account(&r1, &keccak(&a[1])), None);
+
+		assert!(flat_state::generate(&*flat_state, state_db.as_hashdb(), &AccountFactory::default(), &*abort).unwrap());
+		assert!(!state_db.is_generating_flat_state());
+		assert_flat_state_matches_trie(&state_db, &r0, &a, &k);
+		assert_flat_state_matches_trie(&state_db, &r1, &a, &k);
+		assert!(FlatState::open(state_db.journal_db().backing().clone()).unwrap().is_at(&h1));
+
+		// the generation restarts from the best block when the state it reads is missing.
+		let abort = flat_state.write().begin_generation(0, h0, H256::random());
+		let mut state = State::from_existing(state_db.boxed_clone_canon(&h1), r1, 0.into(), Default::default()).unwrap();
+		state.set_storage(&a[1], k[0], 3.into()).unwrap();
+		let (h2, r2) = commit_block(state, 2);
+
+		assert!(flat_state::generate(&*flat_state, state_db.as_hashdb(), &AccountFactory::default(), &*abort).unwrap());
+		assert_flat_state_matches_trie(&state_db, &r2, &a, &k);
+		assert!(flat_state.read().is_at(&h2));
+		assert!(!flat_state.read().is_at(&h1));
+
+		// discarding aborts the generation.
+		let abort = flat_state.write().begin_generation(2, h2, r2);
+		flat_state.write().discard();
+		assert!(!flat_state::generate(&*flat_state, state_db.as_hashdb(), &AccountFactory::default(), &*abort).unwrap());
+	}
 }
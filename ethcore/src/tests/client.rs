@@ -16,9 +16,12 @@
 
 use std::str::FromStr;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 use hash::keccak;
 use io::IoChannel;
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId};
+use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId, TransactionId, CallAnalytics};
+use client::{StateGcConfig, StateGcStage};
 use state::{self, State, CleanupMode};
 use executive::{Executive, TransactOptions};
 use ethereum;
@@ -26,6 +29,7 @@ use block::IsBlock;
 use tests::helpers::*;
 use types::filter::Filter;
 use bigint::prelude::U256;
+use bigint::hash::H256;
 use util::*;
 use kvdb_rocksdb::{Database, DatabaseConfig};
 use devtools::*;
@@ -35,6 +39,15 @@ use views::BlockView;
 use ethkey::KeyPair;
 use transaction::{PendingTransaction, Transaction, Action, Condition};
 use miner::MinerService;
+use error::{HistoryError, CallError};
+use migrations::prune_state;
+use db_stats::state_stats;
+use db_check::{check_database, Inconsistency};
+use trace::{VMTracer, NoopTracer, ExecutiveTracer};
+use journaldb::Algorithm;
+use types::account_diff::Diff;
+use kvdb::{KeyValueDB, DBTransaction};
+use rustc_hex::FromHex;
 
 #[test]
 fn imports_from_empty() {
@@ -299,6 +312,395 @@ fn change_history_size() {
 	assert_eq!(client.state().balance(&address).unwrap(), 100.into());
 }
 
+#[test]
+fn can_prune_archive_database() {
+	let test_spec = Spec::new_null();
+	let archive_db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+	let address = Address::random();
+	{
+		let mut config = ClientConfig::default();
+		config.pruning = Algorithm::Archive;
+		let client = Client::new(
+			config,
+			&test_spec,
+			archive_db.clone(),
+			Arc::new(Miner::with_spec(&test_spec)),
+			IoChannel::disconnected()
+		).unwrap();
+
+		for _ in 0..10 {
+			let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+			b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+			b.block_mut().fields_mut().state.commit().unwrap();
+			let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+			client.import_sealed_block(b).unwrap();
+		}
+	}
+
+	let pruned_db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+	let report = prune_state(archive_db.clone(), pruned_db.clone(), &test_spec.genesis_block(), Algorithm::OverlayRecent, 3).unwrap();
+	assert_eq!(report.base_era, 7);
+	assert_eq!(report.latest_era, 10);
+	assert!(pruned_db.iter(::db::COL_STATE).count() < archive_db.iter(::db::COL_STATE).count());
+
+	let client = Client::new(
+		ClientConfig::default(),
+		&test_spec,
+		pruned_db,
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+	assert_eq!(client.chain_info().best_block_number, 10);
+	assert_eq!(client.state().balance(&address).unwrap(), 50.into());
+	assert_eq!(client.state_at(BlockId::Number(7)).unwrap().balance(&address).unwrap(), 35.into());
+	assert!(client.state_at(BlockId::Number(6)).is_none());
+}
+
+#[test]
+fn state_gc_removes_unreachable_nodes() {
+	let test_spec = Spec::new_null();
+	let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+	let mut config = ClientConfig::default();
+	config.history = 8;
+	let client = Client::new(
+		config,
+		&test_spec,
+		db.clone(),
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let address = Address::random();
+	for _ in 0..20 {
+		let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().fields_mut().state.commit().unwrap();
+		let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	}
+
+	let garbage = H256::random();
+	let mut batch = DBTransaction::new();
+	batch.put(::db::COL_STATE, &garbage, b"garbage");
+	db.write(batch).unwrap();
+
+	client.start_state_gc(StateGcConfig { batch_size: 2, throttle: Duration::from_millis(0) }).unwrap();
+	let status = loop {
+		match client.state_gc_status() {
+			Some(ref status) if status.is_running() => thread::sleep(Duration::from_millis(10)),
+			status => break status.unwrap(),
+		}
+	};
+
+	assert_eq!(status.stage, StateGcStage::Done);
+	assert!(status.removed >= 1);
+	assert!(db.get(::db::COL_STATE, &garbage).unwrap().is_none());
+	assert!(db.iter(::db::COL_STATE).all(|(key, _)| !key.starts_with(b"state-gc-mark")));
+	assert_eq!(client.state().balance(&address).unwrap(), 100.into());
+	let earliest = client.pruning_info().earliest_state;
+	assert_eq!(client.state_at(BlockId::Number(earliest)).unwrap().balance(&address).unwrap(), (earliest * 5).into());
+}
+
+#[test]
+fn state_gc_fails_on_missing_nodes() {
+	let test_spec = Spec::new_null();
+	let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+	let mut config = ClientConfig::default();
+	config.history = 8;
+	let client = Client::new(
+		config,
+		&test_spec,
+		db.clone(),
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let address = Address::random();
+	for _ in 0..20 {
+		let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().fields_mut().state.commit().unwrap();
+		let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	}
+
+	let garbage = H256::random();
+	let root = client.block_header(BlockId::Number(client.pruning_info().earliest_state - 1)).unwrap().state_root();
+	let mut batch = DBTransaction::new();
+	batch.put(::db::COL_STATE, &garbage, b"garbage");
+	batch.delete(::db::COL_STATE, &root);
+	db.write(batch).unwrap();
+
+	client.start_state_gc(StateGcConfig { batch_size: 2, throttle: Duration::from_millis(0) }).unwrap();
+	let status = loop {
+		match client.state_gc_status() {
+			Some(ref status) if status.is_running() => thread::sleep(Duration::from_millis(10)),
+			status => break status.unwrap(),
+		}
+	};
+
+	match status.stage {
+		StateGcStage::Failed(_) => {},
+		stage => panic!("Unexpected stage: {:?}", stage),
+	}
+	assert_eq!(status.removed, 0);
+	assert!(db.get(::db::COL_STATE, &garbage).unwrap().is_some());
+	assert!(db.iter(::db::COL_STATE).all(|(key, _)| !key.starts_with(b"state-gc-mark")));
+}
+
+#[test]
+fn prunes_history_bodies() {
+	let test_spec = Spec::new_null();
+	let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+	let mut config = ClientConfig::default();
+	config.history = 8;
+	config.history_bodies = Some(10);
+	let client = Client::new(
+		config,
+		&test_spec,
+		db,
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	for _ in 0..20 {
+		let b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	}
+
+	assert_eq!(client.pruning_info().earliest_chain, 11);
+	assert_eq!(client.block_body(BlockId::Number(10)), Err(HistoryError::Pruned));
+	assert!(client.block_body(BlockId::Number(11)).is_ok());
+	assert!(client.block_header(BlockId::Number(10)).is_some());
+	assert!(client.block(BlockId::Number(10)).is_none());
+	assert_eq!(client.block_body(BlockId::Number(21)), Err(HistoryError::UnknownBlock));
+}
+
+#[test]
+fn gathers_state_stats() {
+	let test_spec = Spec::new_null();
+	let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+	let config = ClientConfig::default();
+	let pruning = config.pruning;
+	let client = Client::new(
+		config,
+		&test_spec,
+		db.clone(),
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let contract = Address::from(2);
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	{
+		let state = &mut b.block_mut().fields_mut().state;
+		state.add_balance(&Address::from(1), &5.into(), CleanupMode::NoEmpty).unwrap();
+		state.new_contract(&contract, 0.into(), 0.into());
+		state.init_code(&contract, vec![0x00]).unwrap();
+		state.set_storage(&contract, H256::from(1), H256::from(1)).unwrap();
+		state.set_storage(&contract, H256::from(2), H256::from(2)).unwrap();
+		state.commit().unwrap();
+	}
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let genesis = state_stats(db.clone(), &test_spec.genesis_block(), pruning, BlockId::Earliest).unwrap().unwrap();
+	let latest = state_stats(db.clone(), &test_spec.genesis_block(), pruning, BlockId::Latest).unwrap().unwrap();
+	assert_eq!(latest.number, 1);
+	assert_eq!(latest.hash, client.chain_info().best_block_hash);
+	assert_eq!(latest.contracts, genesis.contracts + 1);
+	assert_eq!(latest.storage_slots, genesis.storage_slots + 2);
+	assert!(latest.accounts >= genesis.accounts + 2);
+	assert_eq!(latest.account_depths.iter().sum::<u64>(), latest.accounts);
+	assert_eq!(latest.storage_depths.iter().sum::<u64>(), latest.storage_slots);
+	assert!(state_stats(db, &test_spec.genesis_block(), pruning, BlockId::Number(2)).unwrap().is_none());
+}
+
+#[test]
+fn checks_and_repairs_database() {
+	let test_spec = Spec::new_null();
+	let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0)));
+	let config = ClientConfig::default();
+	let pruning = config.pruning;
+	let client = Client::new(
+		config,
+		&test_spec,
+		db.clone(),
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let mut hashes = vec![client.chain_info().genesis_hash];
+	for _ in 0..3 {
+		let b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+		hashes.push(client.import_sealed_block(b).unwrap());
+	}
+	drop(client);
+
+	let genesis = test_spec.genesis_block();
+	let report = check_database(db.clone(), &genesis, pruning, BlockId::Earliest, false).unwrap();
+	assert_eq!(report.blocks, 4);
+	assert_eq!(report.inconsistency, None);
+	assert_eq!(report.last_consistent, Some((3, hashes[3])));
+
+	let mut batch = DBTransaction::new();
+	batch.delete(::db::COL_BODIES, &hashes[2]);
+	db.write(batch).unwrap();
+
+	let report = check_database(db.clone(), &genesis, pruning, BlockId::Number(1), false).unwrap();
+	assert_eq!(report.blocks, 1);
+	assert_eq!(report.inconsistency, Some(Inconsistency::Body(hashes[2])));
+	assert_eq!(report.last_consistent, Some((1, hashes[1])));
+	assert!(!report.repaired);
+
+	// the best block can be read, so it's left to the client to rewind it.
+	let report = check_database(db.clone(), &genesis, pruning, BlockId::Earliest, true).unwrap();
+	assert!(!report.repaired);
+
+	let client = Client::new(
+		ClientConfig::default(),
+		&test_spec,
+		db.clone(),
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+	assert_eq!(client.reset_to(BlockId::Hash(hashes[1])), Ok(hashes[1]));
+	drop(client);
+
+	let report = check_database(db.clone(), &genesis, pruning, BlockId::Earliest, false).unwrap();
+	assert_eq!(report.inconsistency, None);
+	assert_eq!(report.last_consistent, Some((1, hashes[1])));
+}
+
+#[test]
+fn replays_with_custom_tracers() {
+	// counts the frames entered during the execution.
+	struct FrameCounter(usize);
+
+	impl VMTracer for FrameCounter {
+		type Output = usize;
+
+		fn prepare_subtrace(&self, _code: &[u8]) -> Self { FrameCounter(0) }
+
+		fn done_subtrace(&mut self, sub: Self) { self.0 += sub.0 + 1; }
+
+		fn drain(self) -> Option<usize> { Some(self.0) }
+	}
+
+	let client = generate_dummy_client_with_data(2, 2, slice_into![1]);
+	let mut traced = Vec::new();
+	let results = client.replay_block_with_tracers(BlockId::Number(2), |t| {
+		traced.push(t.hash());
+		(ExecutiveTracer::default(), FrameCounter(0))
+	}).unwrap();
+
+	assert_eq!(traced, client.block(BlockId::Number(2)).unwrap().transaction_hashes());
+	assert_eq!(results.len(), 2);
+	for result in &results {
+		assert_eq!(result.trace.len(), 1);
+		assert_eq!(result.vm_trace, Some(1));
+	}
+	assert_eq!(results[1].cumulative_gas_used, results[0].gas_used + results[1].gas_used);
+
+	let result = client.replay_with_tracers(TransactionId::Location(BlockId::Number(2), 1), NoopTracer, FrameCounter(0)).unwrap();
+	assert!(result.trace.is_empty());
+	assert_eq!(result.vm_trace, Some(1));
+	assert_eq!(result.cumulative_gas_used, results[1].cumulative_gas_used);
+}
+
+#[test]
+fn replays_block_transactions() {
+	let client = generate_dummy_client_with_data(2, 2, slice_into![1]);
+	let analytics = CallAnalytics {
+		transaction_tracing: true,
+		vm_tracing: false,
+		state_diffing: true,
+	};
+
+	let results = client.replay_block_transactions(BlockId::Number(2), analytics).unwrap();
+	let hashes: Vec<_> = results.iter().map(|&(ref hash, _)| hash.clone()).collect();
+	assert_eq!(hashes, client.block(BlockId::Number(2)).unwrap().transaction_hashes());
+	for &(ref hash, ref result) in &results {
+		let replayed = client.replay(TransactionId::Hash(hash.clone()), analytics).unwrap();
+		assert_eq!(result.trace, replayed.trace);
+		assert_eq!(result.state_diff, replayed.state_diff);
+		assert_eq!(result.cumulative_gas_used, replayed.cumulative_gas_used);
+	}
+}
+
+#[test]
+fn calls_see_state_changes_of_previous_calls() {
+	let client = generate_dummy_client_with_data(0, 0, &[]);
+	let recipient = Address::from(2);
+	let transfer = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 21_000.into(),
+		action: Action::Call(recipient),
+		value: 5.into(),
+		data: vec![],
+	}.fake_sign(Address::from(1));
+	let analytics = CallAnalytics {
+		transaction_tracing: false,
+		vm_tracing: false,
+		state_diffing: true,
+	};
+
+	let results = client.call_many(&[(transfer.clone(), analytics), (transfer, analytics)], BlockId::Latest).unwrap();
+	let balance = |index: usize| results[index].state_diff.as_ref().unwrap().raw[&recipient].balance.clone();
+	assert_eq!(balance(0), Diff::Born(5.into()));
+	assert_eq!(balance(1), Diff::Changed(5.into(), 10.into()));
+}
+
+#[test]
+fn estimate_gas_returns_revert_output() {
+	let client = generate_dummy_client_with_spec_and_data(ethereum::new_byzantium_test, 0, 0, &[]);
+	// Error("fail")
+	let output = "08c379a0\
+		0000000000000000000000000000000000000000000000000000000000000020\
+		0000000000000000000000000000000000000000000000000000000000000004\
+		6661696c00000000000000000000000000000000000000000000000000000000".from_hex().unwrap();
+	// init code reverting with the 100 bytes following it.
+	let mut code = "6064600c60003960646000fd".from_hex().unwrap();
+	code.extend_from_slice(&output);
+	let create = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 100_000.into(),
+		action: Action::Create,
+		value: 0.into(),
+		data: code,
+	}.fake_sign(Address::from(1));
+
+	assert_eq!(client.estimate_gas(&create, BlockId::Latest), Err(CallError::Reverted(output)));
+}
+
+#[test]
+fn resets_to_earlier_block() {
+	let client = generate_dummy_client_with_data(0, 0, &[]);
+	let mut hashes = vec![client.chain_info().genesis_hash];
+	for _ in 0..3 {
+		let b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		let b = b.close_and_lock().seal(client.engine(), vec![]).unwrap();
+		hashes.push(client.import_sealed_block(b).unwrap());
+	}
+
+	assert_eq!(client.reset_to(BlockId::Number(1)), Ok(hashes[1]));
+	let info = client.chain_info();
+	assert_eq!(info.best_block_number, 1);
+	assert_eq!(info.best_block_hash, hashes[1]);
+	assert!(client.block_header(BlockId::Number(2)).is_none());
+	assert!(client.state_at(BlockId::Latest).is_some());
+	assert!(client.reset_to(BlockId::Hash(hashes[3])).is_err());
+
+	let b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	let b = b.close_and_lock().seal(client.engine(), vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+	assert_eq!(client.chain_info().best_block_number, 2);
+}
+
 #[test]
 fn does_not_propagate_delayed_transactions() {
 	let key = KeyPair::from_secret(keccak("test").into()).unwrap();
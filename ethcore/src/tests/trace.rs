@@ -33,7 +33,8 @@ use header::Header;
 use miner::Miner;
 use transaction::{Action, Transaction};
 use views::BlockView;
-use trace::{RewardType, LocalizedTrace};
+use trace::{RewardType, LocalizedTrace, TracingLevel};
+use rustc_hex::FromHex;
 use trace::trace::Action::Reward;
 
 #[test]
@@ -210,3 +211,102 @@ fn can_trace_block_and_uncle_reward() {
 	let traces = client.block_traces(BlockId::Number(3));
 	assert_eq!(traces.unwrap().len(), 3);
 }
+
+#[test]
+fn stores_state_diffs_and_vm_traces() {
+	let dir = RandomTempPath::new();
+	let spec = Spec::new_test_with_reward();
+	let engine = &*spec.engine;
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let mut client_config = ClientConfig::default();
+	client_config.tracing.enabled = true;
+	client_config.tracing.level = TracingLevel::Full;
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+	let client = Client::new(
+		client_config,
+		&spec,
+		client_db,
+		Arc::new(Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let genesis_header = spec.genesis_header();
+	let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+	let mut last_hashes = vec![genesis_header.hash()];
+
+	let kp = KeyPair::from_secret_slice(&keccak("")).unwrap();
+	let author = kp.address();
+
+	// the reward of the first block pays for the transaction of the second one
+	let mut reward_block = OpenBlock::new(
+		engine,
+		Default::default(),
+		false,
+		db,
+		&genesis_header,
+		Arc::new(last_hashes.clone()),
+		author.clone(),
+		(3141562.into(), 31415620.into()),
+		vec![],
+		false,
+	).unwrap();
+	reward_block.set_difficulty(U256::from(0x20000));
+	reward_block.set_timestamp(50);
+
+	let reward_block = reward_block.close_and_lock().seal(engine, vec![]).unwrap();
+	client.import_block(reward_block.rlp_bytes()).unwrap();
+
+	let reward_header = BlockView::new(&reward_block.rlp_bytes()).header();
+	let db = reward_block.drain();
+	last_hashes.push(reward_header.hash());
+
+	let mut block = OpenBlock::new(
+		engine,
+		Default::default(),
+		false,
+		db,
+		&reward_header,
+		Arc::new(last_hashes.clone()),
+		author.clone(),
+		(3141562.into(), 31415620.into()),
+		vec![],
+		false,
+	).unwrap();
+	block.set_difficulty(U256::from(0x20000));
+	block.set_timestamp(60);
+
+	// stores 1 at slot 0 of the created contract
+	let transaction = Transaction {
+		nonce: 0.into(),
+		gas_price: 10000.into(),
+		gas: 100000.into(),
+		action: Action::Create,
+		data: "6001600055".from_hex().unwrap(),
+		value: U256::zero(),
+	}.sign(kp.secret(), Some(spec.network_id()));
+	let hash = transaction.hash();
+	block.push_transaction(transaction, None).unwrap();
+
+	let block = block.close_and_lock().seal(engine, vec![]).unwrap();
+	client.import_block(block.rlp_bytes()).unwrap();
+	block.drain();
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	assert_eq!(client.block_state_diffs(BlockId::Number(1)), Some(vec![]));
+	assert_eq!(client.block_vm_traces(BlockId::Number(1)), Some(vec![]));
+	assert_eq!(client.block_state_diffs(BlockId::Number(2)).map(|diffs| diffs.len()), Some(1));
+	assert_eq!(client.block_vm_traces(BlockId::Number(2)).map(|traces| traces.len()), Some(1));
+
+	// stored data matches the one produced by replaying the transaction
+	let analytics = CallAnalytics {
+		transaction_tracing: false,
+		vm_tracing: true,
+		state_diffing: true,
+	};
+	let replayed = client.replay(TransactionId::Hash(hash), analytics).unwrap();
+	assert!(replayed.state_diff.is_some());
+	assert_eq!(client.transaction_state_diff(TransactionId::Hash(hash)), replayed.state_diff);
+	assert_eq!(client.transaction_vm_trace(TransactionId::Hash(hash)), replayed.vm_trace);
+}
@@ -15,14 +15,70 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Traces config.
+use std::fmt;
+use std::str::FromStr;
 use bloomchain::Config as BloomConfig;
 
+/// Data stored in the traces database while tracing is enabled.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TracingLevel {
+	/// Flat call traces only.
+	Calls,
+	/// Call traces and the state diff of every transaction.
+	CallsStateDiff,
+	/// Call traces, state diffs and VM traces.
+	Full,
+}
+
+impl TracingLevel {
+	/// Whether state diffs are stored.
+	pub fn state_diffs(&self) -> bool {
+		*self != TracingLevel::Calls
+	}
+
+	/// Whether VM traces are stored.
+	pub fn vm_traces(&self) -> bool {
+		*self == TracingLevel::Full
+	}
+}
+
+impl Default for TracingLevel {
+	fn default() -> Self {
+		TracingLevel::Calls
+	}
+}
+
+impl FromStr for TracingLevel {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"calls" => Ok(TracingLevel::Calls),
+			"calls+statediff" => Ok(TracingLevel::CallsStateDiff),
+			"full" => Ok(TracingLevel::Full),
+			other => Err(format!("Invalid tracing level: {}", other)),
+		}
+	}
+}
+
+impl fmt::Display for TracingLevel {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			TracingLevel::Calls => write!(f, "calls"),
+			TracingLevel::CallsStateDiff => write!(f, "calls+statediff"),
+			TracingLevel::Full => write!(f, "full"),
+		}
+	}
+}
+
 /// Traces config.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
 	/// Indicates if tracing should be enabled or not.
 	/// If it's None, it will be automatically configured.
 	pub enabled: bool,
+	/// Data stored along with the call traces.
+	pub level: TracingLevel,
 	/// Traces blooms configuration.
 	pub blooms: BloomConfig,
 	/// Preferef cache-size.
@@ -35,6 +91,7 @@ impl Default for Config {
 	fn default() -> Self {
 		Config {
 			enabled: false,
+			level: TracingLevel::default(),
 			blooms: BloomConfig {
 				levels: 3,
 				elements_per_index: 16,
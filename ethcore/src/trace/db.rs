@@ -25,13 +25,16 @@ use bigint::hash::{H256, H264};
 use kvdb::{KeyValueDB, DBTransaction};
 use parking_lot::RwLock;
 use header::BlockNumber;
-use trace::{LocalizedTrace, Config, Filter, Database as TraceDatabase, ImportRequest, DatabaseExtras};
+use trace::{LocalizedTrace, Config, Filter, Database as TraceDatabase, ImportRequest, DatabaseExtras, VMTrace};
 use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
 use blooms;
 use super::flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces};
+use types::state_diff::StateDiff;
 use cache_manager::CacheManager;
 
-const TRACE_DB_VER: &'static [u8] = b"1.0";
+// 1.1: reverted calls and creates are stored with their output (result tags 5 and 6),
+// which older versions can't decode.
+const TRACE_DB_VER: &'static [u8] = b"1.1";
 
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature="dev", allow(enum_variant_names))]
@@ -53,6 +56,64 @@ impl Key<FlatBlockTraces> for H256 {
 	}
 }
 
+/// State diffs of all transactions in a block.
+#[derive(RlpEncodableWrapper, RlpDecodableWrapper)]
+pub struct BlockStateDiffs(Vec<StateDiff>);
+
+impl Key<BlockStateDiffs> for H256 {
+	type Target = H256;
+
+	fn key(&self) -> H256 {
+		self.clone()
+	}
+}
+
+/// Number of transactions of a block whose VM traces are stored.
+#[derive(RlpEncodableWrapper, RlpDecodableWrapper)]
+pub struct BlockVMTraces(u64);
+
+impl Key<BlockVMTraces> for H256 {
+	type Target = H256;
+
+	fn key(&self) -> H256 {
+		self.clone()
+	}
+}
+
+/// VM trace of a single transaction.
+#[derive(RlpEncodableWrapper, RlpDecodableWrapper)]
+pub struct TransactionVMTrace(VMTrace);
+
+/// Position of a transaction in a block, under which its VM trace is stored.
+pub struct VMTracePosition {
+	block_hash: H256,
+	index: usize,
+}
+
+/// Helper data structure created cause [u8; 40] does not implement Deref to &[u8].
+pub struct VMTraceKey([u8; 40]);
+
+impl Deref for VMTraceKey {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl Key<TransactionVMTrace> for VMTracePosition {
+	type Target = VMTraceKey;
+
+	fn key(&self) -> Self::Target {
+		let mut result = [0u8; 40];
+		result[0..32].copy_from_slice(&self.block_hash);
+		for i in 0..8 {
+			result[32 + i] = ((self.index as u64) >> (56 - 8 * i)) as u8;
+		}
+		VMTraceKey(result)
+	}
+}
+
 /// Wrapper around `blooms::GroupPosition` so it could be
 /// uniquely identified in the database.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -308,6 +369,20 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			// note_used must be called after locking traces to avoid cache/traces deadlock on garbage collection
 			self.note_used(CacheId::Trace(request.block_hash.clone()));
 		}
+
+		// state diffs and vm traces are too large to be cached
+		if let Some(state_diffs) = request.state_diffs {
+			batch.write(db::COL_STATE_DIFF, &request.block_hash, &BlockStateDiffs(state_diffs));
+		}
+
+		// vm traces are stored per transaction, so they can be read one at a time.
+		if let Some(vm_traces) = request.vm_traces {
+			batch.write(db::COL_VM_TRACE, &request.block_hash, &BlockVMTraces(vm_traces.len() as u64));
+			for (index, vm_trace) in vm_traces.into_iter().enumerate() {
+				let position = VMTracePosition { block_hash: request.block_hash.clone(), index: index };
+				batch.write(db::COL_VM_TRACE, &position, &TransactionVMTrace(vm_trace));
+			}
+		}
 	}
 
 	fn trace(&self, block_number: BlockNumber, tx_position: usize, trace_position: Vec<usize>) -> Option<LocalizedTrace> {
@@ -393,6 +468,42 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			)
 	}
 
+	fn transaction_state_diff(&self, block_number: BlockNumber, tx_position: usize) -> Option<StateDiff> {
+		self.block_state_diffs(block_number)
+			.and_then(|diffs| diffs.into_iter().nth(tx_position))
+	}
+
+	fn block_state_diffs(&self, block_number: BlockNumber) -> Option<Vec<StateDiff>> {
+		self.extras.block_hash(block_number)
+			.and_then(|block_hash| self.tracesdb.read::<BlockStateDiffs, H256>(db::COL_STATE_DIFF, &block_hash))
+			.map(|diffs| diffs.0)
+	}
+
+	fn transaction_vm_trace(&self, block_number: BlockNumber, tx_position: usize) -> Option<VMTrace> {
+		self.extras.block_hash(block_number)
+			.and_then(|block_hash| {
+				let position = VMTracePosition { block_hash: block_hash, index: tx_position };
+				self.tracesdb.read::<TransactionVMTrace, _>(db::COL_VM_TRACE, &position)
+			})
+			.map(|trace| trace.0)
+	}
+
+	fn block_vm_traces(&self, block_number: BlockNumber) -> Option<Vec<VMTrace>> {
+		self.extras.block_hash(block_number)
+			.and_then(|block_hash| {
+				let count = match self.tracesdb.read::<BlockVMTraces, H256>(db::COL_VM_TRACE, &block_hash) {
+					Some(count) => count.0 as usize,
+					None => return None,
+				};
+				(0..count)
+					.map(|index| {
+						let position = VMTracePosition { block_hash: block_hash.clone(), index: index };
+						self.tracesdb.read::<TransactionVMTrace, _>(db::COL_VM_TRACE, &position).map(|trace| trace.0)
+					})
+					.collect()
+			})
+	}
+
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace> {
 		let chain = BloomGroupChain::new(self.bloom_config, self);
 		let numbers = chain.filter(filter);
@@ -411,7 +522,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 
 #[cfg(test)]
 mod tests {
-	use std::collections::HashMap;
+	use std::collections::{BTreeMap, HashMap};
 	use std::sync::Arc;
 	use bigint::prelude::U256;
 	use bigint::hash::H256;
@@ -422,6 +533,9 @@ mod tests {
 	use trace::{Config, TraceDB, Database as TraceDatabase, DatabaseExtras, ImportRequest};
 	use trace::{Filter, LocalizedTrace, AddressesFilter, TraceError};
 	use trace::trace::{Call, Action, Res};
+	use trace::VMTrace;
+	use types::account_diff::{AccountDiff, Diff};
+	use types::state_diff::StateDiff;
 	use trace::flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces};
 	use evm::CallType;
 
@@ -514,6 +628,8 @@ mod tests {
 				}),
 				result: Res::FailedCall(TraceError::OutOfGas),
 			}])]),
+			state_diffs: None,
+			vm_traces: None,
 			block_hash: block_hash.clone(),
 			block_number: block_number,
 			enacted: vec![block_hash],
@@ -536,6 +652,8 @@ mod tests {
 				}),
 				result: Res::FailedCall(TraceError::OutOfGas),
 			}])]),
+			state_diffs: None,
+			vm_traces: None,
 			block_hash: block_hash.clone(),
 			block_number: block_number,
 			enacted: vec![],
@@ -703,6 +821,61 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_import_state_diffs_and_vm_traces() {
+		let db = new_db();
+		let mut config = Config::default();
+		config.enabled = true;
+		let block_1 = H256::from(0xa1);
+		let block_2 = H256::from(0xa2);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::default());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.block_hashes.insert(2, block_2.clone());
+		extras.transaction_hashes.insert(1, vec![H256::from(0xff)]);
+		extras.transaction_hashes.insert(2, vec![H256::from(0xaf)]);
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+
+		let mut raw = BTreeMap::new();
+		raw.insert(Address::from(2), AccountDiff {
+			balance: Diff::Born(3.into()),
+			nonce: Diff::Born(0.into()),
+			code: Diff::Born(vec![]),
+			storage: BTreeMap::new(),
+		});
+		let state_diff = StateDiff { raw: raw };
+		let vm_trace = VMTrace {
+			parent_step: 0,
+			code: vec![0x60, 0x00],
+			operations: vec![],
+			subs: vec![],
+		};
+
+		let mut request = create_simple_import_request(1, block_1.clone());
+		request.state_diffs = Some(vec![state_diff.clone()]);
+		request.vm_traces = Some(vec![vm_trace.clone()]);
+		let mut batch = DBTransaction::new();
+		tracedb.import(&mut batch, request);
+		db.write(batch).unwrap();
+
+		// stored only if present in the request
+		let request = create_simple_import_request(2, block_2.clone());
+		let mut batch = DBTransaction::new();
+		tracedb.import(&mut batch, request);
+		db.write(batch).unwrap();
+
+		assert_eq!(tracedb.block_state_diffs(1), Some(vec![state_diff.clone()]));
+		assert_eq!(tracedb.transaction_state_diff(1, 0), Some(state_diff));
+		assert_eq!(tracedb.transaction_state_diff(1, 1), None);
+		assert_eq!(tracedb.block_vm_traces(1), Some(vec![vm_trace.clone()]));
+		assert_eq!(tracedb.transaction_vm_trace(1, 0), Some(vm_trace));
+		assert_eq!(tracedb.transaction_vm_trace(1, 1), None);
+		assert_eq!(tracedb.block_state_diffs(2), None);
+		assert_eq!(tracedb.block_vm_traces(2), None);
+	}
+
 	#[test]
 	fn query_genesis() {
 		let db = new_db();
@@ -150,6 +150,36 @@ impl Tracer for ExecutiveTracer {
 		self.traces.extend(prefix_subtrace_addresses(subs));
 	}
 
+	fn trace_reverted_call(&mut self, call: Option<Call>, gas_used: U256, output: Option<Bytes>, subs: Vec<FlatTrace>) {
+		let trace = FlatTrace {
+			trace_address: Default::default(),
+			subtraces: top_level_subtraces(&subs),
+			action: Action::Call(call.expect("self.prepare_trace_call().is_some(): so we must be tracing: qed")),
+			result: Res::RevertedCall(CallResult {
+				gas_used: gas_used,
+				output: output.expect("self.prepare_trace_output().is_some(): so we must be tracing: qed")
+			}),
+		};
+		debug!(target: "trace", "Traced reverted call {:?}", trace);
+		self.traces.push(trace);
+		self.traces.extend(prefix_subtrace_addresses(subs));
+	}
+
+	fn trace_reverted_create(&mut self, create: Option<Create>, gas_used: U256, output: Option<Bytes>, subs: Vec<FlatTrace>) {
+		let trace = FlatTrace {
+			subtraces: top_level_subtraces(&subs),
+			action: Action::Create(create.expect("self.prepare_trace_create().is_some(): so we must be tracing: qed")),
+			result: Res::RevertedCreate(CallResult {
+				gas_used: gas_used,
+				output: output.expect("self.prepare_trace_output().is_some(): so we must be tracing: qed")
+			}),
+			trace_address: Default::default(),
+		};
+		debug!(target: "trace", "Traced reverted create {:?}", trace);
+		self.traces.push(trace);
+		self.traces.extend(prefix_subtrace_addresses(subs));
+	}
+
 	fn trace_suicide(&mut self, address: Address, balance: U256, refund_address: Address) {
 		let trace = FlatTrace {
 			subtraces: 0,
@@ -201,6 +231,8 @@ impl ExecutiveVMTracer {
 }
 
 impl VMTracer for ExecutiveVMTracer {
+	type Output = VMTrace;
+
 	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8) -> bool { true }
 
 	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: U256) {
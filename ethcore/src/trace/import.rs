@@ -17,12 +17,17 @@
 //! Traces import request.
 use bigint::hash::H256;
 use header::BlockNumber;
-use trace::FlatBlockTraces;
+use trace::{FlatBlockTraces, VMTrace};
+use types::state_diff::StateDiff;
 
 /// Traces import request.
 pub struct ImportRequest {
 	/// Traces to import.
 	pub traces: FlatBlockTraces,
+	/// State diffs of the block's transactions. Stored if present.
+	pub state_diffs: Option<Vec<StateDiff>>,
+	/// VM traces of the block's transactions. Stored if present.
+	pub vm_traces: Option<Vec<VMTrace>>,
 	/// Hash of traces block.
 	pub block_hash: H256,
 	/// Number of traces block.
@@ -22,12 +22,14 @@ mod db;
 mod executive_tracer;
 mod import;
 mod noop_tracer;
+mod struct_logger;
 mod types;
 
-pub use self::config::Config;
+pub use self::config::{Config, TracingLevel};
 pub use self::db::TraceDB;
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
+pub use self::struct_logger::{StructLogger, StructLoggerOptions, StructLog};
 pub use self::import::ImportRequest;
 pub use self::localized::LocalizedTrace;
 
@@ -45,6 +47,7 @@ use bytes::Bytes;
 use self::trace::{Call, Create};
 use vm::ActionParams;
 use header::BlockNumber;
+use types::state_diff::StateDiff;
 
 /// This trait is used by executive to build traces.
 pub trait Tracer: Send {
@@ -82,6 +85,12 @@ pub trait Tracer: Send {
 	/// Stores failed create trace.
 	fn trace_failed_create(&mut self, create: Option<Create>, subs: Vec<FlatTrace>, error: TraceError);
 
+	/// Stores trace of a call reverted with the REVERT instruction.
+	fn trace_reverted_call(&mut self, call: Option<Call>, gas_used: U256, output: Option<Bytes>, subs: Vec<FlatTrace>);
+
+	/// Stores trace of a create reverted with the REVERT instruction.
+	fn trace_reverted_create(&mut self, create: Option<Create>, gas_used: U256, output: Option<Bytes>, subs: Vec<FlatTrace>);
+
 	/// Stores suicide info.
 	fn trace_suicide(&mut self, address: Address, balance: U256, refund_address: Address);
 
@@ -97,6 +106,8 @@ pub trait Tracer: Send {
 
 /// Used by executive to build VM traces.
 pub trait VMTracer: Send {
+	/// Data returned when draining the VMTracer.
+	type Output;
 
 	/// Trace the progression of interpreter to next instruction.
 	/// If tracer returns `false` it won't be called again.
@@ -116,7 +127,7 @@ pub trait VMTracer: Send {
 	fn done_subtrace(&mut self, sub: Self) where Self: Sized;
 
 	/// Consumes self and returns the VM trace.
-	fn drain(self) -> Option<VMTrace>;
+	fn drain(self) -> Option<Self::Output>;
 }
 
 /// `DbExtras` provides an interface to query extra data which is not stored in tracesdb,
@@ -146,6 +157,18 @@ pub trait Database {
 	/// Returns localized traces created in given block.
 	fn block_traces(&self, block_number: BlockNumber) -> Option<Vec<LocalizedTrace>>;
 
+	/// Returns the state diff of a single transaction, if stored.
+	fn transaction_state_diff(&self, block_number: BlockNumber, tx_position: usize) -> Option<StateDiff>;
+
+	/// Returns the state diffs of all transactions in given block, if stored.
+	fn block_state_diffs(&self, block_number: BlockNumber) -> Option<Vec<StateDiff>>;
+
+	/// Returns the VM trace of a single transaction, if stored.
+	fn transaction_vm_trace(&self, block_number: BlockNumber, tx_position: usize) -> Option<VMTrace>;
+
+	/// Returns the VM traces of all transactions in given block, if stored.
+	fn block_vm_traces(&self, block_number: BlockNumber) -> Option<Vec<VMTrace>>;
+
 	/// Filter traces matching given filter.
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace>;
 }
@@ -0,0 +1,237 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible struct logger.
+
+use std::collections::BTreeMap;
+use bigint::prelude::U256;
+use bigint::hash::H256;
+use bytes::Bytes;
+use evm::INSTRUCTIONS;
+use trace::VMTracer;
+
+const SLOAD: u8 = 0x54;
+const SSTORE: u8 = 0x55;
+
+/// Struct logger options.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StructLoggerOptions {
+	/// Don't capture the stack.
+	pub disable_stack: bool,
+	/// Don't capture the memory.
+	pub disable_memory: bool,
+	/// Don't capture the storage.
+	pub disable_storage: bool,
+}
+
+/// State of the VM right before the execution of a single instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction opcode.
+	pub instruction: u8,
+	/// Instruction name.
+	pub op: &'static str,
+	/// Gas available before the instruction is executed.
+	pub gas: U256,
+	/// Gas cost of the instruction.
+	pub gas_cost: U256,
+	/// Call depth, starting from 1.
+	pub depth: usize,
+	/// Stack, bottom first. `None` if stack capture is disabled.
+	pub stack: Option<Vec<U256>>,
+	/// Memory. `None` if memory capture is disabled.
+	pub memory: Option<Bytes>,
+	/// Storage slots of the executing contract accessed so far.
+	/// `None` if storage capture is disabled.
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// VM tracer producing geth-style struct logs.
+///
+/// Stack, memory and storage are rebuilt from the diffs reported by the VM,
+/// so memory only covers regions which have been written to.
+pub struct StructLogger {
+	options: StructLoggerOptions,
+	depth: usize,
+	stack: Vec<U256>,
+	memory: Bytes,
+	storage: BTreeMap<H256, H256>,
+	gas_left: Option<U256>,
+	current: Option<usize>,
+	logs: Vec<StructLog>,
+}
+
+impl StructLogger {
+	/// Create a new top-level instance.
+	pub fn new(options: StructLoggerOptions) -> Self {
+		StructLogger {
+			options: options,
+			depth: 0,
+			stack: Vec::new(),
+			memory: Vec::new(),
+			storage: BTreeMap::new(),
+			gas_left: None,
+			current: None,
+			logs: Vec::new(),
+		}
+	}
+}
+
+impl VMTracer for StructLogger {
+	type Output = Vec<StructLog>;
+
+	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8) -> bool { true }
+
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: U256) {
+		if instruction == SSTORE && self.stack.len() >= 2 {
+			let len = self.stack.len();
+			self.storage.insert(self.stack[len - 1].into(), self.stack[len - 2].into());
+		}
+
+		let log = StructLog {
+			pc: pc,
+			instruction: instruction,
+			op: INSTRUCTIONS[instruction as usize].name,
+			// gas is only known after the first instruction of a frame; fixed up in `trace_executed`.
+			gas: self.gas_left.unwrap_or_else(U256::zero),
+			gas_cost: gas_cost,
+			depth: self.depth,
+			stack: if self.options.disable_stack { None } else { Some(self.stack.clone()) },
+			memory: if self.options.disable_memory { None } else { Some(self.memory.clone()) },
+			storage: if self.options.disable_storage { None } else { Some(self.storage.clone()) },
+		};
+
+		self.current = Some(self.logs.len());
+		self.logs.push(log);
+	}
+
+	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		let index = self.current.take().expect("trace_executed is always called after a trace_prepare_execute; qed");
+		let instruction = self.logs[index].instruction;
+
+		if self.gas_left.is_none() {
+			let log = &mut self.logs[index];
+			log.gas = gas_used + log.gas_cost;
+		}
+		self.gas_left = Some(gas_used);
+
+		if instruction == SLOAD {
+			if let (Some(key), Some(value)) = (self.stack.last().cloned(), stack_push.first().cloned()) {
+				self.storage.insert(key.into(), value.into());
+				if let Some(ref mut storage) = self.logs[index].storage {
+					storage.insert(key.into(), value.into());
+				}
+			}
+		}
+
+		let info = &INSTRUCTIONS[instruction as usize];
+		let len = self.stack.len();
+		self.stack.truncate(if len > info.args { len - info.args } else { 0 });
+		self.stack.extend_from_slice(stack_push);
+
+		if !self.options.disable_memory {
+			if let Some((pos, data)) = mem_diff {
+				if self.memory.len() < pos + data.len() {
+					self.memory.resize(pos + data.len(), 0);
+				}
+				self.memory[pos..pos + data.len()].copy_from_slice(data);
+			}
+		}
+
+		if let Some((key, value)) = store_diff {
+			self.storage.insert(key.into(), value.into());
+		}
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self {
+		let mut sub = StructLogger::new(self.options);
+		sub.depth = self.depth + 1;
+		sub
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.logs.extend(sub.logs);
+	}
+
+	fn drain(self) -> Option<Vec<StructLog>> { Some(self.logs) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_rebuild_stack_memory_and_storage() {
+		let mut logger = StructLogger::new(Default::default());
+		let mut frame = logger.prepare_subtrace(&[]);
+
+		// PUSH1 0x01
+		frame.trace_next_instruction(0, 0x60);
+		frame.trace_prepare_execute(0, 0x60, 3.into());
+		frame.trace_executed(97.into(), &[1.into()], None, None);
+		// PUSH1 0x00
+		frame.trace_prepare_execute(2, 0x60, 3.into());
+		frame.trace_executed(94.into(), &[0.into()], None, None);
+		// MSTORE
+		frame.trace_prepare_execute(4, 0x52, 6.into());
+		frame.trace_executed(88.into(), &[], Some((0, &[1u8; 32])), None);
+		// PUSH1 0x02, PUSH1 0x03, SSTORE
+		frame.trace_prepare_execute(5, 0x60, 3.into());
+		frame.trace_executed(85.into(), &[2.into()], None, None);
+		frame.trace_prepare_execute(7, 0x60, 3.into());
+		frame.trace_executed(82.into(), &[3.into()], None, None);
+		frame.trace_prepare_execute(9, 0x55, 20000.into());
+		frame.trace_executed(62.into(), &[], None, Some((3.into(), 2.into())));
+
+		logger.done_subtrace(frame);
+		let logs = logger.drain().unwrap();
+
+		assert_eq!(logs.len(), 6);
+		assert_eq!(logs.iter().map(|l| l.op).collect::<Vec<_>>(), vec!["PUSH1", "PUSH1", "MSTORE", "PUSH1", "PUSH1", "SSTORE"]);
+		assert_eq!(logs[0].gas, 100.into());
+		assert_eq!(logs[0].depth, 1);
+		assert_eq!(logs[1].gas, 97.into());
+		assert_eq!(logs[2].stack, Some(vec![1.into(), 0.into()]));
+		assert_eq!(logs[3].stack, Some(vec![]));
+		assert_eq!(logs[3].memory, Some(vec![1u8; 32]));
+		assert_eq!(logs[5].stack, Some(vec![2.into(), 3.into()]));
+		let mut storage = BTreeMap::new();
+		storage.insert(H256::from(3), H256::from(2));
+		assert_eq!(logs[5].storage, Some(storage));
+	}
+
+	#[test]
+	fn should_respect_disabled_captures() {
+		let options = StructLoggerOptions {
+			disable_stack: true,
+			disable_memory: true,
+			disable_storage: true,
+		};
+		let mut logger = StructLogger::new(options);
+		let mut frame = logger.prepare_subtrace(&[]);
+		frame.trace_prepare_execute(0, 0x60, 3.into());
+		frame.trace_executed(97.into(), &[1.into()], None, None);
+		logger.done_subtrace(frame);
+
+		let logs = logger.drain().unwrap();
+		assert_eq!(logs.len(), 1);
+		assert_eq!(logs[0].stack, None);
+		assert_eq!(logs[0].memory, None);
+		assert_eq!(logs[0].storage, None);
+	}
+}
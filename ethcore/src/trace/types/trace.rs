@@ -290,6 +290,10 @@ pub enum Res {
 	FailedCreate(Error),
 	/// None
 	None,
+	/// Call reverted with the REVERT instruction. Keeps the output.
+	RevertedCall(CallResult),
+	/// Create reverted with the REVERT instruction. Keeps the output.
+	RevertedCreate(CallResult),
 }
 
 impl Encodable for Res {
@@ -318,7 +322,17 @@ impl Encodable for Res {
 			Res::None => {
 				s.begin_list(1);
 				s.append(&4u8);
-			}
+			},
+			Res::RevertedCall(ref call) => {
+				s.begin_list(2);
+				s.append(&5u8);
+				s.append(call);
+			},
+			Res::RevertedCreate(ref create) => {
+				s.begin_list(2);
+				s.append(&6u8);
+				s.append(create);
+			},
 		}
 	}
 }
@@ -332,6 +346,8 @@ impl Decodable for Res {
 			2 => rlp.val_at(1).map(Res::FailedCall),
 			3 => rlp.val_at(1).map(Res::FailedCreate),
 			4 => Ok(Res::None),
+			5 => rlp.val_at(1).map(Res::RevertedCall),
+			6 => rlp.val_at(1).map(Res::RevertedCreate),
 			_ => Err(DecoderError::Custom("Invalid result type.")),
 		}
 	}
@@ -342,7 +358,8 @@ impl Res {
 	pub fn bloom(&self) -> LogBloom {
 		match *self {
 			Res::Create(ref create) => create.bloom(),
-			Res::Call(_) | Res::FailedCall(_) | Res::FailedCreate(_) | Res::None => Default::default(),
+			Res::Call(_) | Res::FailedCall(_) | Res::FailedCreate(_) | Res::None |
+				Res::RevertedCall(_) | Res::RevertedCreate(_) => Default::default(),
 		}
 	}
 
@@ -353,6 +370,14 @@ impl Res {
 			_ => false,
 		}
 	}
+
+	/// Output of a reverted call or create.
+	pub fn revert_output(&self) -> Option<&[u8]> {
+		match *self {
+			Res::RevertedCall(ref result) | Res::RevertedCreate(ref result) => Some(&result.output),
+			_ => None,
+		}
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable)]
@@ -22,6 +22,7 @@ use std::collections::BTreeMap;
 use bigint::prelude::U256;
 use bigint::hash::H256;
 use bytes::Bytes;
+use rlp::{Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 /// Diff type for specifying a change (or not).
@@ -50,6 +51,46 @@ impl<T> Diff<T> where T: Eq {
 	pub fn is_same(&self) -> bool { match *self { Diff::Same => true, _ => false }}
 }
 
+impl<T> Encodable for Diff<T> where T: Eq + Encodable {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		match *self {
+			Diff::Same => {
+				s.begin_list(1);
+				s.append(&0u8);
+			},
+			Diff::Born(ref x) => {
+				s.begin_list(2);
+				s.append(&1u8);
+				s.append(x);
+			},
+			Diff::Changed(ref pre, ref post) => {
+				s.begin_list(3);
+				s.append(&2u8);
+				s.append(pre);
+				s.append(post);
+			},
+			Diff::Died(ref x) => {
+				s.begin_list(2);
+				s.append(&3u8);
+				s.append(x);
+			},
+		}
+	}
+}
+
+impl<T> Decodable for Diff<T> where T: Eq + Decodable {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		let tag: u8 = rlp.val_at(0)?;
+		match tag {
+			0 => Ok(Diff::Same),
+			1 => Ok(Diff::Born(rlp.val_at(1)?)),
+			2 => Ok(Diff::Changed(rlp.val_at(1)?, rlp.val_at(2)?)),
+			3 => Ok(Diff::Died(rlp.val_at(1)?)),
+			_ => Err(DecoderError::Custom("Invalid diff type")),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 /// Account diff.
 pub struct AccountDiff {
@@ -86,6 +127,37 @@ impl fmt::Display for Existance {
 	}
 }
 
+impl Encodable for AccountDiff {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4);
+		s.append(&self.balance);
+		s.append(&self.nonce);
+		s.append(&self.code);
+		s.begin_list(self.storage.len());
+		for (key, diff) in &self.storage {
+			s.begin_list(2);
+			s.append(key);
+			s.append(diff);
+		}
+	}
+}
+
+impl Decodable for AccountDiff {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		let mut storage = BTreeMap::new();
+		for item in rlp.at(3)?.iter() {
+			storage.insert(item.val_at(0)?, item.val_at(1)?);
+		}
+
+		Ok(AccountDiff {
+			balance: rlp.val_at(0)?,
+			nonce: rlp.val_at(1)?,
+			code: rlp.val_at(2)?,
+			storage: storage,
+		})
+	}
+}
+
 impl AccountDiff {
 	/// Get `Existance` projection.
 	pub fn existance(&self) -> Existance {
@@ -20,6 +20,7 @@ use std::fmt;
 use std::ops::*;
 use std::collections::BTreeMap;
 use util::Address;
+use rlp::{Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
 use account_diff::*;
 
 /// Expression for the delta between two system states. Encoded the
@@ -37,6 +38,30 @@ impl StateDiff {
 	}
 }
 
+impl Encodable for StateDiff {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(self.raw.len());
+		for (address, diff) in &self.raw {
+			s.begin_list(2);
+			s.append(address);
+			s.append(diff);
+		}
+	}
+}
+
+impl Decodable for StateDiff {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		let mut raw = BTreeMap::new();
+		for item in rlp.iter() {
+			raw.insert(item.val_at(0)?, item.val_at(1)?);
+		}
+
+		Ok(StateDiff {
+			raw: raw,
+		})
+	}
+}
+
 impl fmt::Display for StateDiff {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		for (add, acc) in &self.raw {
@@ -53,3 +78,37 @@ impl Deref for StateDiff {
 		&self.raw
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use account_diff::{AccountDiff, Diff};
+	use super::StateDiff;
+
+	#[test]
+	fn state_diff_rlp_roundtrip() {
+		let mut storage = BTreeMap::new();
+		storage.insert(1.into(), Diff::Born(2.into()));
+		storage.insert(3.into(), Diff::Changed(4.into(), 5.into()));
+		storage.insert(6.into(), Diff::Died(7.into()));
+
+		let mut raw = BTreeMap::new();
+		raw.insert(10.into(), AccountDiff {
+			balance: Diff::Changed(100.into(), 50.into()),
+			nonce: Diff::Same,
+			code: Diff::Born(vec![0x60, 0x00]),
+			storage: storage,
+		});
+		raw.insert(11.into(), AccountDiff {
+			balance: Diff::Died(1.into()),
+			nonce: Diff::Died(0.into()),
+			code: Diff::Died(vec![]),
+			storage: BTreeMap::new(),
+		});
+		let diff = StateDiff { raw: raw };
+
+		let encoded = ::rlp::encode(&diff);
+		let decoded: StateDiff = ::rlp::decode(&encoded);
+		assert_eq!(decoded, diff);
+	}
+}
@@ -19,6 +19,7 @@ use std::collections::{HashMap, HashSet};
 
 use bigint::prelude::U256;
 use bigint::hash::H256;
+use hash::keccak;
 use util::Address;
 use bytes::Bytes;
 use {
@@ -56,7 +57,7 @@ pub struct FakeExt {
 	pub store: HashMap<H256, H256>,
 	pub suicides: HashSet<Address>,
 	pub calls: HashSet<FakeCall>,
-	pub sstore_clears: usize,
+	pub sstore_clears: i128,
 	pub depth: usize,
 	pub blockhashes: HashMap<U256, H256>,
 	pub codes: HashMap<Address, Arc<Bytes>>,
@@ -86,6 +87,12 @@ impl FakeExt {
 		ext.schedule = Schedule::new_byzantium();
 		ext
 	}
+
+	pub fn new_constantinople() -> Self {
+		let mut ext = FakeExt::default();
+		ext.schedule = Schedule::new_constantinople();
+		ext
+	}
 }
 
 impl Ext for FakeExt {
@@ -93,6 +100,10 @@ impl Ext for FakeExt {
 		Ok(self.store.get(key).unwrap_or(&H256::new()).clone())
 	}
 
+	fn initial_storage_at(&self, _key: &H256) -> Result<H256> {
+		Ok(H256::new())
+	}
+
 	fn set_storage(&mut self, key: H256, value: H256) -> Result<()> {
 		self.store.insert(key, value);
 		Ok(())
@@ -162,6 +173,10 @@ impl Ext for FakeExt {
 		Ok(self.codes.get(address).map_or(0, |c| c.len()))
 	}
 
+	fn extcodehash(&self, address: &Address) -> Result<H256> {
+		Ok(self.codes.get(address).map_or(H256::new(), |c| keccak(c.as_ref())))
+	}
+
 	fn log(&mut self, topics: Vec<H256>, data: &[u8]) -> Result<()> {
 		self.logs.push(FakeLogEntry {
 			topics: topics,
@@ -195,8 +210,12 @@ impl Ext for FakeExt {
 		false
 	}
 
-	fn inc_sstore_clears(&mut self) {
-		self.sstore_clears += 1;
+	fn add_sstore_refund(&mut self, value: usize) {
+		self.sstore_clears += value as i128;
+	}
+
+	fn sub_sstore_refund(&mut self, value: usize) {
+		self.sstore_clears -= value as i128;
 	}
 
 	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8) -> bool {
@@ -89,6 +89,8 @@ impl vm::Informant for Informant {
 }
 
 impl trace::VMTracer for Informant {
+	type Output = trace::VMTrace;
+
 	fn trace_next_instruction(&mut self, pc: usize, instruction: u8) -> bool {
 		self.pc = pc;
 		self.instruction = instruction;
@@ -15,11 +15,12 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::{FromStr, from_utf8};
-use std::{io, fs};
-use std::io::{BufReader, BufRead};
+use std::{cmp, io, fs};
+use std::io::{BufReader, BufRead, Read};
 use std::time::{Instant, Duration};
 use std::thread::sleep;
 use std::sync::Arc;
+use std::path::{Path, PathBuf};
 use rustc_hex::FromHex;
 use hash::{keccak, KECCAK_NULL_RLP};
 use bigint::prelude::U256;
@@ -28,22 +29,34 @@ use util::Address;
 use bytes::ToPretty;
 use rlp::PayloadInfo;
 use ethcore::service::ClientService;
-use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockId};
+use ethcore::client::{Mode, DatabaseCompactionProfile, DatabaseBackend, VMType, BlockImportError, BlockChainClient, BlockId};
+use ethcore::db;
 use ethcore::error::ImportError;
+use ethcore::db_stats::{column_stats, state_stats, SIZE_BUCKETS};
+use ethcore::db_check::{check_database, Inconsistency};
+use ethcore::era::{self, ArchiveReader, ArchiveWriter, ArchiveBlock, MAX_BLOCKS};
+use ethcore::migrations::prune_state;
 use ethcore::miner::Miner;
+use ethcore::trace::TracingLevel;
 use ethcore::verification::queue::VerifierSettings;
 use cache::CacheConfig;
 use informant::{Informant, FullNodeInformantData, MillisecondDuration};
 use params::{SpecType, Pruning, Switch, tracing_switch_to_bool, fatdb_switch_to_bool};
 use helpers::{to_client_config, execute_upgrades};
+use migration;
 use dir::Directories;
 use user_defaults::UserDefaults;
 use fdlimit;
+use journaldb::Algorithm;
+use kvdb::KeyValueDB;
+use kvdb_rocksdb::DatabaseConfig;
 
 #[derive(Debug, PartialEq)]
 pub enum DataFormat {
 	Hex,
 	Binary,
+	/// Era1-style archives, holding receipts and total difficulty along with the blocks.
+	Era,
 }
 
 impl Default for DataFormat {
@@ -59,6 +72,7 @@ impl FromStr for DataFormat {
 		match s {
 			"binary" | "bin" => Ok(DataFormat::Binary),
 			"hex" => Ok(DataFormat::Hex),
+			"era" | "era1" => Ok(DataFormat::Era),
 			x => Err(format!("Invalid format: {}", x))
 		}
 	}
@@ -67,6 +81,10 @@ impl FromStr for DataFormat {
 #[derive(Debug, PartialEq)]
 pub enum BlockchainCmd {
 	Kill(KillBlockchain),
+	Prune(PruneBlockchain),
+	Stats(DbStats),
+	Check(CheckBlockchain),
+	Reset(ResetBlockchain),
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
@@ -79,6 +97,58 @@ pub struct KillBlockchain {
 	pub pruning: Pruning,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct PruneBlockchain {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub db_backend: DatabaseBackend,
+	pub target: Algorithm,
+	pub keep: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DbStats {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub db_backend: DatabaseBackend,
+	pub state: Option<BlockId>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckBlockchain {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub db_backend: DatabaseBackend,
+	pub from: BlockId,
+	pub repair: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ResetBlockchain {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub db_backend: DatabaseBackend,
+	pub fat_db: Switch,
+	pub tracing: Switch,
+	pub block: BlockId,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ImportBlockchain {
 	pub spec: SpecType,
@@ -91,7 +161,9 @@ pub struct ImportBlockchain {
 	pub pruning_memory: usize,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub tracing: Switch,
+	pub tracing_level: TracingLevel,
 	pub fat_db: Switch,
 	pub vm_type: VMType,
 	pub check_seal: bool,
@@ -112,6 +184,7 @@ pub struct ExportBlockchain {
 	pub pruning_memory: usize,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub fat_db: Switch,
 	pub tracing: Switch,
 	pub from_block: BlockId,
@@ -131,6 +204,7 @@ pub struct ExportState {
 	pub pruning_memory: usize,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub fat_db: Switch,
 	pub tracing: Switch,
 	pub at: BlockId,
@@ -143,6 +217,10 @@ pub struct ExportState {
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
+		BlockchainCmd::Prune(prune_cmd) => prune_db(prune_cmd),
+		BlockchainCmd::Stats(stats_cmd) => db_stats(stats_cmd),
+		BlockchainCmd::Check(check_cmd) => check_db(check_cmd),
+		BlockchainCmd::Reset(reset_cmd) => reset_db(reset_cmd),
 		BlockchainCmd::Import(import_cmd) => {
 			if import_cmd.light {
 				execute_import_light(import_cmd)
@@ -187,7 +265,7 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 
 	// execute upgrades
 	let compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, compaction)?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, DatabaseBackend::RocksDB, compaction)?;
 
 	// create dirs used by parity
 	cmd.dirs.create_dirs(false, false, false)?;
@@ -219,7 +297,13 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 
+	let archive_dir = cmd.file_path.as_ref().map(PathBuf::from).and_then(|path| match path.is_dir() {
+		true => Some(path),
+		false => None,
+	});
+
 	let mut instream: Box<io::Read> = match cmd.file_path {
+		Some(_) if archive_dir.is_some() => Box::new(io::empty()),
 		Some(f) => Box::new(fs::File::open(&f).map_err(|_| format!("Cannot open given file: {}", f))?),
 		None => Box::new(io::stdin()),
 	};
@@ -231,10 +315,12 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 
 	let format = match cmd.format {
 		Some(format) => format,
+		None if archive_dir.is_some() => DataFormat::Era,
 		None => {
 			first_read = instream.read(&mut first_bytes).map_err(|_| "Error reading from the file/stream.")?;
 			match first_bytes[0] {
 				0xf9 => DataFormat::Binary,
+				_ if era::is_archive(&first_bytes[..first_read]) => DataFormat::Era,
 				_ => DataFormat::Hex,
 			}
 		}
@@ -290,6 +376,10 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 				do_import(bytes)?;
 			}
 		}
+		DataFormat::Era => {
+			let instream = io::Cursor::new(first_bytes[..first_read].to_vec()).chain(instream);
+			read_archives(archive_dir, instream, |archived| do_import(archived.block))?;
+		}
 	}
 	client.flush_queue();
 
@@ -339,7 +429,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	let snapshot_path = db_dirs.snapshot_path();
 
 	// execute upgrades
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.db_backend, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
 
 	// create dirs used by parity
 	cmd.dirs.create_dirs(false, false, false)?;
@@ -353,6 +443,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 		fat_db,
 		cmd.compaction,
 		cmd.wal,
+		cmd.db_backend,
 		cmd.vm_type,
 		"".into(),
 		algorithm,
@@ -362,6 +453,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.tracing.level = cmd.tracing_level;
 
 	// build client
 	let service = ClientService::start(
@@ -378,7 +470,13 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 
+	let archive_dir = cmd.file_path.as_ref().map(PathBuf::from).and_then(|path| match path.is_dir() {
+		true => Some(path),
+		false => None,
+	});
+
 	let mut instream: Box<io::Read> = match cmd.file_path {
+		Some(_) if archive_dir.is_some() => Box::new(io::empty()),
 		Some(f) => Box::new(fs::File::open(&f).map_err(|_| format!("Cannot open given file: {}", f))?),
 		None => Box::new(io::stdin()),
 	};
@@ -390,10 +488,12 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 
 	let format = match cmd.format {
 		Some(format) => format,
+		None if archive_dir.is_some() => DataFormat::Era,
 		None => {
 			first_read = instream.read(&mut first_bytes).map_err(|_| "Error reading from the file/stream.")?;
 			match first_bytes[0] {
 				0xf9 => DataFormat::Binary,
+				_ if era::is_archive(&first_bytes[..first_read]) => DataFormat::Era,
 				_ => DataFormat::Hex,
 			}
 		}
@@ -426,6 +526,46 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 		Ok(())
 	};
 
+	// hash and total difficulty of the last archived block.
+	let mut last_archived: Option<(H256, U256)> = None;
+
+	// archived blocks are inserted along with their receipts without being executed,
+	// unless they extend the chain, which requires their state.
+	let do_import_archived = |archived: ArchiveBlock| {
+		let header: ::ethcore::header::Header = ::rlp::UntrustedRlp::new(&archived.block).val_at(0)
+			.map_err(|e| format!("Bad block: {}", e))?;
+		let hash = header.hash();
+
+		// the total difficulty of the archive is checked before the block is imported.
+		// the parent of the first block of an archive has to be in the database already.
+		let parent_total_difficulty = match last_archived {
+			Some((last_hash, total_difficulty)) if last_hash == *header.parent_hash() => Some(total_difficulty),
+			_ if header.number() == 0 => Some(U256::zero()),
+			_ => client.block_total_difficulty(BlockId::Hash(*header.parent_hash())),
+		};
+		match parent_total_difficulty {
+			Some(total_difficulty) if total_difficulty + *header.difficulty() == archived.total_difficulty => {},
+			Some(_) => return Err(format!("Total difficulty of block #{} ({}) doesn't match the archive.", header.number(), hash)),
+			None => return Err(format!("Cannot import block #{} ({}): unknown parent {}.", header.number(), hash, header.parent_hash())),
+		}
+		last_archived = Some((hash, archived.total_difficulty));
+
+		if header.number() > client.chain_info().best_block_number {
+			return do_import(archived.block);
+		}
+
+		match client.import_block_with_receipts(archived.block, archived.receipts) {
+			Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
+				trace!("Skipping block already in chain.");
+			}
+			Err(e) => {
+				return Err(format!("Cannot import block: {:?}", e));
+			},
+			Ok(_) => {},
+		}
+		Ok(())
+	};
+
 	match format {
 		DataFormat::Binary => {
 			loop {
@@ -452,6 +592,10 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 				do_import(bytes)?;
 			}
 		}
+		DataFormat::Era => {
+			let instream = io::Cursor::new(first_bytes[..first_read].to_vec()).chain(instream);
+			read_archives(archive_dir, instream, do_import_archived)?;
+		}
 	}
 	client.flush_queue();
 
@@ -486,6 +630,7 @@ fn start_client(
 	fat_db: Switch,
 	compaction: DatabaseCompactionProfile,
 	wal: bool,
+	db_backend: DatabaseBackend,
 	cache_config: CacheConfig,
 	require_fat_db: bool,
 ) -> Result<ClientService, String> {
@@ -524,7 +669,7 @@ fn start_client(
 	let snapshot_path = db_dirs.snapshot_path();
 
 	// execute upgrades
-	execute_upgrades(&dirs.base, &db_dirs, algorithm, compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	execute_upgrades(&dirs.base, &db_dirs, algorithm, db_backend, compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
 
 	// create dirs used by parity
 	dirs.create_dirs(false, false, false)?;
@@ -538,6 +683,7 @@ fn start_client(
 		fat_db,
 		compaction,
 		wal,
+		db_backend,
 		VMType::default(),
 		"".into(),
 		algorithm,
@@ -570,6 +716,7 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 		cmd.fat_db,
 		cmd.compaction,
 		cmd.wal,
+		cmd.db_backend,
 		cmd.cache_config,
 		false,
 	)?;
@@ -577,14 +724,19 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	if format == DataFormat::Era {
+		let dir = cmd.file_path.ok_or("Exporting archives requires a directory to write them to.")?;
+		return export_archives(&*client, Path::new(&dir), from, to);
+	}
+
 	let mut out: Box<io::Write> = match cmd.file_path {
 		Some(f) => Box::new(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))?),
 		None => Box::new(io::stdout()),
 	};
 
-	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
-	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
-
 	for i in from..(to + 1) {
 		if i % 10000 == 0 {
 			info!("#{}", i);
@@ -593,6 +745,7 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 		match format {
 			DataFormat::Binary => { out.write(&b).expect("Couldn't write to stream."); }
 			DataFormat::Hex => { out.write_fmt(format_args!("{}", b.pretty())).expect("Couldn't write to stream."); }
+			DataFormat::Era => unreachable!("archives are exported by export_archives; qed"),
 		}
 	}
 
@@ -600,6 +753,73 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+// write blocks `from..to` into archives in `dir`. archives end on multiples of `MAX_BLOCKS`
+// and are named `<chain>-<era>-<accumulator prefix>.era1`.
+fn export_archives(client: &BlockChainClient, dir: &Path, from: u64, to: u64) -> Result<(), String> {
+	fs::create_dir_all(dir).map_err(|e| format!("Cannot create directory {}: {}", dir.display(), e))?;
+	let chain = client.spec_name().to_lowercase();
+
+	let mut start = from;
+	while start <= to {
+		let era = start / MAX_BLOCKS;
+		let end = cmp::min(to, (era + 1) * MAX_BLOCKS - 1);
+		let temp_path = dir.join(format!("{}-{:05}.era1.tmp", chain, era));
+
+		let file = fs::File::create(&temp_path).map_err(|_| format!("Cannot write to file given: {}", temp_path.display()))?;
+		let mut writer = ArchiveWriter::new(io::BufWriter::new(file), start).map_err(|e| format!("Error writing archive: {}", e))?;
+		for i in start..(end + 1) {
+			if i % 10000 == 0 {
+				info!("#{}", i);
+			}
+			let block = client.block(BlockId::Number(i)).ok_or("Error exporting incomplete chain")?;
+			let receipts = client.block_receipts(&block.hash()).ok_or_else(|| format!("Missing receipts of block #{}", i))?;
+			let total_difficulty = client.block_total_difficulty(BlockId::Number(i)).ok_or("Error exporting incomplete chain")?;
+			writer.push(&block.into_inner(), &receipts, total_difficulty).map_err(|e| format!("Error writing archive: {}", e))?;
+		}
+		let root = writer.finish().map_err(|e| format!("Error writing archive: {}", e))?;
+
+		let path = dir.join(format!("{}-{:05}-{}.era1", chain, era, &root.hex()[..8]));
+		fs::rename(&temp_path, &path).map_err(|e| format!("Cannot write to file given: {}: {}", path.display(), e))?;
+		info!("Exported blocks #{} to #{} into {}", start, end, path.display());
+		start = end + 1;
+	}
+
+	info!("Export completed.");
+	Ok(())
+}
+
+// read the blocks of the archives in `dir`, ordered by name, or of the archive in `input`.
+fn read_archives<R, F>(dir: Option<PathBuf>, mut input: R, mut f: F) -> Result<(), String> where
+	R: io::Read,
+	F: FnMut(ArchiveBlock) -> Result<(), String>,
+{
+	let read = |input: &mut io::Read, name: &str, f: &mut F| -> Result<(), String> {
+		let mut reader = ArchiveReader::new(input).map_err(|e| format!("Invalid archive {}: {}", name, e))?;
+		while let Some(block) = reader.next_block().map_err(|e| format!("Invalid archive {}: {}", name, e))? {
+			f(block)?;
+		}
+		Ok(())
+	};
+
+	let dir = match dir {
+		Some(dir) => dir,
+		None => return read(&mut input, "stream", &mut f),
+	};
+
+	let mut paths = fs::read_dir(&dir).map_err(|e| format!("Cannot read directory {}: {}", dir.display(), e))?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|path| path.extension().map_or(false, |ext| ext == "era1"))
+		.collect::<Vec<_>>();
+	paths.sort();
+
+	for path in paths {
+		info!("Importing {}", path.display());
+		let file = fs::File::open(&path).map_err(|_| format!("Cannot open given file: {}", path.display()))?;
+		read(&mut BufReader::new(file), &path.display().to_string(), &mut f)?;
+	}
+	Ok(())
+}
+
 fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 	let service = start_client(
 		cmd.dirs,
@@ -611,6 +831,7 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 		cmd.fat_db,
 		cmd.compaction,
 		cmd.wal,
+		cmd.db_backend,
 		cmd.cache_config,
 		true
 	)?;
@@ -702,6 +923,214 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+pub fn prune_db(cmd: PruneBlockchain) -> Result<(), String> {
+	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir.clone());
+	let user_defaults_path = db_dirs.user_defaults_path();
+	let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+
+	if algorithm != Algorithm::Archive {
+		return Err(format!("Only an archive database can be pruned. The database uses {} pruning.", algorithm));
+	}
+	if cmd.target != Algorithm::OverlayRecent {
+		return Err(format!("Pruning into {} is not supported. Use --target=fast.", cmd.target));
+	}
+	if user_defaults.fat_db {
+		return Err("Pruning a fat database is not supported.".into());
+	}
+
+	let target_path = db_dirs.client_path(cmd.target);
+	if target_path.exists() {
+		return Err(format!("A {} database already exists at {}. Remove it with `parity db kill --pruning={}` first.", cmd.target, target_path.display(), cmd.target));
+	}
+
+	fdlimit::raise_fd_limit();
+
+	let compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.db_backend, compaction)?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, cmd.target, cmd.db_backend, compaction)?;
+
+	let mut db_config = DatabaseConfig::with_columns(db::NUM_COLUMNS);
+	db_config.compaction = compaction;
+	let open = |path: &Path| -> Result<Arc<KeyValueDB>, String> {
+		fs::create_dir_all(path).map_err(|e| format!("Error creating database directory: {:?}", e))?;
+		let path = path.to_str().ok_or_else(|| "Invalid database path".to_owned())?;
+		cmd.db_backend.open(&db_config, path).map_err(|e| format!("Error opening database: {}", e))
+	};
+	let source = open(&db_dirs.client_path(algorithm))?;
+	let dest = open(&target_path)?;
+
+	let report = prune_state(source, dest, &spec.genesis_block(), cmd.target, cmd.keep)
+		.map_err(|e| format!("Error pruning database: {}", e))?;
+
+	user_defaults.pruning = cmd.target;
+	user_defaults.save(&user_defaults_path)?;
+	info!("Database pruned. Kept the state of blocks #{} to #{} ({} state nodes).", report.base_era, report.latest_era, report.nodes);
+	info!("The archive database has been left untouched. Remove it with `parity db kill --pruning=archive`.");
+	Ok(())
+}
+
+pub fn db_stats(cmd: DbStats) -> Result<(), String> {
+	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir.clone());
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+
+	let client_path = db_dirs.client_path(algorithm);
+	if !client_path.exists() {
+		return Err(format!("No {} database found at {}", algorithm, client_path.display()));
+	}
+
+	fdlimit::raise_fd_limit();
+
+	// the statistics are read as they are: an outdated database isn't migrated.
+	migration::check_version(&db_dirs.db_path(algorithm)).map_err(|e| format!("{}", e))?;
+
+	let mut db_config = DatabaseConfig::with_columns(db::NUM_COLUMNS);
+	db_config.compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
+	let path = client_path.to_str().ok_or_else(|| "Invalid database path".to_owned())?;
+	let db = cmd.db_backend.open_read_only(&db_config, path).map_err(|e| format!("Error opening database: {}", e))?;
+
+	println!("{:<14} {:>12} {:>14} {:>16}", "Column", "Keys", "Key bytes", "Value bytes");
+	let columns = column_stats(&*db);
+	for column in &columns {
+		println!("{:<14} {:>12} {:>14} {:>16}", column.name(), column.keys, column.key_bytes, column.value_bytes);
+	}
+
+	for column in columns.iter().filter(|c| c.keys > 0) {
+		println!("\nValue sizes of column {}:", column.name());
+		for (i, count) in column.value_sizes.iter().enumerate().filter(|&(_, count)| *count > 0) {
+			match i == SIZE_BUCKETS - 1 {
+				true => println!("  > {:>8} bytes: {}", 1u64 << (i - 1), count),
+				false => println!("  <= {:>7} bytes: {}", 1u64 << i, count),
+			}
+		}
+	}
+
+	if let Some(at) = cmd.state {
+		info!("Walking the state of block {:?}. This may take a while.", at);
+		let stats = state_stats(db, &spec.genesis_block(), algorithm, at)
+			.map_err(|e| format!("Error walking the state: {}", e))?
+			.ok_or_else(|| format!("Unknown block: {:?}", at))?;
+
+		println!("\nState of block #{} ({:?}):", stats.number, stats.hash);
+		println!("  accounts:      {}", stats.accounts);
+		println!("  contracts:     {}", stats.contracts);
+		println!("  storage slots: {}", stats.storage_slots);
+		println!("  trie nodes and code: {} ({} bytes)", stats.entries, stats.entry_bytes);
+		println!("  account depths:");
+		for (depth, count) in stats.account_depths.iter().enumerate().filter(|&(_, count)| *count > 0) {
+			println!("    {:>3}: {}", depth, count);
+		}
+		println!("  storage slot depths:");
+		for (depth, count) in stats.storage_depths.iter().enumerate().filter(|&(_, count)| *count > 0) {
+			println!("    {:>3}: {}", depth, count);
+		}
+	}
+
+	Ok(())
+}
+
+pub fn check_db(cmd: CheckBlockchain) -> Result<(), String> {
+	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir.clone());
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+
+	let client_path = db_dirs.client_path(algorithm);
+	if !client_path.exists() {
+		return Err(format!("No {} database found at {}", algorithm, client_path.display()));
+	}
+
+	fdlimit::raise_fd_limit();
+
+	let compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.db_backend, compaction)?;
+
+	let mut db_config = DatabaseConfig::with_columns(db::NUM_COLUMNS);
+	db_config.compaction = compaction;
+	let path = client_path.to_str().ok_or_else(|| "Invalid database path".to_owned())?;
+	let db = cmd.db_backend.open(&db_config, path).map_err(|e| format!("Error opening database: {}", e))?;
+
+	info!("Checking the database from block {:?}. This may take a while.", cmd.from);
+	let report = check_database(db, &spec.genesis_block(), algorithm, cmd.from, cmd.repair)
+		.map_err(|e| format!("Error checking database: {}", e))?;
+	drop(spec);
+
+	info!("Checked {} blocks.", report.blocks);
+	let inconsistency = match report.inconsistency {
+		Some(inconsistency) => inconsistency,
+		None => {
+			info!("The database is consistent.");
+			return Ok(());
+		},
+	};
+
+	warn!("{}", inconsistency);
+	let incomplete_best_block = match inconsistency {
+		Inconsistency::BestBlock(_) => true,
+		_ => false,
+	};
+	match (report.last_consistent, cmd.repair) {
+		(Some((number, hash)), true) => {
+			// rewind through the client, so that the state journal is reverted as well.
+			let service = start_client(
+				cmd.dirs,
+				cmd.spec,
+				cmd.pruning,
+				cmd.pruning_history,
+				cmd.pruning_memory,
+				Switch::Auto,
+				Switch::Auto,
+				cmd.compaction,
+				cmd.wal,
+				cmd.db_backend,
+				cmd.cache_config,
+				false,
+			)?;
+
+			let client = service.client();
+			if client.chain_info().best_block_hash != hash {
+				client.reset_to(BlockId::Hash(hash))?;
+			}
+			info!("Best block rewound to #{} ({:?}).", number, hash);
+		},
+		(Some((number, hash)), false) => info!("Last consistent block is #{} ({:?}). Run with --repair to rewind to it.", number, hash),
+		(None, false) if incomplete_best_block => info!("Run with --repair to move the best block to the latest complete block."),
+		(None, _) => return Err("No consistent block with a complete state was found. Resync the database.".into()),
+	}
+
+	Ok(())
+}
+
+pub fn reset_db(cmd: ResetBlockchain) -> Result<(), String> {
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.db_backend,
+		cmd.cache_config,
+		false,
+	)?;
+
+	let client = service.client();
+	let best = client.chain_info().best_block_number;
+	let hash = client.reset_to(cmd.block)?;
+	let number = client.chain_info().best_block_number;
+	info!("Best block reset from #{} to #{} ({:?}).", best, number, hash);
+	Ok(())
+}
+
 #[cfg(test)]
 mod test {
 	use super::DataFormat;
@@ -711,5 +1140,7 @@ mod test {
 		assert_eq!(DataFormat::Binary, "binary".parse().unwrap());
 		assert_eq!(DataFormat::Binary, "bin".parse().unwrap());
 		assert_eq!(DataFormat::Hex, "hex".parse().unwrap());
+		assert_eq!(DataFormat::Era, "era".parse().unwrap());
+		assert_eq!(DataFormat::Era, "era1".parse().unwrap());
 	}
 }
@@ -97,11 +97,11 @@ usage! {
 
 			ARG arg_import_format: (Option<String>) = None,
 			"--format=[FORMAT]",
-			"Import in a given format. FORMAT must be either 'hex' or 'binary'. (default: auto)",
+			"Import in a given format. FORMAT must be either 'hex', 'binary' or 'era'. (default: auto)",
 
 			ARG arg_import_file: (Option<String>) = None,
 			"[FILE]",
-			"Path to the file to import from",
+			"Path to the file to import from, or to a directory of archives with --format=era",
 		}
 
 		CMD cmd_export
@@ -114,7 +114,7 @@ usage! {
 
 				ARG arg_export_blocks_format: (Option<String>) = None,
 				"--format=[FORMAT]",
-				"Export in a given format. FORMAT must be either 'hex' or 'binary'. (default: binary)",
+				"Export in a given format. FORMAT must be either 'hex', 'binary' or 'era'. (default: binary)",
 
 				ARG arg_export_blocks_from: (String) = "1",
 				"--from=[BLOCK]",
@@ -126,7 +126,7 @@ usage! {
 
 				ARG arg_export_blocks_file: (Option<String>) = None,
 				"[FILE]",
-				"Path to the exported file",
+				"Path to the exported file, or to the directory to write archives to with --format=era",
 			}
 
 			CMD cmd_export_state
@@ -241,6 +241,46 @@ usage! {
 			CMD cmd_db_kill {
 				"Clean the database",
 			}
+
+			CMD cmd_db_prune {
+				"Convert an archive database into a pruned one",
+
+				ARG arg_db_prune_target: (String) = "fast",
+				"--target=[METHOD]",
+				"Pruning method of the new database. Only fast is supported.",
+
+				ARG arg_db_prune_keep: (u64) = 64u64,
+				"--keep=[N]",
+				"Keep the state of the last N blocks. Use at least the --pruning-history of the node.",
+			}
+
+			CMD cmd_db_stats {
+				"Show the size of the database columns",
+
+				ARG arg_db_stats_state: (Option<String>) = None,
+				"--state=[BLOCK]",
+				"Also walk the state of BLOCK (a number, hash or latest) and show statistics of its trie.",
+			}
+
+			CMD cmd_db_check {
+				"Check the consistency of the blockchain and of the state of the best block",
+
+				ARG arg_db_check_from: (String) = "0",
+				"--from=[BLOCK]",
+				"Check the blocks from BLOCK (a number, hash or latest) onwards.",
+
+				FLAG flag_db_check_repair: (bool) = false,
+				"--repair",
+				"Rewind the best block to the last consistent block.",
+			}
+
+			CMD cmd_db_reset {
+				"Rewind the best block to an earlier canonical block",
+
+				ARG arg_db_reset_block: (Option<String>) = None,
+				"<BLOCK>",
+				"Number or hash of the block. Its state must not have been pruned.",
+			}
 		}
 	}
 	{
@@ -464,7 +504,7 @@ usage! {
 
 			ARG arg_jsonrpc_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,traces,rpc,secretstore,shh,shh_pubsub", or |c: &Config| otry!(c.rpc).apis.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-apis=[APIS]",
-			"Specify the APIs available through the JSONRPC interface. APIS is a comma-delimited list of API name. Possible name are all, safe, web3, eth, net, personal, parity, parity_set, traces, rpc, parity_accounts. You can also disable a specific API by putting '-' in the front: all,-personal.",
+			"Specify the APIs available through the JSONRPC interface. APIS is a comma-delimited list of API name. Possible name are all, safe, web3, eth, net, personal, parity, parity_set, clique, traces, debug, rpc, parity_accounts. You can also disable a specific API by putting '-' in the front: all,-personal.",
 
 			ARG arg_jsonrpc_hosts: (String) = "none", or |c: &Config| otry!(c.rpc).hosts.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-hosts=[HOSTS]",
@@ -497,7 +537,7 @@ usage! {
 
 			ARG arg_ws_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,traces,rpc,secretstore,shh,shh_pubsub", or |c: &Config| otry!(c.websockets).apis.as_ref().map(|vec| vec.join(",")),
 			"--ws-apis=[APIS]",
-			"Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, pubsub, net, personal, parity, parity_set, traces, rpc, parity_accounts..",
+			"Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, pubsub, net, personal, parity, parity_set, clique, traces, debug, rpc, parity_accounts..",
 
 			ARG arg_ws_origins: (String) = "chrome-extension://*,moz-extension://*", or |c: &Config| otry!(c.websockets).origins.as_ref().map(|vec| vec.join(",")),
 			"--ws-origins=[URL]",
@@ -550,6 +590,19 @@ usage! {
 			"--ipfs-api-cors=[URL]",
 			"Specify CORS header for IPFS API responses.",
 
+		["Metrics options"]
+			FLAG flag_metrics: (bool) = false, or |c: &Config| otry!(c.metrics).enable.clone(),
+			"--metrics",
+			"Enable the Prometheus metrics HTTP endpoint.",
+
+			ARG arg_metrics_port: (u16) = 3000u16, or |c: &Config| otry!(c.metrics).port.clone(),
+			"--metrics-port=[PORT]",
+			"Specify the port portion of the metrics server.",
+
+			ARG arg_metrics_interface: (String) = "local", or |c: &Config| otry!(c.metrics).interface.clone(),
+			"--metrics-interface=[IP]",
+			"Specify the hostname portion of the metrics server, IP should be an interface's IP address, or all (all interfaces) or local.",
+
 		["Secret store options"]
 			FLAG flag_no_secretstore: (bool) = false, or |c: &Config| otry!(c.secretstore).disable.clone(),
 			"--no-secretstore",
@@ -676,6 +729,10 @@ usage! {
 			"--tx-queue-strategy=[S]",
 			"Prioritization strategy used to order transactions in the queue. S may be: gas - Prioritize txs with low gas limit; gas_price - Prioritize txs with high gas price; gas_factor - Prioritize txs using gas price and gas limit ratio.",
 
+			ARG arg_tx_queue_min_bump: (Option<usize>) = None, or |c: &Config| otry!(c.mining).tx_queue_min_bump.clone(),
+			"--tx-queue-min-bump=[PERCENT]",
+			"Minimal gas price increase (in percent) required to replace a transaction with the same sender and nonce in the queue. Defaults to 12.5%.",
+
 			ARG arg_tx_queue_ban_count: (u16) = 1u16, or |c: &Config| otry!(c.mining).tx_queue_ban_count.clone(),
 			"--tx-queue-ban-count=[C]",
 			"Number of times maximal time for execution (--tx-time-limit) can be exceeded before banning sender/recipient/code.",
@@ -767,6 +824,10 @@ usage! {
 			"--tracing=[BOOL]",
 			"Indicates if full transaction tracing should be enabled. Works only if client had been fully synced with tracing enabled. BOOL may be one of auto, on, off. auto uses last used value of this option (off if it does not exist).", // footprint option
 
+			ARG arg_tracing_level: (String) = "calls", or |c: &Config| otry!(c.footprint).tracing_level.clone(),
+			"--tracing-level=[LEVEL]",
+			"Data stored in the traces database when tracing is on. LEVEL may be one of calls, calls+statediff, full. calls+statediff stores the state diff of every transaction, full stores VM traces as well. Stored data is served by trace_replayTransaction and trace_replayBlockTransactions without re-executing the transactions. Applies to blocks imported afterwards.",
+
 			ARG arg_pruning: (String) = "auto", or |c: &Config| otry!(c.footprint).pruning.clone(),
 			"--pruning=[METHOD]",
 			"Configure pruning of the state/storage trie. METHOD may be one of auto, archive, fast: archive - keep all state trie data. No pruning. fast - maintain journal overlay. Fast but 50MB used. auto - use the method most recently synced or default to fast if none synced.",
@@ -779,6 +840,10 @@ usage! {
 			"--pruning-memory=[MB]",
 			"The ideal amount of memory in megabytes to use to store recent states. As many states as possible will be kept within this limit, and at least --pruning-history states will always be kept.",
 
+			ARG arg_history_bodies: (Option<u64>) = None, or |c: &Config| otry!(c.footprint).history_bodies.clone(),
+			"--history-bodies=[NUM]",
+			"Keep the bodies, receipts and transaction indexes of the last NUM blocks only. Headers are always kept. Older blocks are not served to peers and ancient blocks are not downloaded. At least 5100 blocks are kept unless --no-periodic-snapshot is set.",
+
 			ARG arg_cache_size_db: (u32) = 32u32, or |c: &Config| otry!(c.footprint).cache_size_db.clone(),
 			"--cache-size-db=[MB]",
 			"Override database cache size.",
@@ -799,6 +864,10 @@ usage! {
 			"--db-compaction=[TYPE]",
 			"Database compaction type. TYPE may be one of: ssd - suitable for SSDs and fast HDDs; hdd - suitable for slow HDDs; auto - determine automatically.",
 
+			ARG arg_db_backend: (String) = "rocksdb", or |c: &Config| otry!(c.footprint).db_backend.clone(),
+			"--db-backend=[BACKEND]",
+			"Key-value store used for the client database. BACKEND may be one of: rocksdb; paritydb - append-only store with an in-memory index. An existing database is converted on startup.",
+
 			ARG arg_fat_db: (String) = "auto", or |c: &Config| otry!(c.footprint).fat_db.clone(),
 			"--fat-db=[BOOL]",
 			"Build appropriate information to allow enumeration of all accounts and storage keys. Doubles the size of the state database. BOOL may be one of on, off or auto.",
@@ -980,6 +1049,7 @@ struct Config {
 	dapps: Option<Dapps>,
 	secretstore: Option<SecretStore>,
 	ipfs: Option<Ipfs>,
+	metrics: Option<Metrics>,
 	mining: Option<Mining>,
 	footprint: Option<Footprint>,
 	snapshots: Option<Snapshots>,
@@ -1111,6 +1181,13 @@ struct Ipfs {
 	hosts: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+struct Metrics {
+	enable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 struct Mining {
 	author: Option<String>,
@@ -1135,6 +1212,7 @@ struct Mining {
 	tx_queue_mem_limit: Option<u32>,
 	tx_queue_gas: Option<String>,
 	tx_queue_strategy: Option<String>,
+	tx_queue_min_bump: Option<usize>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
 	remove_solved: Option<bool>,
@@ -1152,9 +1230,11 @@ struct Stratum {
 #[derive(Default, Debug, PartialEq, Deserialize)]
 struct Footprint {
 	tracing: Option<String>,
+	tracing_level: Option<String>,
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
+	history_bodies: Option<u64>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
@@ -1162,6 +1242,7 @@ struct Footprint {
 	cache_size_queue: Option<u32>,
 	cache_size_state: Option<u32>,
 	db_compaction: Option<String>,
+	db_backend: Option<String>,
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
@@ -1362,6 +1443,10 @@ mod tests {
 			cmd_tools_hash: false,
 			cmd_db: false,
 			cmd_db_kill: false,
+			cmd_db_prune: false,
+			cmd_db_stats: false,
+			cmd_db_check: false,
+			cmd_db_reset: false,
 
 			// Arguments
 			arg_daemon_pid_file: None,
@@ -1374,6 +1459,12 @@ mod tests {
 			arg_snapshot_file: None,
 			arg_restore_file: None,
 			arg_tools_hash_file: None,
+			arg_db_prune_target: "fast".into(),
+			arg_db_prune_keep: 64u64,
+			arg_db_stats_state: None,
+			arg_db_check_from: "0".into(),
+			flag_db_check_repair: false,
+			arg_db_reset_block: None,
 
 			arg_account_new_password: None,
 			arg_signer_sign_password: None,
@@ -1487,6 +1578,11 @@ mod tests {
 			arg_ipfs_api_cors: Some("null".into()),
 			arg_ipfs_api_hosts: "none".into(),
 
+			// -- Metrics Options
+			flag_metrics: false,
+			arg_metrics_port: 3000u16,
+			arg_metrics_interface: "local".into(),
+
 			// -- Sealing/Mining Options
 			arg_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			arg_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -1510,6 +1606,7 @@ mod tests {
 			arg_tx_queue_mem_limit: 2u32,
 			arg_tx_queue_gas: "off".into(),
 			arg_tx_queue_strategy: "gas_factor".into(),
+			arg_tx_queue_min_bump: Some(12),
 			arg_tx_queue_ban_count: 1u16,
 			arg_tx_queue_ban_time: 180u16,
 			flag_remove_solved: false,
@@ -1523,9 +1620,11 @@ mod tests {
 
 			// -- Footprint Options
 			arg_tracing: "auto".into(),
+			arg_tracing_level: "calls".into(),
 			arg_pruning: "auto".into(),
 			arg_pruning_history: 64u64,
 			arg_pruning_memory: 500usize,
+			arg_history_bodies: None,
 			arg_cache_size_db: 64u32,
 			arg_cache_size_blocks: 8u32,
 			arg_cache_size_queue: 50u32,
@@ -1533,6 +1632,7 @@ mod tests {
 			arg_cache_size: Some(128),
 			flag_fast_and_loose: false,
 			arg_db_compaction: "ssd".into(),
+			arg_db_backend: "rocksdb".into(),
 			arg_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			arg_num_verifiers: Some(6),
@@ -1727,6 +1827,7 @@ mod tests {
 				cors: None,
 				hosts: None,
 			}),
+			metrics: None,
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -1747,6 +1848,7 @@ mod tests {
 				tx_queue_mem_limit: None,
 				tx_queue_gas: Some("off".into()),
 				tx_queue_strategy: None,
+				tx_queue_min_bump: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
 				tx_gas_limit: None,
@@ -1758,9 +1860,11 @@ mod tests {
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
+				tracing_level: None,
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
+				history_bodies: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(128),
@@ -1768,6 +1872,7 @@ mod tests {
 				cache_size_queue: Some(100),
 				cache_size_state: Some(25),
 				db_compaction: Some("ssd".into()),
+				db_backend: None,
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
@@ -45,10 +45,11 @@ use ethcore_logger::Config as LogConfig;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use dapps::Configuration as DappsConfiguration;
 use ipfs::Configuration as IpfsConfiguration;
+use metrics::Configuration as MetricsConfiguration;
 use secretstore::{Configuration as SecretStoreConfiguration, NodeSecretKey};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, PruneBlockchain, DbStats, CheckBlockchain, ResetBlockchain, ExportState, DataFormat};
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot::{self, SnapshotCommand};
@@ -127,11 +128,13 @@ impl Configuration {
 		let fat_db = self.args.arg_fat_db.parse()?;
 		let compaction = self.args.arg_db_compaction.parse()?;
 		let wal = !self.args.flag_fast_and_loose;
+		let db_backend = self.args.arg_db_backend.parse()?;
 		let public_node = self.args.flag_public_node;
 		let warp_sync = !self.args.flag_no_warp;
 		let geth_compatibility = self.args.flag_geth;
 		let dapps_conf = self.dapps_config();
 		let ipfs_conf = self.ipfs_config();
+		let metrics_conf = self.metrics_config();
 		let secretstore_conf = self.secretstore_config()?;
 		let format = self.format()?;
 
@@ -174,6 +177,60 @@ impl Configuration {
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_db_prune {
+			Cmd::Blockchain(BlockchainCmd::Prune(PruneBlockchain {
+				spec: spec,
+				dirs: dirs,
+				pruning: pruning,
+				compaction: compaction,
+				db_backend: db_backend,
+				target: self.args.arg_db_prune_target.parse()?,
+				keep: self.args.arg_db_prune_keep,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_stats {
+			Cmd::Blockchain(BlockchainCmd::Stats(DbStats {
+				spec: spec,
+				dirs: dirs,
+				pruning: pruning,
+				compaction: compaction,
+				db_backend: db_backend,
+				state: match self.args.arg_db_stats_state {
+					Some(ref block) => Some(to_block_id(block)?),
+					None => None,
+				},
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_check {
+			Cmd::Blockchain(BlockchainCmd::Check(CheckBlockchain {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				db_backend: db_backend,
+				from: to_block_id(&self.args.arg_db_check_from)?,
+				repair: self.args.flag_db_check_repair,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_reset {
+			Cmd::Blockchain(BlockchainCmd::Reset(ResetBlockchain {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				db_backend: db_backend,
+				tracing: tracing,
+				fat_db: fat_db,
+				block: match self.args.arg_db_reset_block {
+					Some(ref block) => to_block_id(block)?,
+					None => return Err("Missing block to reset to.".into()),
+				},
+			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
 				let new_acc = NewAccount {
@@ -230,7 +287,9 @@ impl Configuration {
 				pruning_memory: self.args.arg_pruning_memory,
 				compaction: compaction,
 				wal: wal,
+				db_backend: db_backend,
 				tracing: tracing,
+				tracing_level: self.args.arg_tracing_level.parse()?,
 				fat_db: fat_db,
 				vm_type: vm_type,
 				check_seal: !self.args.flag_no_seal_check,
@@ -252,6 +311,7 @@ impl Configuration {
 					pruning_memory: self.args.arg_pruning_memory,
 					compaction: compaction,
 					wal: wal,
+					db_backend: db_backend,
 					tracing: tracing,
 					fat_db: fat_db,
 					from_block: to_block_id(&self.args.arg_export_blocks_from)?,
@@ -271,6 +331,7 @@ impl Configuration {
 					pruning_memory: self.args.arg_pruning_memory,
 					compaction: compaction,
 					wal: wal,
+					db_backend: db_backend,
 					tracing: tracing,
 					fat_db: fat_db,
 					at: to_block_id(&self.args.arg_export_state_at)?,
@@ -296,6 +357,7 @@ impl Configuration {
 				compaction: compaction,
 				file_path: self.args.arg_snapshot_file.clone(),
 				wal: wal,
+				db_backend: db_backend,
 				kind: snapshot::Kind::Take,
 				block_at: to_block_id(&self.args.arg_snapshot_at)?,
 			};
@@ -313,6 +375,7 @@ impl Configuration {
 				compaction: compaction,
 				file_path: self.args.arg_restore_file.clone(),
 				wal: wal,
+				db_backend: db_backend,
 				kind: snapshot::Kind::Restore,
 				block_at: to_block_id("latest")?, // unimportant.
 			};
@@ -334,6 +397,7 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.arg_pruning_memory,
+				history_bodies: self.args.arg_history_bodies,
 				daemon: daemon,
 				logger_config: logger_config.clone(),
 				miner_options: self.miner_options()?,
@@ -350,9 +414,11 @@ impl Configuration {
 				update_policy: update_policy,
 				mode: mode,
 				tracing: tracing,
+				tracing_level: self.args.arg_tracing_level.parse()?,
 				fat_db: fat_db,
 				compaction: compaction,
 				wal: wal,
+				db_backend: db_backend,
 				vm_type: vm_type,
 				warp_sync: warp_sync,
 				public_node: public_node,
@@ -360,6 +426,7 @@ impl Configuration {
 				net_settings: self.network_settings()?,
 				dapps_conf: dapps_conf,
 				ipfs_conf: ipfs_conf,
+				metrics_conf: metrics_conf,
 				ui_conf: ui_conf,
 				secretstore_conf: secretstore_conf,
 				dapp: self.dapp_to_open()?,
@@ -529,6 +596,7 @@ impl Configuration {
 			} else { None },
 			tx_queue_gas_limit: to_gas_limit(&self.args.arg_tx_queue_gas)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.arg_tx_queue_strategy)?,
+			tx_queue_min_gas_price_bump: self.args.arg_tx_queue_min_bump,
 			pending_set: to_pending_set(&self.args.arg_relay_set)?,
 			reseal_min_period: Duration::from_millis(self.args.arg_reseal_min_period),
 			reseal_max_period: Duration::from_millis(self.args.arg_reseal_max_period),
@@ -626,6 +694,14 @@ impl Configuration {
 		}
 	}
 
+	fn metrics_config(&self) -> MetricsConfiguration {
+		MetricsConfiguration {
+			enabled: self.args.flag_metrics,
+			port: self.args.arg_ports_shift + self.args.arg_metrics_port,
+			interface: self.metrics_interface(),
+		}
+	}
+
 	fn dapp_to_open(&self) -> Result<Option<String>, String> {
 		if !self.args.cmd_dapp {
 			return Ok(None);
@@ -989,6 +1065,10 @@ impl Configuration {
 		self.interface(&self.args.arg_ipfs_api_interface)
 	}
 
+	fn metrics_interface(&self) -> String {
+		self.interface(&self.args.arg_metrics_interface)
+	}
+
 	fn secretstore_interface(&self) -> String {
 		self.interface(&self.args.arg_secretstore_interface)
 	}
@@ -1104,17 +1184,19 @@ mod tests {
 	use std::str::FromStr;
 
 	use devtools::{RandomTempPath};
-	use ethcore::client::{VMType, BlockId};
+	use ethcore::client::{VMType, BlockId, DatabaseBackend};
 	use ethcore::miner::{MinerOptions, PrioritizationStrategy};
+	use ethcore::trace::TracingLevel;
 	use parity_rpc::NetworkSettings;
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 
 	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
-	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
+	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, PruneBlockchain, DbStats, CheckBlockchain, ResetBlockchain, DataFormat, ExportState};
+	use journaldb::Algorithm;
 	use cli::Args;
 	use dir::{Directories, default_hypervisor_path};
 	use helpers::{default_network_config};
-	use params::SpecType;
+	use params::{SpecType, Switch};
 	use presale::ImportWallet;
 	use rpc::{WsConfiguration, UiConfiguration};
 	use run::RunCmd;
@@ -1206,7 +1288,9 @@ mod tests {
 			pruning_memory: 32,
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
+			tracing_level: Default::default(),
 			fat_db: Default::default(),
 			vm_type: VMType::Interpreter,
 			check_seal: true,
@@ -1216,6 +1300,19 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_blockchain_import_tracing_level() {
+		let args = vec!["parity", "import", "blockchain.json", "--tracing", "on", "--tracing-level", "full"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Blockchain(BlockchainCmd::Import(cmd)) => {
+				assert_eq!(cmd.tracing, Switch::On);
+				assert_eq!(cmd.tracing_level, TracingLevel::Full);
+			},
+			cmd => panic!("unexpected command: {:?}", cmd),
+		}
+	}
+
 	#[test]
 	fn test_command_blockchain_export() {
 		let args = vec!["parity", "export", "blocks", "blockchain.json"];
@@ -1231,6 +1328,7 @@ mod tests {
 			format: Default::default(),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
 			fat_db: Default::default(),
 			from_block: BlockId::Number(1),
@@ -1254,6 +1352,7 @@ mod tests {
 			format: Default::default(),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
 			fat_db: Default::default(),
 			at: BlockId::Latest,
@@ -1279,6 +1378,7 @@ mod tests {
 			format: Some(DataFormat::Hex),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			tracing: Default::default(),
 			fat_db: Default::default(),
 			from_block: BlockId::Number(1),
@@ -1287,6 +1387,74 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_db_prune() {
+		let args = vec!["parity", "db", "prune", "--target", "fast", "--keep", "128"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Prune(PruneBlockchain {
+			spec: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			compaction: Default::default(),
+			db_backend: Default::default(),
+			target: Algorithm::OverlayRecent,
+			keep: 128,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_stats() {
+		let args = vec!["parity", "db", "stats", "--state", "latest"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Stats(DbStats {
+			spec: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			compaction: Default::default(),
+			db_backend: Default::default(),
+			state: Some(BlockId::Latest),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_check() {
+		let args = vec!["parity", "db", "check", "--from", "100", "--repair"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Check(CheckBlockchain {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			wal: true,
+			db_backend: Default::default(),
+			from: BlockId::Number(100),
+			repair: true,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_reset() {
+		let args = vec!["parity", "db", "reset", "1000"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Reset(ResetBlockchain {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			wal: true,
+			db_backend: Default::default(),
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			block: BlockId::Number(1000),
+		})));
+	}
+
 	#[test]
 	fn test_command_signer_new_token() {
 		let args = vec!["parity", "signer", "new-token"];
@@ -1326,6 +1494,7 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			history_bodies: None,
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
@@ -1348,13 +1517,16 @@ mod tests {
 			update_policy: UpdatePolicy { enable_downloading: true, require_consensus: true, filter: UpdateFilter::Critical, track: ReleaseTrack::Unknown, path: default_hypervisor_path() },
 			mode: Default::default(),
 			tracing: Default::default(),
+			tracing_level: Default::default(),
 			compaction: Default::default(),
 			wal: true,
+			db_backend: Default::default(),
 			vm_type: Default::default(),
 			geth_compatibility: false,
 			net_settings: Default::default(),
 			dapps_conf: Default::default(),
 			ipfs_conf: Default::default(),
+			metrics_conf: Default::default(),
 			ui_conf: Default::default(),
 			secretstore_conf: Default::default(),
 			ui: false,
@@ -1377,6 +1549,18 @@ mod tests {
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Run(expected));
 	}
 
+	#[test]
+	fn should_parse_db_backend() {
+		let conf0 = parse(&["parity", "--db-backend", "paritydb"]);
+		let conf1 = parse(&["parity", "--db-backend", "leveldb"]);
+
+		match conf0.into_command().unwrap().cmd {
+			Cmd::Run(c) => assert_eq!(c.db_backend, DatabaseBackend::ParityDB),
+			_ => panic!("Should be Cmd::Run"),
+		}
+		assert!(conf1.into_command().is_err());
+	}
+
 	#[test]
 	fn should_parse_mining_options() {
 		// given
@@ -1387,6 +1571,7 @@ mod tests {
 		let conf1 = parse(&["parity", "--tx-queue-strategy", "gas_factor"]);
 		let conf2 = parse(&["parity", "--tx-queue-strategy", "gas_price"]);
 		let conf3 = parse(&["parity", "--tx-queue-strategy", "gas"]);
+		let conf4 = parse(&["parity", "--tx-queue-min-bump", "25"]);
 
 		// then
 		assert_eq!(conf0.miner_options().unwrap(), mining_options);
@@ -1396,6 +1581,9 @@ mod tests {
 		assert_eq!(conf2.miner_options().unwrap(), mining_options);
 		mining_options.tx_queue_strategy = PrioritizationStrategy::GasAndGasPrice;
 		assert_eq!(conf3.miner_options().unwrap(), mining_options);
+		mining_options.tx_queue_strategy = PrioritizationStrategy::GasPriceOnly;
+		mining_options.tx_queue_min_gas_price_bump = Some(25);
+		assert_eq!(conf4.miner_options().unwrap(), mining_options);
 	}
 
 	#[test]
@@ -1750,6 +1938,7 @@ mod tests {
 		assert_eq!(conf0.secretstore_config().unwrap().port, 8084);
 		assert_eq!(conf0.secretstore_config().unwrap().http_port, 8083);
 		assert_eq!(conf0.ipfs_config().port, 5002);
+		assert_eq!(conf0.metrics_config().port, 3001);
 		assert_eq!(conf0.stratum_options().unwrap().unwrap().port, 8009);
 
 
@@ -1762,6 +1951,7 @@ mod tests {
 		assert_eq!(conf1.secretstore_config().unwrap().port, 8084);
 		assert_eq!(conf1.secretstore_config().unwrap().http_port, 8083);
 		assert_eq!(conf1.ipfs_config().port, 5002);
+		assert_eq!(conf1.metrics_config().port, 3001);
 	}
 
 	#[test]
@@ -1784,6 +1974,7 @@ mod tests {
 		assert_eq!(&conf0.secretstore_config().unwrap().http_interface, "0.0.0.0");
 		assert_eq!(&conf0.ipfs_config().interface, "0.0.0.0");
 		assert_eq!(conf0.ipfs_config().hosts, None);
+		assert_eq!(&conf0.metrics_config().interface, "0.0.0.0");
 	}
 
 	#[test]
@@ -23,7 +23,7 @@ use bigint::hash::clean_0x;
 use util::Address;
 use kvdb_rocksdb::CompactionProfile;
 use journaldb::Algorithm;
-use ethcore::client::{Mode, BlockId, VMType, DatabaseCompactionProfile, ClientConfig, VerifierType};
+use ethcore::client::{Mode, BlockId, VMType, DatabaseCompactionProfile, ClientConfig, VerifierType, DatabaseBackend};
 use ethcore::miner::{PendingSet, GasLimit, PrioritizationStrategy};
 use cache::CacheConfig;
 use dir::DatabaseDirectories;
@@ -225,6 +225,7 @@ pub fn to_client_config(
 		fat_db: bool,
 		compaction: DatabaseCompactionProfile,
 		wal: bool,
+		db_backend: DatabaseBackend,
 		vm_type: VMType,
 		name: String,
 		pruning: Algorithm,
@@ -263,6 +264,7 @@ pub fn to_client_config(
 	client_config.history = pruning_history;
 	client_config.db_compaction = compaction;
 	client_config.db_wal = wal;
+	client_config.db_backend = db_backend;
 	client_config.vm_type = vm_type;
 	client_config.name = name;
 	client_config.verifier_type = if check_seal { VerifierType::Canon } else { VerifierType::CanonNoSeal };
@@ -274,6 +276,7 @@ pub fn execute_upgrades(
 	base_path: &str,
 	dirs: &DatabaseDirectories,
 	pruning: Algorithm,
+	db_backend: DatabaseBackend,
 	compaction_profile: CompactionProfile
 ) -> Result<(), String> {
 
@@ -290,7 +293,7 @@ pub fn execute_upgrades(
 	}
 
 	let client_path = dirs.db_path(pruning);
-	migrate(&client_path, pruning, compaction_profile).map_err(|e| format!("{}", e))
+	migrate(&client_path, pruning, db_backend, compaction_profile).map_err(|e| format!("{}", e))
 }
 
 /// Prompts user asking for password.
@@ -109,6 +109,7 @@ mod dir;
 mod helpers;
 mod informant;
 mod light_helpers;
+mod metrics;
 mod migration;
 mod modules;
 mod params;
@@ -0,0 +1,323 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics endpoint.
+
+use std::fmt::{self, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::thread;
+
+use ethcore::client::{BlockChainClient, ChainNotify, Client};
+use ethcore::miner::{Miner, MinerService};
+use ethcore::snapshot::{RestorationStatus, SnapshotService as SS};
+use ethcore::snapshot::service::Service as SnapshotService;
+use ethsync::SyncProvider;
+use futures::{self, future, Future};
+use bigint::hash::H256;
+use bytes::Bytes;
+use kvdb::KeyValueDB;
+use parity_rpc::hyper::{self, server, Method, StatusCode};
+use parity_rpc::hyper::header::ContentType;
+
+/// Metrics server configuration.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Configuration {
+	pub enabled: bool,
+	pub port: u16,
+	pub interface: String,
+}
+
+impl Default for Configuration {
+	fn default() -> Self {
+		Configuration {
+			enabled: false,
+			port: 3000,
+			interface: "127.0.0.1".into(),
+		}
+	}
+}
+
+/// Block import statistics, collected from chain notifications.
+#[derive(Default)]
+pub struct ImportStats {
+	blocks: AtomicUsize,
+	batches: AtomicUsize,
+	duration_ns: AtomicUsize,
+	last_duration_ns: AtomicUsize,
+}
+
+impl ChainNotify for ImportStats {
+	fn new_blocks(&self, imported: Vec<H256>, _invalid: Vec<H256>, _enacted: Vec<H256>, _retracted: Vec<H256>, _sealed: Vec<H256>, _proposed: Vec<Bytes>, duration: u64) {
+		if imported.is_empty() {
+			return;
+		}
+
+		self.blocks.fetch_add(imported.len(), AtomicOrdering::Relaxed);
+		self.batches.fetch_add(1, AtomicOrdering::Relaxed);
+		self.duration_ns.fetch_add(duration as usize, AtomicOrdering::Relaxed);
+		self.last_duration_ns.store(duration as usize, AtomicOrdering::Relaxed);
+	}
+}
+
+/// Sources of the exposed metrics.
+pub struct Dependencies {
+	pub client: Arc<Client>,
+	pub sync: Arc<SyncProvider>,
+	pub miner: Arc<Miner>,
+	pub snapshot: Arc<SnapshotService>,
+	pub db: Arc<KeyValueDB>,
+	pub import_stats: Arc<ImportStats>,
+}
+
+/// Writer for the Prometheus text exposition format.
+struct Encoder {
+	out: String,
+}
+
+impl Encoder {
+	fn new() -> Self {
+		Encoder { out: String::new() }
+	}
+
+	fn describe(&mut self, name: &str, kind: &str, help: &str) -> fmt::Result {
+		writeln!(self.out, "# HELP {} {}", name, help)?;
+		writeln!(self.out, "# TYPE {} {}", name, kind)
+	}
+
+	fn sample<T: fmt::Display>(&mut self, name: &str, labels: &[(&str, &str)], value: T) -> fmt::Result {
+		self.out.push_str(name);
+		if !labels.is_empty() {
+			let labels = labels.iter().map(|&(k, v)| format!("{}=\"{}\"", k, v)).collect::<Vec<_>>().join(",");
+			write!(self.out, "{{{}}}", labels)?;
+		}
+		writeln!(self.out, " {}", value)
+	}
+
+	fn metric<T: fmt::Display>(&mut self, name: &str, kind: &str, help: &str, value: T) -> fmt::Result {
+		self.describe(name, kind, help)?;
+		self.sample(name, &[], value)
+	}
+
+	fn labelled<T: fmt::Display>(&mut self, name: &str, kind: &str, help: &str, label: &str, values: &[(&str, T)]) -> fmt::Result {
+		self.describe(name, kind, help)?;
+		for &(key, ref value) in values {
+			self.sample(name, &[(label, key)], value)?;
+		}
+		Ok(())
+	}
+}
+
+fn encode(deps: &Dependencies, e: &mut Encoder) -> fmt::Result {
+	let report = deps.client.report();
+	let chain_info = deps.client.chain_info();
+	let queue_info = deps.client.queue_info();
+	let blockchain_cache = deps.client.blockchain_cache_info();
+	let sync_status = deps.sync.status();
+	let peers = deps.sync.peers();
+	let miner_status = deps.miner.status();
+	let io_stats = deps.db.io_stats();
+	let nanos = 1_000_000_000f64;
+
+	// chain and import
+	e.metric("parity_chain_best_block", "gauge", "Best block number", chain_info.best_block_number)?;
+	e.metric("parity_blocks_imported_total", "counter", "Blocks imported since startup", report.blocks_imported)?;
+	e.metric("parity_transactions_applied_total", "counter", "Transactions applied since startup", report.transactions_applied)?;
+	e.metric("parity_gas_processed_total", "counter", "Gas processed since startup", report.gas_processed)?;
+	e.describe("parity_block_import_duration_seconds", "summary", "Time spent importing blocks")?;
+	e.sample("parity_block_import_duration_seconds_sum", &[], deps.import_stats.duration_ns.load(AtomicOrdering::Relaxed) as f64 / nanos)?;
+	e.sample("parity_block_import_duration_seconds_count", &[], deps.import_stats.blocks.load(AtomicOrdering::Relaxed))?;
+	e.metric("parity_block_import_batches_total", "counter", "Batches of blocks imported", deps.import_stats.batches.load(AtomicOrdering::Relaxed))?;
+	e.metric("parity_block_import_last_duration_seconds", "gauge", "Duration of the most recent block import batch", deps.import_stats.last_duration_ns.load(AtomicOrdering::Relaxed) as f64 / nanos)?;
+
+	// verification queue
+	e.labelled("parity_verification_queue_size", "gauge", "Blocks in the verification queue", "stage", &[
+		("unverified", queue_info.unverified_queue_size),
+		("verifying", queue_info.verifying_queue_size),
+		("verified", queue_info.verified_queue_size),
+	])?;
+	e.metric("parity_verification_queue_max_size", "gauge", "Maximum number of blocks in the verification queue", queue_info.max_queue_size)?;
+
+	// transaction queue
+	e.labelled("parity_transaction_queue_size", "gauge", "Transactions in the queue", "state", &[
+		("pending", miner_status.transactions_in_pending_queue),
+		("future", miner_status.transactions_in_future_queue),
+	])?;
+	e.metric("parity_pending_block_transactions", "gauge", "Transactions in the currently mined block", miner_status.transactions_in_pending_block)?;
+
+	// peers
+	let eth_peers = peers.iter().filter(|p| p.eth_info.is_some()).count();
+	let pip_peers = peers.iter().filter(|p| p.pip_info.is_some()).count();
+	e.metric("parity_sync_peers", "gauge", "Connected peers", sync_status.num_peers)?;
+	e.labelled("parity_sync_protocol_peers", "gauge", "Connected peers by protocol", "protocol", &[
+		("eth", eth_peers),
+		("pip", pip_peers),
+	])?;
+
+	// snapshot
+	let (restoring, state_chunks, state_chunks_done, block_chunks, block_chunks_done) = match deps.snapshot.status() {
+		RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } =>
+			(1, state_chunks, state_chunks_done, block_chunks, block_chunks_done),
+		_ => (0, 0, 0, 0, 0),
+	};
+	e.metric("parity_snapshot_restoring", "gauge", "Whether a snapshot restoration is ongoing", restoring)?;
+	e.labelled("parity_snapshot_chunks", "gauge", "Total chunks of the snapshot being restored", "kind", &[
+		("state", state_chunks),
+		("block", block_chunks),
+	])?;
+	e.labelled("parity_snapshot_chunks_done", "gauge", "Restored chunks of the snapshot being restored", "kind", &[
+		("state", state_chunks_done),
+		("block", block_chunks_done),
+	])?;
+
+	// caches
+	e.labelled("parity_cache_size_bytes", "gauge", "Memory used by caches", "cache", &[
+		("state_db", report.state_db_mem),
+		("queue", queue_info.mem_used),
+		("blocks", blockchain_cache.blocks),
+		("block_details", blockchain_cache.block_details),
+		("transaction_addresses", blockchain_cache.transaction_addresses),
+		("blocks_blooms", blockchain_cache.blocks_blooms),
+		("block_receipts", blockchain_cache.block_receipts),
+		("sync", sync_status.mem_used),
+	])?;
+
+	// key-value store I/O, as counted by the `KeyValueDB` layer rather than by RocksDB itself
+	e.metric("parity_kvdb_transactions_total", "counter", "Transactions committed through the key-value store layer", io_stats.transactions)?;
+	e.metric("parity_kvdb_writes_total", "counter", "Keys written or deleted through the key-value store layer", io_stats.writes)?;
+	e.metric("parity_kvdb_written_bytes_total", "counter", "Key and value bytes written through the key-value store layer", io_stats.bytes_written)?;
+	e.labelled("parity_kvdb_reads_total", "counter", "Reads through the key-value store layer", "source", &[
+		("backing_store", io_stats.reads),
+		("write_buffer", io_stats.cache_reads),
+	])?;
+	e.metric("parity_kvdb_read_bytes_total", "counter", "Value bytes read from the backing store", io_stats.bytes_read)
+}
+
+/// Render all metrics in the Prometheus text format.
+pub fn render(deps: &Dependencies) -> String {
+	let mut encoder = Encoder::new();
+	encode(deps, &mut encoder).expect("writing to a String never fails; qed");
+	encoder.out
+}
+
+struct Handler {
+	deps: Arc<Dependencies>,
+}
+
+impl server::Service for Handler {
+	type Request = hyper::Request;
+	type Response = hyper::Response;
+	type Error = hyper::Error;
+	type Future = future::FutureResult<hyper::Response, hyper::Error>;
+
+	fn call(&self, req: Self::Request) -> Self::Future {
+		let res = match (req.method(), req.path()) {
+			(&Method::Get, "/metrics") => {
+				hyper::Response::new()
+					.with_status(StatusCode::Ok)
+					.with_header(ContentType("text/plain; version=0.0.4".parse().expect("valid mime type; qed")))
+					.with_body(render(&self.deps))
+			},
+			_ => {
+				hyper::Response::new()
+					.with_status(StatusCode::NotFound)
+					.with_header(ContentType::plaintext())
+					.with_body("Not Found")
+			},
+		};
+
+		future::ok(res)
+	}
+}
+
+/// Running metrics server. The server is stopped when dropped.
+pub struct Listening {
+	close: Option<futures::sync::oneshot::Sender<()>>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for Listening {
+	fn drop(&mut self) {
+		if let Some(close) = self.close.take() {
+			let _ = close.send(());
+		}
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+pub fn start_server(conf: Configuration, deps: Dependencies) -> Result<Option<Listening>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let ip: IpAddr = conf.interface.parse().map_err(|_| format!("Invalid metrics interface: {}", conf.interface))?;
+	let addr = SocketAddr::new(ip, conf.port);
+	let deps = Arc::new(deps);
+
+	let (close, shutdown_signal) = futures::sync::oneshot::channel::<()>();
+	let (tx, rx) = mpsc::sync_channel(1);
+	let thread = thread::spawn(move || {
+		let send = |res| tx.send(res).expect("rx end is never dropped; qed");
+		let server = match server::Http::new().bind(&addr, move || Ok(Handler { deps: deps.clone() })) {
+			Ok(server) => {
+				send(Ok(()));
+				server
+			},
+			Err(err) => {
+				send(Err(err));
+				return;
+			}
+		};
+
+		let _ = server.run_until(shutdown_signal.map_err(|_| {}));
+	});
+
+	// Wait for server to start successfuly.
+	rx.recv().expect("tx end is never dropped; qed")
+		.map_err(|e| format!("Metrics server error at {}: {:?}. Make sure the --metrics-port and --metrics-interface options are correct.", addr, e))?;
+
+	info!("Metrics server listening on http://{}/metrics", addr);
+
+	Ok(Some(Listening {
+		close: close.into(),
+		thread: thread.into(),
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Encoder;
+
+	#[test]
+	fn should_encode_metrics_in_text_format() {
+		let mut e = Encoder::new();
+		e.metric("parity_chain_best_block", "gauge", "Best block number", 42).unwrap();
+		e.labelled("parity_sync_protocol_peers", "gauge", "Connected peers by protocol", "protocol", &[("eth", 3), ("pip", 1)]).unwrap();
+
+		assert_eq!(e.out, "\
+# HELP parity_chain_best_block Best block number
+# TYPE parity_chain_best_block gauge
+parity_chain_best_block 42
+# HELP parity_sync_protocol_peers Connected peers by protocol
+# TYPE parity_sync_protocol_peers gauge
+parity_sync_protocol_peers{protocol=\"eth\"} 3
+parity_sync_protocol_peers{protocol=\"pip\"} 1
+");
+	}
+}
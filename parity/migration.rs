@@ -24,6 +24,7 @@ use journaldb::Algorithm;
 use migr::{self, Manager as MigrationManager, Config as MigrationConfig, Migration};
 use kvdb;
 use kvdb_rocksdb::{CompactionProfile, Database, DatabaseConfig};
+use ethcore::client::DatabaseBackend;
 use ethcore::migrations;
 use ethcore::db;
 use ethcore::migrations::Extract;
@@ -31,7 +32,7 @@ use ethcore::migrations::Extract;
 /// Database is assumed to be at default version, when no version file is found.
 const DEFAULT_VERSION: u32 = 5;
 /// Current version of database models.
-const CURRENT_VERSION: u32 = 12;
+const CURRENT_VERSION: u32 = 14;
 /// First version of the consolidated database.
 const CONSOLIDATION_VERSION: u32 = 9;
 /// Defines how many items are migrated to the new version of database at once.
@@ -48,6 +49,8 @@ pub enum Error {
 	UnsupportedPruningMethod,
 	/// Existing DB is newer than the known one.
 	FutureDBVersion,
+	/// Existing DB is older than the known one and must be migrated first.
+	OutdatedDBVersion(u32),
 	/// Migration is not possible.
 	MigrationImpossible,
 	/// Migration unexpectadly failed.
@@ -65,6 +68,7 @@ impl Display for Error {
 			Error::UnknownDatabaseVersion => "Current database version cannot be read".into(),
 			Error::UnsupportedPruningMethod => "Unsupported pruning method for database migration. Delete DB and resync.".into(),
 			Error::FutureDBVersion => "Database was created with newer client version. Upgrade your client or delete DB and resync.".into(),
+			Error::OutdatedDBVersion(version) => format!("Database version {} is outdated. Run parity once to migrate it to version {}.", version, CURRENT_VERSION),
 			Error::MigrationImpossible => format!("Database migration to version {} is not possible.", CURRENT_VERSION),
 			Error::MigrationFailed => "Database migration unexpectedly failed".into(),
 			Error::Internal(ref err) => format!("{}", err),
@@ -149,6 +153,8 @@ fn consolidated_database_migrations(compaction_profile: &CompactionProfile) -> R
 	manager.add_migration(migrations::ToV10::new()).map_err(|_| Error::MigrationImpossible)?;
 	manager.add_migration(migrations::TO_V11).map_err(|_| Error::MigrationImpossible)?;
 	manager.add_migration(migrations::TO_V12).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(migrations::TO_V13).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(migrations::TO_V14).map_err(|_| Error::MigrationImpossible)?;
 	Ok(manager)
 }
 
@@ -196,9 +202,8 @@ fn migrate_database(version: u32, db_path: PathBuf, mut migrations: MigrationMan
 		return Ok(())
 	}
 
-	let backup_path = backup_database_path(&db_path);
 	// remove the backup dir if it exists
-	let _ = fs::remove_dir_all(&backup_path);
+	let _ = fs::remove_dir_all(&backup_database_path(&db_path));
 
 	// migrate old database to the new one
 	let temp_path = migrations.execute(&db_path, version)?;
@@ -207,6 +212,32 @@ fn migrate_database(version: u32, db_path: PathBuf, mut migrations: MigrationMan
 	// in that case, no need to shuffle directories.
 	if temp_path == db_path { return Ok(()) }
 
+	replace_database(&db_path, &temp_path)
+}
+
+/// Migrates a paritydb database at given position in place.
+///
+/// All columns of paritydb share a single log, so migrations which only add columns have nothing to do.
+/// Migrations altering existing data are only implemented for RocksDB.
+fn migrate_paritydb_database(version: u32, db_path: &Path, migrations: MigrationManager) -> Result<(), Error> {
+	if !migrations.is_needed(version) {
+		return Ok(())
+	}
+
+	if migrations.alters_existing(version) {
+		return Err(Error::MigrationImpossible);
+	}
+
+	let db_path_str = db_path.to_str().ok_or(Error::MigrationImpossible)?;
+	let db = DatabaseBackend::ParityDB.open(&DatabaseConfig::with_columns(db::NUM_COLUMNS), db_path_str).map_err(migr::Error::from)?;
+	db.flush().map_err(migr::Error::from)?;
+	Ok(())
+}
+
+/// Replaces the database at `db_path` with the one at `temp_path`, keeping a backup until it succeeds.
+fn replace_database(db_path: &Path, temp_path: &Path) -> Result<(), Error> {
+	let backup_path = backup_database_path(db_path);
+
 	// create backup
 	fs::rename(&db_path, &backup_path)?;
 
@@ -225,8 +256,60 @@ fn exists(path: &Path) -> bool {
 	fs::metadata(path).is_ok()
 }
 
-/// Migrates the database.
-pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionProfile) -> Result<(), Error> {
+/// Checks that the database at the given path is of the current version, without migrating it.
+pub fn check_version(path: &Path) -> Result<(), Error> {
+	match current_version(path)? {
+		CURRENT_VERSION => Ok(()),
+		version if version > CURRENT_VERSION => Err(Error::FutureDBVersion),
+		version => Err(Error::OutdatedDBVersion(version)),
+	}
+}
+
+/// Migrates the database to the current version and the given backend.
+pub fn migrate(path: &Path, pruning: Algorithm, backend: DatabaseBackend, compaction_profile: CompactionProfile) -> Result<(), Error> {
+	migrate_version(path, pruning, compaction_profile)?;
+	migrate_backend(path, backend, &compaction_profile)
+}
+
+/// Moves the consolidated database to the given backend if it was created with another one.
+fn migrate_backend(path: &Path, backend: DatabaseBackend, compaction_profile: &CompactionProfile) -> Result<(), Error> {
+	let db_path = consolidated_database_path(path);
+	let current = match DatabaseBackend::detect(&db_path) {
+		Some(current) if current != backend => current,
+		_ => return Ok(()),
+	};
+
+	println!("Migrating database from {} to {} backend", current, backend);
+	let config = default_migration_settings(compaction_profile);
+	let db_config = DatabaseConfig {
+		max_open_files: 64,
+		cache_sizes: Default::default(),
+		compaction: config.compaction_profile,
+		columns: db::NUM_COLUMNS,
+		wal: true,
+	};
+
+	let mut temp_path = path.to_owned();
+	temp_path.push("temp_backend_migration");
+	// Remove leftovers of an interrupted migration
+	let _ = fs::remove_dir_all(&temp_path);
+
+	{
+		let db_path_str = db_path.to_str().ok_or(Error::MigrationImpossible)?;
+		let temp_path_str = temp_path.to_str().ok_or(Error::MigrationImpossible)?;
+		let source = current.open(&db_config, db_path_str).map_err(migr::Error::from)?;
+		let dest = backend.open(&db_config, temp_path_str).map_err(migr::Error::from)?;
+		migr::copy_columns(&*source, &*dest, db::NUM_COLUMNS, &config)?;
+	}
+
+	let _ = fs::remove_dir_all(&backup_database_path(&db_path));
+	replace_database(&db_path, &temp_path)?;
+	println!("Migration finished");
+	Ok(())
+}
+
+/// Migrates the database to the current version.
+fn migrate_version(path: &Path, pruning: Algorithm, compaction_profile: CompactionProfile) -> Result<(), Error> {
 	// read version file.
 	let version = current_version(path)?;
 
@@ -270,7 +353,12 @@ pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionPr
 	// Further migrations
 	if version >= CONSOLIDATION_VERSION && version < CURRENT_VERSION && exists(&consolidated_database_path(path)) {
 		println!("Migrating database from version {} to {}", ::std::cmp::max(CONSOLIDATION_VERSION, version), CURRENT_VERSION);
-		migrate_database(version, consolidated_database_path(path), consolidated_database_migrations(&compaction_profile)?)?;
+		let db_path = consolidated_database_path(path);
+		let migrations = consolidated_database_migrations(&compaction_profile)?;
+		match DatabaseBackend::detect(&db_path) {
+			Some(DatabaseBackend::ParityDB) => migrate_paritydb_database(version, &db_path, migrations)?,
+			_ => migrate_database(version, db_path, migrations)?,
+		}
 		println!("Migration finished");
 	}
 
@@ -341,3 +429,44 @@ mod legacy {
 		Ok(manager)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::fs::{self, File};
+	use std::io::Write;
+	use devtools::RandomTempPath;
+	use journaldb::Algorithm;
+	use kvdb_rocksdb::{CompactionProfile, DatabaseConfig};
+	use ethcore::client::DatabaseBackend;
+	use ethcore::db;
+	use super::{migrate, check_version, current_version, consolidated_database_path, version_file_path, CURRENT_VERSION, Error};
+
+	#[test]
+	fn should_upgrade_paritydb_database_in_place() {
+		let temp = RandomTempPath::create_dir();
+		let path = temp.as_path();
+		let db_path = consolidated_database_path(path);
+		File::create(version_file_path(path)).unwrap().write_all(b"12").unwrap();
+		{
+			let db = DatabaseBackend::ParityDB.open(&DatabaseConfig::with_columns(Some(8)), db_path.to_str().unwrap()).unwrap();
+			let mut batch = db.transaction();
+			batch.put(db::COL_STATE, b"key", b"value");
+			batch.put(Some(7), b"key", b"light");
+			db.write(batch).unwrap();
+		}
+
+		match check_version(path) {
+			Err(Error::OutdatedDBVersion(12)) => {},
+			x => panic!("unexpected result: {:?}", x),
+		}
+		migrate(path, Algorithm::OverlayRecent, DatabaseBackend::ParityDB, CompactionProfile::default()).unwrap();
+		check_version(path).unwrap();
+
+		assert_eq!(current_version(path).unwrap(), CURRENT_VERSION);
+		assert_eq!(DatabaseBackend::detect(&db_path), Some(DatabaseBackend::ParityDB));
+		assert!(fs::metadata(db_path.join("CURRENT")).is_err());
+		let db = DatabaseBackend::ParityDB.open(&DatabaseConfig::with_columns(db::NUM_COLUMNS), db_path.to_str().unwrap()).unwrap();
+		assert_eq!(&*db.get(db::COL_STATE, b"key").unwrap().unwrap(), b"value");
+		assert_eq!(&*db.get(Some(7), b"key").unwrap().unwrap(), b"light");
+	}
+}
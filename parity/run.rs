@@ -21,13 +21,15 @@ use std::net::{TcpListener};
 use ctrlc::CtrlC;
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
-use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient};
+use ethcore::client::{Client, Mode, DatabaseCompactionProfile, DatabaseBackend, VMType, BlockChainClient};
+use ethcore::ethereum;
 use ethcore::ethstore::ethkey;
 use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
 use ethcore::miner::{StratumOptions, Stratum};
 use ethcore::service::ClientService;
 use ethcore::snapshot;
 use ethcore::spec::{SpecParams, OptimizeFor};
+use ethcore::trace::TracingLevel;
 use ethcore::verification::queue::VerifierSettings;
 use ethsync::{self, SyncConfig};
 use fdlimit::raise_fd_limit;
@@ -55,6 +57,7 @@ use cache::CacheConfig;
 use user_defaults::UserDefaults;
 use dapps;
 use ipfs;
+use metrics;
 use modules;
 use rpc;
 use rpc_apis;
@@ -68,6 +71,9 @@ const SNAPSHOT_PERIOD: u64 = 5000;
 // how many blocks to wait before starting a periodic snapshot.
 const SNAPSHOT_HISTORY: u64 = 100;
 
+// fewest block bodies kept with `--history-bodies` while periodic snapshots are taken.
+const MIN_HISTORY_BODIES: u64 = ethereum::ethash::SNAPSHOT_BLOCKS + SNAPSHOT_HISTORY;
+
 // Number of minutes before a given gas price corpus should expire.
 // Light client only.
 const GAS_CORPUS_EXPIRATION_MINUTES: i64 = 60 * 6;
@@ -83,6 +89,7 @@ pub struct RunCmd {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub history_bodies: Option<u64>,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
@@ -101,14 +108,17 @@ pub struct RunCmd {
 	pub update_policy: UpdatePolicy,
 	pub mode: Option<Mode>,
 	pub tracing: Switch,
+	pub tracing_level: TracingLevel,
 	pub fat_db: Switch,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub vm_type: VMType,
 	pub geth_compatibility: bool,
 	pub net_settings: NetworkSettings,
 	pub dapps_conf: dapps::Configuration,
 	pub ipfs_conf: ipfs::Configuration,
+	pub metrics_conf: metrics::Configuration,
 	pub ui_conf: rpc::UiConfiguration,
 	pub secretstore_conf: secretstore::Configuration,
 	pub dapp: Option<String>,
@@ -176,6 +186,10 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 	use ethsync::{LightSyncParams, LightSync, ManageNetwork};
 	use parking_lot::{Mutex, RwLock};
 
+	if cmd.metrics_conf.enabled {
+		warn!("Metrics are not supported in light client mode. Ignoring --metrics.");
+	}
+
 	// load spec
 	let spec = cmd.spec.spec(SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory))?;
 
@@ -196,8 +210,12 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 
 	let compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
 
+	if cmd.db_backend != DatabaseBackend::RocksDB {
+		warn!("Light client only supports the rocksdb database backend. Ignoring --db-backend={}.", cmd.db_backend);
+	}
+
 	// execute upgrades
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, compaction.clone())?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, DatabaseBackend::RocksDB, compaction.clone())?;
 
 	// create dirs used by parity
 	cmd.dirs.create_dirs(cmd.dapps_conf.enabled, cmd.ui_conf.enabled, cmd.secretstore_conf.enabled)?;
@@ -452,7 +470,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let snapshot_path = db_dirs.snapshot_path();
 
 	// execute upgrades
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.db_backend, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
 
 	// create dirs used by parity
 	cmd.dirs.create_dirs(cmd.dapps_conf.enabled, cmd.ui_conf.enabled, cmd.secretstore_conf.enabled)?;
@@ -512,7 +530,8 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		}
 	}
 	sync_config.warp_sync = spec.engine.supports_warp() && warp_sync;
-	sync_config.download_old_blocks = cmd.download_old_blocks;
+	// ancient blocks precede the history kept.
+	sync_config.download_old_blocks = cmd.download_old_blocks && cmd.history_bodies.is_none();
 	sync_config.serve_light = cmd.serve_light;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
@@ -560,6 +579,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		fat_db,
 		cmd.compaction,
 		cmd.wal,
+		cmd.db_backend,
 		cmd.vm_type,
 		cmd.name,
 		algorithm,
@@ -569,6 +589,15 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.history_bodies = match cmd.history_bodies {
+		// periodic snapshots need the bodies of the blocks they cover.
+		Some(n) if n < MIN_HISTORY_BODIES && !cmd.no_periodic_snapshot => {
+			warn!("Keeping the bodies of the last {} blocks for periodic snapshots. Use --no-periodic-snapshot to keep fewer.", MIN_HISTORY_BODIES);
+			Some(MIN_HISTORY_BODIES)
+		},
+		other => other,
+	};
+	client_config.tracing.level = cmd.tracing_level;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -793,6 +822,18 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	// the ipfs server
 	let ipfs_server = ipfs::start_server(cmd.ipfs_conf.clone(), client.clone())?;
 
+	// the metrics server
+	let import_stats = Arc::new(metrics::ImportStats::default());
+	service.add_notify(import_stats.clone());
+	let metrics_server = metrics::start_server(cmd.metrics_conf.clone(), metrics::Dependencies {
+		client: client.clone(),
+		sync: sync_provider.clone(),
+		miner: miner.clone(),
+		snapshot: snapshot_service.clone(),
+		db: service.db(),
+		import_stats: import_stats,
+	})?;
+
 	// the informant
 	let informant = Arc::new(Informant::new(
 		FullNodeInformantData {
@@ -857,7 +898,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	info!("Finishing work, please wait...");
 
 	// drop this stuff as soon as exit detected.
-	drop((ws_server, http_server, ipc_server, ui_server, secretstore_key_server, ipfs_server, event_loop));
+	drop((ws_server, http_server, ipc_server, ui_server, secretstore_key_server, ipfs_server, metrics_server, event_loop));
 
 	// to make sure timer does not spawn requests while shutdown is in progress
 	informant.shutdown();
@@ -25,7 +25,7 @@ use ethcore::snapshot::{Progress, RestorationStatus, SnapshotService as SS};
 use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter};
 use ethcore::snapshot::service::Service as SnapshotService;
 use ethcore::service::ClientService;
-use ethcore::client::{Mode, DatabaseCompactionProfile, VMType};
+use ethcore::client::{Mode, DatabaseCompactionProfile, DatabaseBackend, VMType};
 use ethcore::miner::Miner;
 use ethcore::ids::BlockId;
 
@@ -59,6 +59,7 @@ pub struct SnapshotCommand {
 	pub compaction: DatabaseCompactionProfile,
 	pub file_path: Option<String>,
 	pub wal: bool,
+	pub db_backend: DatabaseBackend,
 	pub kind: Kind,
 	pub block_at: BlockId,
 }
@@ -162,7 +163,7 @@ impl SnapshotCommand {
 		let snapshot_path = db_dirs.snapshot_path();
 
 		// execute upgrades
-		execute_upgrades(&self.dirs.base, &db_dirs, algorithm, self.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+		execute_upgrades(&self.dirs.base, &db_dirs, algorithm, self.db_backend, self.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
 
 		// prepare client config
 		let client_config = to_client_config(
@@ -173,6 +174,7 @@ impl SnapshotCommand {
 			fat_db,
 			self.compaction,
 			self.wal,
+			self.db_backend,
 			VMType::default(),
 			"".into(),
 			algorithm,
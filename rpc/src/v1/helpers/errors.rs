@@ -20,7 +20,10 @@ use std::fmt;
 use rlp::DecoderError;
 use ethcore::error::{Error as EthcoreError, CallError, TransactionError};
 use ethcore::account_provider::{SignError as AccountError};
+use ethcore::engines::EngineError;
 use jsonrpc_core::{futures, Error, ErrorCode, Value};
+use rustc_hex::ToHex;
+use v1::helpers::revert;
 
 mod codes {
 	// NOTE [ToDr] Codes from [-32099, -32000]
@@ -138,6 +141,19 @@ pub fn execution<T: fmt::Debug>(data: T) -> Error {
 	}
 }
 
+pub fn reverted(output: &[u8]) -> Error {
+	let message = match revert::reason(output) {
+		Some(reason) => format!("Transaction execution reverted: {}", reason),
+		None => "Transaction execution reverted.".into(),
+	};
+
+	Error {
+		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+		message: message,
+		data: Some(Value::String(format!("0x{}", output.to_hex()))),
+	}
+}
+
 pub fn state_pruned() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -146,6 +162,14 @@ pub fn state_pruned() -> Error {
 	}
 }
 
+pub fn history_pruned() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "This request is not supported because your node is running with history pruning. Run without --history-bodies.".into(),
+		data: None,
+	}
+}
+
 pub fn state_corrupt() -> Error {
 	internal("State corrupt", "")
 }
@@ -286,14 +310,22 @@ pub fn password(error: AccountError) -> Error {
 	}
 }
 
+pub fn engine(error: EngineError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: format!("{}", error),
+		data: None,
+	}
+}
+
 pub fn transaction_message(error: TransactionError) -> String {
 	use ethcore::error::TransactionError::*;
 
 	match error {
 		AlreadyImported => "Transaction with the same hash was already imported.".into(),
 		Old => "Transaction nonce is too low. Try incrementing the nonce.".into(),
-		TooCheapToReplace => {
-			"Transaction gas price is too low. There is another transaction with same nonce in the queue. Try increasing the gas price or incrementing the nonce.".into()
+		ReplacementUnderpriced { minimal, got } => {
+			format!("Transaction gas price is too low. There is another transaction with same nonce in the queue and replacing it requires a higher gas price (minimal: {}, got: {}). Try increasing the gas price or incrementing the nonce.", minimal, got)
 		},
 		LimitReached => {
 			"There are too many transactions in the queue. Your transaction was dropped due to limit. Try increasing the fee.".into()
@@ -350,6 +382,7 @@ pub fn call(error: CallError) -> Error {
 		CallError::StateCorrupt => state_corrupt(),
 		CallError::Exceptional => exceptional(),
 		CallError::Execution(e) => execution(e),
+		CallError::Reverted(output) => reverted(&output),
 		CallError::TransactionNotFound => internal("{}, this should not be the case with eth_call, most likely a bug.", CallError::TransactionNotFound),
 	}
 }
@@ -41,6 +41,7 @@ use ethsync::LightSync;
 use bigint::prelude::U256;
 use hash::H256;
 use util::Address;
+use bytes::Bytes;
 use parking_lot::Mutex;
 
 use v1::helpers::{CallRequest as CallRequestHelper, errors, dispatch};
@@ -187,6 +188,54 @@ impl LightFetch {
 		}
 	}
 
+	/// Helper for getting account info and the given storage values at a given block,
+	/// together with the proofs which verified them.
+	/// `None` indicates the account doesn't exist at the given block.
+	pub fn proof(&self, address: Address, keys: Vec<H256>, id: BlockId)
+		-> BoxFuture<(Vec<Bytes>, Option<BasicAccount>, Vec<(Vec<Bytes>, H256)>), Error>
+	{
+		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
+
+		Box::new(self.header(id).and_then(move |hdr| {
+			let req = request::AccountProof { header: hdr.clone().into(), address: address };
+			let account = sync.with_context(|ctx| on_demand.request(ctx, req)
+				.expect(NO_INVALID_BACK_REFS)
+				.map_err(errors::on_demand_cancel));
+
+			let account = match account {
+				Some(account) => account,
+				None => return Either::A(future::err(errors::network_disabled())),
+			};
+
+			Either::B(account.and_then(move |(account_proof, account)| {
+				// a missing account has no storage to prove.
+				let storage_root = match account {
+					Some(ref acc) => acc.storage_root,
+					None => {
+						let storage = keys.into_iter().map(|_| (Vec::new(), H256::zero())).collect();
+						return Either::A(future::ok((account_proof, None, storage)));
+					}
+				};
+
+				let reqs = keys.into_iter().map(|key| request::Storage {
+					header: hdr.clone().into(),
+					address: address,
+					storage_root: storage_root,
+					key: key,
+				}).collect::<Vec<_>>();
+
+				let storage = sync.with_context(|ctx| on_demand.request(ctx, reqs)
+					.expect(NO_INVALID_BACK_REFS)
+					.map_err(errors::on_demand_cancel));
+
+				match storage {
+					Some(storage) => Either::B(storage.map(move |storage| (account_proof, account, storage))),
+					None => Either::A(future::err(errors::network_disabled())),
+				}
+			}))
+		}))
+	}
+
 	/// Helper for getting proved execution.
 	pub fn proved_execution(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<ExecutionResult, Error> {
 		const DEFAULT_GAS_PRICE: u64 = 21_000;
@@ -25,6 +25,7 @@ pub mod fake_sign;
 pub mod light_fetch;
 pub mod oneshot;
 pub mod ipfs;
+pub mod revert;
 pub mod secretstore;
 
 mod network_settings;
@@ -0,0 +1,97 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Decoding of revert reasons.
+
+/// Selector of the `Error(string)` function, used by solidity to encode revert reasons.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Decodes the reason string from the output of a reverted call.
+/// Returns `None` if the output is not an ABI-encoded `Error(string)`.
+pub fn reason(output: &[u8]) -> Option<String> {
+	if output.len() < 4 + 64 || output[..4] != ERROR_SELECTOR {
+		return None;
+	}
+
+	let data = &output[4..];
+	let offset = match read_word(data, 0) {
+		Some(offset) => offset,
+		None => return None,
+	};
+	let len = match read_word(data, offset) {
+		Some(len) => len,
+		None => return None,
+	};
+	let start = offset + 32;
+	match start.checked_add(len) {
+		Some(end) if end <= data.len() => String::from_utf8(data[start..end].to_vec()).ok(),
+		_ => None,
+	}
+}
+
+// reads the 32-byte word at `pos` as a length or an offset.
+fn read_word(data: &[u8], pos: usize) -> Option<usize> {
+	match pos.checked_add(32) {
+		Some(end) if end <= data.len() => {
+			let word = &data[pos..end];
+			if word[..24].iter().any(|b| *b != 0) {
+				return None;
+			}
+			let value = word[24..].iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+			match value > (data.len() as u64) {
+				true => None,
+				false => Some(value as usize),
+			}
+		},
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_hex::FromHex;
+	use super::reason;
+
+	#[test]
+	fn should_decode_revert_reason() {
+		// Error("Not enough Ether provided.")
+		let output = "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			000000000000000000000000000000000000000000000000000000000000001a\
+			4e6f7420656e6f7567682045746865722070726f76696465642e000000000000".from_hex().unwrap();
+		assert_eq!(reason(&output), Some("Not enough Ether provided.".into()));
+	}
+
+	#[test]
+	fn should_ignore_malformed_output() {
+		assert_eq!(reason(&[]), None);
+		assert_eq!(reason(&[0x08, 0xc3, 0x79, 0xa0]), None);
+
+		// length past the end of the output.
+		let output = "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			00000000000000000000000000000000000000000000000000000000000000ff\
+			4e6f7420656e6f7567682045746865722070726f76696465642e000000000000".from_hex().unwrap();
+		assert_eq!(reason(&output), None);
+
+		// other selector.
+		let output = "deadbeef\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			000000000000000000000000000000000000000000000000000000000000001a\
+			4e6f7420656e6f7567682045746865722070726f76696465642e000000000000".from_hex().unwrap();
+		assert_eq!(reason(&output), None);
+	}
+}
@@ -0,0 +1,58 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+//! Clique signer voting rpc implementation.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use ethcore::miner::MinerService;
+
+use jsonrpc_core::Error;
+use v1::helpers::errors;
+use v1::traits::Clique;
+use v1::types::H160;
+
+/// Clique signer voting rpc implementation.
+pub struct CliqueClient<M> {
+	miner: Arc<M>,
+}
+
+impl<M> CliqueClient<M> {
+	/// Creates new `CliqueClient`.
+	pub fn new(miner: &Arc<M>) -> Self {
+		CliqueClient {
+			miner: miner.clone(),
+		}
+	}
+}
+
+impl<M: MinerService + 'static> Clique for CliqueClient<M> {
+	fn propose(&self, address: H160, authorize: bool) -> Result<bool, Error> {
+		self.miner.propose_signer(address.into(), authorize).map_err(errors::engine)?;
+		Ok(true)
+	}
+
+	fn discard(&self, address: H160) -> Result<bool, Error> {
+		self.miner.discard_signer(&address.into()).map_err(errors::engine)?;
+		Ok(true)
+	}
+
+	fn proposals(&self) -> Result<BTreeMap<H160, bool>, Error> {
+		self.miner.signer_proposals()
+			.map(|proposals| proposals.into_iter().map(|(address, authorize)| (address.into(), authorize)).collect())
+			.map_err(errors::engine)
+	}
+}
@@ -0,0 +1,59 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug api implementation.
+
+use std::sync::Arc;
+
+use ethcore::client::{MiningBlockChainClient, TransactionId};
+
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+use v1::traits::Debug;
+use v1::helpers::errors;
+use v1::types::{BlockNumber, H256, StructLoggerOptions, StructLogResult, BlockStructLogResult};
+
+/// Debug api implementation.
+pub struct DebugClient<C> {
+	client: Arc<C>,
+}
+
+impl<C> DebugClient<C> {
+	/// Creates new Debug client.
+	pub fn new(client: &Arc<C>) -> Self {
+		DebugClient {
+			client: client.clone(),
+		}
+	}
+}
+
+impl<C> Debug for DebugClient<C> where C: MiningBlockChainClient + 'static {
+	fn trace_transaction(&self, transaction_hash: H256, options: Trailing<StructLoggerOptions>) -> Result<StructLogResult, Error> {
+		let options = options.unwrap_or_default();
+
+		self.client.replay_struct_logs(TransactionId::Hash(transaction_hash.into()), options.into())
+			.map(StructLogResult::from)
+			.map_err(errors::call)
+	}
+
+	fn trace_block_by_number(&self, block_number: BlockNumber, options: Trailing<StructLoggerOptions>) -> Result<Vec<BlockStructLogResult>, Error> {
+		let options = options.unwrap_or_default();
+
+		self.client.replay_block_struct_logs(block_number.into(), options.into())
+			.map(|results| results.into_iter().map(|result| BlockStructLogResult { result: result.into() }).collect())
+			.map_err(errors::call)
+	}
+}
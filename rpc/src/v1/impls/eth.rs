@@ -21,6 +21,7 @@ use std::time::{Instant, Duration};
 use std::sync::Arc;
 
 use rlp::{self, UntrustedRlp};
+use hash::keccak;
 use time::get_time;
 use bigint::prelude::U256;
 use bigint::hash::{H64, H160, H256};
@@ -30,7 +31,8 @@ use parking_lot::Mutex;
 use ethash::SeedHashCompute;
 use ethcore::account_provider::{AccountProvider, DappId};
 use ethcore::block::IsBlock;
-use ethcore::client::{MiningBlockChainClient, BlockId, TransactionId, UncleId};
+use ethcore::client::{MiningBlockChainClient, ProvingBlockChainClient, BlockId, TransactionId, UncleId};
+use ethcore::error::HistoryError;
 use ethcore::ethereum::Ethash;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::header::{Header as BlockHeader, BlockNumber as EthBlockNumber};
@@ -39,6 +41,7 @@ use ethcore::miner::{MinerService, ExternalMinerService};
 use ethcore::transaction::SignedTransaction;
 use ethcore::snapshot::SnapshotService;
 use ethsync::{SyncProvider};
+use vm;
 
 use jsonrpc_core::{BoxFuture, Error};
 use jsonrpc_core::futures::future;
@@ -51,7 +54,7 @@ use v1::helpers::accounts::unwrap_provider;
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -145,7 +148,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 
 	fn block(&self, id: BlockId, include_txs: bool) -> Result<Option<RichBlock>, Error> {
 		let client = &self.client;
-		match (client.block(id.clone()), client.block_total_difficulty(id)) {
+		match (client.block(id.clone()), client.block_total_difficulty(id.clone())) {
 			(Some(block), Some(total_difficulty)) => {
 				let view = block.header_view();
 				Ok(Some(RichBlock {
@@ -177,14 +180,22 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 					extra_info: client.block_extra_info(id.clone()).expect(EXTRA_INFO_PROOF),
 				}))
 			},
-			_ => Ok(None)
+			_ => check_history(&**client, id).map(|_| None),
 		}
 	}
 
 	fn transaction(&self, id: TransactionId) -> Result<Option<Transaction>, Error> {
+		let block = match id {
+			TransactionId::Location(ref block, _) => Some(block.clone()),
+			_ => None,
+		};
+
 		match self.client.transaction(id) {
 			Some(t) => Ok(Some(Transaction::from_localized(t, self.eip86_transition))),
-			None => Ok(None),
+			None => match block {
+				Some(block) => check_history(&*self.client, block).map(|_| None),
+				None => Ok(None),
+			},
 		}
 	}
 
@@ -192,7 +203,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 		let client = &self.client;
 		let uncle: BlockHeader = match client.uncle(id) {
 			Some(hdr) => hdr.decode(),
-			None => { return Ok(None); }
+			None => { return check_history(&**client, id.block).map(|_| None); }
 		};
 		let parent_difficulty = match client.block_total_difficulty(BlockId::Hash(uncle.parent_hash().clone())) {
 			Some(difficulty) => difficulty,
@@ -270,10 +281,26 @@ fn check_known<C>(client: &C, number: BlockNumber) -> Result<(), Error> where C:
 	}
 }
 
+fn check_history<C>(client: &C, id: BlockId) -> Result<(), Error> where C: MiningBlockChainClient {
+	match client.block_body(id) {
+		Err(HistoryError::Pruned) => Err(errors::history_pruned()),
+		_ => Ok(()),
+	}
+}
+
+// first block with a body, receipts and transaction addresses, or `None` if nothing was pruned.
+// the genesis block has none of them, so nothing is missing if the chain is complete from block 1.
+fn earliest_history<C>(client: &C) -> Option<EthBlockNumber> where C: MiningBlockChainClient {
+	match client.pruning_info().earliest_chain {
+		0 | 1 => None,
+		earliest => Some(earliest),
+	}
+}
+
 const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;	// because uncles go back 6.
 
 impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
-	C: MiningBlockChainClient + 'static,
+	C: MiningBlockChainClient + ProvingBlockChainClient + 'static,
 	SN: SnapshotService + 'static,
 	S: SyncProvider + 'static,
 	M: MinerService + 'static,
@@ -381,6 +408,45 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		Box::new(future::done(res))
 	}
 
+	fn proof(&self, address: RpcH160, keys: Vec<RpcU256>, num: Trailing<BlockNumber>) -> BoxFuture<EthAccount, Error> {
+		let address: Address = RpcH160::into(address);
+		let address_hash = keccak(&address);
+
+		let id = num.unwrap_or_default();
+		try_bf!(check_known(&*self.client, id.clone()));
+		let id: BlockId = id.into();
+
+		let (account_proof, account) = match self.client.prove_account(address_hash, id) {
+			Some(proof) => proof,
+			None => return Box::new(future::err(errors::state_pruned())),
+		};
+
+		let storage_proof = keys.into_iter().map(|key| {
+			let key: U256 = RpcU256::into(key);
+			let key_hash = keccak(&H256::from(key));
+			self.client.prove_storage(address_hash, key_hash, id).map(|(proof, value)| StorageProof {
+				key: key.into(),
+				value: U256::from(&*value).into(),
+				proof: proof.into_iter().map(Bytes::new).collect(),
+			})
+		}).collect::<Option<Vec<_>>>();
+
+		let res = match storage_proof {
+			Some(storage_proof) => Ok(EthAccount {
+				address: address.into(),
+				account_proof: account_proof.into_iter().map(Bytes::new).collect(),
+				balance: account.balance.into(),
+				code_hash: account.code_hash.into(),
+				nonce: account.nonce.into(),
+				storage_hash: account.storage_root.into(),
+				storage_proof: storage_proof,
+			}),
+			None => Err(errors::state_pruned()),
+		};
+
+		Box::new(future::done(res))
+	}
+
 	fn transaction_count(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
 		let address: Address = RpcH160::into(address);
 
@@ -407,32 +473,40 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn block_transaction_count_by_hash(&self, hash: RpcH256) -> BoxFuture<Option<RpcU256>, Error> {
-		Box::new(future::ok(self.client.block(BlockId::Hash(hash.into()))
-			.map(|block| block.transactions_count().into())))
+		let id = BlockId::Hash(hash.into());
+		Box::new(future::done(match self.client.block(id.clone()) {
+			Some(block) => Ok(Some(block.transactions_count().into())),
+			None => check_history(&*self.client, id).map(|_| None),
+		}))
 	}
 
 	fn block_transaction_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<RpcU256>, Error> {
-		Box::new(future::ok(match num {
-			BlockNumber::Pending => Some(
+		Box::new(future::done(match num {
+			BlockNumber::Pending => Ok(Some(
 				self.miner.status().transactions_in_pending_block.into()
-			),
-			_ =>
-				self.client.block(num.into())
-					.map(|block| block.transactions_count().into())
+			)),
+			_ => match self.client.block(num.clone().into()) {
+				Some(block) => Ok(Some(block.transactions_count().into())),
+				None => check_history(&*self.client, num.into()).map(|_| None),
+			},
 		}))
 	}
 
 	fn block_uncles_count_by_hash(&self, hash: RpcH256) -> BoxFuture<Option<RpcU256>, Error> {
-		Box::new(future::ok(self.client.block(BlockId::Hash(hash.into()))
-			.map(|block| block.uncles_count().into())))
+		let id = BlockId::Hash(hash.into());
+		Box::new(future::done(match self.client.block(id.clone()) {
+			Some(block) => Ok(Some(block.uncles_count().into())),
+			None => check_history(&*self.client, id).map(|_| None),
+		}))
 	}
 
 	fn block_uncles_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<RpcU256>, Error> {
-		Box::new(future::ok(match num {
-			BlockNumber::Pending => Some(0.into()),
-			_ => self.client.block(num.into())
-					.map(|block| block.uncles_count().into()
-			),
+		Box::new(future::done(match num {
+			BlockNumber::Pending => Ok(Some(0.into())),
+			_ => match self.client.block(num.clone().into()) {
+				Some(block) => Ok(Some(block.uncles_count().into())),
+				None => check_history(&*self.client, num.into()).map(|_| None),
+			},
 		}))
 	}
 
@@ -466,6 +540,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 				.map(|t| Transaction::from_pending(t, block_number, self.eip86_transition))
 		});
 
+		// transaction addresses of pruned blocks are gone, so it may be in one of them.
+		if tx.is_none() && earliest_history(&*self.client).is_some() {
+			return Box::new(future::err(errors::history_pruned()));
+		}
+
 		Box::new(future::ok(tx))
 	}
 
@@ -489,6 +568,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 			(Some(receipt), true) => Box::new(future::ok(Some(receipt.into()))),
 			_ => {
 				let receipt = self.client.transaction_receipt(TransactionId::Hash(hash));
+				// transaction addresses of pruned blocks are gone, so it may be in one of them.
+				if receipt.is_none() && earliest_history(&*self.client).is_some() {
+					return Box::new(future::err(errors::history_pruned()));
+				}
 				Box::new(future::ok(receipt.map(Into::into)))
 			}
 		}
@@ -515,6 +598,12 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	fn logs(&self, filter: Filter) -> BoxFuture<Vec<Log>, Error> {
 		let include_pending = filter.to_block == Some(BlockNumber::Pending);
 		let filter: EthcoreFilter = filter.into();
+		// blooms are kept, but receipts of pruned blocks are not.
+		if let (Some(earliest), Some(from)) = (earliest_history(&*self.client), self.client.block_number(filter.from_block)) {
+			if from < earliest {
+				return Box::new(future::err(errors::history_pruned()));
+			}
+		}
 		let mut logs = self.client.logs(filter.clone())
 			.into_iter()
 			.map(From::from)
@@ -628,8 +717,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		let result = self.client.call(&signed, Default::default(), num.into());
 
 		Box::new(future::done(result
-			.map(|b| b.output.into())
 			.map_err(errors::call)
+			.and_then(|executed| match executed.exception {
+				Some(vm::Error::Reverted) => Err(errors::reverted(&executed.output)),
+				_ => Ok(executed.output.into()),
+			})
 		))
 	}
 
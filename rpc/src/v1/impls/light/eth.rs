@@ -29,15 +29,19 @@ use light::{cht, TransactionQueue};
 use light::on_demand::{request, OnDemand};
 
 use ethcore::account_provider::{AccountProvider, DappId};
+use ethcore::basic_account::BasicAccount;
 use ethcore::encoded;
 use ethcore::ids::BlockId;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::transaction::SignedTransaction;
 use ethsync::LightSync;
 use rlp::UntrustedRlp;
-use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY_LIST_RLP};
+use vm;
+use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY, KECCAK_EMPTY_LIST_RLP};
 use bigint::prelude::U256;
+use bigint::hash::H256;
 use parking_lot::{RwLock, Mutex};
+use util::Address;
 
 use v1::impls::eth_filter::Filterable;
 use v1::helpers::{errors, limit_logs};
@@ -46,7 +50,7 @@ use v1::helpers::light_fetch::{self, LightFetch};
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -267,6 +271,36 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 		Box::new(future::err(errors::unimplemented(None)))
 	}
 
+	fn proof(&self, address: RpcH160, keys: Vec<RpcU256>, num: Trailing<BlockNumber>) -> BoxFuture<EthAccount, Error> {
+		let address: Address = address.into();
+		let keys: Vec<U256> = keys.into_iter().map(Into::into).collect();
+		let storage_keys = keys.iter().map(|key| H256::from(*key)).collect();
+
+		Box::new(self.fetcher().proof(address, storage_keys, num.unwrap_or_default().into())
+			.map(move |(account_proof, account, storage)| {
+				let account = account.unwrap_or_else(|| BasicAccount {
+					nonce: 0.into(),
+					balance: 0.into(),
+					storage_root: KECCAK_NULL_RLP,
+					code_hash: KECCAK_EMPTY,
+				});
+
+				EthAccount {
+					address: address.into(),
+					account_proof: account_proof.into_iter().map(Bytes::new).collect(),
+					balance: account.balance.into(),
+					code_hash: account.code_hash.into(),
+					nonce: account.nonce.into(),
+					storage_hash: account.storage_root.into(),
+					storage_proof: keys.into_iter().zip(storage).map(|(key, (proof, value))| StorageProof {
+						key: key.into(),
+						value: U256::from(&*value).into(),
+						proof: proof.into_iter().map(Bytes::new).collect(),
+					}).collect(),
+				}
+			}))
+	}
+
 	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>, Error> {
 		Box::new(self.rich_block(BlockId::Hash(hash.into()), include_txs).map(Some))
 	}
@@ -375,6 +409,7 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 	fn call(&self, _meta: Self::Metadata, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
 		Box::new(self.fetcher().proved_execution(req, num).and_then(|res| {
 			match res {
+				Ok(ref exec) if exec.exception == Some(vm::Error::Reverted) => Err(errors::reverted(&exec.output)),
 				Ok(exec) => Ok(exec.output.into()),
 				Err(e) => Err(errors::execution(e)),
 			}
@@ -385,6 +420,7 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 		// TODO: binary chop for more accurate estimates.
 		Box::new(self.fetcher().proved_execution(req, num).and_then(|res| {
 			match res {
+				Ok(ref exec) if exec.exception == Some(vm::Error::Reverted) => Err(errors::reverted(&exec.output)),
 				Ok(exec) => Ok((exec.refunded + exec.gas_used).into()),
 				Err(e) => Err(errors::execution(e)),
 			}
@@ -26,10 +26,11 @@ use hash::keccak_buffer;
 
 use jsonrpc_core::{BoxFuture, Error};
 use jsonrpc_core::futures::Future;
+use jsonrpc_macros::Trailing;
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, StateGcOptions, StateGcStatus, BlockNumber};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
@@ -154,4 +155,20 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>, Error> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn start_state_gc(&self, _options: Trailing<StateGcOptions>) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn stop_state_gc(&self) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn state_gc_status(&self) -> Result<Option<StateGcStatus>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn reset_chain(&self, _number: BlockNumber) -> Result<H256, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -27,7 +27,7 @@ use ethkey::{Brain, Generator};
 use ethstore::random_phrase;
 use ethsync::{SyncProvider, ManageNetwork};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{MiningBlockChainClient};
+use ethcore::client::{MiningBlockChainClient, Executed};
 use ethcore::ids::BlockId;
 use ethcore::miner::MinerService;
 use ethcore::mode::Mode;
@@ -48,7 +48,7 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader
+	AccountInfo, HwAccountInfo, RichHeader, CallResult,
 };
 use Host;
 
@@ -110,6 +110,22 @@ impl<C, M, U> ParityClient<C, M, U> where
 	fn account_provider(&self) -> Result<Arc<AccountProvider>, Error> {
 		unwrap_provider(&self.accounts)
 	}
+
+	fn call_many(&self, meta: Metadata, requests: Vec<CallRequest>, block: Trailing<BlockNumber>) -> Result<Vec<Executed>, Error> where
+		M: MinerService,
+	{
+		let requests = requests
+			.into_iter()
+			.map(|request| Ok((
+				fake_sign::sign_call(&self.client, &self.miner, request.into(), meta.is_dapp())?,
+				Default::default()
+			)))
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		let block = block.unwrap_or_default();
+
+		self.client.call_many(&requests, block.into()).map_err(errors::call)
+	}
 }
 
 impl<C, M, U> Parity for ParityClient<C, M, U> where
@@ -415,19 +431,13 @@ impl<C, M, U> Parity for ParityClient<C, M, U> where
 	}
 
 	fn call(&self, meta: Self::Metadata, requests: Vec<CallRequest>, block: Trailing<BlockNumber>) -> Result<Vec<Bytes>, Error> {
-		let requests = requests
-			.into_iter()
-			.map(|request| Ok((
-				fake_sign::sign_call(&self.client, &self.miner, request.into(), meta.is_dapp())?,
-				Default::default()
-			)))
-			.collect::<Result<Vec<_>, Error>>()?;
-
-		let block = block.unwrap_or_default();
-
-		self.client.call_many(&requests, block.into())
+		self.call_many(meta, requests, block)
 				.map(|res| res.into_iter().map(|res| res.output.into()).collect())
-				.map_err(errors::call)
+	}
+
+	fn call_results(&self, meta: Self::Metadata, requests: Vec<CallRequest>, block: Trailing<BlockNumber>) -> Result<Vec<CallResult>, Error> {
+		self.call_many(meta, requests, block)
+				.map(|res| res.into_iter().map(Into::into).collect())
 	}
 
 	fn node_health(&self) -> BoxFuture<Health, Error> {
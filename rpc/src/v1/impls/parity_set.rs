@@ -28,10 +28,11 @@ use updater::{Service as UpdateService};
 
 use jsonrpc_core::{BoxFuture, Error};
 use jsonrpc_core::futures::Future;
+use jsonrpc_macros::Trailing;
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, StateGcOptions, StateGcStatus, BlockNumber};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<C, M, U, F = fetch::Client> {
@@ -198,4 +199,26 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 
 		Ok(self.miner.remove_pending_transaction(&*self.client, &hash).map(|t| Transaction::from_pending(t, block_number, self.eip86_transition)))
 	}
+
+	fn start_state_gc(&self, options: Trailing<StateGcOptions>) -> Result<bool, Error> {
+		self.client.start_state_gc(options.unwrap_or_default().into())
+			.map_err(errors::database)
+			.map(|_| true)
+	}
+
+	fn stop_state_gc(&self) -> Result<bool, Error> {
+		let running = self.client.state_gc_status().map_or(false, |s| s.is_running());
+		self.client.stop_state_gc();
+		Ok(running)
+	}
+
+	fn state_gc_status(&self) -> Result<Option<StateGcStatus>, Error> {
+		Ok(self.client.state_gc_status().map(Into::into))
+	}
+
+	fn reset_chain(&self, number: BlockNumber) -> Result<H256, Error> {
+		self.client.reset_to(number.into())
+			.map(Into::into)
+			.map_err(errors::database)
+	}
 }
@@ -18,8 +18,10 @@
 
 use std::sync::Arc;
 
-use ethcore::client::{MiningBlockChainClient, CallAnalytics, TransactionId, TraceId};
+use ethcore::client::{MiningBlockChainClient, CallAnalytics, Executed, BlockId, TransactionId, TraceId};
 use ethcore::miner::MinerService;
+use ethcore::state_diff::StateDiff as EthStateDiff;
+use ethcore::trace::{LocalizedTrace as EthLocalizedTrace, VMTrace as EthVMTrace};
 use ethcore::transaction::SignedTransaction;
 use rlp::UntrustedRlp;
 
@@ -28,16 +30,68 @@ use jsonrpc_macros::Trailing;
 use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::{errors, fake_sign};
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceOptions, H256};
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceOptions, H256, CallFrame};
 
-fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
+fn to_call_analytics(flags: &TraceOptions) -> CallAnalytics {
 	CallAnalytics {
-		transaction_tracing: flags.contains(&("trace".to_owned())),
+		transaction_tracing: flags.contains(&("trace".to_owned())) || flags.contains(&("callTracer".to_owned())),
 		vm_tracing: flags.contains(&("vmTrace".to_owned())),
 		state_diffing: flags.contains(&("stateDiff".to_owned())),
 	}
 }
 
+/// Converts the execution result, nesting the call traces if `callTracer` was requested.
+fn to_trace_results(executed: Executed, flags: &TraceOptions) -> TraceResults {
+	let call_trace = match flags.contains(&("callTracer".to_owned())) {
+		true => CallFrame::from_executed(&executed),
+		false => None,
+	};
+
+	let mut results = TraceResults::from(executed);
+	if !flags.contains(&("trace".to_owned())) {
+		results.trace.clear();
+	}
+	results.call_trace = call_trace;
+	results
+}
+
+/// Whether the results are read from the traces database rather than replayed, when stored.
+/// Only state diffs and VM traces are worth it. Call trees are always replayed.
+fn reads_stored_results(flags: &TraceOptions) -> bool {
+	!flags.contains(&("callTracer".to_owned())) &&
+		(flags.contains(&("stateDiff".to_owned())) || flags.contains(&("vmTrace".to_owned())))
+}
+
+/// Converts the stored data of a transaction, dropping the flat trace unless requested.
+fn to_stored_trace_results(
+	traces: Vec<EthLocalizedTrace>,
+	vm_trace: Option<EthVMTrace>,
+	state_diff: Option<EthStateDiff>,
+	flags: &TraceOptions,
+) -> TraceResults {
+	let mut results = TraceResults::from_stored(traces, vm_trace, state_diff);
+	if !flags.contains(&("trace".to_owned())) {
+		results.trace.clear();
+	}
+	results
+}
+
+/// Splits the traces of a block by transaction, leaving out the rewards.
+fn split_by_transaction(traces: Vec<EthLocalizedTrace>) -> Vec<Vec<EthLocalizedTrace>> {
+	let mut transactions: Vec<Vec<EthLocalizedTrace>> = Vec::new();
+	for trace in traces {
+		let position = match trace.transaction_number {
+			Some(position) => position,
+			None => continue,
+		};
+		if transactions.len() <= position {
+			transactions.resize(position + 1, Vec::new());
+		}
+		transactions[position].push(trace);
+	}
+	transactions
+}
+
 /// Traces api implementation.
 pub struct TracesClient<C, M> {
 	client: Arc<C>,
@@ -54,6 +108,57 @@ impl<C, M> TracesClient<C, M> {
 	}
 }
 
+impl<C, M> TracesClient<C, M> where C: MiningBlockChainClient {
+	// results of a transaction read from the traces database.
+	// `None` if any of the requested data isn't stored.
+	fn stored_transaction_results(&self, id: TransactionId, flags: &TraceOptions) -> Option<TraceResults> {
+		let vm_trace = match flags.contains(&("vmTrace".to_owned())) {
+			true => self.client.transaction_vm_trace(id.clone()).map(Some),
+			false => Some(None),
+		};
+		let state_diff = match flags.contains(&("stateDiff".to_owned())) {
+			true => self.client.transaction_state_diff(id.clone()).map(Some),
+			false => Some(None),
+		};
+
+		match (self.client.transaction_traces(id), vm_trace, state_diff) {
+			(Some(traces), Some(vm_trace), Some(state_diff)) => Some(to_stored_trace_results(traces, vm_trace, state_diff, flags)),
+			_ => None,
+		}
+	}
+
+	// results of all transactions in a block read from the traces database.
+	// `None` if any of the requested data isn't stored.
+	fn stored_block_results(&self, id: BlockId, flags: &TraceOptions) -> Option<Vec<TraceResults>> {
+		let transactions = match self.client.block_traces(id) {
+			Some(traces) => split_by_transaction(traces),
+			None => return None,
+		};
+		let vm_traces: Option<Vec<Option<EthVMTrace>>> = match flags.contains(&("vmTrace".to_owned())) {
+			true => self.client.block_vm_traces(id).map(|traces| traces.into_iter().map(Some).collect()),
+			false => Some(vec![None; transactions.len()]),
+		};
+		let state_diffs: Option<Vec<Option<EthStateDiff>>> = match flags.contains(&("stateDiff".to_owned())) {
+			true => self.client.block_state_diffs(id).map(|diffs| diffs.into_iter().map(Some).collect()),
+			false => Some(vec![None; transactions.len()]),
+		};
+
+		match (vm_traces, state_diffs) {
+			(Some(ref vm_traces), Some(ref state_diffs)) if vm_traces.len() != transactions.len() || state_diffs.len() != transactions.len() => None,
+			(Some(vm_traces), Some(state_diffs)) => Some(transactions.into_iter()
+				.zip(vm_traces.into_iter().zip(state_diffs))
+				.map(|(traces, (vm_trace, state_diff))| {
+					let hash = traces.first().and_then(|trace| trace.transaction_hash);
+					let mut results = to_stored_trace_results(traces, vm_trace, state_diff, flags);
+					results.transaction_hash = hash.map(Into::into);
+					results
+				})
+				.collect()),
+			_ => None,
+		}
+	}
+}
+
 impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'static, M: MinerService + 'static {
 	type Metadata = Metadata;
 
@@ -88,24 +193,27 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 		let request = CallRequest::into(request);
 		let signed = fake_sign::sign_call(&self.client, &self.miner, request, meta.is_dapp())?;
 
-		self.client.call(&signed, to_call_analytics(flags), block.into())
-			.map(TraceResults::from)
+		self.client.call(&signed, to_call_analytics(&flags), block.into())
+			.map(|executed| to_trace_results(executed, &flags))
 			.map_err(errors::call)
 	}
 
 	fn call_many(&self, meta: Self::Metadata, requests: Vec<(CallRequest, TraceOptions)>, block: Trailing<BlockNumber>) -> Result<Vec<TraceResults>, Error> {
 		let block = block.unwrap_or_default();
 
-		let requests = requests.into_iter()
+		let (requests, flags): (Vec<_>, Vec<_>) = requests.into_iter()
 			.map(|(request, flags)| {
 				let request = CallRequest::into(request);
 				let signed = fake_sign::sign_call(&self.client, &self.miner, request, meta.is_dapp())?;
-				Ok((signed, to_call_analytics(flags)))
+				let analytics = to_call_analytics(&flags);
+				Ok(((signed, analytics), flags))
 			})
-			.collect::<Result<Vec<_>, Error>>()?;
+			.collect::<Result<Vec<_>, Error>>()?
+			.into_iter()
+			.unzip();
 
 		self.client.call_many(&requests, block.into())
-			.map(|results| results.into_iter().map(TraceResults::from).collect())
+			.map(|results| results.into_iter().zip(flags.iter()).map(|(executed, flags)| to_trace_results(executed, flags)).collect())
 			.map_err(errors::call)
 	}
 
@@ -115,14 +223,38 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 		let tx = UntrustedRlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
 		let signed = SignedTransaction::new(tx).map_err(errors::transaction)?;
 
-		self.client.call(&signed, to_call_analytics(flags), block.into())
-			.map(TraceResults::from)
+		self.client.call(&signed, to_call_analytics(&flags), block.into())
+			.map(|executed| to_trace_results(executed, &flags))
 			.map_err(errors::call)
 	}
 
 	fn replay_transaction(&self, transaction_hash: H256, flags: TraceOptions) -> Result<TraceResults, Error> {
-		self.client.replay(TransactionId::Hash(transaction_hash.into()), to_call_analytics(flags))
-			.map(TraceResults::from)
+		let id = TransactionId::Hash(transaction_hash.into());
+		if reads_stored_results(&flags) {
+			if let Some(results) = self.stored_transaction_results(id.clone(), &flags) {
+				return Ok(results);
+			}
+		}
+
+		self.client.replay(id, to_call_analytics(&flags))
+			.map(|executed| to_trace_results(executed, &flags))
+			.map_err(errors::call)
+	}
+
+	fn replay_block_transactions(&self, block_number: BlockNumber, flags: TraceOptions) -> Result<Vec<TraceResults>, Error> {
+		let id: BlockId = block_number.into();
+		if reads_stored_results(&flags) {
+			if let Some(results) = self.stored_block_results(id, &flags) {
+				return Ok(results);
+			}
+		}
+
+		self.client.replay_block_transactions(id, to_call_analytics(&flags))
+			.map(|results| results.into_iter().map(|(hash, executed)| {
+				let mut results = to_trace_results(executed, &flags);
+				results.transaction_hash = Some(hash.into());
+				results
+			}).collect())
 			.map_err(errors::call)
 	}
 }
@@ -26,6 +26,7 @@ use parking_lot::{RwLock, Mutex};
 use ethcore::error::Error;
 use ethcore::client::MiningBlockChainClient;
 use ethcore::block::ClosedBlock;
+use ethcore::engines::EngineError;
 use ethcore::header::BlockNumber;
 use ethcore::transaction::{UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use ethcore::receipt::{Receipt, RichReceipt};
@@ -114,6 +115,18 @@ impl MinerService for TestMinerService {
 		Ok(())
 	}
 
+	fn propose_signer(&self, _address: Address, _authorize: bool) -> Result<(), EngineError> {
+		Err(EngineError::VotingUnsupported)
+	}
+
+	fn discard_signer(&self, _address: &Address) -> Result<(), EngineError> {
+		Err(EngineError::VotingUnsupported)
+	}
+
+	fn signer_proposals(&self) -> Result<BTreeMap<Address, bool>, EngineError> {
+		Err(EngineError::VotingUnsupported)
+	}
+
 	fn set_extra_data(&self, extra_data: Bytes) {
 		*self.extra_data.write() = extra_data;
 	}
@@ -0,0 +1,60 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+use std::sync::Arc;
+
+use ethcore::miner::Miner;
+use ethcore::spec::Spec;
+
+use jsonrpc_core::IoHandler;
+use v1::{Clique, CliqueClient};
+use v1::tests::helpers::TestMinerService;
+
+#[test]
+fn rpc_clique_propose_and_discard() {
+	let miner = Arc::new(Miner::with_spec(&Spec::new_test_clique()));
+	let mut io = IoHandler::new();
+	io.extend_with(CliqueClient::new(&miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_propose", "params": ["0x0000000000000000000000000000000000001234", true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_propose", "params": ["0x9cce34f7ab185c7aba1b7c8140d620b4bda941d6", false], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_proposals", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x0000000000000000000000000000000000001234":true,"0x9cce34f7ab185c7aba1b7c8140d620b4bda941d6":false},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_discard", "params": ["0x0000000000000000000000000000000000001234"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_proposals", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x9cce34f7ab185c7aba1b7c8140d620b4bda941d6":false},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_clique_propose_without_voting_engine() {
+	let miner = Arc::new(TestMinerService::default());
+	let mut io = IoHandler::new();
+	io.extend_with(CliqueClient::new(&miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_propose", "params": ["0x0000000000000000000000000000000000001234", true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Engine error (This Engine does not support voting on signers.)"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -0,0 +1,95 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use ethcore::executed::{Executed, CallError};
+use ethcore::trace::StructLog;
+use ethcore::client::TestBlockChainClient;
+
+use jsonrpc_core::IoHandler;
+use v1::{Debug, DebugClient};
+
+struct Tester {
+	client: Arc<TestBlockChainClient>,
+	io: IoHandler,
+}
+
+fn io() -> Tester {
+	let client = Arc::new(TestBlockChainClient::new());
+	client.set_struct_logs_result(Ok(Executed {
+		exception: None,
+		gas: 100_000.into(),
+		gas_used: 21_009.into(),
+		refunded: 0.into(),
+		cumulative_gas_used: 21_009.into(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![],
+		trace: vec![],
+		vm_trace: Some(vec![StructLog {
+			pc: 0,
+			instruction: 0x60,
+			op: "PUSH1",
+			gas: 78_991.into(),
+			gas_cost: 3.into(),
+			depth: 1,
+			stack: Some(vec![1.into(), 0x20.into()]),
+			memory: Some(vec![]),
+			storage: Some(Default::default()),
+		}]),
+		state_diff: None,
+	}));
+	let debug = DebugClient::new(&client);
+	let mut io = IoHandler::default();
+	io.extend_with(debug.to_delegate());
+
+	Tester {
+		client: client,
+		io: io,
+	}
+}
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":21009,"returnValue":"","structLogs":[{"depth":1,"gas":78991,"gasCost":3,"memory":[],"op":"PUSH1","pc":0,"stack":["0000000000000000000000000000000000000000000000000000000000000001","0000000000000000000000000000000000000000000000000000000000000020"],"storage":{}}]},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_block_by_number() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceBlockByNumber","params":["0x10", {"disableStack":true}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"result":{"failed":false,"gas":21009,"returnValue":"","structLogs":[{"depth":1,"gas":78991,"gasCost":3,"memory":[],"op":"PUSH1","pc":0,"storage":{}}]}}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction_pruned() {
+	let tester = io();
+	tester.client.set_struct_logs_result(Err(CallError::StatePruned));
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -21,7 +21,8 @@ use bigint::prelude::U256;
 use util::Address;
 
 use ethcore::miner::MinerService;
-use ethcore::client::TestBlockChainClient;
+use ethcore::client::{TestBlockChainClient, BlockChainClient, EachBlockWith};
+use ethcore::ids::BlockId;
 use ethsync::ManageNetwork;
 
 use jsonrpc_core::IoHandler;
@@ -180,6 +181,62 @@ fn rpc_parity_set_engine_signer() {
 }
 
 
+#[test]
+fn rpc_parity_state_gc() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stateGcStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_startStateGc", "params":[{"batchSize":100,"throttle":0}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_startStateGc", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32017,"message":"Database error.","data":"\"State garbage collection is already running\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stateGcStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"stage":"marking","marked":0,"examined":0,"removed":0,"progress":0},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stopStateGc", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stopStateGc", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_reset_chain() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+	client.add_blocks(10, EachBlockWith::Nothing);
+	client.set_history(Some(2));
+	let hash = client.block_hash(BlockId::Number(8)).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resetChain", "params":["0x5"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32017,"message":"Database error.","data":"\"State of block #5 has been pruned\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resetChain", "params":["0x8"], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":"0x{}","id":1}}"#, hash.hex());
+	assert_eq!(io.handle_request_sync(request), Some(response));
+	assert_eq!(client.chain_info().best_block_number, 8);
+}
+
 #[test]
 fn rpc_parity_set_transactions_limit() {
 	let miner = miner_service();
@@ -14,12 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use ethcore::executed::{Executed, CallError};
-use ethcore::trace::trace::{Action, Res, Call};
-use ethcore::trace::LocalizedTrace;
-use ethcore::client::TestBlockChainClient;
+use ethcore::state_diff::StateDiff;
+use ethcore::trace::trace::{Action, Res, Call, CallResult};
+use ethcore::trace::{LocalizedTrace, FlatTrace, VMTrace};
+use ethcore::client::{TestBlockChainClient, BlockChainClient, EachBlockWith, BlockId};
 
 use vm::CallType;
 
@@ -222,6 +224,25 @@ fn rpc_trace_replay_transaction() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_replay_transaction_stored() {
+	let tester = io();
+	// served from the traces database without replaying
+	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
+	*tester.client.state_diffs.write() = Some(vec![StateDiff { raw: BTreeMap::new() }]);
+	*tester.client.vm_traces.write() = Some(vec![VMTrace {
+		parent_step: 0,
+		code: vec![1],
+		operations: vec![],
+		subs: vec![],
+	}]);
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", ["trace", "stateDiff", "vmTrace"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"output":"0x","stateDiff":{},"trace":[{"action":{"callType":"call","from":"0x000000000000000000000000000000000000000f","gas":"0x100","input":"0x010203","to":"0x0000000000000000000000000000000000000010","value":"0x1"},"result":null,"subtraces":0,"traceAddress":[0],"type":"call"}],"vmTrace":{"code":"0x01","ops":[]}},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_trace_replay_transaction_state_pruned() {
 	let tester = io();
@@ -232,3 +253,74 @@ fn rpc_trace_replay_transaction_state_pruned() {
 
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_trace_replay_transaction_call_tracer() {
+	let tester = io();
+	let trace = vec![FlatTrace {
+		action: Action::Call(Call {
+			from: 0xf.into(),
+			to: 0x10.into(),
+			value: 0x1.into(),
+			gas: 0x100.into(),
+			input: vec![1, 2, 3],
+			call_type: CallType::Call,
+		}),
+		result: Res::Call(CallResult {
+			gas_used: 0x50.into(),
+			output: vec![1, 2, 3],
+		}),
+		subtraces: 0,
+		trace_address: Default::default(),
+	}];
+	if let Some(Ok(ref mut executed)) = *tester.client.execution_result.write() {
+		executed.trace = trace;
+	}
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", ["callTracer"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"callTrace":{"from":"0x000000000000000000000000000000000000000f","gas":"0x4e20","gasUsed":"0x2710","input":"0x010203","output":"0x010203","to":"0x0000000000000000000000000000000000000010","type":"CALL","value":"0x1"},"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_replay_block_transactions() {
+	let tester = io();
+	tester.client.add_blocks(1, EachBlockWith::Transaction);
+	let hash = tester.client.block(BlockId::Number(1)).unwrap().transaction_hashes()[0];
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayBlockTransactions","params":["0x1", ["trace", "stateDiff", "vmTrace"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"output":"0x010203","stateDiff":null,"trace":[],"transactionHash":""#.to_owned() + &format!("0x{:?}", hash) + r#"","vmTrace":null}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_replay_block_transactions_stored() {
+	let tester = io();
+	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
+	*tester.client.state_diffs.write() = Some(vec![StateDiff { raw: BTreeMap::new() }]);
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayBlockTransactions","params":["0x1", ["stateDiff"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"output":"0x","stateDiff":{},"trace":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000005","vmTrace":null}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	// VM traces aren't stored, so the block is replayed
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayBlockTransactions","params":["0x1", ["stateDiff", "vmTrace"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_replay_block_transactions_state_pruned() {
+	let tester = io();
+	tester.client.add_blocks(1, EachBlockWith::Transaction);
+	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayBlockTransactions","params":["0x1", ["trace", "stateDiff", "vmTrace"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -0,0 +1,38 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+//! Clique signer voting RPC interface.
+
+use std::collections::BTreeMap;
+
+use jsonrpc_core::Error;
+use v1::types::H160;
+
+build_rpc_trait! {
+	/// Clique signer voting RPC interface.
+	pub trait Clique {
+		/// Proposes adding (`true`) or removing (`false`) a signer in blocks sealed by this node.
+		#[rpc(name = "clique_propose")]
+		fn propose(&self, H160, bool) -> Result<bool, Error>;
+
+		/// Withdraws a vote proposed by this node.
+		#[rpc(name = "clique_discard")]
+		fn discard(&self, H160) -> Result<bool, Error>;
+
+		/// Returns the votes proposed by this node and whether each adds the signer.
+		#[rpc(name = "clique_proposals")]
+		fn proposals(&self) -> Result<BTreeMap<H160, bool>, Error>;
+	}
+}
@@ -0,0 +1,34 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug RPC interface.
+
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+use v1::types::{BlockNumber, H256, StructLoggerOptions, StructLogResult, BlockStructLogResult};
+
+build_rpc_trait! {
+	/// Debug RPC interface.
+	pub trait Debug {
+		/// Replays the transaction with the given hash and returns its geth-style struct logs.
+		#[rpc(name = "debug_traceTransaction")]
+		fn trace_transaction(&self, H256, Trailing<StructLoggerOptions>) -> Result<StructLogResult, Error>;
+
+		/// Replays all transactions in the given block and returns their geth-style struct logs.
+		#[rpc(name = "debug_traceBlockByNumber")]
+		fn trace_block_by_number(&self, BlockNumber, Trailing<StructLoggerOptions>) -> Result<Vec<BlockStructLogResult>, Error>;
+	}
+}
@@ -19,7 +19,7 @@ use jsonrpc_core::{BoxFuture, Error};
 use jsonrpc_macros::Trailing;
 
 use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
+use v1::types::{Log, Receipt, SyncStatus, Transaction, Work, EthAccount};
 use v1::types::{H64, H160, H256, U256};
 
 build_rpc_trait! {
@@ -67,6 +67,10 @@ build_rpc_trait! {
 		#[rpc(name = "eth_getStorageAt")]
 		fn storage_at(&self, H160, U256, Trailing<BlockNumber>) -> BoxFuture<H256, Error>;
 
+		/// Returns the account and storage values of the specified account including the Merkle proof.
+		#[rpc(name = "eth_getProof")]
+		fn proof(&self, H160, Vec<U256>, Trailing<BlockNumber>) -> BoxFuture<EthAccount, Error>;
+
 		/// Returns block with given hash.
 		#[rpc(name = "eth_getBlockByHash")]
 		fn block_by_hash(&self, H256, bool) -> BoxFuture<Option<RichBlock>, Error>;
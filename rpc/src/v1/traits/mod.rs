@@ -17,6 +17,8 @@
 //! Ethereum rpc interfaces.
 
 pub mod web3;
+pub mod clique;
+pub mod debug;
 pub mod eth;
 pub mod eth_pubsub;
 pub mod eth_signing;
@@ -33,6 +35,8 @@ pub mod rpc;
 pub mod secretstore;
 
 pub use self::web3::Web3;
+pub use self::clique::Clique;
+pub use self::debug::Debug;
 pub use self::eth::{Eth, EthFilter};
 pub use self::eth_pubsub::EthPubSub;
 pub use self::eth_signing::EthSigning;
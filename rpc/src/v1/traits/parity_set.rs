@@ -17,8 +17,9 @@
 //! Parity-specific rpc interface for operations altering the settings.
 
 use jsonrpc_core::{BoxFuture, Error};
+use jsonrpc_macros::Trailing;
 
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, StateGcOptions, StateGcStatus, BlockNumber};
 
 build_rpc_trait! {
 	/// Parity-specific rpc interface for operations altering the settings.
@@ -119,5 +120,23 @@ build_rpc_trait! {
 		/// Returns `true` when transaction was removed, `false` if it was not found.
 		#[rpc(name = "parity_removeTransaction")]
 		fn remove_transaction(&self, H256) -> Result<Option<Transaction>, Error>;
+
+		/// Starts removing unreachable state from the database in the background.
+		/// Fails if the pruning method doesn't support it or it's already running.
+		#[rpc(name = "parity_startStateGc")]
+		fn start_state_gc(&self, Trailing<StateGcOptions>) -> Result<bool, Error>;
+
+		/// Stops the state garbage collection. Returns `false` if it wasn't running.
+		#[rpc(name = "parity_stopStateGc")]
+		fn stop_state_gc(&self) -> Result<bool, Error>;
+
+		/// Returns the progress of the last state garbage collection started.
+		#[rpc(name = "parity_stateGcStatus")]
+		fn state_gc_status(&self) -> Result<Option<StateGcStatus>, Error>;
+
+		/// Rewinds the chain to the given canonical block, whose state must not have been pruned.
+		/// Returns the hash of the new best block.
+		#[rpc(name = "parity_resetChain")]
+		fn reset_chain(&self, BlockNumber) -> Result<H256, Error>;
 	}
 }
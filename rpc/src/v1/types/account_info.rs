@@ -13,6 +13,7 @@
 
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+use v1::types::{Bytes, H160, H256, U256};
 
 /// Account information.
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
@@ -42,3 +43,37 @@ pub struct HwAccountInfo {
 	pub manufacturer: String,
 }
 
+
+/// Account proof information (used by `eth_getProof`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EthAccount {
+	/// Account address
+	pub address: H160,
+	/// Merkle proof of the account, from the state root down to the account node
+	#[serde(rename="accountProof")]
+	pub account_proof: Vec<Bytes>,
+	/// Account balance
+	pub balance: U256,
+	/// Hash of the account code
+	#[serde(rename="codeHash")]
+	pub code_hash: H256,
+	/// Account nonce
+	pub nonce: U256,
+	/// Root of the account storage trie
+	#[serde(rename="storageHash")]
+	pub storage_hash: H256,
+	/// Proofs for the requested storage keys
+	#[serde(rename="storageProof")]
+	pub storage_proof: Vec<StorageProof>,
+}
+
+/// Storage proof information (used by `eth_getProof`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageProof {
+	/// Storage key
+	pub key: U256,
+	/// Value stored under the key
+	pub value: U256,
+	/// Merkle proof of the value, from the storage root down to the value node
+	pub proof: Vec<Bytes>,
+}
@@ -0,0 +1,89 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Results of calls.
+
+use ethcore::client::Executed;
+use vm;
+use v1::helpers::revert;
+use v1::types::Bytes;
+
+/// Result of a call.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallResult {
+	/// Output of the call
+	pub output: Bytes,
+	/// Whether the call was reverted with the REVERT instruction
+	pub reverted: bool,
+	/// Decoded revert reason
+	#[serde(rename="revertReason", skip_serializing_if="Option::is_none")]
+	pub revert_reason: Option<String>,
+}
+
+impl From<Executed> for CallResult {
+	fn from(executed: Executed) -> Self {
+		let reverted = executed.exception == Some(vm::Error::Reverted);
+		CallResult {
+			revert_reason: match reverted {
+				true => revert::reason(&executed.output),
+				false => None,
+			},
+			output: executed.output.into(),
+			reverted: reverted,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use rustc_hex::FromHex;
+	use ethcore::client::Executed;
+	use vm;
+	use super::CallResult;
+
+	fn executed(exception: Option<vm::Error>, output: Vec<u8>) -> Executed {
+		Executed {
+			exception: exception,
+			gas: 0.into(),
+			gas_used: 0.into(),
+			refunded: 0.into(),
+			cumulative_gas_used: 0.into(),
+			logs: vec![],
+			contracts_created: vec![],
+			output: output,
+			trace: vec![],
+			vm_trace: None,
+			state_diff: None,
+		}
+	}
+
+	#[test]
+	fn call_result_serialization() {
+		let output = "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000004\
+			6661696c00000000000000000000000000000000000000000000000000000000".from_hex().unwrap();
+		let serialized = serde_json::to_string(&CallResult::from(executed(Some(vm::Error::Reverted), output.clone()))).unwrap();
+		assert!(serialized.ends_with(r#","reverted":true,"revertReason":"fail"}"#));
+
+		let serialized = serde_json::to_string(&CallResult::from(executed(Some(vm::Error::Reverted), vec![0x12]))).unwrap();
+		assert_eq!(serialized, r#"{"output":"0x12","reverted":true}"#);
+
+		let serialized = serde_json::to_string(&CallResult::from(executed(None, vec![0x12]))).unwrap();
+		assert_eq!(serialized, r#"{"output":"0x12","reverted":false}"#);
+	}
+}
@@ -22,6 +22,7 @@ mod block;
 mod block_number;
 mod bytes;
 mod call_request;
+mod call_result;
 mod confirmations;
 mod consensus_status;
 mod dapps;
@@ -35,6 +36,8 @@ mod node_kind;
 mod provenance;
 mod receipt;
 mod rpc_settings;
+mod state_gc;
+mod struct_log;
 mod sync;
 mod trace;
 mod trace_filter;
@@ -46,11 +49,12 @@ mod work;
 
 pub mod pubsub;
 
-pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo};
+pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo, EthAccount, StorageProof};
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
 pub use self::block_number::BlockNumber;
 pub use self::call_request::CallRequest;
+pub use self::call_result::CallResult;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
 	TransactionModification, SignRequest, DecryptRequest, Either
@@ -67,11 +71,13 @@ pub use self::node_kind::{NodeKind, Availability, Capability};
 pub use self::provenance::{Origin, DappId};
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
+pub use self::state_gc::{StateGcOptions, StateGcStatus};
+pub use self::struct_log::{StructLoggerOptions, StructLog, StructLogResult, BlockStructLogResult};
 pub use self::sync::{
 	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
 };
-pub use self::trace::{LocalizedTrace, TraceResults};
+pub use self::trace::{CallFrame, LocalizedTrace, TraceResults};
 pub use self::trace_filter::TraceFilter;
 pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
 pub use self::transaction_request::TransactionRequest;
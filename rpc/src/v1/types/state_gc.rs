@@ -0,0 +1,111 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State garbage collection.
+
+use std::time::Duration;
+use ethcore::client::{self, StateGcStage};
+
+/// State garbage collection options
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct StateGcOptions {
+	/// Number of keys removed at once
+	#[serde(rename="batchSize")]
+	pub batch_size: Option<usize>,
+	/// Pause between two batches in milliseconds
+	pub throttle: Option<u64>,
+}
+
+impl Into<client::StateGcConfig> for StateGcOptions {
+	fn into(self) -> client::StateGcConfig {
+		let default = client::StateGcConfig::default();
+		client::StateGcConfig {
+			batch_size: self.batch_size.unwrap_or(default.batch_size),
+			throttle: self.throttle.map_or(default.throttle, Duration::from_millis),
+		}
+	}
+}
+
+/// State garbage collection progress
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StateGcStatus {
+	/// Current stage: "marking", "sweeping", "done", "aborted" or "failed"
+	pub stage: String,
+	/// Error which stopped the collection
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Number of reachable keys found
+	pub marked: usize,
+	/// Number of database keys examined
+	pub examined: usize,
+	/// Number of keys removed
+	pub removed: usize,
+	/// Estimated percentage of the current stage done
+	pub progress: u8,
+}
+
+impl From<client::StateGcStatus> for StateGcStatus {
+	fn from(s: client::StateGcStatus) -> Self {
+		let (stage, error) = match s.stage {
+			StateGcStage::Marking => ("marking", None),
+			StateGcStage::Sweeping => ("sweeping", None),
+			StateGcStage::Done => ("done", None),
+			StateGcStage::Aborted => ("aborted", None),
+			StateGcStage::Failed(e) => ("failed", Some(e)),
+		};
+
+		StateGcStatus {
+			stage: stage.into(),
+			error: error,
+			marked: s.marked,
+			examined: s.examined,
+			removed: s.removed,
+			progress: s.progress,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use ethcore::client::{self, StateGcStage};
+	use super::{StateGcOptions, StateGcStatus};
+
+	#[test]
+	fn test_state_gc_options_deserialize() {
+		let s = r#"{"batchSize":10}"#;
+		let deserialized: StateGcOptions = serde_json::from_str(s).unwrap();
+		let config: client::StateGcConfig = deserialized.into();
+		assert_eq!(config, client::StateGcConfig {
+			batch_size: 10,
+			throttle: Duration::from_millis(100),
+		});
+	}
+
+	#[test]
+	fn test_state_gc_status_serialize() {
+		let status = StateGcStatus::from(client::StateGcStatus {
+			stage: StateGcStage::Failed("Corrupted database".into()),
+			marked: 5,
+			examined: 3,
+			removed: 1,
+			progress: 50,
+		});
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"stage":"failed","error":"Corrupted database","marked":5,"examined":3,"removed":1,"progress":50}"#);
+	}
+}
@@ -0,0 +1,156 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible struct logs.
+
+use std::collections::BTreeMap;
+use bigint::hash::H256;
+use ethcore::client::Executed;
+use ethcore::trace;
+use rustc_hex::ToHex;
+
+/// Struct logger options
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct StructLoggerOptions {
+	/// Don't capture the stack
+	#[serde(rename="disableStack")]
+	pub disable_stack: Option<bool>,
+	/// Don't capture the memory
+	#[serde(rename="disableMemory")]
+	pub disable_memory: Option<bool>,
+	/// Don't capture the storage
+	#[serde(rename="disableStorage")]
+	pub disable_storage: Option<bool>,
+}
+
+impl Into<trace::StructLoggerOptions> for StructLoggerOptions {
+	fn into(self) -> trace::StructLoggerOptions {
+		trace::StructLoggerOptions {
+			disable_stack: self.disable_stack.unwrap_or(false),
+			disable_memory: self.disable_memory.unwrap_or(false),
+			disable_storage: self.disable_storage.unwrap_or(false),
+		}
+	}
+}
+
+/// Single step of the execution
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StructLog {
+	/// Program counter
+	pub pc: usize,
+	/// Instruction name
+	pub op: String,
+	/// Gas available before the instruction
+	pub gas: u64,
+	/// Gas cost of the instruction
+	#[serde(rename="gasCost")]
+	pub gas_cost: u64,
+	/// Call depth
+	pub depth: usize,
+	/// Stack words, bottom first
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<String>>,
+	/// Memory split into 32-byte words
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// Accessed storage of the executing contract
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+}
+
+impl From<trace::StructLog> for StructLog {
+	fn from(t: trace::StructLog) -> Self {
+		StructLog {
+			pc: t.pc,
+			op: t.op.into(),
+			gas: t.gas.low_u64(),
+			gas_cost: t.gas_cost.low_u64(),
+			depth: t.depth,
+			stack: t.stack.map(|stack| stack.into_iter().map(|word| H256::from(word).to_hex()).collect()),
+			memory: t.memory.map(|memory| memory.chunks(32).map(|word| word.to_hex()).collect()),
+			storage: t.storage.map(|storage| storage.into_iter().map(|(k, v)| (k.to_hex(), v.to_hex())).collect()),
+		}
+	}
+}
+
+/// Result of a transaction replayed with the struct logger
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StructLogResult {
+	/// Gas used by the transaction
+	pub gas: u64,
+	/// Whether the transaction failed
+	pub failed: bool,
+	/// Transaction output
+	#[serde(rename="returnValue")]
+	pub return_value: String,
+	/// Executed steps
+	#[serde(rename="structLogs")]
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl From<Executed<Vec<trace::StructLog>>> for StructLogResult {
+	fn from(t: Executed<Vec<trace::StructLog>>) -> Self {
+		StructLogResult {
+			gas: t.gas_used.low_u64(),
+			failed: t.exception.is_some(),
+			return_value: t.output.to_hex(),
+			struct_logs: t.vm_trace.unwrap_or_else(Vec::new).into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Struct logger result of a single transaction within a block
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BlockStructLogResult {
+	/// Transaction result
+	pub result: StructLogResult,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::trace;
+	use super::{StructLog, StructLoggerOptions};
+
+	#[test]
+	fn test_struct_logger_options_deserialize() {
+		let s = r#"{"disableStack":true,"disableStorage":false}"#;
+		let deserialized: StructLoggerOptions = serde_json::from_str(s).unwrap();
+		let options: trace::StructLoggerOptions = deserialized.into();
+		assert_eq!(options, trace::StructLoggerOptions {
+			disable_stack: true,
+			disable_memory: false,
+			disable_storage: false,
+		});
+	}
+
+	#[test]
+	fn test_struct_log_serialize() {
+		let log = StructLog::from(trace::StructLog {
+			pc: 2,
+			instruction: 0x01,
+			op: "ADD",
+			gas: 79997.into(),
+			gas_cost: 3.into(),
+			depth: 1,
+			stack: Some(vec![1.into(), 2.into()]),
+			memory: None,
+			storage: None,
+		});
+		let serialized = serde_json::to_string(&log).unwrap();
+		assert_eq!(serialized, r#"{"pc":2,"op":"ADD","gas":79997,"gasCost":3,"depth":1,"stack":["0000000000000000000000000000000000000000000000000000000000000001","0000000000000000000000000000000000000000000000000000000000000002"]}"#);
+	}
+}
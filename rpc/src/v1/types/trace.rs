@@ -23,6 +23,7 @@ use ethcore::state_diff;
 use ethcore::account_diff;
 use ethcore::client::Executed;
 use vm;
+use v1::helpers::revert;
 use v1::types::{Bytes, H160, H256, U256};
 
 #[derive(Debug, Serialize)]
@@ -429,6 +430,29 @@ impl From<trace::CreateResult> for CreateResult {
 	}
 }
 
+/// Reverted call or create result
+#[derive(Debug, Serialize)]
+pub struct RevertedResult {
+	/// Gas used
+	#[serde(rename="gasUsed")]
+	gas_used: U256,
+	/// Output bytes
+	output: Bytes,
+	/// Decoded revert reason
+	#[serde(rename="revertReason", skip_serializing_if="Option::is_none")]
+	revert_reason: Option<String>,
+}
+
+impl From<trace::CallResult> for RevertedResult {
+	fn from(c: trace::CallResult) -> Self {
+		RevertedResult {
+			gas_used: c.gas_used.into(),
+			revert_reason: revert::reason(&c.output),
+			output: c.output.into(),
+		}
+	}
+}
+
 /// Response
 #[derive(Debug)]
 pub enum Res {
@@ -440,6 +464,8 @@ pub enum Res {
 	FailedCall(TraceError),
 	/// Creation failure
 	FailedCreate(TraceError),
+	/// Call or creation reverted with the REVERT instruction
+	Reverted(RevertedResult),
 	/// None
 	None,
 }
@@ -451,6 +477,7 @@ impl From<trace::Res> for Res {
 			trace::Res::Create(create) => Res::Create(CreateResult::from(create)),
 			trace::Res::FailedCall(error) => Res::FailedCall(error),
 			trace::Res::FailedCreate(error) => Res::FailedCreate(error),
+			trace::Res::RevertedCall(result) | trace::Res::RevertedCreate(result) => Res::Reverted(RevertedResult::from(result)),
 			trace::Res::None => Res::None,
 		}
 	}
@@ -506,6 +533,10 @@ impl Serialize for LocalizedTrace {
 			Res::Create(ref create) => struc.serialize_field("result", create)?,
 			Res::FailedCall(ref error) => struc.serialize_field("error", &error.to_string())?,
 			Res::FailedCreate(ref error) => struc.serialize_field("error", &error.to_string())?,
+			Res::Reverted(ref result) => {
+				struc.serialize_field("result", result)?;
+				struc.serialize_field("error", &TraceError::Reverted.to_string())?;
+			},
 			Res::None => struc.serialize_field("result", &None as &Option<u8>)?,
 		}
 
@@ -577,6 +608,10 @@ impl Serialize for Trace {
 			Res::Create(ref create) => struc.serialize_field("result", create)?,
 			Res::FailedCall(ref error) => struc.serialize_field("error", &error.to_string())?,
 			Res::FailedCreate(ref error) => struc.serialize_field("error", &error.to_string())?,
+			Res::Reverted(ref result) => {
+				struc.serialize_field("result", result)?;
+				struc.serialize_field("error", &TraceError::Reverted.to_string())?;
+			},
 			Res::None => struc.serialize_field("result", &None as &Option<u8>)?,
 		}
 
@@ -611,6 +646,12 @@ pub struct TraceResults {
 	/// The transaction trace.
 	#[serde(rename="stateDiff")]
 	pub state_diff: Option<StateDiff>,
+	/// The nested call tree of the transaction.
+	#[serde(rename="callTrace", skip_serializing_if="Option::is_none")]
+	pub call_trace: Option<CallFrame>,
+	/// Hash of the replayed transaction. Only set when replaying a whole block.
+	#[serde(rename="transactionHash", skip_serializing_if="Option::is_none")]
+	pub transaction_hash: Option<H256>,
 }
 
 impl From<Executed> for TraceResults {
@@ -620,7 +661,172 @@ impl From<Executed> for TraceResults {
 			trace: t.trace.into_iter().map(Into::into).collect(),
 			vm_trace: t.vm_trace.map(Into::into),
 			state_diff: t.state_diff.map(Into::into),
+			call_trace: None,
+			transaction_hash: None,
+		}
+	}
+}
+
+impl TraceResults {
+	/// Creates the results of a transaction from the data stored in the traces database.
+	/// The output is taken from the top-level trace.
+	pub fn from_stored(traces: Vec<EthLocalizedTrace>, vm_trace: Option<et::VMTrace>, state_diff: Option<state_diff::StateDiff>) -> Self {
+		let output = traces.iter()
+			.find(|t| t.trace_address.is_empty())
+			.map(|t| match t.result {
+				trace::Res::Call(ref call) => call.output.clone(),
+				trace::Res::Create(ref create) => create.code.clone(),
+				trace::Res::RevertedCall(ref result) | trace::Res::RevertedCreate(ref result) => result.output.clone(),
+				_ => Vec::new(),
+			})
+			.unwrap_or_else(Vec::new);
+
+		TraceResults {
+			output: output.into(),
+			trace: traces.into_iter()
+				.map(|t| FlatTrace {
+					action: t.action,
+					result: t.result,
+					subtraces: t.subtraces,
+					trace_address: t.trace_address.into_iter().collect(),
+				})
+				.map(Into::into)
+				.collect(),
+			vm_trace: vm_trace.map(Into::into),
+			state_diff: state_diff.map(Into::into),
+			call_trace: None,
+			transaction_hash: None,
+		}
+	}
+}
+
+/// Single frame of a nested call tree, in the format of geth's `callTracer`.
+#[derive(Debug, Serialize)]
+pub struct CallFrame {
+	/// Kind of the frame: `CALL`, `CALLCODE`, `DELEGATECALL`, `STATICCALL`, `CREATE` or `SELFDESTRUCT`
+	#[serde(rename="type")]
+	pub frame_type: &'static str,
+	/// Sender
+	pub from: H160,
+	/// Recipient, or the created contract
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub to: Option<H160>,
+	/// Transfered value
+	pub value: U256,
+	/// Gas
+	pub gas: U256,
+	/// Gas used. Frames failing with an error other than a revert use all of their gas.
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// Input data, or init code
+	pub input: Bytes,
+	/// Output data, or the code of the created contract
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub output: Option<Bytes>,
+	/// Error the frame failed with
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub error: Option<String>,
+	/// Decoded revert reason
+	#[serde(rename="revertReason", skip_serializing_if="Option::is_none")]
+	pub revert_reason: Option<String>,
+	/// Frames of the subcalls
+	#[serde(skip_serializing_if="Vec::is_empty")]
+	pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+	/// Builds the call tree of an executed transaction from its flat traces.
+	/// Returns `None` if the transaction was executed without tracing.
+	pub fn from_executed(t: &Executed) -> Option<Self> {
+		let mut traces = t.trace.iter().cloned();
+		let mut root = match traces.next() {
+			Some(trace) => CallFrame::nest(trace, &mut traces),
+			None => return None,
+		};
+
+		// the outermost frame reports the gas and output of the whole transaction.
+		root.gas = t.gas.into();
+		root.gas_used = t.gas_used.into();
+		if t.exception.is_some() && !t.output.is_empty() {
+			root.output = Some(t.output.clone().into());
+		}
+		if let Some(vm::Error::Reverted) = t.exception {
+			root.revert_reason = revert::reason(&t.output);
+		}
+		Some(root)
+	}
+
+	// consumes the subtraces of `trace` from `rest`. flat traces are ordered depth-first.
+	fn nest<I>(trace: FlatTrace, rest: &mut I) -> Self where I: Iterator<Item = FlatTrace> {
+		let subtraces = trace.subtraces;
+		let mut frame = CallFrame::from(trace);
+		for _ in 0..subtraces {
+			match rest.next() {
+				Some(sub) => frame.calls.push(CallFrame::nest(sub, rest)),
+				None => break,
+			}
 		}
+		frame
+	}
+}
+
+impl From<FlatTrace> for CallFrame {
+	fn from(t: FlatTrace) -> Self {
+		let (frame_type, from, to, value, gas, input) = match t.action {
+			trace::Action::Call(c) => {
+				let frame_type = match c.call_type {
+					vm::CallType::None | vm::CallType::Call => "CALL",
+					vm::CallType::CallCode => "CALLCODE",
+					vm::CallType::DelegateCall => "DELEGATECALL",
+					vm::CallType::StaticCall => "STATICCALL",
+				};
+				(frame_type, c.from, Some(c.to), c.value, c.gas, c.input)
+			},
+			trace::Action::Create(c) => ("CREATE", c.from, None, c.value, c.gas, c.init),
+			trace::Action::Suicide(s) => ("SELFDESTRUCT", s.address, Some(s.refund_address), s.balance, 0.into(), vec![]),
+			trace::Action::Reward(r) => ("REWARD", r.author, None, r.value, 0.into(), vec![]),
+		};
+
+		let mut frame = CallFrame {
+			frame_type: frame_type,
+			from: from.into(),
+			to: to.map(Into::into),
+			value: value.into(),
+			gas: gas.into(),
+			gas_used: 0.into(),
+			input: input.into(),
+			output: None,
+			error: None,
+			revert_reason: None,
+			calls: Vec::new(),
+		};
+
+		match t.result {
+			trace::Res::Call(r) => {
+				frame.gas_used = r.gas_used.into();
+				frame.output = Some(r.output.into());
+			},
+			trace::Res::Create(r) => {
+				frame.gas_used = r.gas_used.into();
+				frame.output = Some(r.code.into());
+				frame.to = Some(r.address.into());
+			},
+			// reverted frames are traced with the gas they used, the executive drains
+			// the gas of frames failing with any other error.
+			trace::Res::FailedCall(e) | trace::Res::FailedCreate(e) => {
+				frame.gas_used = frame.gas;
+				frame.error = Some(e.to_string());
+			},
+			trace::Res::RevertedCall(r) | trace::Res::RevertedCreate(r) => {
+				frame.gas_used = r.gas_used.into();
+				frame.error = Some(TraceError::Reverted.to_string());
+				frame.revert_reason = revert::reason(&r.output);
+				frame.output = Some(r.output.into());
+			},
+			trace::Res::None => {},
+		}
+
+		frame
 	}
 }
 
@@ -639,11 +845,103 @@ mod tests {
 			trace: vec![],
 			vm_trace: None,
 			state_diff: None,
+			call_trace: None,
+			transaction_hash: None,
 		};
 		let serialized = serde_json::to_string(&r).unwrap();
 		assert_eq!(serialized, r#"{"output":"0x60","trace":[],"vmTrace":null,"stateDiff":null}"#);
 	}
 
+	#[test]
+	fn should_nest_call_traces() {
+		use ethcore::client::Executed;
+		use ethcore::trace::{trace as eth_trace, FlatTrace};
+		use rustc_hex::FromHex;
+		use vm;
+
+		let call = |to: u64, call_type, subtraces, result, trace_address: Vec<usize>| FlatTrace {
+			action: eth_trace::Action::Call(eth_trace::Call {
+				from: 1.into(),
+				to: to.into(),
+				value: 0.into(),
+				gas: 50.into(),
+				input: vec![0x12],
+				call_type: call_type,
+			}),
+			result: result,
+			subtraces: subtraces,
+			trace_address: trace_address.into_iter().collect(),
+		};
+		let create = FlatTrace {
+			action: eth_trace::Action::Create(eth_trace::Create {
+				from: 3.into(),
+				value: 0.into(),
+				gas: 20.into(),
+				init: vec![0x60, 0x00],
+			}),
+			result: eth_trace::Res::Create(eth_trace::CreateResult {
+				gas_used: 7.into(),
+				code: vec![0x60],
+				address: 4.into(),
+			}),
+			subtraces: 0,
+			trace_address: vec![0, 0].into_iter().collect(),
+		};
+		let call_result = eth_trace::Res::Call(eth_trace::CallResult { gas_used: 5.into(), output: vec![0x01] });
+
+		// Error("fail")
+		let output = "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000004\
+			6661696c00000000000000000000000000000000000000000000000000000000".from_hex().unwrap();
+		let reverted = |gas_used: u64| eth_trace::Res::RevertedCall(eth_trace::CallResult {
+			gas_used: gas_used.into(),
+			output: output.clone(),
+		});
+		let executed = Executed {
+			exception: Some(vm::Error::Reverted),
+			gas: 100.into(),
+			gas_used: 90.into(),
+			refunded: 0.into(),
+			cumulative_gas_used: 90.into(),
+			logs: vec![],
+			contracts_created: vec![],
+			output: output.clone(),
+			trace: vec![
+				call(2, vm::CallType::Call, 3, reverted(40), vec![]),
+				call(3, vm::CallType::StaticCall, 1, call_result, vec![0]),
+				create,
+				call(5, vm::CallType::DelegateCall, 0, eth_trace::Res::FailedCall(TraceError::OutOfGas), vec![1]),
+				call(6, vm::CallType::Call, 0, reverted(3), vec![2]),
+			],
+			vm_trace: None,
+			state_diff: None,
+		};
+
+		let root = CallFrame::from_executed(&executed).unwrap();
+		assert_eq!(root.frame_type, "CALL");
+		assert_eq!(root.gas, 100.into());
+		assert_eq!(root.gas_used, 90.into());
+		assert_eq!(root.output, Some(output.clone().into()));
+		assert_eq!(root.error, Some("Reverted".into()));
+		assert_eq!(root.revert_reason, Some("fail".into()));
+		assert_eq!(root.calls.len(), 3);
+
+		assert_eq!(root.calls[0].frame_type, "STATICCALL");
+		assert_eq!(root.calls[0].calls.len(), 1);
+		assert_eq!(root.calls[1].frame_type, "DELEGATECALL");
+		assert_eq!(root.calls[1].gas_used, 50.into());
+		assert_eq!(root.calls[1].error, Some("Out of gas".into()));
+		assert!(root.calls[1].calls.is_empty());
+		assert_eq!(root.calls[2].gas_used, 3.into());
+		assert_eq!(root.calls[2].output, Some(output.into()));
+		assert_eq!(root.calls[2].error, Some("Reverted".into()));
+		assert_eq!(root.calls[2].revert_reason, Some("fail".into()));
+
+		let serialized = serde_json::to_string(&root.calls[0]).unwrap();
+		assert_eq!(serialized, r#"{"type":"STATICCALL","from":"0x0000000000000000000000000000000000000001","to":"0x0000000000000000000000000000000000000003","value":"0x0","gas":"0x32","gasUsed":"0x5","input":"0x12","output":"0x01","calls":[{"type":"CREATE","from":"0x0000000000000000000000000000000000000003","to":"0x0000000000000000000000000000000000000004","value":"0x0","gas":"0x14","gasUsed":"0x7","input":"0x6000","output":"0x60"}]}"#);
+	}
+
 	#[test]
 	fn test_trace_call_serialize() {
 		let t = LocalizedTrace {
@@ -693,6 +991,36 @@ mod tests {
 		assert_eq!(serialized, r#"{"type":"call","action":{"from":"0x0000000000000000000000000000000000000004","to":"0x0000000000000000000000000000000000000005","value":"0x6","gas":"0x7","input":"0x1234","callType":"call"},"error":"Out of gas","traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
 	}
 
+	#[test]
+	fn test_trace_reverted_call_serialize() {
+		use ethcore::trace::trace::CallResult as EthCallResult;
+		use rustc_hex::FromHex;
+
+		// Error("fail")
+		let output = "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000004\
+			6661696c00000000000000000000000000000000000000000000000000000000".from_hex().unwrap();
+		let t = Trace {
+			action: Action::Call(Call {
+				from: 4.into(),
+				to: 5.into(),
+				value: 6.into(),
+				gas: 7.into(),
+				input: Bytes::new(vec![0x12, 0x34]),
+				call_type: CallType::Call,
+			}),
+			result: Res::Reverted(RevertedResult::from(EthCallResult {
+				gas_used: 3.into(),
+				output: output,
+			})),
+			trace_address: vec![10],
+			subtraces: 0,
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"type":"call","action":{"from":"0x0000000000000000000000000000000000000004","to":"0x0000000000000000000000000000000000000005","value":"0x6","gas":"0x7","input":"0x1234","callType":"call"},"result":{"gasUsed":"0x3","output":"0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000046661696c00000000000000000000000000000000000000000000000000000000","revertReason":"fail"},"error":"Reverted","traceAddress":[10],"subtraces":0}"#);
+	}
+
 	#[test]
 	fn test_trace_create_serialize() {
 		let t = LocalizedTrace {
@@ -85,8 +85,8 @@ pub enum LocalTransactionStatus {
 	Mined(Transaction),
 	/// Transaction was dropped because of limit.
 	Dropped(Transaction),
-	/// Transaction was replaced by transaction with higher gas price.
-	Replaced(Transaction, U256, H256),
+	/// Transaction was replaced by transaction with the same nonce (gas price, hash and reason).
+	Replaced(Transaction, U256, H256, String),
 	/// Transaction never got into the queue.
 	Rejected(Transaction, String),
 	/// Transaction is invalid.
@@ -105,7 +105,7 @@ impl Serialize for LocalTransactionStatus {
 			Pending | Future => 1,
 			Mined(..) | Dropped(..) | Invalid(..) | Canceled(..) => 2,
 			Rejected(..) => 3,
-			Replaced(..) => 4,
+			Replaced(..) => 5,
 		};
 
 		let status = "status";
@@ -136,11 +136,12 @@ impl Serialize for LocalTransactionStatus {
 				struc.serialize_field(transaction, tx)?;
 				struc.serialize_field("error", reason)?;
 			},
-			Replaced(ref tx, ref gas_price, ref hash) => {
+			Replaced(ref tx, ref gas_price, ref hash, ref reason) => {
 				struc.serialize_field(status, "replaced")?;
 				struc.serialize_field(transaction, tx)?;
 				struc.serialize_field("hash", hash)?;
 				struc.serialize_field("gasPrice", gas_price)?;
+				struc.serialize_field("reason", reason)?;
 			},
 		}
 
@@ -256,7 +257,7 @@ impl LocalTransactionStatus {
 			Mined(tx) => LocalTransactionStatus::Mined(Transaction::from_signed(tx, block_number, eip86_transition)),
 			Dropped(tx) => LocalTransactionStatus::Dropped(Transaction::from_signed(tx, block_number, eip86_transition)),
 			Rejected(tx, err) => LocalTransactionStatus::Rejected(Transaction::from_signed(tx, block_number, eip86_transition), errors::transaction_message(err)),
-			Replaced(tx, gas_price, hash) => LocalTransactionStatus::Replaced(Transaction::from_signed(tx, block_number, eip86_transition), gas_price.into(), hash.into()),
+			Replaced(tx, gas_price, hash, reason) => LocalTransactionStatus::Replaced(Transaction::from_signed(tx, block_number, eip86_transition), gas_price.into(), hash.into(), reason.to_string()),
 			Invalid(tx) => LocalTransactionStatus::Invalid(Transaction::from_signed(tx, block_number, eip86_transition)),
 			Canceled(tx) => LocalTransactionStatus::Canceled(Transaction::from_pending(tx, block_number, eip86_transition)),
 		}
@@ -284,7 +285,7 @@ mod tests {
 		let status4 = LocalTransactionStatus::Dropped(Transaction::default());
 		let status5 = LocalTransactionStatus::Invalid(Transaction::default());
 		let status6 = LocalTransactionStatus::Rejected(Transaction::default(), "Just because".into());
-		let status7 = LocalTransactionStatus::Replaced(Transaction::default(), 5.into(), 10.into());
+		let status7 = LocalTransactionStatus::Replaced(Transaction::default(), 5.into(), 10.into(), "Outbid".into());
 
 		assert_eq!(
 			serde_json::to_string(&status1).unwrap(),
@@ -316,7 +317,7 @@ mod tests {
 			serde_json::to_string(&status7).unwrap(),
 			r#"{"status":"replaced","transaction":"#.to_owned() +
 			&format!("{}", tx_ser) +
-			r#","hash":"0x000000000000000000000000000000000000000000000000000000000000000a","gasPrice":"0x5"}"#
+			r#","hash":"0x000000000000000000000000000000000000000000000000000000000000000a","gasPrice":"0x5","reason":"Outbid"}"#
 		);
 	}
 }
@@ -383,14 +383,16 @@ impl ChainNotify for EthSync {
 			};
 
 			let chain_info = self.eth_handler.chain.chain_info();
+			// pruned state and block bodies move the earliest servable blocks forward.
+			let pruning_info = self.eth_handler.chain.pruning_info();
 			light_proto.make_announcement(context, Announcement {
 				head_hash: chain_info.best_block_hash,
 				head_num: chain_info.best_block_number,
 				head_td: chain_info.total_difficulty,
 				reorg_depth: 0, // recalculated on a per-peer basis.
 				serve_headers: false, // these fields consist of _changes_ in capability.
-				serve_state_since: None,
-				serve_chain_since: None,
+				serve_state_since: Some(pruning_info.earliest_state),
+				serve_chain_since: Some(pruning_info.earliest_chain),
 				tx_relay: false,
 			})
 		})
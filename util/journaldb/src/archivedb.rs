@@ -151,6 +151,15 @@ impl JournalDB for ArchiveDB {
 		Ok(0)
 	}
 
+	fn rewind(&mut self, batch: &mut DBTransaction, era: u64) -> Result<u32, UtilError> {
+		// nothing is journalled, only the latest era moves back.
+		if self.latest_era.map_or(false, |e| e > era) {
+			batch.put(self.column, &LATEST_ERA_KEY, &encode(&era));
+			self.latest_era = Some(era);
+		}
+		Ok(0)
+	}
+
 	fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
 		let mut inserts = 0usize;
 		let mut deletes = 0usize;
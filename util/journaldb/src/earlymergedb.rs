@@ -511,6 +511,22 @@ impl JournalDB for EarlyMergeDB {
 		Ok(0)
 	}
 
+	fn rewind(&mut self, batch: &mut DBTransaction, era: u64) -> Result<u32, UtilError> {
+		let latest_era = match self.latest_era {
+			Some(e) if e > era => e,
+			_ => return Ok(0),
+		};
+
+		// no block has a zero hash, so the inserts of every journal entry are reverted.
+		for now in (era + 1)..(latest_era + 1) {
+			self.mark_canonical(batch, now, &H256::zero())?;
+		}
+
+		batch.put(self.column, &LATEST_ERA_KEY, &encode(&era));
+		self.latest_era = Some(era);
+		Ok(0)
+	}
+
 	fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
 		let mut ops = 0;
 		for (key, (value, rc)) in self.overlay.drain() {
@@ -1081,4 +1097,39 @@ mod tests {
 
 		assert!(jdb.get(&key).is_none());
 	}
+
+	#[test]
+	fn rewind() {
+		let mut jdb = new_db();
+
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &keccak(b"0"), None).unwrap();
+		jdb.remove(&foo);
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &keccak(b"1"), None).unwrap();
+		let baz = jdb.insert(b"baz");
+		jdb.commit_batch(2, &keccak(b"2a"), None).unwrap();
+		let qux = jdb.insert(b"qux");
+		jdb.commit_batch(2, &keccak(b"2b"), None).unwrap();
+		assert_eq!(jdb.latest_era(), Some(2));
+
+		let mut batch = jdb.backing().transaction();
+		jdb.rewind(&mut batch, 0).unwrap();
+		jdb.backing().write(batch).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+		assert_eq!(jdb.latest_era(), Some(0));
+		assert!(jdb.contains(&foo));
+		assert!(!jdb.contains(&bar));
+		assert!(!jdb.contains(&baz));
+		assert!(!jdb.contains(&qux));
+
+		// the reverted eras can be journalled again.
+		jdb.remove(&foo);
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &keccak(b"1"), Some((0, keccak(b"0")))).unwrap();
+		jdb.commit_batch(2, &keccak(b"2"), Some((1, keccak(b"1")))).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+		assert!(!jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+	}
 }
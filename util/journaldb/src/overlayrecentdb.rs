@@ -27,7 +27,7 @@ use memorydb::*;
 use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
 use kvdb::{KeyValueDB, DBTransaction};
 use super::JournalDB;
-use bigint::hash::{H256, H256FastMap};
+use bigint::hash::{H256, H256FastMap, H256FastSet};
 use error::{BaseDataError, UtilError};
 use bytes::Bytes;
 
@@ -77,6 +77,7 @@ struct JournalOverlay {
 	latest_era: Option<u64>,
 	earliest_era: Option<u64>,
 	cumulative_size: usize, // cumulative size of all entries.
+	gc_written: Option<H256FastSet>, // Keys written to backing db during a garbage collection
 }
 
 #[derive(PartialEq)]
@@ -193,6 +194,7 @@ impl OverlayRecentDB {
 			latest_era: latest_era,
 			earliest_era: earliest_era,
 			cumulative_size: cumulative_size,
+			gc_written: None,
 		}
 	}
 
@@ -344,6 +346,9 @@ impl JournalDB for OverlayRecentDB {
 			// apply canon inserts first
 			for (k, v) in canon_insertions {
 				batch.put(self.column, &k, &v);
+				if let Some(ref mut written) = journal_overlay.gc_written {
+					written.insert(k.clone());
+				}
 				journal_overlay.pending_overlay.insert(to_short_key(&k), v);
 			}
 			// update the overlay
@@ -369,6 +374,45 @@ impl JournalDB for OverlayRecentDB {
 		Ok(ops as u32)
 	}
 
+	fn rewind(&mut self, batch: &mut DBTransaction, era: u64) -> Result<u32, UtilError> {
+		trace!(target: "journaldb", "rewind: #{}", era);
+
+		let mut journal_overlay = self.journal_overlay.write();
+		let journal_overlay = &mut *journal_overlay;
+		let latest_era = match journal_overlay.latest_era {
+			Some(e) if e > era => e,
+			_ => return Ok(0),
+		};
+
+		let mut ops = 0;
+		for now in (era + 1)..(latest_era + 1) {
+			let records = journal_overlay.journal.remove(&now).unwrap_or_else(Vec::new);
+			for (index, journal) in records.into_iter().enumerate() {
+				let mut r = RlpStream::new_list(3);
+				r.append(&now);
+				r.append(&index);
+				r.append(&&PADDING[..]);
+				batch.delete(self.column, &r.drain());
+				trace!(target: "journaldb", "Revert journal for time #{}.{}: {}, -{} entries", now, index, journal.id, journal.insertions.len());
+
+				ops += journal.insertions.len();
+				for k in journal.insertions {
+					if let Some(val) = journal_overlay.backing_overlay.remove_and_purge(&to_short_key(&k)) {
+						journal_overlay.cumulative_size -= val.len();
+					}
+				}
+			}
+		}
+
+		batch.put_vec(self.column, &LATEST_ERA_KEY, encode(&era).into_vec());
+		journal_overlay.latest_era = Some(era);
+		if journal_overlay.journal.is_empty() {
+			journal_overlay.earliest_era = None;
+		}
+
+		Ok(ops as u32)
+	}
+
 	fn flush(&self) {
 		self.journal_overlay.write().pending_overlay.clear();
 	}
@@ -399,6 +443,36 @@ impl JournalDB for OverlayRecentDB {
 	fn consolidate(&mut self, with: MemoryDB) {
 		self.transaction_overlay.consolidate(with);
 	}
+
+	fn begin_gc(&self) -> bool {
+		let mut journal_overlay = self.journal_overlay.write();
+		if journal_overlay.gc_written.is_some() {
+			return false;
+		}
+		journal_overlay.gc_written = Some(H256FastSet::default());
+		true
+	}
+
+	fn collect_garbage(&self, keys: Vec<H256>) -> Result<usize, UtilError> {
+		// hold the lock until the batch is written, so that the keys can't be
+		// written by `mark_canonical` in the meantime.
+		let journal_overlay = self.journal_overlay.write();
+		let written = journal_overlay.gc_written.as_ref()
+			.expect("collect_garbage is only called between begin_gc and end_gc; qed");
+
+		let mut batch = DBTransaction::new();
+		for key in keys.iter().filter(|k| !written.contains(*k)) {
+			batch.delete(self.column, key);
+		}
+
+		let removed = batch.ops.len();
+		self.backing.write(batch)?;
+		Ok(removed)
+	}
+
+	fn end_gc(&self) {
+		self.journal_overlay.write().gc_written = None;
+	}
 }
 
 impl HashDB for OverlayRecentDB {
@@ -987,6 +1061,30 @@ mod tests {
 		assert!(jdb.get(&key).is_none());
 	}
 
+	#[test]
+	fn garbage_collection_keeps_keys_written_meanwhile() {
+		let mut jdb = new_db();
+
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &keccak(b"0"), None).unwrap();
+		jdb.commit_batch(1, &keccak(b"1"), Some((0, keccak(b"0")))).unwrap();
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(2, &keccak(b"2"), None).unwrap();
+
+		assert!(jdb.begin_gc());
+		assert!(!jdb.begin_gc());
+		jdb.commit_batch(3, &keccak(b"3"), Some((1, keccak(b"1")))).unwrap();
+		jdb.commit_batch(4, &keccak(b"4"), Some((2, keccak(b"2")))).unwrap();
+
+		assert_eq!(jdb.collect_garbage(vec![foo.clone(), bar.clone()]).unwrap(), 1);
+		jdb.end_gc();
+
+		assert!(!jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+		assert!(jdb.can_reconstruct_refs());
+		assert!(jdb.begin_gc());
+	}
+
 	#[test]
 	fn earliest_era() {
 		let shared_db = Arc::new(kvdb_memorydb::create(0));
@@ -1029,4 +1127,39 @@ mod tests {
 		let jdb = OverlayRecentDB::new(shared_db, None);
 		assert_eq!(jdb.earliest_era(), None);
 	}
+
+	#[test]
+	fn rewind() {
+		let mut jdb = new_db();
+
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &keccak(b"0"), None).unwrap();
+		jdb.remove(&foo);
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &keccak(b"1"), None).unwrap();
+		let baz = jdb.insert(b"baz");
+		jdb.commit_batch(2, &keccak(b"2a"), None).unwrap();
+		let qux = jdb.insert(b"qux");
+		jdb.commit_batch(2, &keccak(b"2b"), None).unwrap();
+		assert_eq!(jdb.latest_era(), Some(2));
+
+		let mut batch = jdb.backing().transaction();
+		jdb.rewind(&mut batch, 0).unwrap();
+		jdb.backing().write(batch).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+		assert_eq!(jdb.latest_era(), Some(0));
+		assert!(jdb.contains(&foo));
+		assert!(!jdb.contains(&bar));
+		assert!(!jdb.contains(&baz));
+		assert!(!jdb.contains(&qux));
+
+		// the reverted eras can be journalled again.
+		jdb.remove(&foo);
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &keccak(b"1"), Some((0, keccak(b"0")))).unwrap();
+		jdb.commit_batch(2, &keccak(b"2"), Some((1, keccak(b"1")))).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+		assert!(!jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+	}
 }
@@ -182,6 +182,42 @@ impl JournalDB for RefCountedDB {
 		Ok(r)
 	}
 
+	fn rewind(&mut self, batch: &mut DBTransaction, era: u64) -> Result<u32, UtilError> {
+		let latest_era = match self.latest_era {
+			Some(e) if e > era => e,
+			_ => return Ok(0),
+		};
+
+		// revert the inserts of every journal entry, committing once as the batch isn't written yet.
+		for now in (era + 1)..(latest_era + 1) {
+			let mut index = 0usize;
+			let mut last;
+			while let Some(rlp_data) = {
+				self.backing.get(self.column, {
+					let mut r = RlpStream::new_list(3);
+					r.append(&now);
+					r.append(&index);
+					r.append(&&PADDING[..]);
+					last = r.drain();
+					&last
+				})?
+			} {
+				let inserts: Vec<H256> = Rlp::new(&rlp_data).list_at(1);
+				trace!(target: "rcdb", "revert journal for time #{}.{}: deleting {:?}", now, index, inserts);
+				for i in &inserts {
+					self.forward.remove(i);
+				}
+				batch.delete(self.column, &last);
+				index += 1;
+			}
+		}
+
+		batch.put(self.column, &LATEST_ERA_KEY, &encode(&era));
+		self.latest_era = Some(era);
+		let r = self.forward.commit_to_batch(batch)?;
+		Ok(r)
+	}
+
 	fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
 		self.inserts.clear();
 		for remove in self.removes.drain(..) {
@@ -333,4 +369,37 @@ mod tests {
 
 		assert!(jdb.get(&key).is_none());
 	}
+
+	#[test]
+	fn rewind() {
+		let mut jdb = new_db();
+
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &keccak(b"0"), None).unwrap();
+		jdb.remove(&foo);
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &keccak(b"1"), None).unwrap();
+		let baz = jdb.insert(b"baz");
+		jdb.commit_batch(2, &keccak(b"2a"), None).unwrap();
+		let qux = jdb.insert(b"qux");
+		jdb.commit_batch(2, &keccak(b"2b"), None).unwrap();
+		assert_eq!(jdb.latest_era(), Some(2));
+
+		let mut batch = jdb.backing().transaction();
+		jdb.rewind(&mut batch, 0).unwrap();
+		jdb.backing().write(batch).unwrap();
+		assert_eq!(jdb.latest_era(), Some(0));
+		assert!(jdb.contains(&foo));
+		assert!(!jdb.contains(&bar));
+		assert!(!jdb.contains(&baz));
+		assert!(!jdb.contains(&qux));
+
+		// the reverted eras can be journalled again.
+		jdb.remove(&foo);
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &keccak(b"1"), Some((0, keccak(b"0")))).unwrap();
+		jdb.commit_batch(2, &keccak(b"2"), Some((1, keccak(b"1")))).unwrap();
+		assert!(!jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+	}
 }
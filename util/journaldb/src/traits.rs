@@ -53,6 +53,10 @@ pub trait JournalDB: HashDB {
 	/// Mark a given block as canonical, indicating that competing blocks' states may be pruned out.
 	fn mark_canonical(&mut self, batch: &mut DBTransaction, era: u64, id: &H256) -> Result<u32, UtilError>;
 
+	/// Revert the journalled operations of all eras after `era`, on every fork, and make `era`
+	/// the latest one. The states of `era` and of the earlier eras still in the journal are kept.
+	fn rewind(&mut self, batch: &mut DBTransaction, era: u64) -> Result<u32, UtilError>;
+
 	/// Commit all queued insert and delete operations without affecting any journalling -- this requires that all insertions
 	/// and deletions are indeed canonical and will likely lead to an invalid database if that assumption is violated.
 	///
@@ -78,6 +82,18 @@ pub trait JournalDB: HashDB {
 	/// Consolidate all the insertions and deletions in the given memory overlay.
 	fn consolidate(&mut self, overlay: ::memorydb::MemoryDB);
 
+	/// Start a garbage collection of the backing database. Keys written from now on
+	/// are never removed by `collect_garbage`. Returns `false` if it's not supported
+	/// or a garbage collection is already in progress.
+	fn begin_gc(&self) -> bool { false }
+
+	/// Remove given keys from the backing database, apart from those written since `begin_gc`.
+	/// Returns the number of removed keys.
+	fn collect_garbage(&self, _keys: Vec<H256>) -> Result<usize, UtilError> { Ok(0) }
+
+	/// Finish the garbage collection started with `begin_gc`.
+	fn end_gc(&self) {}
+
 	/// Commit all changes in a single batch
 	#[cfg(test)]
 	fn commit_batch(&mut self, now: u64, id: &H256, end: Option<(u64, H256)>) -> Result<u32, UtilError> {
@@ -0,0 +1,1035 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Append-only key-value database.
+//!
+//! All columns share a single log file. Each flush appends the changed values followed by a
+//! commit record holding a checksum of them, so the changes of a flush are either all found
+//! after a crash or not at all. An in-memory index maps every key to the position of its
+//! most recent value, so reads take a single seek. Once stale records take more space than
+//! the live ones the log is rewritten in key order.
+
+#[macro_use]
+extern crate log;
+
+extern crate elastic_array;
+extern crate parking_lot;
+
+extern crate kvdb;
+extern crate rlp;
+
+use std::collections::{BTreeMap, HashMap, VecDeque, Bound};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::mem;
+
+use parking_lot::{Mutex, RwLock};
+use elastic_array::ElasticArray32;
+use rlp::{UntrustedRlp, RlpType, Compressible};
+use kvdb::{KeyValueDB, DBTransaction, DBValue, DBOp, IoCounters, IoStats, Result};
+
+/// Name of the file which marks a directory as a database of this backend.
+pub const METADATA_FILE: &'static str = "PARITYDB";
+
+/// Version of the on-disk format.
+const FORMAT_VERSION: u32 = 2;
+/// Name of the log file.
+const LOG_FILE: &'static str = "db.log";
+/// Record layout: tag (1 byte), column (1 byte), key length (4 bytes LE), value length (4 bytes LE), key, value.
+const RECORD_HEADER_LEN: u64 = 10;
+const TAG_INSERT: u8 = 0;
+const TAG_DELETE: u8 = 1;
+/// Ends the records of a flush. Its value is the checksum of those records.
+const TAG_COMMIT: u8 = 2;
+/// Number of entries fetched from the index at once while iterating or compacting.
+const ITER_BATCH_SIZE: usize = 256;
+
+/// Database configuration
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+	/// Set number of columns
+	pub columns: Option<u32>,
+	/// Amount of stale data (in bytes) the log may hold before it is compacted.
+	pub compaction_threshold: u64,
+	/// Should the log be synced to disk on every flush?
+	pub sync: bool,
+	/// Open an existing database without ever writing to it.
+	pub read_only: bool,
+}
+
+impl DatabaseConfig {
+	/// Create new `DatabaseConfig` with default parameters and specified set of columns.
+	pub fn with_columns(columns: Option<u32>) -> Self {
+		let mut config = Self::default();
+		config.columns = columns;
+		config
+	}
+}
+
+impl Default for DatabaseConfig {
+	fn default() -> DatabaseConfig {
+		DatabaseConfig {
+			columns: None,
+			compaction_threshold: 64 * 1024 * 1024,
+			sync: true,
+			read_only: false,
+		}
+	}
+}
+
+enum KeyState {
+	Insert(DBValue),
+	InsertCompressed(DBValue),
+	Delete,
+}
+
+/// Location of a value within the log.
+#[derive(Debug, Clone, Copy)]
+struct ValuePos {
+	offset: u64,
+	len: u32,
+}
+
+type Index = BTreeMap<Vec<u8>, ValuePos>;
+
+fn encode_u32(value: u32) -> [u8; 4] {
+	[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8]
+}
+
+fn decode_u32(bytes: &[u8]) -> u32 {
+	bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24
+}
+
+fn record_len(key_len: usize, value_len: usize) -> u64 {
+	RECORD_HEADER_LEN + key_len as u64 + value_len as u64
+}
+
+/// FNV-1a checksum of the records of a commit.
+#[derive(Clone, Copy)]
+struct Checksum(u64);
+
+impl Checksum {
+	fn new() -> Self {
+		Checksum(0xcbf2_9ce4_8422_2325)
+	}
+
+	fn update(&mut self, data: &[u8]) {
+		for byte in data {
+			self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+
+	fn to_bytes(&self) -> [u8; 8] {
+		let mut bytes = [0u8; 8];
+		for (i, byte) in bytes.iter_mut().enumerate() {
+			*byte = (self.0 >> (8 * i)) as u8;
+		}
+		bytes
+	}
+}
+
+fn write_record<W: Write>(w: &mut W, checksum: &mut Checksum, tag: u8, col: u8, key: &[u8], value: &[u8]) -> io::Result<()> {
+	let mut header = [0u8; RECORD_HEADER_LEN as usize];
+	header[0] = tag;
+	header[1] = col;
+	header[2..6].copy_from_slice(&encode_u32(key.len() as u32));
+	header[6..10].copy_from_slice(&encode_u32(value.len() as u32));
+	checksum.update(&header);
+	checksum.update(key);
+	checksum.update(value);
+	w.write_all(&header)?;
+	w.write_all(key)?;
+	w.write_all(value)
+}
+
+fn write_commit<W: Write>(w: &mut W, checksum: &Checksum) -> io::Result<()> {
+	write_record(w, &mut Checksum::new(), TAG_COMMIT, 0, &[], &checksum.to_bytes())
+}
+
+/// Insert or remove the value of a key, keeping track of the size of the live records.
+fn update_index(columns: &mut [Index], live: &mut u64, col: usize, key: Vec<u8>, pos: Option<ValuePos>) {
+	let key_len = key.len();
+	let old = match pos {
+		Some(pos) => {
+			*live += record_len(key_len, pos.len as usize);
+			columns[col].insert(key, pos)
+		},
+		None => columns[col].remove(&key),
+	};
+	if let Some(old) = old {
+		*live -= record_len(key_len, old.len as usize);
+	}
+}
+
+/// Replay the records read from `reader`, which starts at `offset` in the log and is `len`
+/// bytes long, into the index. Only complete commits with a matching checksum are applied.
+/// Returns the position in the log right after the last of them.
+fn replay<R: Read>(reader: R, offset: u64, len: u64, columns: &mut [Index], live: &mut u64) -> io::Result<u64> {
+	let mut reader = BufReader::new(reader);
+	let end = offset + len;
+	let mut position = offset;
+	let mut committed = offset;
+	let mut checksum = Checksum::new();
+	let mut pending = Vec::new();
+	let mut header = [0u8; RECORD_HEADER_LEN as usize];
+
+	while position + RECORD_HEADER_LEN <= end {
+		reader.read_exact(&mut header)?;
+		let (tag, col) = (header[0], header[1] as usize);
+		let key_len = decode_u32(&header[2..6]) as usize;
+		let value_len = decode_u32(&header[6..10]) as usize;
+		let record_end = position + record_len(key_len, value_len);
+		if tag > TAG_COMMIT || col >= columns.len() || record_end > end {
+			break;
+		}
+
+		let mut key = vec![0u8; key_len];
+		reader.read_exact(&mut key)?;
+		let mut value = vec![0u8; value_len];
+		reader.read_exact(&mut value)?;
+
+		match tag {
+			TAG_COMMIT => {
+				if value[..] != checksum.to_bytes()[..] {
+					break;
+				}
+				for (col, key, pos) in pending.drain(..) {
+					update_index(columns, live, col, key, pos);
+				}
+				checksum = Checksum::new();
+				committed = record_end;
+			},
+			_ => {
+				checksum.update(&header);
+				checksum.update(&key);
+				checksum.update(&value);
+				let pos = match tag {
+					TAG_INSERT => Some(ValuePos { offset: position + RECORD_HEADER_LEN + key_len as u64, len: value_len as u32 }),
+					_ => None,
+				};
+				pending.push((col, key, pos));
+			},
+		}
+		position = record_end;
+	}
+
+	Ok(committed)
+}
+
+/// The log file along with the index of the live values of every column.
+struct Log {
+	path: PathBuf,
+	file: Mutex<File>,
+	/// Index of every column, the default column first.
+	columns: Vec<Index>,
+	/// Size of the log.
+	size: u64,
+	/// Size of the records which are still referenced by the index.
+	live: u64,
+}
+
+impl Log {
+	/// Open the log at given path, rebuilding the index.
+	/// Anything following the last complete commit is discarded, or only ignored when `read_only`.
+	fn open(path: PathBuf, num_columns: usize, read_only: bool) -> io::Result<Log> {
+		let file = match read_only {
+			true => OpenOptions::new().read(true).open(&path)?,
+			false => OpenOptions::new().read(true).append(true).create(true).open(&path)?,
+		};
+		let file_len = file.metadata()?.len();
+		let mut columns = vec![Index::new(); num_columns];
+		let mut live = 0u64;
+		let size = replay(&file, 0, file_len, &mut columns, &mut live)?;
+
+		if size != file_len && read_only {
+			warn!(target: "paritydb", "Ignoring {} bytes of incomplete commits at the end of {}", file_len - size, path.display());
+		} else if size != file_len {
+			warn!(target: "paritydb", "Discarding {} bytes of incomplete commits at the end of {}", file_len - size, path.display());
+			file.set_len(size)?;
+		}
+
+		Ok(Log {
+			path: path,
+			file: Mutex::new(file),
+			columns: columns,
+			size: size,
+			live: live,
+		})
+	}
+
+	fn read(&self, pos: &ValuePos) -> io::Result<DBValue> {
+		let mut value = vec![0u8; pos.len as usize];
+		let mut file = self.file.lock();
+		file.seek(SeekFrom::Start(pos.offset))?;
+		file.read_exact(&mut value)?;
+		Ok(DBValue::from_vec(value))
+	}
+
+	fn read_range(&self, from: u64, to: u64) -> io::Result<Vec<u8>> {
+		let mut data = vec![0u8; (to - from) as usize];
+		let mut file = self.file.lock();
+		file.seek(SeekFrom::Start(from))?;
+		file.read_exact(&mut data)?;
+		Ok(data)
+	}
+
+	fn get(&self, col: usize, key: &[u8]) -> io::Result<Option<DBValue>> {
+		match self.columns[col].get(key) {
+			Some(pos) => self.read(pos).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// Append the changes of every column to the log as a single commit and update the index.
+	/// Returns whether the log holds enough stale data to be compacted.
+	fn apply(&mut self, changes: &[HashMap<ElasticArray32<u8>, KeyState>], config: &DatabaseConfig) -> io::Result<bool> {
+		let mut buffer = Vec::new();
+		let mut checksum = Checksum::new();
+		let mut updates = Vec::new();
+		for (col, changes) in changes.iter().enumerate() {
+			for (key, state) in changes {
+				let offset = self.size + buffer.len() as u64 + RECORD_HEADER_LEN + key.len() as u64;
+				match *state {
+					KeyState::Insert(ref value) => {
+						write_record(&mut buffer, &mut checksum, TAG_INSERT, col as u8, key, value)?;
+						updates.push((col, key, Some(ValuePos { offset: offset, len: value.len() as u32 })));
+					},
+					KeyState::InsertCompressed(ref value) => {
+						let compressed = UntrustedRlp::new(value).compress(RlpType::Blocks);
+						write_record(&mut buffer, &mut checksum, TAG_INSERT, col as u8, key, &compressed)?;
+						updates.push((col, key, Some(ValuePos { offset: offset, len: compressed.len() as u32 })));
+					},
+					KeyState::Delete => {
+						if self.columns[col].contains_key(&**key) {
+							write_record(&mut buffer, &mut checksum, TAG_DELETE, col as u8, key, &[])?;
+							updates.push((col, key, None));
+						}
+					},
+				}
+			}
+		}
+
+		if updates.is_empty() {
+			return Ok(false);
+		}
+		write_commit(&mut buffer, &checksum)?;
+
+		{
+			let mut file = self.file.lock();
+			let written = file.write_all(&buffer).and_then(|_| match config.sync {
+				true => file.sync_data(),
+				false => Ok(()),
+			});
+			if let Err(e) = written {
+				// drop whatever made it to the file so that the next commit starts where the index expects it.
+				if let Err(e) = file.set_len(self.size) {
+					warn!(target: "paritydb", "Failed to truncate {} after a failed write: {}", self.path.display(), e);
+				}
+				return Err(e);
+			}
+		}
+		self.size += buffer.len() as u64;
+
+		for (col, key, pos) in updates {
+			update_index(&mut self.columns, &mut self.live, col, key.to_vec(), pos);
+		}
+
+		let stale = self.size - self.live;
+		Ok(stale > config.compaction_threshold && stale > self.live)
+	}
+}
+
+/// Database iterator (for flushed data only)
+///
+/// Entries are fetched from the index in small batches, so changes flushed while iterating
+/// may or may not be visible.
+pub struct DatabaseIterator<'a> {
+	db: &'a Database,
+	column: usize,
+	from: Bound<Vec<u8>>,
+	buffer: VecDeque<(Box<[u8]>, Box<[u8]>)>,
+	done: bool,
+}
+
+impl<'a> DatabaseIterator<'a> {
+	fn fill(&mut self) {
+		let log = self.db.log.read();
+		let log = match *log {
+			Some(ref log) if self.column < log.columns.len() => log,
+			_ => {
+				self.done = true;
+				return;
+			}
+		};
+
+		let mut last = None;
+		for (key, pos) in log.columns[self.column].range((self.from.clone(), Bound::Unbounded)).take(ITER_BATCH_SIZE) {
+			match log.read(pos) {
+				Ok(value) => self.buffer.push_back((key.clone().into_boxed_slice(), value.into_vec().into_boxed_slice())),
+				Err(e) => warn!(target: "paritydb", "Error reading {}: {}", log.path.display(), e),
+			}
+			last = Some(key.clone());
+		}
+
+		match last {
+			Some(key) => self.from = Bound::Excluded(key),
+			None => self.done = true,
+		}
+	}
+}
+
+impl<'a> Iterator for DatabaseIterator<'a> {
+	type Item = (Box<[u8]>, Box<[u8]>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.buffer.is_empty() && !self.done {
+			self.fill();
+		}
+		self.buffer.pop_front()
+	}
+}
+
+/// Key-Value database.
+pub struct Database {
+	config: DatabaseConfig,
+	path: PathBuf,
+	// The log and its index. `None` when the database is closed.
+	log: RwLock<Option<Log>>,
+	// Dirty values added with `write_buffered`. Cleaned on `flush`.
+	overlay: RwLock<Vec<HashMap<ElasticArray32<u8>, KeyState>>>,
+	// Values currently being flushed. Cleared when `flush` completes.
+	flushing: RwLock<Vec<HashMap<ElasticArray32<u8>, KeyState>>>,
+	// Prevents concurrent flushes.
+	flushing_lock: Mutex<()>,
+	// Prevents concurrent compactions, and closing the database during one.
+	compaction_lock: Mutex<()>,
+	// I/O statistics.
+	io_counters: IoCounters,
+}
+
+impl Database {
+	/// Open database with default settings.
+	pub fn open_default(path: &str) -> Result<Database> {
+		Database::open(&DatabaseConfig::default(), path)
+	}
+
+	/// Open database file. Creates if it does not exist.
+	pub fn open(config: &DatabaseConfig, path: &str) -> Result<Database> {
+		let path = PathBuf::from(path);
+		let num_cols = config.columns.unwrap_or(0) as usize;
+		if num_cols > u8::max_value() as usize {
+			return Err(format!("paritydb supports at most {} columns", u8::max_value()).into());
+		}
+
+		if !config.read_only {
+			fs::create_dir_all(&path)?;
+		}
+		Self::check_metadata(&path, config.read_only)?;
+		let log = Log::open(path.join(LOG_FILE), num_cols + 1, config.read_only)?;
+
+		Ok(Database {
+			config: config.clone(),
+			path: path,
+			log: RwLock::new(Some(log)),
+			overlay: RwLock::new((0..(num_cols + 1)).map(|_| HashMap::new()).collect()),
+			flushing: RwLock::new((0..(num_cols + 1)).map(|_| HashMap::new()).collect()),
+			flushing_lock: Mutex::new(()),
+			compaction_lock: Mutex::new(()),
+			io_counters: IoCounters::default(),
+		})
+	}
+
+	/// Check that the directory holds a database of this backend and version.
+	/// Unless `read_only`, writes the metadata file into empty directories.
+	fn check_metadata(path: &Path, read_only: bool) -> Result<()> {
+		let metadata_path = path.join(METADATA_FILE);
+		let mut file = match File::open(&metadata_path) {
+			Ok(file) => file,
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound && read_only => {
+				return Err(format!("{} does not contain a paritydb database", path.display()).into());
+			},
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+				if fs::read_dir(path)?.next().is_some() {
+					return Err(format!("{} is not empty and does not contain a paritydb database", path.display()).into());
+				}
+				let mut file = File::create(&metadata_path)?;
+				file.write_all(format!("{}", FORMAT_VERSION).as_bytes())?;
+				file.sync_all()?;
+				return Ok(());
+			},
+			Err(e) => return Err(e.into()),
+		};
+
+		let mut version = String::new();
+		file.read_to_string(&mut version)?;
+		match version.trim().parse::<u32>() {
+			Ok(FORMAT_VERSION) => Ok(()),
+			_ => Err(format!("Unsupported paritydb format version: {}", version.trim()).into()),
+		}
+	}
+
+	/// Helper to create new transaction for this database.
+	pub fn transaction(&self) -> DBTransaction {
+		DBTransaction::new()
+	}
+
+	fn to_overlay_column(col: Option<u32>) -> usize {
+		col.map_or(0, |c| (c + 1) as usize)
+	}
+
+	/// Commit transaction to database.
+	pub fn write_buffered(&self, tr: DBTransaction) {
+		self.io_counters.tally_transaction(&tr);
+		let mut overlay = self.overlay.write();
+		for op in tr.ops {
+			match op {
+				DBOp::Insert { col, key, value } => {
+					let c = Self::to_overlay_column(col);
+					overlay[c].insert(key, KeyState::Insert(value));
+				},
+				DBOp::InsertCompressed { col, key, value } => {
+					let c = Self::to_overlay_column(col);
+					overlay[c].insert(key, KeyState::InsertCompressed(value));
+				},
+				DBOp::Delete { col, key } => {
+					let c = Self::to_overlay_column(col);
+					overlay[c].insert(key, KeyState::Delete);
+				},
+			}
+		}
+	}
+
+	/// Commit buffered changes to database.
+	///
+	/// All of them are written as a single commit of the log, so the transactions
+	/// being flushed either all survive a crash or none of them does.
+	pub fn flush(&self) -> Result<()> {
+		if self.config.read_only {
+			return match self.overlay.read().iter().all(HashMap::is_empty) {
+				true => Ok(()),
+				false => Err(format!("{} is opened read-only", self.path.display()).into()),
+			};
+		}
+
+		let compact = {
+			let _lock = self.flushing_lock.lock();
+			mem::swap(&mut *self.overlay.write(), &mut *self.flushing.write());
+			let compact = {
+				let flushing = self.flushing.read();
+				match *self.log.write() {
+					Some(ref mut log) => log.apply(&flushing, &self.config)?,
+					None => false,
+				}
+			};
+			for column in self.flushing.write().iter_mut() {
+				column.clear();
+				column.shrink_to_fit();
+			}
+			compact
+		};
+
+		if compact {
+			self.compact_log()?;
+		}
+		Ok(())
+	}
+
+	/// Commit transaction to database.
+	pub fn write(&self, tr: DBTransaction) -> Result<()> {
+		self.write_buffered(tr);
+		self.flush()
+	}
+
+	/// Get value by key.
+	pub fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<DBValue>> {
+		let c = Self::to_overlay_column(col);
+		for pending in &[&self.overlay, &self.flushing] {
+			match pending.read().get(c).and_then(|changes| changes.get(key)) {
+				Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => {
+					self.io_counters.tally_cache_read();
+					return Ok(Some(value.clone()));
+				},
+				Some(&KeyState::Delete) => return Ok(None),
+				None => {},
+			}
+		}
+
+		match *self.log.read() {
+			Some(ref log) if c < log.columns.len() => {
+				let value = log.get(c, key)?;
+				self.io_counters.tally_read(value.as_ref().map_or(0, |v| v.len()));
+				Ok(value)
+			},
+			_ => Err(format!("No such column family: {:?}", col).into()),
+		}
+	}
+
+	/// Get value by partial key. Only searches flushed values.
+	pub fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		self.iter_from_prefix(col, prefix).next().and_then(|(k, v)| {
+			if k.starts_with(prefix) { Some(v) } else { None }
+		})
+	}
+
+	/// Get database iterator for flushed data.
+	pub fn iter(&self, col: Option<u32>) -> DatabaseIterator {
+		self.iter_from(col, Bound::Unbounded)
+	}
+
+	/// Get database iterator for flushed data, starting from the given prefix.
+	pub fn iter_from_prefix(&self, col: Option<u32>, prefix: &[u8]) -> DatabaseIterator {
+		self.iter_from(col, Bound::Included(prefix.to_vec()))
+	}
+
+	fn iter_from(&self, col: Option<u32>, from: Bound<Vec<u8>>) -> DatabaseIterator {
+		DatabaseIterator {
+			db: self,
+			column: Self::to_overlay_column(col),
+			from: from,
+			buffer: VecDeque::new(),
+			done: false,
+		}
+	}
+
+	/// Flush and rewrite the log, dropping stale records.
+	pub fn compact(&self) -> Result<()> {
+		if self.config.read_only {
+			return Ok(());
+		}
+		self.flush()?;
+		self.compact_log()
+	}
+
+	/// Rewrite the log keeping only the live records.
+	///
+	/// The live values are copied a batch at a time, so reads and flushes go on meanwhile.
+	/// The commits flushed since the copy started are then appended to it as they are,
+	/// and the new log replaces the old one. Only that last step blocks the database.
+	fn compact_log(&self) -> Result<()> {
+		let _lock = self.compaction_lock.lock();
+		let (path, num_columns, start) = match *self.log.read() {
+			Some(ref log) => (log.path.clone(), log.columns.len(), log.size),
+			None => return Ok(()),
+		};
+		debug!(target: "paritydb", "Compacting {} ({} bytes)", path.display(), start);
+
+		let temp_path = path.with_extension("compact");
+		let mut writer = BufWriter::new(File::create(&temp_path)?);
+		let mut checksum = Checksum::new();
+		let mut columns = vec![Index::new(); num_columns];
+		let mut offset = 0u64;
+
+		for col in 0..num_columns {
+			let mut from = Bound::Unbounded;
+			loop {
+				let values = match *self.log.read() {
+					Some(ref log) => log.columns[col].range((from.clone(), Bound::Unbounded))
+						.take(ITER_BATCH_SIZE)
+						.map(|(key, pos)| log.read(pos).map(|value| (key.clone(), value)))
+						.collect::<io::Result<Vec<_>>>()?,
+					None => Vec::new(),
+				};
+				match values.last() {
+					Some(&(ref key, _)) => from = Bound::Excluded(key.clone()),
+					None => break,
+				}
+
+				for (key, value) in values {
+					write_record(&mut writer, &mut checksum, TAG_INSERT, col as u8, &key, &value)?;
+					let pos = ValuePos { offset: offset + RECORD_HEADER_LEN + key.len() as u64, len: value.len() as u32 };
+					offset += record_len(key.len(), value.len());
+					columns[col].insert(key, pos);
+				}
+			}
+		}
+		write_commit(&mut writer, &checksum)?;
+		offset += record_len(0, 8);
+		let mut live = offset;
+
+		let mut guard = self.log.write();
+		let log = match guard.as_mut() {
+			Some(log) => log,
+			None => {
+				let _ = fs::remove_file(&temp_path);
+				return Ok(());
+			},
+		};
+
+		// commits flushed while copying. Their changes may or may not have been copied already.
+		let tail = log.read_range(start, log.size)?;
+		writer.write_all(&tail)?;
+		writer.flush()?;
+		writer.get_ref().sync_all()?;
+		let size = replay(Cursor::new(&tail[..]), offset, tail.len() as u64, &mut columns, &mut live)?;
+		debug_assert_eq!(size, offset + tail.len() as u64);
+
+		fs::rename(&temp_path, &log.path)?;
+		*log.file.get_mut() = OpenOptions::new().read(true).append(true).open(&log.path)?;
+		log.columns = columns;
+		log.size = size;
+		log.live = live;
+		debug!(target: "paritydb", "Compacted {} to {} bytes", log.path.display(), size);
+		Ok(())
+	}
+
+	/// Close the database
+	fn close(&self) {
+		let _lock = self.compaction_lock.lock();
+		*self.log.write() = None;
+		self.overlay.write().clear();
+		self.flushing.write().clear();
+	}
+
+	/// Restore the database from a copy at given path.
+	pub fn restore(&self, new_db: &str) -> Result<()> {
+		self.close();
+
+		let mut backup_db = self.path.clone();
+		backup_db.pop();
+		backup_db.push("backup_db");
+
+		let existed = match fs::rename(&self.path, &backup_db) {
+			Ok(_) => true,
+			Err(e) => if let io::ErrorKind::NotFound = e.kind() {
+				false
+			} else {
+				return Err(e.into());
+			}
+		};
+
+		match fs::rename(&new_db, &self.path) {
+			Ok(_) => {
+				// clean up the backup.
+				if existed {
+					fs::remove_dir_all(&backup_db)?;
+				}
+			}
+			Err(e) => {
+				// restore the backup.
+				if existed {
+					fs::rename(&backup_db, &self.path)?;
+				}
+				return Err(e.into())
+			}
+		}
+
+		// reopen the database and steal its log
+		let db = Self::open(&self.config, &self.path.to_string_lossy())?;
+		*self.log.write() = db.log.write().take();
+		*self.overlay.write() = mem::replace(&mut *db.overlay.write(), Vec::new());
+		*self.flushing.write() = mem::replace(&mut *db.flushing.write(), Vec::new());
+		Ok(())
+	}
+
+	/// The number of non-default columns.
+	pub fn num_columns(&self) -> u32 {
+		self.log.read().as_ref().map_or(0, |log| log.columns.len().saturating_sub(1) as u32)
+	}
+}
+
+// duplicate declaration of methods here to avoid trait import in certain existing cases
+// at time of addition.
+impl KeyValueDB for Database {
+	fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<DBValue>> {
+		Database::get(self, col, key)
+	}
+
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		Database::get_by_prefix(self, col, prefix)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		Database::write_buffered(self, transaction)
+	}
+
+	fn write(&self, transaction: DBTransaction) -> Result<()> {
+		Database::write(self, transaction)
+	}
+
+	fn flush(&self) -> Result<()> {
+		Database::flush(self)
+	}
+
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(Database::iter(self, col))
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: Option<u32>, prefix: &'a [u8])
+		-> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>
+	{
+		Box::new(Database::iter_from_prefix(self, col, prefix))
+	}
+
+	fn restore(&self, new_db: &str) -> Result<()> {
+		Database::restore(self, new_db)
+	}
+
+	fn io_stats(&self) -> IoStats {
+		self.io_counters.stats()
+	}
+}
+
+impl Drop for Database {
+	fn drop(&mut self) {
+		// write all buffered changes if we can.
+		let _ = self.flush();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use self::tempdir::TempDir;
+	use super::*;
+
+	fn open(tempdir: &TempDir, columns: Option<u32>) -> Database {
+		Database::open(&DatabaseConfig::with_columns(columns), tempdir.path().to_str().unwrap()).unwrap()
+	}
+
+	#[test]
+	fn kvdb() {
+		let tempdir = TempDir::new("").unwrap();
+		let db = open(&tempdir, None);
+		let key1 = [2u8; 32];
+		let key2 = [3u8; 32];
+		let key3 = [1u8; 32];
+
+		let mut batch = db.transaction();
+		batch.put(None, &key1, b"cat");
+		batch.put(None, &key2, b"dog");
+		db.write(batch).unwrap();
+
+		assert_eq!(&*db.get(None, &key1).unwrap().unwrap(), b"cat");
+
+		let contents: Vec<_> = db.iter(None).collect();
+		assert_eq!(contents.len(), 2);
+		assert_eq!(&*contents[0].0, &key1[..]);
+		assert_eq!(&*contents[0].1, b"cat");
+		assert_eq!(&*contents[1].0, &key2[..]);
+		assert_eq!(&*contents[1].1, b"dog");
+
+		let mut batch = db.transaction();
+		batch.delete(None, &key1);
+		batch.put(None, &key3, b"elephant");
+		db.write(batch).unwrap();
+		assert!(db.get(None, &key1).unwrap().is_none());
+		assert_eq!(&*db.get(None, &key3).unwrap().unwrap(), b"elephant");
+		assert_eq!(&*db.get_by_prefix(None, &key3[..4]).unwrap(), b"elephant");
+		assert_eq!(&*db.get_by_prefix(None, &key2[..4]).unwrap(), b"dog");
+		assert!(db.get_by_prefix(None, &key1[..4]).is_none());
+
+		let mut batch = db.transaction();
+		batch.put(None, &key1, b"horse");
+		batch.delete(None, &key3);
+		db.write_buffered(batch);
+		assert!(db.get(None, &key3).unwrap().is_none());
+		assert_eq!(&*db.get(None, &key1).unwrap().unwrap(), b"horse");
+
+		db.flush().unwrap();
+		assert!(db.get(None, &key3).unwrap().is_none());
+		assert_eq!(&*db.get(None, &key1).unwrap().unwrap(), b"horse");
+	}
+
+	#[test]
+	fn should_persist_columns_across_reopen() {
+		let tempdir = TempDir::new("").unwrap();
+		{
+			let db = open(&tempdir, Some(2));
+			let mut batch = db.transaction();
+			batch.put(Some(0), b"key", b"zero");
+			batch.put(Some(1), b"key", b"one");
+			batch.put(Some(1), b"other", b"value");
+			db.write(batch).unwrap();
+			let mut batch = db.transaction();
+			batch.delete(Some(1), b"other");
+			db.write_buffered(batch);
+		}
+
+		let db = open(&tempdir, Some(2));
+		assert_eq!(db.num_columns(), 2);
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"zero");
+		assert_eq!(&*db.get(Some(1), b"key").unwrap().unwrap(), b"one");
+		assert!(db.get(Some(1), b"other").unwrap().is_none());
+		assert!(db.get(None, b"key").unwrap().is_none());
+		assert!(db.get(Some(2), b"key").is_err());
+	}
+
+	#[test]
+	fn should_iterate_from_prefix_in_batches() {
+		let tempdir = TempDir::new("").unwrap();
+		let db = open(&tempdir, None);
+		let mut batch = db.transaction();
+		for i in 0..1000u32 {
+			batch.put(None, &encode_u32(i.to_be()), &encode_u32(i));
+		}
+		db.write(batch).unwrap();
+
+		assert_eq!(db.iter(None).count(), 1000);
+		let from = encode_u32(600u32.to_be());
+		let rest: Vec<_> = db.iter_from_prefix(None, &from).collect();
+		assert_eq!(rest.len(), 400);
+		assert_eq!(&*rest[0].0, &from[..]);
+	}
+
+	#[test]
+	fn should_compact_stale_records() {
+		let tempdir = TempDir::new("").unwrap();
+		let mut config = DatabaseConfig::default();
+		config.compaction_threshold = 1024;
+		let path = tempdir.path().to_str().unwrap();
+		{
+			let db = Database::open(&config, path).unwrap();
+			for i in 0..100u8 {
+				let mut batch = db.transaction();
+				batch.put(None, b"key", &[i; 64]);
+				db.write(batch).unwrap();
+			}
+			assert!(db.log.read().as_ref().unwrap().size < 1024 + 2 * (record_len(3, 64) + record_len(0, 8)));
+			assert_eq!(&*db.get(None, b"key").unwrap().unwrap(), &[99u8; 64][..]);
+		}
+
+		let db = Database::open(&config, path).unwrap();
+		assert_eq!(&*db.get(None, b"key").unwrap().unwrap(), &[99u8; 64][..]);
+	}
+
+	#[test]
+	fn should_discard_incomplete_commit() {
+		let tempdir = TempDir::new("").unwrap();
+		{
+			let db = open(&tempdir, Some(2));
+			let mut batch = db.transaction();
+			batch.put(Some(0), b"key", b"value");
+			db.write(batch).unwrap();
+		}
+
+		// simulate a crash in the middle of writing a transaction spanning two columns.
+		let log = tempdir.path().join(LOG_FILE);
+		let committed = fs::metadata(&log).unwrap().len();
+		{
+			let mut file = OpenOptions::new().append(true).open(&log).unwrap();
+			let mut checksum = Checksum::new();
+			write_record(&mut file, &mut checksum, TAG_INSERT, 1, b"key", b"new value").unwrap();
+			write_record(&mut file, &mut checksum, TAG_INSERT, 2, b"key", b"other value").unwrap();
+			write_commit(&mut file, &checksum).unwrap();
+			let len = file.metadata().unwrap().len();
+			file.set_len(len - 2).unwrap();
+		}
+
+		let db = open(&tempdir, Some(2));
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"value");
+		assert!(db.get(Some(1), b"key").unwrap().is_none());
+		assert_eq!(fs::metadata(&log).unwrap().len(), committed);
+	}
+
+	#[test]
+	fn should_discard_commit_with_bad_checksum() {
+		let tempdir = TempDir::new("").unwrap();
+		let log = tempdir.path().join(LOG_FILE);
+		{
+			let db = open(&tempdir, Some(1));
+			let mut batch = db.transaction();
+			batch.put(Some(0), b"key", b"value");
+			db.write(batch).unwrap();
+		}
+		let committed = fs::metadata(&log).unwrap().len();
+		{
+			let mut file = OpenOptions::new().append(true).open(&log).unwrap();
+			let mut checksum = Checksum::new();
+			write_record(&mut file, &mut checksum, TAG_INSERT, 1, b"key", b"new value").unwrap();
+			write_commit(&mut file, &Checksum::new()).unwrap();
+		}
+
+		let db = open(&tempdir, Some(1));
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"value");
+		assert_eq!(fs::metadata(&log).unwrap().len(), committed);
+	}
+
+	#[test]
+	fn should_keep_commits_flushed_during_compaction() {
+		use std::sync::Arc;
+		use std::thread;
+
+		let tempdir = TempDir::new("").unwrap();
+		let db = Arc::new(open(&tempdir, Some(1)));
+		let writer = {
+			let db = db.clone();
+			thread::spawn(move || {
+				for i in 0..500u32 {
+					let mut batch = db.transaction();
+					batch.put(Some(0), &encode_u32(i % 50), &encode_u32(i));
+					batch.delete(Some(0), &encode_u32(i % 50 + 1000));
+					batch.put(Some(0), &encode_u32(i % 50 + 1000), &encode_u32(i));
+					db.write(batch).unwrap();
+				}
+			})
+		};
+		for _ in 0..50 {
+			db.compact().unwrap();
+		}
+		writer.join().unwrap();
+
+		let check = |db: &Database| {
+			for i in 450..500u32 {
+				assert_eq!(&*db.get(Some(0), &encode_u32(i % 50)).unwrap().unwrap(), &encode_u32(i)[..]);
+				assert_eq!(&*db.get(Some(0), &encode_u32(i % 50 + 1000)).unwrap().unwrap(), &encode_u32(i)[..]);
+			}
+			assert_eq!(db.iter(Some(0)).count(), 100);
+		};
+		check(&db);
+		drop(db);
+		check(&open(&tempdir, Some(1)));
+	}
+
+	#[test]
+	fn should_refuse_foreign_directory() {
+		let tempdir = TempDir::new("").unwrap();
+		File::create(tempdir.path().join("CURRENT")).unwrap();
+		assert!(Database::open_default(tempdir.path().to_str().unwrap()).is_err());
+	}
+
+	#[test]
+	fn should_open_read_only() {
+		let tempdir = TempDir::new("").unwrap();
+		let path = tempdir.path().to_str().unwrap();
+		let mut config = DatabaseConfig::with_columns(Some(1));
+		config.read_only = true;
+		assert!(Database::open(&config, path).is_err());
+		assert!(fs::metadata(tempdir.path().join(METADATA_FILE)).is_err());
+
+		{
+			let db = open(&tempdir, Some(1));
+			let mut batch = db.transaction();
+			batch.put(Some(0), b"key", b"value");
+			db.write(batch).unwrap();
+		}
+		let log = tempdir.path().join(LOG_FILE);
+		let len = fs::metadata(&log).unwrap().len();
+		{
+			// an incomplete commit.
+			let mut file = OpenOptions::new().append(true).open(&log).unwrap();
+			write_record(&mut file, &mut Checksum::new(), TAG_INSERT, 1, b"key", b"new value").unwrap();
+		}
+
+		let db = Database::open(&config, path).unwrap();
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"value");
+		let mut batch = db.transaction();
+		batch.put(Some(0), b"key", b"other value");
+		assert!(db.write(batch).is_err());
+		assert!(fs::metadata(&log).unwrap().len() > len);
+	}
+}
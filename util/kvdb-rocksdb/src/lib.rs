@@ -39,7 +39,7 @@ use rocksdb::{
 
 use elastic_array::ElasticArray32;
 use rlp::{UntrustedRlp, RlpType, Compressible};
-use kvdb::{KeyValueDB, DBTransaction, DBValue, DBOp, Result};
+use kvdb::{KeyValueDB, DBTransaction, DBValue, DBOp, IoCounters, IoStats, Result};
 
 #[cfg(target_os = "linux")]
 use regex::Regex;
@@ -252,6 +252,8 @@ pub struct Database {
 	// Prevents concurrent flushes.
 	// Value indicates if a flush is in progress.
 	flushing_lock: Mutex<bool>,
+	// I/O statistics.
+	io_counters: IoCounters,
 }
 
 impl Database {
@@ -346,6 +348,7 @@ impl Database {
 			flushing_lock: Mutex::new((false)),
 			path: path.to_owned(),
 			read_opts: read_opts,
+			io_counters: IoCounters::default(),
 		})
 	}
 
@@ -361,6 +364,7 @@ impl Database {
 
 	/// Commit transaction to database.
 	pub fn write_buffered(&self, tr: DBTransaction) {
+		self.io_counters.tally_transaction(&tr);
 		let mut overlay = self.overlay.write();
 		let ops = tr.ops;
 		for op in ops {
@@ -447,6 +451,7 @@ impl Database {
 	pub fn write(&self, tr: DBTransaction) -> Result<()> {
 		match *self.db.read() {
 			Some(DBAndColumns { ref db, ref cfs }) => {
+				self.io_counters.tally_transaction(&tr);
 				let batch = WriteBatch::new();
 				let ops = tr.ops;
 				for op in ops {
@@ -475,18 +480,25 @@ impl Database {
 			Some(DBAndColumns { ref db, ref cfs }) => {
 				let overlay = &self.overlay.read()[Self::to_overlay_column(col)];
 				match overlay.get(key) {
-					Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => Ok(Some(value.clone())),
+					Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => {
+						self.io_counters.tally_cache_read();
+						Ok(Some(value.clone()))
+					},
 					Some(&KeyState::Delete) => Ok(None),
 					None => {
 						let flushing = &self.flushing.read()[Self::to_overlay_column(col)];
 						match flushing.get(key) {
-							Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => Ok(Some(value.clone())),
+							Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => {
+								self.io_counters.tally_cache_read();
+								Ok(Some(value.clone()))
+							},
 							Some(&KeyState::Delete) => Ok(None),
 							None => {
-								col.map_or_else(
+								let value = col.map_or_else(
 									|| db.get_opt(key, &self.read_opts).map(|r| r.map(|v| DBValue::from_slice(&v))),
-									|c| db.get_cf_opt(cfs[c as usize], key, &self.read_opts).map(|r| r.map(|v| DBValue::from_slice(&v))))
-									.map_err(Into::into)
+									|c| db.get_cf_opt(cfs[c as usize], key, &self.read_opts).map(|r| r.map(|v| DBValue::from_slice(&v))))?;
+								self.io_counters.tally_read(value.as_ref().map_or(0, |v| v.len()));
+								Ok(value)
 							},
 						}
 					},
@@ -667,6 +679,10 @@ impl KeyValueDB for Database {
 	fn restore(&self, new_db: &str) -> Result<()> {
 		Database::restore(self, new_db)
 	}
+
+	fn io_stats(&self) -> IoStats {
+		self.io_counters.stats()
+	}
 }
 
 impl Drop for Database {
@@ -22,6 +22,7 @@ extern crate elastic_array;
 extern crate ethcore_bytes as bytes;
 
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use elastic_array::{ElasticArray128, ElasticArray32};
 use bytes::Bytes;
 
@@ -41,6 +42,71 @@ error_chain! {
 	}
 }
 
+/// Database I/O statistics, accumulated since the database was opened.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IoStats {
+	/// Number of transactions written.
+	pub transactions: u64,
+	/// Number of keys written or deleted.
+	pub writes: u64,
+	/// Number of bytes written.
+	pub bytes_written: u64,
+	/// Number of reads served by the backing store.
+	pub reads: u64,
+	/// Number of bytes read from the backing store.
+	pub bytes_read: u64,
+	/// Number of reads served by the write buffer.
+	pub cache_reads: u64,
+}
+
+/// Atomic counters backing `IoStats`, for use by database implementations.
+#[derive(Debug, Default)]
+pub struct IoCounters {
+	transactions: AtomicUsize,
+	writes: AtomicUsize,
+	bytes_written: AtomicUsize,
+	reads: AtomicUsize,
+	bytes_read: AtomicUsize,
+	cache_reads: AtomicUsize,
+}
+
+impl IoCounters {
+	/// Count a transaction written to the database.
+	pub fn tally_transaction(&self, transaction: &DBTransaction) {
+		let bytes: usize = transaction.ops.iter().map(|op| match *op {
+			DBOp::Insert { ref key, ref value, .. } | DBOp::InsertCompressed { ref key, ref value, .. } => key.len() + value.len(),
+			DBOp::Delete { ref key, .. } => key.len(),
+		}).sum();
+
+		self.transactions.fetch_add(1, AtomicOrdering::Relaxed);
+		self.writes.fetch_add(transaction.ops.len(), AtomicOrdering::Relaxed);
+		self.bytes_written.fetch_add(bytes, AtomicOrdering::Relaxed);
+	}
+
+	/// Count a read of `bytes` bytes served by the backing store.
+	pub fn tally_read(&self, bytes: usize) {
+		self.reads.fetch_add(1, AtomicOrdering::Relaxed);
+		self.bytes_read.fetch_add(bytes, AtomicOrdering::Relaxed);
+	}
+
+	/// Count a read served by the write buffer.
+	pub fn tally_cache_read(&self) {
+		self.cache_reads.fetch_add(1, AtomicOrdering::Relaxed);
+	}
+
+	/// Get the current values of the counters.
+	pub fn stats(&self) -> IoStats {
+		IoStats {
+			transactions: self.transactions.load(AtomicOrdering::Relaxed) as u64,
+			writes: self.writes.load(AtomicOrdering::Relaxed) as u64,
+			bytes_written: self.bytes_written.load(AtomicOrdering::Relaxed) as u64,
+			reads: self.reads.load(AtomicOrdering::Relaxed) as u64,
+			bytes_read: self.bytes_read.load(AtomicOrdering::Relaxed) as u64,
+			cache_reads: self.cache_reads.load(AtomicOrdering::Relaxed) as u64,
+		}
+	}
+}
+
 /// Write transaction. Batches a sequence of put/delete operations for efficiency.
 #[derive(Default, Clone, PartialEq)]
 pub struct DBTransaction {
@@ -174,4 +240,7 @@ pub trait KeyValueDB: Sync + Send {
 
 	/// Attempt to replace this database with a new one located at the given path.
 	fn restore(&self, new_db: &str) -> Result<()>;
+
+	/// Query I/O statistics. Backends which don't track them return empty statistics.
+	fn io_stats(&self) -> IoStats { IoStats::default() }
 }
@@ -31,9 +31,9 @@ extern crate kvdb_rocksdb;
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::{fs, io};
+use std::{fs, io, mem};
 
-use kvdb::DBTransaction;
+use kvdb::{DBTransaction, KeyValueDB};
 use kvdb_rocksdb::{CompactionProfile, Database, DatabaseConfig};
 
 error_chain! {
@@ -188,6 +188,25 @@ impl Migration for ChangeColumns {
 	}
 }
 
+/// Copies all columns of `source` into `dest`, e.g. to move a database to another backend.
+/// Values are copied as stored, so compressed values stay compressed.
+pub fn copy_columns(source: &KeyValueDB, dest: &KeyValueDB, columns: Option<u32>, config: &Config) -> Result<()> {
+	let cols = ::std::iter::once(None).chain((0..columns.unwrap_or(0)).map(Some));
+	for col in cols {
+		let mut transaction = DBTransaction::new();
+		let mut progress = Progress::default();
+		for (key, value) in source.iter(col) {
+			transaction.put(col, &key, &value);
+			progress.tick();
+			if transaction.ops.len() == config.batch_size {
+				dest.write(mem::replace(&mut transaction, DBTransaction::new()))?;
+			}
+		}
+		dest.write(transaction)?;
+	}
+	Ok(())
+}
+
 /// Get the path where all databases reside.
 fn database_path(path: &Path) -> PathBuf {
 	let mut temp_path = path.to_owned();
@@ -332,6 +351,12 @@ impl Manager {
 		}
 	}
 
+	/// Returns true if any migration needed from `version` alters existing data,
+	/// rather than only changing the number of columns.
+	pub fn alters_existing(&self, version: u32) -> bool {
+		self.migrations.iter().filter(|m| m.version() > version).any(|m| m.alters_existing())
+	}
+
 	/// Find all needed migrations.
 	fn migrations_from(&mut self, version: u32) -> Vec<&mut Box<Migration>> {
 		self.migrations.iter_mut().filter(|m| m.version() > version).collect()
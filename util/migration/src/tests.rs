@@ -19,13 +19,14 @@
 //! are performed in temp sub-directories.
 
 extern crate tempdir;
+extern crate kvdb_paritydb;
 
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use self::tempdir::TempDir;
 use kvdb_rocksdb::Database;
-use {Batch, Config, Error, SimpleMigration, Migration, Manager, ChangeColumns};
+use {Batch, Config, Error, SimpleMigration, Migration, Manager, ChangeColumns, copy_columns};
 
 #[inline]
 fn db_path(path: &Path) -> PathBuf {
@@ -250,3 +251,28 @@ fn change_columns() {
 	let db = Database::open(&config, new_path.to_str().unwrap()).unwrap();
 	assert_eq!(db.num_columns(), 4);
 }
+
+#[test]
+fn copies_columns_between_backends() {
+	use kvdb_rocksdb::DatabaseConfig;
+
+	let tempdir = TempDir::new("").unwrap();
+	let source = Database::open(&DatabaseConfig::with_columns(Some(2)), db_path(tempdir.path()).to_str().unwrap()).unwrap();
+	let mut transaction = source.transaction();
+	transaction.put(None, b"default", b"0");
+	transaction.put(Some(0), b"first", b"1");
+	for i in 0..10u8 {
+		transaction.put(Some(1), &[i], &[i]);
+	}
+	source.write(transaction).unwrap();
+
+	let dest_path = tempdir.path().join("paritydb");
+	let dest = kvdb_paritydb::Database::open(&kvdb_paritydb::DatabaseConfig::with_columns(Some(2)), dest_path.to_str().unwrap()).unwrap();
+	let config = Config { batch_size: 3, ..Config::default() };
+	copy_columns(&source, &dest, Some(2), &config).unwrap();
+
+	assert_eq!(&*dest.get(None, b"default").unwrap().unwrap(), b"0");
+	assert_eq!(&*dest.get(Some(0), b"first").unwrap().unwrap(), b"1");
+	assert_eq!(dest.iter(Some(1)).count(), 10);
+	assert_eq!(&*dest.get(Some(1), &[7]).unwrap().unwrap(), &[7]);
+}
@@ -52,6 +52,11 @@ impl<'db> FatDBMut<'db> {
 		self.raw.db_mut()
 	}
 
+	/// Set whether commits encode the top-level subtries on the thread pool.
+	pub fn set_parallel(&mut self, parallel: bool) {
+		self.raw.set_parallel(parallel)
+	}
+
 	fn to_aux_key(key: &[u8]) -> H256 {
 		keccak(key)
 	}
@@ -23,6 +23,7 @@ extern crate hashdb;
 extern crate ethcore_bytes as bytes;
 extern crate elastic_array;
 extern crate memorydb;
+extern crate rayon;
 extern crate ethcore_logger;
 
 #[macro_use]
@@ -47,6 +48,8 @@ pub mod sectriedb;
 pub mod sectriedbmut;
 /// Trie query recording.
 pub mod recorder;
+/// Trie proof verification.
+pub mod proof;
 
 
 mod fatdb;
@@ -63,6 +66,7 @@ pub use self::sectriedb::SecTrieDB;
 pub use self::fatdb::{FatDB, FatDBIterator};
 pub use self::fatdbmut::FatDBMut;
 pub use self::recorder::Recorder;
+pub use self::proof::{ProofError, verify_proof, verify_sec_proof, verify_range_proof};
 
 /// Trie Errors.
 ///
@@ -220,6 +224,7 @@ impl Default for TrieSpec {
 #[derive(Default, Clone)]
 pub struct TrieFactory {
 	spec: TrieSpec,
+	parallel: bool,
 }
 
 /// All different kinds of tries.
@@ -274,9 +279,15 @@ impl TrieFactory {
 	pub fn new(spec: TrieSpec) -> Self {
 		TrieFactory {
 			spec: spec,
+			parallel: false,
 		}
 	}
 
+	/// Set whether mutable tries created by this factory commit in parallel.
+	pub fn set_parallel(&mut self, parallel: bool) {
+		self.parallel = parallel;
+	}
+
 	/// Create new immutable instance of Trie.
 	pub fn readonly<'db>(&self, db: &'db HashDB, root: &'db H256) -> Result<TrieKinds<'db>> {
 		match self.spec {
@@ -289,21 +300,48 @@ impl TrieFactory {
 	/// Create new mutable instance of Trie.
 	pub fn create<'db>(&self, db: &'db mut HashDB, root: &'db mut H256) -> Box<TrieMut + 'db> {
 		match self.spec {
-			TrieSpec::Generic => Box::new(TrieDBMut::new(db, root)),
-			TrieSpec::Secure => Box::new(SecTrieDBMut::new(db, root)),
-			TrieSpec::Fat => Box::new(FatDBMut::new(db, root)),
+			TrieSpec::Generic => {
+				let mut t = TrieDBMut::new(db, root);
+				t.set_parallel(self.parallel);
+				Box::new(t)
+			},
+			TrieSpec::Secure => {
+				let mut t = SecTrieDBMut::new(db, root);
+				t.set_parallel(self.parallel);
+				Box::new(t)
+			},
+			TrieSpec::Fat => {
+				let mut t = FatDBMut::new(db, root);
+				t.set_parallel(self.parallel);
+				Box::new(t)
+			},
 		}
 	}
 
 	/// Create new mutable instance of trie and check for errors.
 	pub fn from_existing<'db>(&self, db: &'db mut HashDB, root: &'db mut H256) -> Result<Box<TrieMut + 'db>> {
 		match self.spec {
-			TrieSpec::Generic => Ok(Box::new(TrieDBMut::from_existing(db, root)?)),
-			TrieSpec::Secure => Ok(Box::new(SecTrieDBMut::from_existing(db, root)?)),
-			TrieSpec::Fat => Ok(Box::new(FatDBMut::from_existing(db, root)?)),
+			TrieSpec::Generic => {
+				let mut t = TrieDBMut::from_existing(db, root)?;
+				t.set_parallel(self.parallel);
+				Ok(Box::new(t))
+			},
+			TrieSpec::Secure => {
+				let mut t = SecTrieDBMut::from_existing(db, root)?;
+				t.set_parallel(self.parallel);
+				Ok(Box::new(t))
+			},
+			TrieSpec::Fat => {
+				let mut t = FatDBMut::from_existing(db, root)?;
+				t.set_parallel(self.parallel);
+				Ok(Box::new(t))
+			},
 		}
 	}
 
 	/// Returns true iff the trie DB is a fat DB (allows enumeration of keys).
 	pub fn is_fat(&self) -> bool { self.spec == TrieSpec::Fat }
+
+	/// Returns true iff mutable tries commit in parallel.
+	pub fn is_parallel(&self) -> bool { self.parallel }
 }
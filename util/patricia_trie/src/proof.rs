@@ -0,0 +1,427 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of trie proofs.
+//!
+//! A proof is a set of trie nodes, such as the ones collected by a `Recorder`
+//! while looking up keys. Verification only relies on the nodes given and the
+//! expected root, so proofs received from untrusted peers may be checked
+//! without access to the rest of the trie.
+
+use std::{fmt, error};
+use std::collections::HashMap;
+use bigint::hash::H256;
+use bytes::Bytes;
+use hashdb::DBValue;
+use keccak::{keccak, KECCAK_NULL_RLP};
+use rlp::{UntrustedRlp, Prototype, DecoderError};
+
+use nibbleslice::NibbleSlice;
+use node::Node;
+
+/// Proof verification errors.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ProofError {
+	/// A node required to look up the key is not part of the proof.
+	MissingNode(H256),
+	/// The node with the given hash, or one of the nodes inlined into it, is not a valid trie node.
+	InvalidNode(H256),
+}
+
+impl fmt::Display for ProofError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ProofError::MissingNode(ref hash) => write!(f, "Proof missing expected node: {}", hash),
+			ProofError::InvalidNode(ref hash) => write!(f, "Proof contains invalid node: {}", hash),
+		}
+	}
+}
+
+impl error::Error for ProofError {
+	fn description(&self) -> &str {
+		match *self {
+			ProofError::MissingNode(_) => "Missing proof node",
+			ProofError::InvalidNode(_) => "Invalid proof node",
+		}
+	}
+}
+
+/// Verify a proof of the value stored under `key` in the trie with the given `root`.
+///
+/// Returns the value if the key is in the trie, and `None` if the proof shows it
+/// isn't. Nodes which are not needed for the lookup are ignored.
+pub fn verify_proof<T: AsRef<[u8]>>(root: &H256, key: &[u8], proof: &[T]) -> Result<Option<DBValue>, ProofError> {
+	ProofNodes::new(proof).look_up(root, NibbleSlice::new(key))
+}
+
+/// Verify a proof of the value stored under `key` in the secure trie with the given `root`.
+///
+/// Same as `verify_proof` except that the key is hashed first, as done by `SecTrieDB`.
+pub fn verify_sec_proof<T: AsRef<[u8]>>(root: &H256, key: &[u8], proof: &[T]) -> Result<Option<DBValue>, ProofError> {
+	verify_proof(root, &keccak(key), proof)
+}
+
+/// Verify a proof of all the items stored in the trie with the given `root` whose keys
+/// lie within `start..=end`, returning these items in key order.
+///
+/// The proof must contain every node of the trie which may hold a key in the range,
+/// for example the nodes visited when looking up `start`, `end` and all the items in
+/// between. Keys of a secure trie are hashes, so for `SecTrieDB` the bounds and the
+/// returned keys are hashed keys.
+pub fn verify_range_proof<T: AsRef<[u8]>>(root: &H256, start: &[u8], end: &[u8], proof: &[T]) -> Result<Vec<(Bytes, DBValue)>, ProofError> {
+	let mut range = Range {
+		start: nibbles(start),
+		end: nibbles(end),
+		items: Vec::new(),
+	};
+	if start <= end {
+		let nodes = ProofNodes::new(proof);
+		let data = nodes.get(root)?;
+		nodes.walk(root, data, &mut Vec::new(), &mut range)?;
+	}
+	Ok(range.items)
+}
+
+// proof nodes indexed by their hash.
+struct ProofNodes<'a> {
+	nodes: HashMap<H256, &'a [u8]>,
+}
+
+// bounds, as nibbles, and items of a range proof.
+struct Range {
+	start: Vec<u8>,
+	end: Vec<u8>,
+	items: Vec<(Bytes, DBValue)>,
+}
+
+// a child reference within a node.
+enum Child<'a> {
+	Empty,
+	Hash(H256),
+	Inline(&'a [u8]),
+}
+
+impl<'a> ProofNodes<'a> {
+	fn new<T: AsRef<[u8]>>(proof: &'a [T]) -> Self {
+		ProofNodes {
+			nodes: proof.iter().map(|node| (keccak(node.as_ref()), node.as_ref())).collect(),
+		}
+	}
+
+	// the node with the given hash. the empty trie root doesn't need to be part of the proof.
+	fn get(&self, hash: &H256) -> Result<&'a [u8], ProofError> {
+		match self.nodes.get(hash) {
+			Some(data) => Ok(*data),
+			None if *hash == KECCAK_NULL_RLP => Ok(&[0x80]),
+			None => Err(ProofError::MissingNode(*hash)),
+		}
+	}
+
+	fn look_up(&self, root: &H256, mut key: NibbleSlice) -> Result<Option<DBValue>, ProofError> {
+		let mut hash = *root;
+		let mut data = self.get(&hash)?;
+
+		loop {
+			let child = match decode_node(data).map_err(|_| ProofError::InvalidNode(hash))? {
+				Node::Empty => return Ok(None),
+				Node::Leaf(slice, value) => return Ok(match slice == key {
+					true => Some(DBValue::from_slice(value)),
+					false => None,
+				}),
+				Node::Extension(slice, item) => match key.starts_with(&slice) {
+					true => {
+						key = key.mid(slice.len());
+						item
+					},
+					false => return Ok(None),
+				},
+				Node::Branch(children, value) => match key.is_empty() {
+					true => return Ok(value.map(DBValue::from_slice)),
+					false => {
+						let child = children[key.at(0) as usize];
+						key = key.mid(1);
+						child
+					},
+				},
+			};
+
+			match decode_child(child).map_err(|_| ProofError::InvalidNode(hash))? {
+				Child::Empty => return Ok(None),
+				Child::Hash(child) => {
+					hash = child;
+					data = self.get(&hash)?;
+				},
+				Child::Inline(child) => data = child,
+			}
+		}
+	}
+
+	// collect the items of the subtrie of `data`, found under `path`, which fall within the range.
+	// `hash` is the one of the closest non-inline node.
+	fn walk(&self, hash: &H256, data: &'a [u8], path: &mut Vec<u8>, range: &mut Range) -> Result<(), ProofError> {
+		let len = path.len();
+		match decode_node(data).map_err(|_| ProofError::InvalidNode(*hash))? {
+			Node::Empty => {},
+			Node::Leaf(slice, value) => {
+				extend(path, &slice);
+				range.push(path, value);
+			},
+			Node::Extension(slice, item) => {
+				extend(path, &slice);
+				self.walk_child(hash, item, path, range)?;
+			},
+			Node::Branch(children, value) => {
+				if let Some(value) = value {
+					range.push(path, value);
+				}
+				for (i, child) in children.iter().enumerate() {
+					path.push(i as u8);
+					self.walk_child(hash, child, path, range)?;
+					path.truncate(len);
+				}
+			},
+		}
+		path.truncate(len);
+		Ok(())
+	}
+
+	fn walk_child(&self, hash: &H256, child: &'a [u8], path: &mut Vec<u8>, range: &mut Range) -> Result<(), ProofError> {
+		if !range.overlaps(path) {
+			return Ok(());
+		}
+
+		match decode_child(child).map_err(|_| ProofError::InvalidNode(*hash))? {
+			Child::Empty => Ok(()),
+			Child::Hash(child) => {
+				let data = self.get(&child)?;
+				self.walk(&child, data, path, range)
+			},
+			Child::Inline(data) => self.walk(hash, data, path, range),
+		}
+	}
+}
+
+impl Range {
+	// whether the subtrie under `prefix` may hold keys within the range.
+	fn overlaps(&self, prefix: &[u8]) -> bool {
+		let start = &self.start[..::std::cmp::min(prefix.len(), self.start.len())];
+		prefix >= start && prefix <= &self.end[..]
+	}
+
+	fn push(&mut self, path: &[u8], value: &[u8]) {
+		// keys are whole bytes.
+		if path.len() % 2 != 0 || path < &self.start[..] || path > &self.end[..] {
+			return;
+		}
+
+		let key = path.chunks(2).map(|n| n[0] << 4 | n[1]).collect();
+		self.items.push((key, DBValue::from_slice(value)));
+	}
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+	let mut path = Vec::with_capacity(key.len() * 2);
+	extend(&mut path, &NibbleSlice::new(key));
+	path
+}
+
+fn extend(path: &mut Vec<u8>, slice: &NibbleSlice) {
+	for i in 0..slice.len() {
+		path.push(slice.at(i));
+	}
+}
+
+// decode a trie node without trusting its encoding.
+fn decode_node(data: &[u8]) -> Result<Node, DecoderError> {
+	let rlp = UntrustedRlp::new(data);
+	if rlp.payload_info()?.total() != data.len() {
+		return Err(DecoderError::RlpInconsistentLengthAndData);
+	}
+
+	match rlp.prototype()? {
+		Prototype::List(2) => {
+			let encoded = rlp.at(0)?.data()?;
+			if encoded.is_empty() {
+				return Err(DecoderError::RlpIsTooShort);
+			}
+			match NibbleSlice::from_encoded(encoded) {
+				(slice, true) => Ok(Node::Leaf(slice, rlp.at(1)?.data()?)),
+				(slice, false) => Ok(Node::Extension(slice, rlp.at(1)?.as_raw())),
+			}
+		},
+		Prototype::List(17) => {
+			let mut children = [&[] as &[u8]; 16];
+			for i in 0..16 {
+				children[i] = rlp.at(i)?.as_raw();
+			}
+			let value = rlp.at(16)?.data()?;
+			Ok(Node::Branch(children, if value.is_empty() { None } else { Some(value) }))
+		},
+		Prototype::Data(0) => Ok(Node::Empty),
+		_ => Err(DecoderError::Custom("Unexpected trie node.")),
+	}
+}
+
+// decode a reference to a child node.
+fn decode_child(data: &[u8]) -> Result<Child, DecoderError> {
+	let rlp = UntrustedRlp::new(data);
+	match rlp.prototype()? {
+		Prototype::Data(0) => Ok(Child::Empty),
+		Prototype::Data(32) => Ok(Child::Hash(H256::from_slice(rlp.data()?))),
+		Prototype::List(_) => Ok(Child::Inline(data)),
+		_ => Err(DecoderError::Custom("Unexpected child node reference.")),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use bigint::hash::H256;
+	use keccak::KECCAK_NULL_RLP;
+	use memorydb::MemoryDB;
+	use super::*;
+	use super::super::{TrieDB, TrieDBMut, SecTrieDB, SecTrieDBMut, Trie, TrieMut, Recorder};
+
+	const ITEMS: &'static [(&'static [u8], &'static [u8])] = &[
+		(b"dog", b"cat"),
+		(b"lunch", b"time"),
+		(b"notdog", b"notcat"),
+		(b"hotdog", b"hotcat"),
+		(b"letter", b"confusion"),
+		(b"insert", b"remove"),
+		(b"pirate", b"aargh!"),
+		(b"yo ho ho", b"and a bottle of rum"),
+	];
+
+	fn populate(db: &mut MemoryDB) -> H256 {
+		let mut root = H256::default();
+		{
+			let mut t = TrieDBMut::new(db, &mut root);
+			for &(key, value) in ITEMS {
+				t.insert(key, value).unwrap();
+			}
+		}
+		root
+	}
+
+	fn prove(db: &MemoryDB, root: &H256, keys: &[&[u8]]) -> Vec<Bytes> {
+		let trie = TrieDB::new(db, root).unwrap();
+		let mut recorder = Recorder::new();
+		for key in keys {
+			trie.get_with(key, &mut recorder).unwrap();
+		}
+		recorder.drain().into_iter().map(|r| r.data).collect()
+	}
+
+	#[test]
+	fn verifies_present_and_absent_keys() {
+		let mut db = MemoryDB::new();
+		let root = populate(&mut db);
+
+		let proof = prove(&db, &root, &[b"pirate"]);
+		assert_eq!(verify_proof(&root, b"pirate", &proof).unwrap(), Some(DBValue::from_slice(b"aargh!")));
+
+		let proof = prove(&db, &root, &[b"letters"]);
+		assert_eq!(verify_proof(&root, b"letters", &proof).unwrap(), None);
+	}
+
+	#[test]
+	fn verifies_secure_trie_proof() {
+		let mut db = MemoryDB::new();
+		let mut root = H256::default();
+		{
+			let mut t = SecTrieDBMut::new(&mut db, &mut root);
+			for &(key, value) in ITEMS {
+				t.insert(key, value).unwrap();
+			}
+		}
+
+		let trie = SecTrieDB::new(&db, &root).unwrap();
+		let mut recorder = Recorder::new();
+		trie.get_with(b"lunch", &mut recorder).unwrap();
+		let proof: Vec<_> = recorder.drain().into_iter().map(|r| r.data).collect();
+
+		assert_eq!(verify_sec_proof(&root, b"lunch", &proof).unwrap(), Some(DBValue::from_slice(b"time")));
+		assert_eq!(verify_proof(&root, b"lunch", &proof).unwrap(), None);
+	}
+
+	#[test]
+	fn empty_trie_needs_no_proof() {
+		let proof: Vec<Bytes> = Vec::new();
+		assert_eq!(verify_proof(&KECCAK_NULL_RLP, b"dog", &proof).unwrap(), None);
+		assert_eq!(verify_range_proof(&KECCAK_NULL_RLP, b"a", b"z", &proof).unwrap(), vec![]);
+	}
+
+	#[test]
+	fn reports_missing_nodes() {
+		let mut db = MemoryDB::new();
+		let root = populate(&mut db);
+
+		let proof = prove(&db, &root, &[b"letter"]);
+		assert_eq!(verify_proof(&root, b"letter", &proof[1..]), Err(ProofError::MissingNode(root)));
+
+		let missing = keccak(&proof[2]);
+		assert_eq!(verify_proof(&root, b"letter", &proof[..2]), Err(ProofError::MissingNode(missing)));
+	}
+
+	#[test]
+	fn reports_tampered_nodes() {
+		let mut db = MemoryDB::new();
+		let root = populate(&mut db);
+
+		// a node with a different value has a different hash, so it's just missing.
+		let mut proof = prove(&db, &root, &[b"pirate"]);
+		let last = proof.len() - 1;
+		let index = proof[last].len() - 20;
+		let expected = keccak(&proof[last]);
+		proof[last][index] ^= 1;
+		assert_eq!(verify_proof(&root, b"pirate", &proof), Err(ProofError::MissingNode(expected)));
+
+		// a node matching its hash which doesn't decode is invalid.
+		let garbage = vec![0xc2, 0x80];
+		let root = keccak(&garbage);
+		assert_eq!(verify_proof(&root, b"pirate", &[garbage]), Err(ProofError::InvalidNode(root)));
+	}
+
+	#[test]
+	fn verifies_range_proof() {
+		let mut db = MemoryDB::new();
+		let root = populate(&mut db);
+
+		let proof = prove(&db, &root, &[b"hotdog", b"insert", b"letter", b"lunch", b"m"]);
+		let items = verify_range_proof(&root, b"hotdog", b"m", &proof).unwrap();
+		assert_eq!(items, vec![
+			(b"hotdog".to_vec(), DBValue::from_slice(b"hotcat")),
+			(b"insert".to_vec(), DBValue::from_slice(b"remove")),
+			(b"letter".to_vec(), DBValue::from_slice(b"confusion")),
+			(b"lunch".to_vec(), DBValue::from_slice(b"time")),
+		]);
+
+		// without the node holding `letter` and `lunch` the range can't be verified.
+		let proof = prove(&db, &root, &[b"hotdog", b"insert", b"m"]);
+		match verify_range_proof(&root, b"hotdog", b"m", &proof) {
+			Err(ProofError::MissingNode(_)) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn reversed_range_is_empty() {
+		let mut db = MemoryDB::new();
+		let root = populate(&mut db);
+		let proof: Vec<Bytes> = Vec::new();
+		assert_eq!(verify_range_proof(&root, b"z", b"a", &proof).unwrap(), vec![]);
+	}
+}
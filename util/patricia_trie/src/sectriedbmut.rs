@@ -47,6 +47,9 @@ impl<'db> SecTrieDBMut<'db> {
 
 	/// Get the backing database.
 	pub fn db_mut(&mut self) -> &mut HashDB { self.raw.db_mut() }
+
+	/// Set whether commits encode the top-level subtries on the thread pool.
+	pub fn set_parallel(&mut self, parallel: bool) { self.raw.set_parallel(parallel) }
 }
 
 impl<'db> TrieMut for SecTrieDBMut<'db> {
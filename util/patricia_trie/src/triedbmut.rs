@@ -22,9 +22,10 @@ use super::node::Node as RlpNode;
 use super::node::NodeKey;
 
 use hashdb::HashDB;
-use bytes::ToPretty;
+use bytes::{Bytes, ToPretty};
 use nibbleslice::NibbleSlice;
-use rlp::{Rlp, RlpStream};
+use rayon::prelude::*;
+use rlp::{self, Rlp, RlpStream};
 use hashdb::DBValue;
 
 use std::collections::{HashSet, VecDeque};
@@ -32,7 +33,14 @@ use std::mem;
 use std::ops::Index;
 use bigint::hash::H256;
 use elastic_array::ElasticArray1024;
-use keccak::{KECCAK_NULL_RLP};
+use keccak::{keccak, KECCAK_NULL_RLP};
+
+/// Number of trie levels, starting from the root, whose subtries are encoded
+/// on the thread pool by a parallel commit.
+const PARALLEL_DEPTH: usize = 2;
+
+/// Commits with fewer nodes in memory are always done on the calling thread.
+const PARALLEL_MIN_NODES: usize = 256;
 
 // For lookups into the Node storage buffer.
 // This is deliberately non-copyable.
@@ -121,40 +129,48 @@ impl Node {
 		}
 	}
 
+	// the handles of the children of this node, in order.
+	fn children(&self) -> Vec<&NodeHandle> {
+		match *self {
+			Node::Extension(_, ref child) => vec![child],
+			Node::Branch(ref children, _) => children.iter().filter_map(Option::as_ref).collect(),
+			_ => Vec::new(),
+		}
+	}
+
 	// encode a node to RLP
-	// TODO: parallelize
-	fn into_rlp<F>(self, mut child_cb: F) -> ElasticArray1024<u8>
-		where F: FnMut(NodeHandle, &mut RlpStream)
+	fn to_rlp<F>(&self, mut child_cb: F) -> ElasticArray1024<u8>
+		where F: FnMut(&NodeHandle, &mut RlpStream)
 	{
-		match self {
+		match *self {
 			Node::Empty => {
 				let mut stream = RlpStream::new();
 				stream.append_empty_data();
 				stream.drain()
 			}
-			Node::Leaf(partial, value) => {
+			Node::Leaf(ref partial, ref value) => {
 				let mut stream = RlpStream::new_list(2);
-				stream.append(&&*partial);
-				stream.append(&&*value);
+				stream.append(&&**partial);
+				stream.append(&&**value);
 				stream.drain()
 			}
-			Node::Extension(partial, child) => {
+			Node::Extension(ref partial, ref child) => {
 				let mut stream = RlpStream::new_list(2);
-				stream.append(&&*partial);
+				stream.append(&&**partial);
 				child_cb(child, &mut stream);
 				stream.drain()
 			}
-			Node::Branch(mut children, value) => {
+			Node::Branch(ref children, ref value) => {
 				let mut stream = RlpStream::new_list(17);
-				for child in children.iter_mut().map(Option::take) {
-					if let Some(handle) = child {
+				for child in children.iter() {
+					if let Some(ref handle) = *child {
 						child_cb(handle, &mut stream);
 					} else {
 						stream.append_empty_data();
 					}
 				}
-				if let Some(value) = value {
-					stream.append(&&*value);
+				if let Some(ref value) = *value {
+					stream.append(&&**value);
 				} else {
 					stream.append_empty_data();
 				}
@@ -292,6 +308,7 @@ pub struct TrieDBMut<'a> {
 	root: &'a mut H256,
 	root_handle: NodeHandle,
 	death_row: HashSet<H256>,
+	parallel: bool,
 	/// The number of hash operations this trie has performed.
 	/// Note that none are performed until changes are committed.
 	pub hash_count: usize,
@@ -309,6 +326,7 @@ impl<'a> TrieDBMut<'a> {
 			root: root,
 			root_handle: root_handle,
 			death_row: HashSet::new(),
+			parallel: false,
 			hash_count: 0,
 		}
 	}
@@ -327,6 +345,7 @@ impl<'a> TrieDBMut<'a> {
 			root: root,
 			root_handle: root_handle,
 			death_row: HashSet::new(),
+			parallel: false,
 			hash_count: 0,
 		})
 	}
@@ -340,6 +359,12 @@ impl<'a> TrieDBMut<'a> {
 		self.db
 	}
 
+	/// Set whether commits encode the top-level subtries on the thread pool.
+	/// The resulting root and nodes are the same either way.
+	pub fn set_parallel(&mut self, parallel: bool) {
+		self.parallel = parallel;
+	}
+
 	// cache a node by hash
 	fn cache(&mut self, hash: H256) -> super::Result<StorageHandle> {
 		let node_rlp = self.db.get(&hash).ok_or_else(|| Box::new(TrieError::IncompleteDatabase(hash)))?;
@@ -823,7 +848,10 @@ impl<'a> TrieDBMut<'a> {
 
 		match self.storage.destroy(handle) {
 			Stored::New(node) => {
-				let root_rlp = node.into_rlp(|child, stream| self.commit_node(child, stream));
+				let root_rlp = match self.parallel && self.storage.nodes.len() >= PARALLEL_MIN_NODES {
+					true => self.commit_parallel(&node),
+					false => node.to_rlp(|child, stream| self.commit_node(child, stream)),
+				};
 				*self.root = self.db.insert(&root_rlp[..]);
 				self.hash_count += 1;
 
@@ -840,13 +868,13 @@ impl<'a> TrieDBMut<'a> {
 
 	/// commit a node, hashing it, committing it to the db,
 	/// and writing it to the rlp stream as necessary.
-	fn commit_node(&mut self, handle: NodeHandle, stream: &mut RlpStream) {
-		match handle {
-			NodeHandle::Hash(h) => stream.append(&h),
-			NodeHandle::InMemory(h) => match self.storage.destroy(h) {
+	fn commit_node(&mut self, handle: &NodeHandle, stream: &mut RlpStream) {
+		match *handle {
+			NodeHandle::Hash(ref h) => stream.append(h),
+			NodeHandle::InMemory(StorageHandle(x)) => match self.storage.destroy(StorageHandle(x)) {
 				Stored::Cached(_, h) => stream.append(&h),
 				Stored::New(node) => {
-					let node_rlp = node.into_rlp(|child, stream| self.commit_node(child, stream));
+					let node_rlp = node.to_rlp(|child, stream| self.commit_node(child, stream));
 					if node_rlp.len() >= 32 {
 						let hash = self.db.insert(&node_rlp[..]);
 						self.hash_count += 1;
@@ -859,6 +887,37 @@ impl<'a> TrieDBMut<'a> {
 		};
 	}
 
+	// commit the children of the new root node, encoding their subtries on the thread pool.
+	// the new nodes are inserted once encoded since the database can't be shared.
+	fn commit_parallel(&mut self, node: &Node) -> ElasticArray1024<u8> {
+		let encoded: Vec<_> = {
+			let storage = &self.storage;
+			node.children().par_iter().map(|child| encode_child(storage, child, 1)).collect()
+		};
+
+		let mut encoded = encoded.into_iter();
+		node.to_rlp(|child, stream| {
+			let (child_rlp, nodes) = encoded.next().expect("one encoding per child; qed");
+			self.hash_count += nodes.len();
+			for (hash, value) in nodes {
+				self.db.emplace(hash, value);
+			}
+			self.free(child);
+			stream.append_raw(&child_rlp, 1);
+		})
+	}
+
+	// free the storage of a committed subtrie, like `commit_node` does.
+	fn free(&mut self, handle: &NodeHandle) {
+		if let NodeHandle::InMemory(StorageHandle(x)) = *handle {
+			if let Stored::New(node) = self.storage.destroy(StorageHandle(x)) {
+				for child in node.children() {
+					self.free(child);
+				}
+			}
+		}
+	}
+
 	// a hack to get the root node's handle
 	fn root_handle(&self) -> NodeHandle {
 		match self.root_handle {
@@ -868,6 +927,40 @@ impl<'a> TrieDBMut<'a> {
 	}
 }
 
+// encode a child node as referenced by its parent along with the new nodes of its subtrie,
+// without committing anything. the first `PARALLEL_DEPTH` levels are encoded on the thread pool.
+fn encode_child(storage: &NodeStorage, handle: &NodeHandle, depth: usize) -> (Bytes, Vec<(H256, DBValue)>) {
+	let node = match *handle {
+		NodeHandle::Hash(ref h) => return (rlp::encode(h).into_vec(), Vec::new()),
+		NodeHandle::InMemory(ref h) => match storage.nodes[h.0] {
+			Stored::Cached(_, ref h) => return (rlp::encode(h).into_vec(), Vec::new()),
+			Stored::New(ref node) => node,
+		},
+	};
+
+	let children = node.children();
+	let encoded: Vec<_> = match depth < PARALLEL_DEPTH {
+		true => children.par_iter().map(|child| encode_child(storage, child, depth + 1)).collect(),
+		false => children.iter().map(|child| encode_child(storage, child, depth + 1)).collect(),
+	};
+
+	let mut nodes = Vec::new();
+	let mut encoded = encoded.into_iter();
+	let node_rlp = node.to_rlp(|_, stream| {
+		let (child_rlp, child_nodes) = encoded.next().expect("one encoding per child; qed");
+		nodes.extend(child_nodes);
+		stream.append_raw(&child_rlp, 1);
+	});
+
+	if node_rlp.len() >= 32 {
+		let hash = keccak(&node_rlp[..]);
+		nodes.push((hash, DBValue::from_slice(&node_rlp)));
+		(rlp::encode(&hash).into_vec(), nodes)
+	} else {
+		(node_rlp.to_vec(), nodes)
+	}
+}
+
 impl<'a> TrieMut for TrieDBMut<'a> {
 	fn root(&mut self) -> &H256 {
 		self.commit();
@@ -1235,6 +1328,48 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn parallel_commit() {
+		let mut seed = H256::new();
+		let x = StandardMap {
+			alphabet: Alphabet::All,
+			min_key: 32,
+			journal_key: 0,
+			value_mode: ValueMode::Random,
+			count: 1000,
+		}.make_with(&mut seed);
+		let (first, second) = x.split_at(800);
+
+		let mut serial_db = MemoryDB::new();
+		let mut serial_root = H256::new();
+		let mut parallel_db = MemoryDB::new();
+		let mut parallel_root = H256::new();
+		{
+			let mut serial = populate_trie(&mut serial_db, &mut serial_root, first);
+			let mut parallel = TrieDBMut::new(&mut parallel_db, &mut parallel_root);
+			parallel.set_parallel(true);
+			for &(ref key, ref value) in first {
+				parallel.insert(key, value).unwrap();
+			}
+			assert_eq!(*parallel.root(), trie_root(first.to_vec()));
+			assert_eq!(parallel.root(), serial.root());
+
+			// update a trie partly loaded from the database.
+			for &(ref key, ref value) in second {
+				serial.insert(key, value).unwrap();
+				parallel.insert(key, value).unwrap();
+			}
+			unpopulate_trie(&mut serial, &first[..100]);
+			for &(ref key, _) in &first[..100] {
+				parallel.remove(key).unwrap();
+			}
+			assert_eq!(parallel.root(), serial.root());
+			assert_eq!(parallel.hash_count, serial.hash_count);
+		}
+
+		assert_eq!(parallel_db.keys(), serial_db.keys());
+	}
+
 	#[test]
 	fn test_trie_existing() {
 		let mut root = H256::new();
@@ -20,14 +20,23 @@
 
 extern crate ethcore_bigint;
 extern crate hash;
+extern crate rayon;
 extern crate rlp;
 
 use std::collections::BTreeMap;
 use std::cmp;
 use ethcore_bigint::hash::H256;
 use hash::keccak;
+use rayon::prelude::*;
 use rlp::RlpStream;
 
+/// Number of branch levels, starting from the root, whose subtries are hashed
+/// on the thread pool by the `_parallel` functions.
+const PARALLEL_DEPTH: usize = 2;
+
+/// Inputs smaller than this are always hashed on the calling thread.
+const PARALLEL_MIN_ITEMS: usize = 256;
+
 fn shared_prefix_len<T: Eq>(first: &[T], second: &[T]) -> usize {
 	let len = cmp::min(first.len(), second.len());
 	(0..len).take_while(|&i| first[i] == second[i]).count()
@@ -48,7 +57,22 @@ fn shared_prefix_len<T: Eq>(first: &[T], second: &[T]) -> usize {
 pub fn ordered_trie_root<I>(input: I) -> H256
 	where I: IntoIterator<Item=Vec<u8>>
 {
-	let gen_input = input
+	gen_trie_root(ordered_input(input), 0)
+}
+
+/// Generates a trie root hash for a vector of values, hashing independent subtries in parallel.
+///
+/// The result is the same as the one of `ordered_trie_root`.
+pub fn ordered_trie_root_parallel<I>(input: I) -> H256
+	where I: IntoIterator<Item=Vec<u8>>
+{
+	gen_trie_root(ordered_input(input), PARALLEL_DEPTH)
+}
+
+fn ordered_input<I>(input: I) -> Vec<(Vec<u8>, Vec<u8>)>
+	where I: IntoIterator<Item=Vec<u8>>
+{
+	input
 		// first put elements into btree to sort them by nibbles
 		// optimize it later
 		.into_iter()
@@ -58,9 +82,7 @@ pub fn ordered_trie_root<I>(input: I) -> H256
 		// then move them to a vector
 		.into_iter()
 		.map(|(k, v)| (as_nibbles(&k), v) )
-		.collect();
-
-	gen_trie_root(gen_input)
+		.collect()
 }
 
 /// Generates a trie root hash for a vector of key-values
@@ -83,16 +105,29 @@ pub fn ordered_trie_root<I>(input: I) -> H256
 pub fn trie_root<I>(input: I) -> H256
 	where I: IntoIterator<Item=(Vec<u8>, Vec<u8>)>
 {
-	let gen_input = input
+	gen_trie_root(sorted_input(input), 0)
+}
+
+/// Generates a trie root hash for a vector of key-values, hashing independent subtries in parallel.
+///
+/// The result is the same as the one of `trie_root`.
+pub fn trie_root_parallel<I>(input: I) -> H256
+	where I: IntoIterator<Item=(Vec<u8>, Vec<u8>)>
+{
+	gen_trie_root(sorted_input(input), PARALLEL_DEPTH)
+}
+
+fn sorted_input<I>(input: I) -> Vec<(Vec<u8>, Vec<u8>)>
+	where I: IntoIterator<Item=(Vec<u8>, Vec<u8>)>
+{
+	input
 		// first put elements into btree to sort them and to remove duplicates
 		.into_iter()
 		.collect::<BTreeMap<_, _>>()
 		// then move them to a vector
 		.into_iter()
 		.map(|(k, v)| (as_nibbles(&k), v) )
-		.collect();
-
-	gen_trie_root(gen_input)
+		.collect()
 }
 
 /// Generates a key-hashed (secure) trie root hash for a vector of key-values.
@@ -113,7 +148,19 @@ pub fn trie_root<I>(input: I) -> H256
 /// }
 /// ```
 pub fn sec_trie_root(input: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
-	let gen_input = input
+	gen_trie_root(sec_input(input), 0)
+}
+
+/// Generates a key-hashed (secure) trie root hash for a vector of key-values, hashing
+/// independent subtries in parallel.
+///
+/// The result is the same as the one of `sec_trie_root`.
+pub fn sec_trie_root_parallel(input: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+	gen_trie_root(sec_input(input), PARALLEL_DEPTH)
+}
+
+fn sec_input(input: Vec<(Vec<u8>, Vec<u8>)>) -> Vec<(Vec<u8>, Vec<u8>)> {
+	input
 		// first put elements into btree to sort them and to remove duplicates
 		.into_iter()
 		.map(|(k, v)| (keccak(k), v))
@@ -121,14 +168,17 @@ pub fn sec_trie_root(input: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
 		// then move them to a vector
 		.into_iter()
 		.map(|(k, v)| (as_nibbles(&k), v) )
-		.collect();
-
-	gen_trie_root(gen_input)
+		.collect()
 }
 
-fn gen_trie_root(input: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+fn gen_trie_root(input: Vec<(Vec<u8>, Vec<u8>)>, parallel_depth: usize) -> H256 {
+	let parallel_depth = match input.len() < PARALLEL_MIN_ITEMS {
+		true => 0,
+		false => parallel_depth,
+	};
+
 	let mut stream = RlpStream::new();
-	hash256rlp(&input, 0, &mut stream);
+	hash256rlp(&input, 0, &mut stream, parallel_depth);
 	keccak(stream.out())
 }
 
@@ -189,7 +239,8 @@ fn as_nibbles(bytes: &[u8]) -> Vec<u8> {
 	res
 }
 
-fn hash256rlp(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStream) {
+// `parallel_depth` is the number of branch levels left to hash on the thread pool.
+fn hash256rlp(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStream, parallel_depth: usize) {
 	let inlen = input.len();
 
 	// in case of empty slice, just append empty data
@@ -226,7 +277,7 @@ fn hash256rlp(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStre
 	if shared_prefix > pre_len {
 		stream.begin_list(2);
 		stream.append(&hex_prefix_encode(&key[pre_len..shared_prefix], false));
-		hash256aux(input, shared_prefix, stream);
+		hash256aux(input, shared_prefix, stream, parallel_depth);
 		return;
 	}
 
@@ -240,7 +291,8 @@ fn hash256rlp(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStre
 		false => 0
 	};
 
-	// iterate over all possible nibbles
+	// split the items by their next nibble
+	let mut children = Vec::with_capacity(16);
 	for i in 0..16 {
 		// cout how many successive elements have same next nibble
 		let len = match begin < input.len() {
@@ -250,15 +302,35 @@ fn hash256rlp(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStre
 			false => 0
 		};
 
-		// if at least 1 successive element has the same nibble
-		// append their suffixes
-		match len {
-			0 => { stream.append_empty_data(); },
-			_ => hash256aux(&input[begin..(begin + len)], pre_len + 1, stream)
-		}
+		children.push(&input[begin..(begin + len)]);
 		begin += len;
 	}
 
+	// append the suffixes of the items of each nibble, if any.
+	match parallel_depth {
+		0 => for child in children {
+			match child.len() {
+				0 => { stream.append_empty_data(); },
+				_ => hash256aux(child, pre_len + 1, stream, 0),
+			}
+		},
+		_ => {
+			let encoded: Vec<_> = children.par_iter()
+				.map(|child| match child.len() {
+					0 => None,
+					_ => Some(child_rlp(child, pre_len + 1, parallel_depth - 1)),
+				})
+				.collect();
+
+			for child in encoded {
+				match child {
+					Some(child) => { stream.append_raw(&child, 1); },
+					None => { stream.append_empty_data(); },
+				}
+			}
+		}
+	}
+
 	// if fist key len is equal prefix, append its value
 	match pre_len == key.len() {
 		true => { stream.append(&value); },
@@ -266,9 +338,9 @@ fn hash256rlp(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStre
 	};
 }
 
-fn hash256aux(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStream) {
+fn hash256aux(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStream, parallel_depth: usize) {
 	let mut s = RlpStream::new();
-	hash256rlp(input, pre_len, &mut s);
+	hash256rlp(input, pre_len, &mut s, parallel_depth);
 	let out = s.out();
 	match out.len() {
 		0...31 => stream.append_raw(&out, 1),
@@ -277,6 +349,17 @@ fn hash256aux(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, stream: &mut RlpStre
 }
 
 
+// same as `hash256aux`, but returns the encoded item instead of appending it to a stream.
+fn child_rlp(input: &[(Vec<u8>, Vec<u8>)], pre_len: usize, parallel_depth: usize) -> Vec<u8> {
+	let mut s = RlpStream::new();
+	hash256rlp(input, pre_len, &mut s, parallel_depth);
+	let out = s.out();
+	match out.len() {
+		0...31 => out,
+		_ => rlp::encode(&keccak(out)).into_vec(),
+	}
+}
+
 #[test]
 fn test_nibbles() {
 	let v = vec![0x31, 0x23, 0x45];
@@ -293,6 +376,7 @@ fn test_nibbles() {
 #[cfg(test)]
 mod tests {
 	use super::{trie_root, shared_prefix_len, hex_prefix_encode};
+	use super::{ordered_trie_root, ordered_trie_root_parallel, trie_root_parallel, sec_trie_root, sec_trie_root_parallel};
 
 	#[test]
 	fn test_hex_prefix_encode() {
@@ -368,4 +452,19 @@ mod tests {
 		let b = vec![1,2,3,4,5,6];
 		assert_eq!(shared_prefix_len(&a, &b), 6);
 	}
+
+	#[test]
+	fn parallel_roots_match() {
+		let items: Vec<_> = (0..2000u32)
+			.map(|i| (::hash::keccak(&[i as u8, (i >> 8) as u8]).to_vec(), vec![i as u8; (i % 40) as usize + 1]))
+			.collect();
+
+		assert_eq!(trie_root(items.clone()), trie_root_parallel(items.clone()));
+		assert_eq!(sec_trie_root(items.clone()), sec_trie_root_parallel(items.clone()));
+		let values: Vec<_> = items.into_iter().map(|(_, v)| v).collect();
+		assert_eq!(ordered_trie_root(values.clone()), ordered_trie_root_parallel(values));
+
+		let small = vec![(b"doe".to_vec(), b"reindeer".to_vec()), (b"dog".to_vec(), b"puppy".to_vec())];
+		assert_eq!(trie_root(small.clone()), trie_root_parallel(small));
+	}
 }